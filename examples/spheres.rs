@@ -14,6 +14,7 @@ use tracer::scene::*;
 use rand::prelude::*;
 use rand_isaac::Isaac64Rng;
 
+use std::env;
 use std::fs::File;
 use std::io;
 use std::io::Write;
@@ -69,12 +70,11 @@ fn generate_scene(rng: &mut Isaac64Rng, width: usize, height: usize) -> Scene {
     ));
 
     scene.push_object(SceneObject::new(Box::new(ModelSpaceGeometryObject::new(
-            Sphere::new(Vector3::zero(), 1000_f32),
+            Plane::new(Vector3::zero(), Vector3::unit_y()),
             Box::new(SimpleLambertianBsdf::new(Vector3::new(0.5, 0.5, 0.5))),
-            Box::new(SimpleLambertianBsdfQuerySampler::new()),
             Box::new(NoLight::new())
         )),
-        Matrix4x4::from_affine_translation(&Vector3::new(0_f32, -1000_f32, 0_f32))
+        Matrix4x4::identity()
     ));
     
     for a in -10..10 {
@@ -96,7 +96,6 @@ fn generate_scene(rng: &mut Isaac64Rng, width: usize, height: usize) -> Scene {
                     scene.push_object(SceneObject::new(Box::new(ModelSpaceGeometryObject::new(
                             Sphere::new(Vector3::zero(), 0.2),
                             Box::new(SimpleLambertianBsdf::new(albedo)),
-                            Box::new(SimpleLambertianBsdfQuerySampler::new()),
                             Box::new(NoLight::new())
                         )),
                         Matrix4x4::from_affine_translation(&center)
@@ -112,7 +111,6 @@ fn generate_scene(rng: &mut Isaac64Rng, width: usize, height: usize) -> Scene {
                     scene.push_object(SceneObject::new(Box::new(ModelSpaceGeometryObject::new(
                             Sphere::new(Vector3::zero(), 0.2),
                             Box::new(SimpleMetalBsdf::new(albedo, fuzz)),
-                            Box::new(SimpleMetalBsdfQuerySampler::new()),
                             Box::new(NoLight::new())
                         )),
                         Matrix4x4::from_affine_translation(&center)
@@ -122,7 +120,6 @@ fn generate_scene(rng: &mut Isaac64Rng, width: usize, height: usize) -> Scene {
                     scene.push_object(SceneObject::new(Box::new(ModelSpaceGeometryObject::new(
                             Sphere::new(Vector3::zero(), 0.2),
                             Box::new(SimpleDielectricBsdf::new(1.5)),
-                            Box::new(SimpleDielectricBsdfQuerySampler::new()),
                             Box::new(NoLight::new())
                         )),
                         Matrix4x4::from_affine_translation(&center)
@@ -132,7 +129,6 @@ fn generate_scene(rng: &mut Isaac64Rng, width: usize, height: usize) -> Scene {
                     scene.push_object(SceneObject::new(Box::new(ModelSpaceGeometryObject::new(
                             Sphere::new(Vector3::zero(), 0.3),
                             Box::new(SimpleLambertianBsdf::new(Vector3::new(0.1, 0.5, 0.4))),
-                            Box::new(SimpleLambertianBsdfQuerySampler::new()),
                             Box::new(PointLight::new(Vector3::new(1_f32, 1_f32, 1_f32)))
                         )),
                         Matrix4x4::from_affine_translation(&center)
@@ -145,7 +141,6 @@ fn generate_scene(rng: &mut Isaac64Rng, width: usize, height: usize) -> Scene {
     scene.push_object(SceneObject::new(Box::new(ModelSpaceGeometryObject::new(
             Sphere::new(Vector3::zero(), 1_f32),
             Box::new(SimpleDielectricBsdf::new(1.5)),
-            Box::new(SimpleDielectricBsdfQuerySampler::new()),
             Box::new(NoLight::new())
         )),
         Matrix4x4::from_affine_translation(&Vector3::new(0_f32, 1_f32, 0_f32))
@@ -153,7 +148,6 @@ fn generate_scene(rng: &mut Isaac64Rng, width: usize, height: usize) -> Scene {
     scene.push_object(SceneObject::new(Box::new(ModelSpaceGeometryObject::new(
             Sphere::new(Vector3::zero(), 1_f32), 
             Box::new(SimpleLambertianBsdf::new(Vector3::new(0.4, 0.2, 0.1))),
-            Box::new(SimpleLambertianBsdfQuerySampler::new()),
             Box::new(NoLight::new())
         )),
         Matrix4x4::from_affine_translation(&Vector3::new(-4_f32, 1_f32, 0_f32))
@@ -161,7 +155,6 @@ fn generate_scene(rng: &mut Isaac64Rng, width: usize, height: usize) -> Scene {
     scene.push_object(SceneObject::new(Box::new(ModelSpaceGeometryObject::new(
             Sphere::new(Vector3::zero(), 1_f32), 
             Box::new(SimpleMetalBsdf::new(Vector3::new(0.7, 0.6, 0.5), 0.1)),
-            Box::new(SimpleMetalBsdfQuerySampler::new()),
             Box::new(NoLight::new())
         )),
         Matrix4x4::from_affine_translation(&Vector3::new(4_f32, 1_f32, 0_f32))
@@ -180,11 +173,39 @@ fn write_image_to_file(canvas: &Canvas, file: &mut File) -> io::Result<()> {
     Ok(())
 }
 
+/// Parse the rendering algorithm from the command line arguments. The argument 
+/// `--debug <mode>` renders the debug visualization `mode` instead of the image.
+fn parse_algorithm<I>(mut args: I) -> io::Result<RenderingAlgorithm> 
+where
+    I: Iterator<Item = String>,
+{
+    let mut algorithm = RenderingAlgorithm::PathTracing;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--debug" => {
+                let name = args.next().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "missing debug mode after `--debug`")
+                })?;
+                let mode = name.parse::<DebugMode>().map_err(|error| {
+                    io::Error::new(io::ErrorKind::InvalidInput, error)
+                })?;
+                algorithm = RenderingAlgorithm::Debug(mode);
+            }
+            _ => {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("unknown argument `{}`", arg)));
+            }
+        }
+    }
+
+    Ok(algorithm)
+}
+
 fn main() -> io::Result<()> {
+    let algorithm = parse_algorithm(env::args().skip(1))?;
     let width = 480;
     let height = 270;
     let mut canvas = Canvas::new(width, height);
-    let settings = RendererSettings::new(SAMPLES_PER_PIXEL, MAX_DEPTH);
+    let settings = RendererSettings::new(SAMPLES_PER_PIXEL, MAX_DEPTH).with_algorithm(algorithm);
     let renderer = Renderer::new(settings);
 
     let mut rng = generate_rng();
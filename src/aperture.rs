@@ -0,0 +1,242 @@
+use crate::core::*;
+use crate::texture::*;
+use cglinalg::{
+    Vector3,
+};
+use std::fmt;
+use std::sync::Arc;
+
+
+/// An error from constructing a bokeh texture from an image that lets no light 
+/// through.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BokehTextureError;
+
+impl fmt::Display for BokehTextureError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a bokeh texture needs a pixel with a positive luminance")
+    }
+}
+
+impl std::error::Error for BokehTextureError {}
+
+/// A mask over the opening of a lens that gives out of focus highlights the
+/// shape of an image.
+///
+/// The image covers the square circumscribing the opening, with its top row
+/// along the top of the lens. The luminance of a pixel sets how much light passes
+/// through the lens there, so points on the lens are sampled with probability
+/// density proportional to the luminance of the image.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BokehTexture {
+    width: usize,
+    height: usize,
+    /// The cumulative distribution of the rows of the image.
+    row_cdf: Vec<f32>,
+    /// The cumulative distributions of the pixels within each row of the image,
+    /// with `width + 1` entries per row.
+    column_cdfs: Vec<f32>,
+    /// The luminance of each pixel divided by the largest luminance.
+    mask: Vec<f32>,
+    /// The fraction of the square covered by the opening, weighted by the mask.
+    coverage: f32,
+}
+
+impl BokehTexture {
+    /// Construct a bokeh texture from the image `image`.
+    ///
+    /// Returns an error if no pixel of the image has a positive luminance, since
+    /// the lens would then let no light through.
+    pub fn new(image: &Image) -> Result<Self, BokehTextureError> {
+        let width = image.width();
+        let height = image.height();
+        let mut luminances = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let pixel = image.pixel(x, y);
+                let luminance = 0.2126 * pixel.x + 0.7152 * pixel.y + 0.0722 * pixel.z;
+                luminances.push(f32::max(luminance, 0_f32));
+            }
+        }
+
+        let max_luminance = luminances.iter().fold(0_f32, |max_luminance, &luminance| f32::max(max_luminance, luminance));
+        if max_luminance <= 0_f32 {
+            return Err(BokehTextureError);
+        }
+
+        let mut row_cdf = Vec::with_capacity(height + 1);
+        let mut column_cdfs = Vec::with_capacity(height * (width + 1));
+        let mut total = 0_f32;
+        row_cdf.push(0_f32);
+        for row in luminances.chunks(width) {
+            let mut row_total = 0_f32;
+            column_cdfs.push(0_f32);
+            for &luminance in row.iter() {
+                row_total += luminance;
+                column_cdfs.push(row_total);
+            }
+
+            let row_cdf_start = column_cdfs.len() - (width + 1);
+            for value in column_cdfs[row_cdf_start..].iter_mut() {
+                *value = if row_total > 0_f32 { *value / row_total } else { 0_f32 };
+            }
+
+            total += row_total;
+            row_cdf.push(total);
+        }
+
+        for value in row_cdf.iter_mut() {
+            *value /= total;
+        }
+
+        let mask = luminances.iter().map(|luminance| luminance / max_luminance).collect::<Vec<f32>>();
+        let coverage = total / (max_luminance * (width * height) as f32);
+
+        Ok(Self { width, height, row_cdf, column_cdfs, mask, coverage, })
+    }
+
+    /// Returns the fraction of the square circumscribing the opening that lets
+    /// light through, weighting each pixel by its luminance relative to the
+    /// brightest one.
+    #[inline]
+    pub fn coverage(&self) -> f32 {
+        self.coverage
+    }
+
+    /// Evaluate the mask at a point in the square `[-1, 1] x [-1, 1]`, from zero
+    /// where the lens blocks light to one at its brightest.
+    pub fn evaluate(&self, x: f32, y: f32) -> f32 {
+        if !(-1_f32..=1_f32).contains(&x) || !(-1_f32..=1_f32).contains(&y) {
+            return 0_f32;
+        }
+
+        let column = usize::min(((x + 1_f32) * 0.5 * self.width as f32) as usize, self.width - 1);
+        let row = usize::min(((1_f32 - y) * 0.5 * self.height as f32) as usize, self.height - 1);
+
+        self.mask[row * self.width + column]
+    }
+
+    /// Sample a point in the square `[-1, 1] x [-1, 1]` with probability density
+    /// proportional to the mask.
+    pub fn sample(&self, sampler: &mut SphereSampler) -> Vector3<f32> {
+        let u_row = sampler.sample_f32();
+        let row = usize::min(self.row_cdf.partition_point(|&value| value <= u_row), self.height) - 1;
+        let dv = remap(u_row, self.row_cdf[row], self.row_cdf[row + 1]);
+
+        let column_cdf = &self.column_cdfs[(row * (self.width + 1))..((row + 1) * (self.width + 1))];
+        let u_column = sampler.sample_f32();
+        let column = usize::min(column_cdf.partition_point(|&value| value <= u_column), self.width) - 1;
+        let du = remap(u_column, column_cdf[column], column_cdf[column + 1]);
+
+        let x = -1_f32 + 2_f32 * (column as f32 + du) / (self.width as f32);
+        let y = 1_f32 - 2_f32 * (row as f32 + dv) / (self.height as f32);
+
+        Vector3::new(x, y, 0_f32)
+    }
+}
+
+/// Map a value in the interval `[start, end)` linearly onto `[0, 1)`.
+#[inline]
+fn remap(value: f32, start: f32, end: f32) -> f32 {
+    if end > start {
+        f32::clamp((value - start) / (end - start), 0_f32, 1_f32 - f32::EPSILON)
+    } else {
+        0.5
+    }
+}
+
+/// The shape of the opening of the lens of a camera, which gives out of focus
+/// highlights their shape.
+///
+/// Shapes are measured on a lens of unit radius, and the camera scales them by
+/// the radius of its lens.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ApertureShape {
+    /// A circular opening.
+    Circle,
+    /// A regular polygon formed by `blade_count` straight blades, inscribed in
+    /// the circle of the lens and rotated by `rotation` radians. Fewer than three
+    /// blades form a triangle.
+    Polygon { blade_count: usize, rotation: f32 },
+    /// An opening masked by a bokeh texture covering the square circumscribing
+    /// the circle of the lens.
+    Texture(Arc<BokehTexture>),
+}
+
+impl ApertureShape {
+    /// Construct a polygonal opening formed by `blade_count` blades.
+    pub fn polygon(blade_count: usize) -> Self {
+        ApertureShape::Polygon { blade_count, rotation: 0_f32 }
+    }
+
+    /// Returns the area of the opening on a lens of unit radius. The area of an
+    /// opening masked by a texture is weighted by the mask.
+    pub fn area(&self) -> f32 {
+        match self {
+            ApertureShape::Circle => std::f32::consts::PI,
+            ApertureShape::Polygon { blade_count, .. } => {
+                let blade_count = usize::max(*blade_count, 3) as f32;
+
+                0.5 * blade_count * f32::sin(2_f32 * std::f32::consts::PI / blade_count)
+            }
+            ApertureShape::Texture(texture) => 4_f32 * texture.coverage(),
+        }
+    }
+
+    /// Evaluate the fraction of light the opening lets through at the point 
+    /// `(x, y)` on a lens of unit radius, from zero outside the opening to one 
+    /// where it is fully open.
+    ///
+    /// Points sampled with [`ApertureShape::sample`] have the probability 
+    /// density `evaluate(x, y) / area()`.
+    pub fn evaluate(&self, x: f32, y: f32) -> f32 {
+        // Points on the edge of the opening count as inside it, up to the 
+        // rounding error of sampling them.
+        let tolerance = 1e-4;
+        match self {
+            ApertureShape::Circle => {
+                if x * x + y * y <= 1_f32 + tolerance { 1_f32 } else { 0_f32 }
+            }
+            ApertureShape::Polygon { blade_count, rotation } => {
+                let blade_count = usize::max(*blade_count, 3);
+                let angle = 2_f32 * std::f32::consts::PI / (blade_count as f32);
+                let apothem = f32::cos(0.5 * angle);
+                let is_inside = (0..blade_count).all(|edge| {
+                    let theta = rotation + angle * (edge as f32 + 0.5);
+
+                    x * f32::cos(theta) + y * f32::sin(theta) <= apothem + tolerance
+                });
+
+                if is_inside { 1_f32 } else { 0_f32 }
+            }
+            ApertureShape::Texture(texture) => texture.evaluate(x, y),
+        }
+    }
+
+    /// Sample a point on the opening on a lens of unit radius in the **xy-plane**
+    /// with probability density proportional to [`ApertureShape::evaluate`].
+    pub fn sample(&self, sampler: &mut SphereSampler) -> Vector3<f32> {
+        match self {
+            ApertureShape::Circle => sampler.sample_unit_disk(),
+            ApertureShape::Polygon { blade_count, rotation } => {
+                // Choose one of the triangles between the center and the edges of
+                // the polygon, all of the same area, then a point in it uniformly.
+                let blade_count = usize::max(*blade_count, 3);
+                let angle = 2_f32 * std::f32::consts::PI / (blade_count as f32);
+                let edge = usize::min((sampler.sample_f32() * blade_count as f32) as usize, blade_count - 1);
+                let theta = rotation + angle * (edge as f32);
+                let corner1 = Vector3::new(f32::cos(theta), f32::sin(theta), 0_f32);
+                let corner2 = Vector3::new(f32::cos(theta + angle), f32::sin(theta + angle), 0_f32);
+                let mut s = sampler.sample_f32();
+                let mut t = sampler.sample_f32();
+                if s + t > 1_f32 {
+                    s = 1_f32 - s;
+                    t = 1_f32 - t;
+                }
+
+                corner1 * s + corner2 * t
+            }
+            ApertureShape::Texture(texture) => texture.sample(sampler),
+        }
+    }
+}
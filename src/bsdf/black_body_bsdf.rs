@@ -1,12 +1,11 @@
 use crate::core::*;
 use cglinalg::{
     Vector3,
-    Magnitude,
 };
 
 
-
-#[derive(Copy, Clone, Debug)]
+/// A scattering function that absorbs all incident light.
+#[derive(Copy, Clone, Debug, Default)]
 pub struct BlackBodyBsdf {}
 
 impl BlackBodyBsdf {
@@ -15,42 +14,21 @@ impl BlackBodyBsdf {
     }
 }
 
-impl BsdfMapping for BlackBodyBsdf {
-    fn sample(&self, query: &BsdfQuery) -> BsdfResult {
-        BsdfResult::new(
-            query.ray_incoming,
-            query.ray_outgoing,
-            query.point,
-            query.normal,
-            Vector3::from_fill(0.001),
-        )
+impl Bsdf for BlackBodyBsdf {
+    #[inline]
+    fn flags(&self) -> BsdfFlags {
+        BsdfFlags::empty()
     }
-}
-
-
-#[derive(Copy, Clone, Debug, Default)]
-pub struct BlackBodyBsdfQuerySampler {}
 
-impl BlackBodyBsdfQuerySampler {
-    pub fn new() -> Self {
-        Self {}
+    fn evaluate(&self, _query: &BsdfQuery) -> Vector3<f32> {
+        Vector3::zero()
     }
-}
 
-impl BsdfQuerySampler for BlackBodyBsdfQuerySampler {
-    type Bsdf = BlackBodyBsdf;
-
-    #[inline]
-    fn sample(
-        &self, 
-        _bsdf: &Self::Bsdf, 
-        ray_incoming: &Vector3<f32>, 
-        normal: &Vector3<f32>, 
-        point: &Vector3<f32>,
-        _sampler: &mut SphereSampler) -> BsdfQuery
-    {
-        let ray_outgoing = *ray_incoming;
+    fn pdf(&self, _query: &BsdfQuery) -> f32 {
+        0_f32
+    }
 
-        BsdfQuery::new(*ray_incoming, ray_outgoing, *point, *normal)
+    fn sample(&self, _query: &BsdfSampleQuery, _sampler: &mut SphereSampler) -> Option<BsdfResult> {
+        None
     }
 }
@@ -1,9 +1,17 @@
+mod black_body_bsdf;
+mod oren_nayar_bsdf;
+mod pass_through_bsdf;
 mod simple_dielectric_bsdf;
 mod simple_lambertian_bsdf;
 mod simple_metal_bsdf;
+mod subsurface_bsdf;
 
 
+pub use black_body_bsdf::*;
+pub use oren_nayar_bsdf::*;
+pub use pass_through_bsdf::*;
 pub use simple_dielectric_bsdf::*;
 pub use simple_lambertian_bsdf::*;
 pub use simple_metal_bsdf::*;
+pub use subsurface_bsdf::*;
 
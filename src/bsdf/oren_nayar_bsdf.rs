@@ -0,0 +1,100 @@
+use crate::core::*;
+use crate::bsdf::*;
+use crate::texture::*;
+use cglinalg::{
+    Vector3,
+    Magnitude,
+};
+use std::sync::Arc;
+
+
+/// A rough diffuse reflection model.
+///
+/// The Oren-Nayar model describes a surface as a collection of Lambertian
+/// V-shaped microfacets whose slopes are normally distributed with a standard
+/// deviation of `sigma` radians. When `sigma` is zero, the model reduces to
+/// the Lambertian model.
+#[derive(Clone, Debug)]
+pub struct OrenNayarBsdf {
+    scattering_fraction: Arc<dyn Texture<Vector3<f32>>>,
+    sigma: Arc<dyn Texture<f32>>,
+}
+
+impl OrenNayarBsdf {
+    /// Construct a new Oren-Nayar scattering function from the surface albedo and
+    /// the standard deviation `sigma` of the microfacet slope angles in radians.
+    pub fn new(scattering_fraction: Vector3<f32>, sigma: f32) -> Self {
+        Self::from_textures(
+            Arc::new(ConstantTexture::new(scattering_fraction)),
+            Arc::new(ConstantTexture::new(sigma)),
+        )
+    }
+
+    pub fn from_textures(scattering_fraction: Arc<dyn Texture<Vector3<f32>>>, sigma: Arc<dyn Texture<f32>>) -> Self {
+        Self { scattering_fraction, sigma, }
+    }
+}
+
+impl Bsdf for OrenNayarBsdf {
+    #[inline]
+    fn flags(&self) -> BsdfFlags {
+        BsdfFlags::DIFFUSE | BsdfFlags::REFLECTION
+    }
+
+    fn evaluate(&self, query: &BsdfQuery) -> Vector3<f32> {
+        if !is_reflection(&query.ray_incoming, &query.ray_outgoing) {
+            return Vector3::zero();
+        }
+
+        let w_incoming = -query.ray_incoming.normalize();
+        let w_outgoing = query.ray_outgoing.normalize();
+        let cos_theta_incoming = abs_cos_theta(&w_incoming);
+        let cos_theta_outgoing = abs_cos_theta(&w_outgoing);
+        let sin_theta_incoming = f32::sqrt(f32::max(0_f32, 1_f32 - cos_theta_incoming * cos_theta_incoming));
+        let sin_theta_outgoing = f32::sqrt(f32::max(0_f32, 1_f32 - cos_theta_outgoing * cos_theta_outgoing));
+
+        // The cosine of the azimuthal angle between the two directions is the cosine of
+        // the angle between their projections onto the tangent plane.
+        let max_cos_phi = if sin_theta_incoming > 1e-4 && sin_theta_outgoing > 1e-4 {
+            let cos_phi = (w_incoming.x * w_outgoing.x + w_incoming.y * w_outgoing.y) / 
+                (sin_theta_incoming * sin_theta_outgoing);
+
+            f32::max(0_f32, cos_phi)
+        } else {
+            0_f32
+        };
+
+        let (sin_alpha, tan_beta) = if cos_theta_incoming > cos_theta_outgoing {
+            (sin_theta_outgoing, sin_theta_incoming / cos_theta_incoming)
+        } else {
+            (sin_theta_incoming, sin_theta_outgoing / cos_theta_outgoing)
+        };
+
+        let texture_query = query.texture_query();
+        let sigma = self.sigma.evaluate(&texture_query);
+        let sigma_squared = sigma * sigma;
+        let a = 1_f32 - sigma_squared / (2_f32 * (sigma_squared + 0.33));
+        let b = 0.45 * sigma_squared / (sigma_squared + 0.09);
+        let factor = a + b * max_cos_phi * sin_alpha * tan_beta;
+
+        self.scattering_fraction.evaluate(&texture_query) * (factor / std::f32::consts::PI)
+    }
+
+    fn pdf(&self, query: &BsdfQuery) -> f32 {
+        pdf_cosine_weighted(&query.ray_incoming, &query.ray_outgoing)
+    }
+
+    fn sample(&self, query: &BsdfSampleQuery, sampler: &mut SphereSampler) -> Option<BsdfResult> {
+        let ray_outgoing = sample_cosine_weighted(&query.ray_incoming, sampler);
+        let pdf = pdf_cosine_weighted(&query.ray_incoming, &ray_outgoing);
+        if pdf == 0_f32 {
+            return None;
+        }
+
+        let mut bsdf_query = BsdfQuery::new(query.ray_incoming, ray_outgoing, query.point, query.uv);
+        bsdf_query.world_point = query.world_point;
+        let scattering_fraction = self.evaluate(&bsdf_query) * (abs_cos_theta(&ray_outgoing) / pdf);
+
+        Some(BsdfResult::new(ray_outgoing, scattering_fraction, pdf, self.flags()))
+    }
+}
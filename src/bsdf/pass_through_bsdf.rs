@@ -0,0 +1,39 @@
+use crate::core::*;
+use cglinalg::{
+    Vector3,
+};
+
+
+/// A scattering function that lets all incident light through the surface 
+/// without changing its direction.
+///
+/// Surfaces with this scattering function are invisible. They bound the interior 
+/// of an object filled with a participating medium, such as a bank of fog or a 
+/// box of smoke.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct PassThroughBsdf {}
+
+impl PassThroughBsdf {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Bsdf for PassThroughBsdf {
+    #[inline]
+    fn flags(&self) -> BsdfFlags {
+        BsdfFlags::PASS_THROUGH | BsdfFlags::DELTA | BsdfFlags::TRANSMISSION
+    }
+
+    fn evaluate(&self, _query: &BsdfQuery) -> Vector3<f32> {
+        Vector3::zero()
+    }
+
+    fn pdf(&self, _query: &BsdfQuery) -> f32 {
+        0_f32
+    }
+
+    fn sample(&self, query: &BsdfSampleQuery, _sampler: &mut SphereSampler) -> Option<BsdfResult> {
+        Some(BsdfResult::new(query.ray_incoming, Vector3::from_fill(1_f32), 1_f32, self.flags()))
+    }
+}
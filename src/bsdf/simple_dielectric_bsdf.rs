@@ -1,54 +1,42 @@
 use crate::core::*;
+use crate::texture::*;
 use cglinalg::{
     Vector3,
     Magnitude,
 };
+use std::sync::Arc;
 
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct SimpleDielectricBsdf {
-    pub refraction_index: f32,
+    refraction_index: Arc<dyn Texture<f32>>,
 }
 
 impl SimpleDielectricBsdf {
     pub fn new(refraction_index: f32) -> Self {
-        Self { refraction_index, }
+        Self::from_texture(Arc::new(ConstantTexture::new(refraction_index)))
     }
-}
 
-impl BsdfMapping for SimpleDielectricBsdf {
-    fn sample(&self, query: &BsdfQuery) -> BsdfResult {
-        BsdfResult::new(
-            query.ray_incoming,
-            query.ray_outgoing,
-            query.point, 
-            query.normal, 
-            Vector3::new(1_f32, 1_f32, 1_f32),
-        )
+    pub fn from_texture(refraction_index: Arc<dyn Texture<f32>>) -> Self {
+        Self { refraction_index, }
     }
 }
 
-#[derive(Copy, Clone, Debug, Default)]
-pub struct SimpleDielectricBsdfQuerySampler {}
+impl Bsdf for SimpleDielectricBsdf {
+    #[inline]
+    fn flags(&self) -> BsdfFlags {
+        BsdfFlags::DELTA | BsdfFlags::REFLECTION | BsdfFlags::TRANSMISSION
+    }
 
-impl SimpleDielectricBsdfQuerySampler {
-    pub fn new() -> Self {
-        Self {}
+    fn evaluate(&self, _query: &BsdfQuery) -> Vector3<f32> {
+        Vector3::zero()
     }
-}
 
-impl BsdfQuerySampler for SimpleDielectricBsdfQuerySampler {
-    type Bsdf = SimpleDielectricBsdf;
+    fn pdf(&self, _query: &BsdfQuery) -> f32 {
+        0_f32
+    }
 
-    #[inline]
-    fn sample(
-        &self, 
-        bsdf: &Self::Bsdf, 
-        ray_incoming: &Vector3<f32>, 
-        normal: &Vector3<f32>, 
-        point: &Vector3<f32>,
-        sampler: &mut SphereSampler) -> BsdfQuery
-    {
+    fn sample(&self, query: &BsdfSampleQuery, sampler: &mut SphereSampler) -> Option<BsdfResult> {
         #[inline]
         fn refract(ray_incoming: Vector3<f32>, normal: Vector3<f32>, ni_over_nt: f32) -> Option<Vector3<f32>> {
             let uv = ray_incoming.normalize();
@@ -69,32 +57,51 @@ impl BsdfQuerySampler for SimpleDielectricBsdfQuerySampler {
             r0 + (1_f32 - r0) * (1_f32 - cosine).powf(5_f32)
         }
 
-        let (normal_outward, ni_over_nt, cosine) = if ray_incoming.dot(normal) > 0_f32 {
+        let refraction_index = self.refraction_index.evaluate(&query.texture_query());
+        let ray_incoming = query.ray_incoming.normalize();
+        let normal = Vector3::unit_z();
+        let (normal_outward, ni_over_nt, cosine) = if cos_theta(&ray_incoming) > 0_f32 {
             (
                 -normal,
-                bsdf.refraction_index, 
-                bsdf.refraction_index * ray_incoming.dot(normal) / ray_incoming.magnitude()
+                refraction_index, 
+                refraction_index * cos_theta(&ray_incoming)
             )
         } else {
             (
-                *normal,
-                1_f32 / bsdf.refraction_index,
-                -ray_incoming.dot(normal) / ray_incoming.magnitude(),
+                normal,
+                1_f32 / refraction_index,
+                -cos_theta(&ray_incoming),
             )
         };
 
-        let ray_outgoing = if let Some(refracted_direction) = refract(*ray_incoming, normal_outward, ni_over_nt) {
-            let reflection_prob = schlick(cosine, bsdf.refraction_index);
+        // Choose between reflection and refraction in proportion to the Fresnel
+        // reflectance, so that the throughput weight of either choice is one.
+        let result = if let Some(refracted_direction) = refract(ray_incoming, normal_outward, ni_over_nt) {
+            let reflection_prob = schlick(cosine, refraction_index);
             if sampler.sample_f32() < reflection_prob {
-                ray_incoming.reflect(normal)
+                BsdfResult::new(
+                    reflect_local(&ray_incoming),
+                    Vector3::from_fill(1_f32),
+                    reflection_prob,
+                    BsdfFlags::DELTA | BsdfFlags::REFLECTION,
+                )
             } else {
-                refracted_direction
+                BsdfResult::new(
+                    refracted_direction.normalize(),
+                    Vector3::from_fill(1_f32),
+                    1_f32 - reflection_prob,
+                    BsdfFlags::DELTA | BsdfFlags::TRANSMISSION,
+                )
             }
         } else {
-            ray_incoming.reflect(normal)
+            BsdfResult::new(
+                reflect_local(&ray_incoming),
+                Vector3::from_fill(1_f32),
+                1_f32,
+                BsdfFlags::DELTA | BsdfFlags::REFLECTION,
+            )
         };
 
-        BsdfQuery::new(*ray_incoming, ray_outgoing, *point, normal_outward)
+        Some(result)
     }
 }
-
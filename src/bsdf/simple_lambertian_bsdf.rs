@@ -1,59 +1,79 @@
 use crate::core::*;
-use crate::bsdf::*;
+use crate::texture::*;
 use cglinalg::{
     Vector3,
-    Magnitude,
 };
+use std::sync::Arc;
 
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct SimpleLambertianBsdf {
-    scattering_fraction: Vector3<f32>,
+    scattering_fraction: Arc<dyn Texture<Vector3<f32>>>,
 }
 
 impl SimpleLambertianBsdf {
     pub fn new(scattering_fraction: Vector3<f32>) -> Self {
+        Self::from_texture(Arc::new(ConstantTexture::new(scattering_fraction)))
+    }
+
+    pub fn from_texture(scattering_fraction: Arc<dyn Texture<Vector3<f32>>>) -> Self {
         Self { scattering_fraction, }
     }
 }
 
-impl BsdfMapping for SimpleLambertianBsdf {
-    fn sample(&self, query: &BsdfQuery) -> BsdfResult {
-        BsdfResult {
-            ray_incoming: query.ray_incoming,
-            ray_outgoing: query.ray_outgoing,
-            point: query.point,
-            normal: query.normal,
-            scattering_fraction: self.scattering_fraction,
+impl Bsdf for SimpleLambertianBsdf {
+    #[inline]
+    fn flags(&self) -> BsdfFlags {
+        BsdfFlags::DIFFUSE | BsdfFlags::REFLECTION
+    }
+
+    fn evaluate(&self, query: &BsdfQuery) -> Vector3<f32> {
+        if is_reflection(&query.ray_incoming, &query.ray_outgoing) {
+            self.scattering_fraction.evaluate(&query.texture_query()) / std::f32::consts::PI
+        } else {
+            Vector3::zero()
         }
     }
-}
 
-#[derive(Copy, Clone, Debug, Default)]
-pub struct SimpleLambertianBsdfQuerySampler {}
+    fn pdf(&self, query: &BsdfQuery) -> f32 {
+        pdf_cosine_weighted(&query.ray_incoming, &query.ray_outgoing)
+    }
+
+    fn sample(&self, query: &BsdfSampleQuery, sampler: &mut SphereSampler) -> Option<BsdfResult> {
+        let ray_outgoing = sample_cosine_weighted(&query.ray_incoming, sampler);
+        let pdf = pdf_cosine_weighted(&query.ray_incoming, &ray_outgoing);
+        if pdf == 0_f32 {
+            return None;
+        }
+
+        // The cosine-weighted sampling density cancels the cosine term and the
+        // normalization factor of the Lambertian distribution exactly, so the
+        // throughput weight of a sample is the albedo itself.
+        let scattering_fraction = self.scattering_fraction.evaluate(&query.texture_query());
 
-impl SimpleLambertianBsdfQuerySampler {
-    pub fn new() -> Self { 
-        Self {}
+        Some(BsdfResult::new(ray_outgoing, scattering_fraction, pdf, self.flags()))
     }
 }
 
-impl BsdfQuerySampler for SimpleLambertianBsdfQuerySampler {
-    type Bsdf = SimpleLambertianBsdf;
-
-    #[inline]
-    fn sample(
-        &self, 
-        _bsdf: &Self::Bsdf, 
-        ray_incoming: &Vector3<f32>, 
-        normal: &Vector3<f32>, 
-        point: &Vector3<f32>,
-        sampler: &mut SphereSampler) -> BsdfQuery
-    {
-        let target = point + normal + sampler.sample_unit_sphere();
-        let ray_outgoing = target - point;
-        
-        BsdfQuery::new(*ray_incoming, ray_outgoing, *point, *normal)
+/// Sample an outgoing direction in the local shading frame with a cosine-weighted
+/// distribution on the side of the surface the incoming ray arrives from.
+#[inline]
+pub(crate) fn sample_cosine_weighted(ray_incoming: &Vector3<f32>, sampler: &mut SphereSampler) -> Vector3<f32> {
+    let ray_outgoing = sampler.sample_cosine_hemisphere();
+    if cos_theta(ray_incoming) > 0_f32 {
+        Vector3::new(ray_outgoing.x, ray_outgoing.y, -ray_outgoing.z)
+    } else {
+        ray_outgoing
     }
 }
 
+/// The probability density of sampling an outgoing direction in the local
+/// shading frame with [`sample_cosine_weighted`].
+#[inline]
+pub(crate) fn pdf_cosine_weighted(ray_incoming: &Vector3<f32>, ray_outgoing: &Vector3<f32>) -> f32 {
+    if is_reflection(ray_incoming, ray_outgoing) {
+        abs_cos_theta(ray_outgoing) / std::f32::consts::PI
+    } else {
+        0_f32
+    }
+}
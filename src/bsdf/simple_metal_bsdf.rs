@@ -1,62 +1,138 @@
 use crate::core::*;
+use crate::texture::*;
 use cglinalg::{
     Vector3,
     Magnitude,
 };
+use std::sync::Arc;
 
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct SimpleMetalBsdf {
-    reflectance: Vector3<f32>,
-    fuzz: f32,
+    reflectance: Arc<dyn Texture<Vector3<f32>>>,
+    fuzz: Arc<dyn Texture<f32>>,
+    flags: BsdfFlags,
 }
 
 impl SimpleMetalBsdf {
+    /// Construct a new metal with a constant reflectance and fuzz. The metal is
+    /// a specular reflector when the fuzz is zero, and a glossy one otherwise.
     pub fn new(reflectance: Vector3<f32>, fuzz: f32) -> Self {
-        Self { reflectance, fuzz, }
+        let flags = if fuzz > 0_f32 {
+            BsdfFlags::GLOSSY | BsdfFlags::REFLECTION
+        } else {
+            BsdfFlags::DELTA | BsdfFlags::REFLECTION
+        };
+        let metal = Self::from_textures(
+            Arc::new(ConstantTexture::new(reflectance)),
+            Arc::new(ConstantTexture::new(fuzz)),
+        );
+
+        Self { flags, ..metal }
     }
-}
 
-impl BsdfMapping for SimpleMetalBsdf {
-    fn sample(&self, query: &BsdfQuery) -> BsdfResult {
-        let scattering_fraction = self.reflectance;
+    /// Construct a new metal with textured reflectance and fuzz. The fuzz can
+    /// vanish at some points of the surface, so the metal reports both a glossy
+    /// and a specular lobe.
+    pub fn from_textures(reflectance: Arc<dyn Texture<Vector3<f32>>>, fuzz: Arc<dyn Texture<f32>>) -> Self {
+        let flags = BsdfFlags::GLOSSY | BsdfFlags::DELTA | BsdfFlags::REFLECTION;
 
-        BsdfResult::new(
-            query.ray_incoming,
-            query.ray_outgoing,
-            query.point,
-            query.normal,
-            scattering_fraction,
-        )
+        Self { reflectance, fuzz, flags, }
     }
 }
 
-#[derive(Copy, Clone, Debug, Default)]
-pub struct SimpleMetalBsdfQuerySampler {}
+/// The probability density with respect to solid angle of the direction of 
+/// `reflected_direction + fuzz * s`, with `s` uniformly distributed in the unit
+/// ball.
+///
+/// The density is the fraction of the volume of the fuzz ball lying in the cone 
+/// of directions around `ray_outgoing`, which is the integral of `t^2` over the 
+/// chord of the ray along `ray_outgoing` through the ball, divided by the volume
+/// of the ball.
+fn pdf_fuzzed_reflection(reflected_direction: &Vector3<f32>, ray_outgoing: &Vector3<f32>, fuzz: f32) -> f32 {
+    let ray_outgoing = ray_outgoing.normalize();
+    let cos_theta = ray_outgoing.dot(reflected_direction);
+    // Compute the half chord from the sine instead of the cosine to avoid 
+    // cancellation close to the reflected direction.
+    let sin_theta_squared = ray_outgoing.cross(reflected_direction).magnitude_squared();
+    let discriminant = fuzz * fuzz - sin_theta_squared;
+    if discriminant <= 0_f32 {
+        return 0_f32;
+    }
 
-impl SimpleMetalBsdfQuerySampler {
-    pub fn new() -> Self {
-        Self {}
+    let half_chord = f32::sqrt(discriminant);
+    let t_far = cos_theta + half_chord;
+    if t_far <= 0_f32 {
+        return 0_f32;
     }
-}
 
-impl BsdfQuerySampler for SimpleMetalBsdfQuerySampler {
-    type Bsdf = SimpleMetalBsdf;
+    // The ray starts inside the ball when the fuzz is at least one.
+    let t_near = f32::max(cos_theta - half_chord, 0_f32);
+    let chord_integral = (t_far - t_near) * (t_far * t_far + t_far * t_near + t_near * t_near);
+
+    chord_integral / (4_f32 * std::f32::consts::PI * fuzz * fuzz * fuzz)
+}
 
+impl Bsdf for SimpleMetalBsdf {
     #[inline]
-    fn sample(
-        &self, 
-        bsdf: &Self::Bsdf, 
-        ray_incoming: &Vector3<f32>, 
-        normal: &Vector3<f32>, 
-        point: &Vector3<f32>,
-        sampler: &mut SphereSampler) -> BsdfQuery
-    {
-        let reflected_direction = ray_incoming.reflect(normal);
-        let fuzzed_vector = sampler.sample_unit_sphere() * bsdf.fuzz;
-        let ray_outgoing = reflected_direction + fuzzed_vector;
-
-        BsdfQuery::new(*ray_incoming, ray_outgoing, *point, *normal)
+    fn flags(&self) -> BsdfFlags {
+        self.flags
+    }
+
+    /// The fuzzed reflection weighs every sample by the reflectance, so the 
+    /// glossy lobe is the sampling density scaled by the reflectance. Points 
+    /// without fuzz reflect specularly, and evaluate to zero.
+    fn evaluate(&self, query: &BsdfQuery) -> Vector3<f32> {
+        let cos_theta_outgoing = abs_cos_theta(&query.ray_outgoing.normalize());
+        if cos_theta_outgoing == 0_f32 {
+            return Vector3::zero();
+        }
+
+        let pdf = self.pdf(query);
+        if pdf == 0_f32 {
+            return Vector3::zero();
+        }
+
+        self.reflectance.evaluate(&query.texture_query()) * (pdf / cos_theta_outgoing)
     }
-}
 
+    fn pdf(&self, query: &BsdfQuery) -> f32 {
+        let fuzz = self.fuzz.evaluate(&query.texture_query());
+        if fuzz <= 0_f32 || !is_reflection(&query.ray_incoming, &query.ray_outgoing) {
+            return 0_f32;
+        }
+
+        let reflected_direction = reflect_local(&query.ray_incoming.normalize());
+
+        pdf_fuzzed_reflection(&reflected_direction, &query.ray_outgoing, fuzz)
+    }
+
+    fn sample(&self, query: &BsdfSampleQuery, sampler: &mut SphereSampler) -> Option<BsdfResult> {
+        let texture_query = query.texture_query();
+        let reflectance = self.reflectance.evaluate(&texture_query);
+        let fuzz = self.fuzz.evaluate(&texture_query);
+        let reflected_direction = reflect_local(&query.ray_incoming.normalize());
+        if fuzz <= 0_f32 {
+            let flags = BsdfFlags::DELTA | BsdfFlags::REFLECTION;
+
+            return Some(BsdfResult::new(reflected_direction, reflectance, 1_f32, flags));
+        }
+
+        let fuzzed_vector = sampler.sample_unit_sphere() * fuzz;
+        let ray_outgoing = (reflected_direction + fuzzed_vector).normalize();
+        
+        // Fuzzing can push the reflected ray beneath the surface, in which case 
+        // the surface absorbs it.
+        if !is_reflection(&query.ray_incoming, &ray_outgoing) {
+            return None;
+        }
+
+        let pdf = pdf_fuzzed_reflection(&reflected_direction, &ray_outgoing, fuzz);
+        if pdf == 0_f32 {
+            return None;
+        }
+        let flags = BsdfFlags::GLOSSY | BsdfFlags::REFLECTION;
+
+        Some(BsdfResult::new(ray_outgoing, reflectance, pdf, flags))
+    }
+}
@@ -0,0 +1,75 @@
+use crate::core::*;
+use crate::texture::*;
+use cglinalg::{
+    Vector3,
+};
+use std::sync::Arc;
+
+
+/// The boundary of a subsurface scattering material.
+///
+/// A subsurface scattering material is an object whose interior is filled with a
+/// dense scattering medium, such as one constructed with 
+/// [`HomogeneousMedium::from_albedo`](crate::medium::HomogeneousMedium::from_albedo),
+/// and whose surface has this scattering function. The path tracer then performs
+/// a random walk through the interior of the object between the point where light
+/// enters and the point where it leaves.
+///
+/// The surface reflects light specularly in proportion to the Fresnel 
+/// reflectance of the interface, and otherwise lets it through without 
+/// refraction, so that light sources remain visible from inside the object.
+#[derive(Clone, Debug)]
+pub struct SubsurfaceBsdf {
+    refraction_index: Arc<dyn Texture<f32>>,
+}
+
+impl SubsurfaceBsdf {
+    pub fn new(refraction_index: f32) -> Self {
+        Self::from_texture(Arc::new(ConstantTexture::new(refraction_index)))
+    }
+
+    pub fn from_texture(refraction_index: Arc<dyn Texture<f32>>) -> Self {
+        Self { refraction_index, }
+    }
+}
+
+impl Bsdf for SubsurfaceBsdf {
+    #[inline]
+    fn flags(&self) -> BsdfFlags {
+        BsdfFlags::DELTA | BsdfFlags::REFLECTION | BsdfFlags::TRANSMISSION | BsdfFlags::PASS_THROUGH
+    }
+
+    fn evaluate(&self, _query: &BsdfQuery) -> Vector3<f32> {
+        Vector3::zero()
+    }
+
+    fn pdf(&self, _query: &BsdfQuery) -> f32 {
+        0_f32
+    }
+
+    fn sample(&self, query: &BsdfSampleQuery, sampler: &mut SphereSampler) -> Option<BsdfResult> {
+        let refraction_index = self.refraction_index.evaluate(&query.texture_query());
+        let r0 = ((1_f32 - refraction_index) / (1_f32 + refraction_index)) * ((1_f32 - refraction_index) / (1_f32 + refraction_index));
+        let reflection_prob = r0 + (1_f32 - r0) * f32::powf(1_f32 - abs_cos_theta(&query.ray_incoming), 5_f32);
+
+        // Choose between reflection and transmission in proportion to the Fresnel
+        // reflectance, so that the throughput weight of either choice is one.
+        let result = if sampler.sample_f32() < reflection_prob {
+            BsdfResult::new(
+                reflect_local(&query.ray_incoming),
+                Vector3::from_fill(1_f32),
+                reflection_prob,
+                BsdfFlags::DELTA | BsdfFlags::REFLECTION,
+            )
+        } else {
+            BsdfResult::new(
+                query.ray_incoming,
+                Vector3::from_fill(1_f32),
+                1_f32 - reflection_prob,
+                BsdfFlags::DELTA | BsdfFlags::TRANSMISSION,
+            )
+        };
+
+        Some(result)
+    }
+}
@@ -1,11 +1,64 @@
 use crate::core::*;
+use crate::aperture::*;
 use cglinalg::{
     Magnitude, 
     Vector3
 };
 
 
-#[derive(Copy, Clone)]
+/// The physical parameters of a camera. 
+///
+/// The focal length and the size of the sensor are measured in millimetres, 
+/// while the scene is measured in metres.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PhysicalCameraSettings {
+    /// The focal length of the lens.
+    pub focal_length: f32,
+    /// The ratio of the focal length to the diameter of the opening of the lens.
+    pub f_number: f32,
+    pub sensor_width: f32,
+    pub sensor_height: f32,
+}
+
+impl PhysicalCameraSettings {
+    /// Construct the settings of a camera with a lens of focal length 
+    /// `focal_length` stopped down to the f-number `f_number`, and a full frame
+    /// sensor of 36 by 24 millimetres.
+    pub fn new(focal_length: f32, f_number: f32) -> Self {
+        Self { focal_length, f_number, sensor_width: 36_f32, sensor_height: 24_f32, }
+    }
+
+    /// Set the size of the sensor of the camera.
+    pub fn with_sensor_size(mut self, sensor_width: f32, sensor_height: f32) -> Self {
+        self.sensor_width = sensor_width;
+        self.sensor_height = sensor_height;
+        self
+    }
+
+    /// Returns the vertical field of view of the camera in degrees.
+    #[inline]
+    pub fn v_fov(&self) -> f32 {
+        2_f32 * f32::atan(self.sensor_height / (2_f32 * self.focal_length)) * 180_f32 / std::f32::consts::PI
+    }
+
+    /// Returns the ratio of the width of the sensor to its height.
+    #[inline]
+    pub fn aspect(&self) -> f32 {
+        self.sensor_width / self.sensor_height
+    }
+
+    /// Returns the diameter of the opening of the lens in metres.
+    #[inline]
+    pub fn aperture(&self) -> f32 {
+        if self.f_number > 0_f32 {
+            self.focal_length / self.f_number / 1000_f32
+        } else {
+            0_f32
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct Camera {
     eye: Vector3<f32>,
     lower_left_corner: Vector3<f32>,
@@ -15,6 +68,20 @@ pub struct Camera {
     v: Vector3<f32>,
     lens_radius: f32,
     forward: Vector3<f32>,
+    half_width: f32,
+    half_height: f32,
+    focus_dist: f32,
+    look_from: Vector3<f32>,
+    look_at: Vector3<f32>,
+    look_from_end: Vector3<f32>,
+    look_at_end: Vector3<f32>,
+    v_up: Vector3<f32>,
+    v_fov: f32,
+    aspect: f32,
+    aperture: f32,
+    shutter_open: f32,
+    shutter_close: f32,
+    aperture_shape: ApertureShape,
 }
 
 impl Camera {
@@ -54,9 +121,256 @@ impl Camera {
             v,
             lens_radius,
             forward,
+            half_width,
+            half_height,
+            focus_dist,
+            look_from,
+            look_at,
+            look_from_end: look_from,
+            look_at_end: look_at,
+            v_up,
+            v_fov,
+            aspect,
+            aperture,
+            shutter_open: 0_f32,
+            shutter_close: 0_f32,
+            aperture_shape: ApertureShape::Circle,
         }
     }
 
+    /// Construct a camera looking from `look_from` towards `look_at` with the 
+    /// field of view, aspect ratio, and aperture given by the physical 
+    /// parameters `settings`, focused at the distance `focus_dist`.
+    pub fn from_physical(
+        look_from: Vector3<f32>,
+        look_at: Vector3<f32>,
+        v_up: Vector3<f32>,
+        settings: &PhysicalCameraSettings,
+        focus_dist: f32) -> Camera
+    {
+        Camera::new(look_from, look_at, v_up, settings.v_fov(), settings.aspect(), settings.aperture(), focus_dist)
+    }
+
+    /// Set the shape of the opening of the lens, which gives out of focus 
+    /// highlights their shape. The default is a circle.
+    pub fn with_aperture_shape(mut self, aperture_shape: ApertureShape) -> Self {
+        self.aperture_shape = aperture_shape;
+        self
+    }
+
+    /// Focus the camera at the distance `focus_dist` along its viewing direction.
+    pub fn with_focus_dist(self, focus_dist: f32) -> Self {
+        self.reframe(self.look_from, self.look_at, focus_dist)
+    }
+
+    #[inline]
+    pub fn aperture_shape(&self) -> &ApertureShape {
+        &self.aperture_shape
+    }
+
+    /// Returns the distance along the viewing direction of the camera at which 
+    /// points are in focus.
+    #[inline]
+    pub fn focus_dist(&self) -> f32 {
+        self.focus_dist
+    }
+
+    /// Returns the distance of the point `point` from the camera along its 
+    /// viewing direction.
+    #[inline]
+    pub fn depth(&self, point: &Vector3<f32>) -> f32 {
+        -(point - self.eye).dot(&self.forward)
+    }
+
+    /// Rebuild the camera looking from `look_from` towards `look_at` and focused
+    /// at the distance `focus_dist`, keeping its motion, shutter, and aperture.
+    fn reframe(&self, look_from: Vector3<f32>, look_at: Vector3<f32>, focus_dist: f32) -> Camera {
+        let camera = Camera::new(look_from, look_at, self.v_up, self.v_fov, self.aspect, self.aperture, focus_dist);
+
+        Camera {
+            look_from: self.look_from,
+            look_at: self.look_at,
+            look_from_end: self.look_from_end,
+            look_at_end: self.look_at_end,
+            shutter_open: self.shutter_open,
+            shutter_close: self.shutter_close,
+            aperture_shape: self.aperture_shape.clone(),
+            ..camera
+        }
+    }
+
+    /// Set the interval of time over which the shutter of the camera is open. 
+    ///
+    /// Each camera ray carries a time sampled uniformly from the shutter interval,
+    /// so objects and cameras that move while the shutter is open are blurred
+    /// along their motion. A camera whose shutter closes when it opens takes
+    /// an instantaneous exposure at the opening time.
+    pub fn with_shutter(mut self, shutter_open: f32, shutter_close: f32) -> Self {
+        self.shutter_open = shutter_open;
+        self.shutter_close = f32::max(shutter_open, shutter_close);
+
+        self
+    }
+
+    /// Move the camera while its shutter is open. The camera looks from 
+    /// `look_from` towards `look_at` when the shutter opens, and moves linearly to 
+    /// look from `look_from_end` towards `look_at_end` by the time the shutter
+    /// closes.
+    pub fn with_motion(mut self, look_from_end: Vector3<f32>, look_at_end: Vector3<f32>) -> Self {
+        self.look_from_end = look_from_end;
+        self.look_at_end = look_at_end;
+
+        self
+    }
+
+    /// Returns the interval of time `(open, close)` over which the shutter of the
+    /// camera is open.
+    #[inline]
+    pub fn shutter(&self) -> (f32, f32) {
+        (self.shutter_open, self.shutter_close)
+    }
+
+    /// Determine whether the camera moves while its shutter is open.
+    #[inline]
+    pub fn is_moving(&self) -> bool {
+        self.look_from_end != self.look_from || self.look_at_end != self.look_at
+    }
+
+    /// Sample a time uniformly from the shutter interval of the camera.
+    ///
+    /// No random numbers are drawn for a camera with an instantaneous shutter.
+    #[inline]
+    pub fn sample_time(&self, sampler: &mut SphereSampler) -> f32 {
+        if self.shutter_close > self.shutter_open {
+            self.shutter_open + (self.shutter_close - self.shutter_open) * sampler.sample_f32()
+        } else {
+            self.shutter_open
+        }
+    }
+
+    /// Returns the camera as it is positioned at the time `time`. The time is 
+    /// clamped to the shutter interval.
+    pub fn at_time(&self, time: f32) -> Camera {
+        if !self.is_moving() || self.shutter_close <= self.shutter_open {
+            return self.clone();
+        }
+
+        let s = f32::clamp((time - self.shutter_open) / (self.shutter_close - self.shutter_open), 0_f32, 1_f32);
+        let look_from = self.look_from + (self.look_from_end - self.look_from) * s;
+        let look_at = self.look_at + (self.look_at_end - self.look_at) * s;
+
+        self.reframe(look_from, look_at, self.focus_dist)
+    }
+
+    /// Sample a point on the opening of the lens of the camera.
+    #[inline]
+    pub fn sample_lens(&self, sampler: &mut SphereSampler) -> Vector3<f32> {
+        let rd = self.aperture_shape.sample(sampler) * self.lens_radius;
+        let offset = self.u * rd.x + self.v * rd.y;
+
+        self.eye + offset
+    }
+
+    /// Returns the area of the opening of the lens of the camera, weighted by the
+    /// fraction of light the opening lets through. A pinhole camera has a lens of
+    /// unit area by convention.
+    #[inline]
+    pub fn lens_area(&self) -> f32 {
+        if self.lens_radius > 0_f32 {
+            self.aperture_shape.area() * self.lens_radius * self.lens_radius
+        } else {
+            1_f32
+        }
+    }
+
+    /// Evaluate the fraction of light the opening of the lens lets through at the
+    /// point `lens_position` on the lens.
+    pub fn lens_transmission(&self, lens_position: &Vector3<f32>) -> f32 {
+        if self.lens_radius > 0_f32 {
+            let offset = (lens_position - self.eye) / self.lens_radius;
+
+            self.aperture_shape.evaluate(offset.dot(&self.u), offset.dot(&self.v))
+        } else {
+            1_f32
+        }
+    }
+
+    /// Evaluate the probability density with respect to area on the lens that 
+    /// [`Camera::sample_lens`] samples the point `lens_position`.
+    #[inline]
+    pub fn pdf_lens(&self, lens_position: &Vector3<f32>) -> f32 {
+        self.lens_transmission(lens_position) / self.lens_area()
+    }
+
+    /// Returns the area of the film of the camera placed at unit distance from
+    /// the lens.
+    #[inline]
+    fn film_area(&self) -> f32 {
+        4_f32 * self.half_width * self.half_height
+    }
+
+    /// Returns the cosine of the angle between a direction leaving the lens and 
+    /// the viewing direction of the camera.
+    #[inline]
+    fn cos_theta(&self, direction: &Vector3<f32>) -> f32 {
+        -direction.normalize().dot(&self.forward)
+    }
+
+    /// Project a point in world space onto the film of the camera through a point
+    /// on its lens. The result is the pair of film coordinates `(u, v)` that 
+    /// [`Camera::cast_ray`] accepts, or `None` when the point lies outside the 
+    /// field of view.
+    pub fn project(&self, lens_position: &Vector3<f32>, point: &Vector3<f32>) -> Option<(f32, f32)> {
+        let direction = point - lens_position;
+        let cos_theta = self.cos_theta(&direction);
+        if cos_theta <= 0_f32 {
+            return None;
+        }
+
+        // Find where the ray from the lens through the point crosses the plane of
+        // focus, on which the film coordinates are laid out.
+        let t = self.focus_dist / (-direction.dot(&self.forward));
+        let point_in_focus = lens_position + direction * t;
+        let offset = point_in_focus - self.lower_left_corner;
+        let u = offset.dot(&self.horizontal) / self.horizontal.magnitude_squared();
+        let v = offset.dot(&self.vertical) / self.vertical.magnitude_squared();
+        if (0_f32..=1_f32).contains(&u) && (0_f32..=1_f32).contains(&v) {
+            Some((u, v))
+        } else {
+            None
+        }
+    }
+
+    /// Evaluate the importance the camera emits along a ray leaving its lens at 
+    /// the point `lens_position` in the direction `direction`, for a ray that 
+    /// lands on the film.
+    ///
+    /// The importance is normalized so that integrating it over the lens and the
+    /// film gives one. It is proportional to the fraction of light the opening 
+    /// of the lens lets through at `lens_position`.
+    pub fn importance(&self, lens_position: &Vector3<f32>, direction: &Vector3<f32>) -> f32 {
+        let cos_theta = self.cos_theta(direction);
+        if cos_theta <= 0_f32 {
+            return 0_f32;
+        }
+
+        let cos_theta_squared = cos_theta * cos_theta;
+
+        self.lens_transmission(lens_position) / (self.film_area() * self.lens_area() * cos_theta_squared * cos_theta_squared)
+    }
+
+    /// Evaluate the probability density with respect to solid angle that 
+    /// [`Camera::cast_ray`] generates a ray in the direction `direction`, for 
+    /// film coordinates chosen uniformly at random.
+    pub fn pdf_direction(&self, direction: &Vector3<f32>) -> f32 {
+        let cos_theta = self.cos_theta(direction);
+        if cos_theta <= 0_f32 {
+            return 0_f32;
+        }
+
+        1_f32 / (self.film_area() * cos_theta * cos_theta * cos_theta)
+    }
+
     pub fn cast_ray(&self, sampler: &mut SphereSampler, u: f32, v: f32) -> Ray {
         // TODO: Cast a ray in eye space, and convert is back to world space?
         // That is, all the aspects of the camera construction, namely, lens position, lower left corner, horizontal, vertical,
@@ -68,15 +382,28 @@ impl Camera {
         // How do we convert between them?
         //
         // Ray<EyeSpace> -> Ray<WorldSpace>
-        let rd = sampler.sample_unit_disk() * self.lens_radius;
-        let offset = self.u * rd.x + self.v * rd.y;
-        let lens_position = self.eye + offset;
+        let time = self.sample_time(sampler);
+        let camera = self.at_time(time);
+        let lens_position = camera.sample_lens(sampler);
         
         // TODO: Camera ray range?
         Ray::new(
             lens_position,
-            self.lower_left_corner + self.horizontal * u + self.vertical * v - lens_position,
+            camera.lower_left_corner + camera.horizontal * u + camera.vertical * v - lens_position,
+        )
+        .with_time(time)
+    }
+
+    /// Cast a ray from the center of the lens through the point on the film with
+    /// the film coordinates `(u, v)` at the time the shutter opens.
+    pub fn cast_pinhole_ray(&self, u: f32, v: f32) -> Ray {
+        let camera = self.at_time(self.shutter_open);
+
+        Ray::new(
+            camera.eye,
+            camera.lower_left_corner + camera.horizontal * u + camera.vertical * v - camera.eye,
         )
+        .with_time(self.shutter_open)
     }
 
     #[inline]
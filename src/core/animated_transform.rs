@@ -0,0 +1,368 @@
+use crate::core::bounding_box::*;
+use cglinalg::{
+    Vector3,
+    Matrix4x4,
+    Magnitude,
+};
+
+
+/// The number of steps per keyframe interval over which the bounds of a moving
+/// bounding box are sampled.
+const MOTION_BOUNDS_STEPS: usize = 32;
+
+/// The largest number of iterations of the polar decomposition.
+const MAX_POLAR_DECOMPOSITION_ITERATIONS: usize = 100;
+
+/// A 3x3 matrix stored as its columns.
+type Matrix3 = [Vector3<f32>; 3];
+
+#[inline]
+fn matrix3_mul_vector(matrix: &Matrix3, vector: &Vector3<f32>) -> Vector3<f32> {
+    matrix[0] * vector.x + matrix[1] * vector.y + matrix[2] * vector.z
+}
+
+/// Compute the determinant of a 3x3 matrix.
+#[inline]
+fn matrix3_determinant(matrix: &Matrix3) -> f32 {
+    matrix[0].dot(&matrix[1].cross(&matrix[2]))
+}
+
+/// Compute the inverse transpose of a 3x3 matrix, whose columns are the cross 
+/// products of pairs of columns of the matrix.
+fn matrix3_inverse_transpose(matrix: &Matrix3) -> Matrix3 {
+    let determinant = matrix3_determinant(matrix);
+
+    [
+        matrix[1].cross(&matrix[2]) / determinant,
+        matrix[2].cross(&matrix[0]) / determinant,
+        matrix[0].cross(&matrix[1]) / determinant,
+    ]
+}
+
+/// A unit quaternion representing a rotation.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Rotation {
+    w: f32,
+    x: f32,
+    y: f32,
+    z: f32,
+}
+
+impl Rotation {
+    fn identity() -> Self {
+        Self { w: 1_f32, x: 0_f32, y: 0_f32, z: 0_f32, }
+    }
+
+    fn from_axis_angle(axis: &Vector3<f32>, angle: f32) -> Self {
+        let axis = axis.normalize();
+        let (sin_half_angle, cos_half_angle) = f32::sin_cos(0.5 * angle);
+
+        Self {
+            w: cos_half_angle,
+            x: axis.x * sin_half_angle,
+            y: axis.y * sin_half_angle,
+            z: axis.z * sin_half_angle,
+        }
+    }
+
+    /// Convert a rotation matrix into a quaternion, starting from the largest 
+    /// component for numerical stability.
+    fn from_matrix(matrix: &Matrix3) -> Self {
+        // The entry in row `r` and column `c` is `matrix[c][r]`.
+        let m = |r: usize, c: usize| matrix[c][r];
+        let trace = m(0, 0) + m(1, 1) + m(2, 2);
+        let rotation = if trace > 0_f32 {
+            let s = 2_f32 * f32::sqrt(trace + 1_f32);
+            Self { w: 0.25 * s, x: (m(2, 1) - m(1, 2)) / s, y: (m(0, 2) - m(2, 0)) / s, z: (m(1, 0) - m(0, 1)) / s, }
+        } else if m(0, 0) > m(1, 1) && m(0, 0) > m(2, 2) {
+            let s = 2_f32 * f32::sqrt(1_f32 + m(0, 0) - m(1, 1) - m(2, 2));
+            Self { w: (m(2, 1) - m(1, 2)) / s, x: 0.25 * s, y: (m(0, 1) + m(1, 0)) / s, z: (m(0, 2) + m(2, 0)) / s, }
+        } else if m(1, 1) > m(2, 2) {
+            let s = 2_f32 * f32::sqrt(1_f32 + m(1, 1) - m(0, 0) - m(2, 2));
+            Self { w: (m(0, 2) - m(2, 0)) / s, x: (m(0, 1) + m(1, 0)) / s, y: 0.25 * s, z: (m(1, 2) + m(2, 1)) / s, }
+        } else {
+            let s = 2_f32 * f32::sqrt(1_f32 + m(2, 2) - m(0, 0) - m(1, 1));
+            Self { w: (m(1, 0) - m(0, 1)) / s, x: (m(0, 2) + m(2, 0)) / s, y: (m(1, 2) + m(2, 1)) / s, z: 0.25 * s, }
+        };
+
+        rotation.normalize()
+    }
+
+    fn to_matrix(self) -> Matrix3 {
+        let Self { w, x, y, z } = self;
+
+        [
+            Vector3::new(1_f32 - 2_f32 * (y * y + z * z), 2_f32 * (x * y + w * z), 2_f32 * (x * z - w * y)),
+            Vector3::new(2_f32 * (x * y - w * z), 1_f32 - 2_f32 * (x * x + z * z), 2_f32 * (y * z + w * x)),
+            Vector3::new(2_f32 * (x * z + w * y), 2_f32 * (y * z - w * x), 1_f32 - 2_f32 * (x * x + y * y)),
+        ]
+    }
+
+    #[inline]
+    fn dot(&self, other: &Self) -> f32 {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    fn normalize(self) -> Self {
+        let magnitude = f32::sqrt(self.dot(&self));
+
+        Self { w: self.w / magnitude, x: self.x / magnitude, y: self.y / magnitude, z: self.z / magnitude, }
+    }
+
+    /// Returns the angle of the rotation taking one rotation to another.
+    fn angle_to(&self, other: &Self) -> f32 {
+        2_f32 * f32::acos(f32::abs(self.dot(other)).min(1_f32))
+    }
+
+    /// Spherically interpolate between two rotations along the shorter arc.
+    fn slerp(&self, other: &Self, s: f32) -> Self {
+        let mut cos_theta = self.dot(other);
+        let other = if cos_theta < 0_f32 {
+            cos_theta = -cos_theta;
+            Self { w: -other.w, x: -other.x, y: -other.y, z: -other.z, }
+        } else {
+            *other
+        };
+        let (weight_self, weight_other) = if cos_theta > 0.9995 {
+            // Nearly equal rotations interpolate linearly without dividing by a 
+            // vanishing sine.
+            (1_f32 - s, s)
+        } else {
+            let theta = f32::acos(cos_theta);
+            let sin_theta = f32::sin(theta);
+
+            (f32::sin((1_f32 - s) * theta) / sin_theta, f32::sin(s * theta) / sin_theta)
+        };
+
+        Self {
+            w: weight_self * self.w + weight_other * other.w,
+            x: weight_self * self.x + weight_other * other.x,
+            y: weight_self * self.y + weight_other * other.y,
+            z: weight_self * self.z + weight_other * other.z,
+        }
+        .normalize()
+    }
+}
+
+/// An affine transformation decomposed into a translation, a rotation, and a
+/// stretch, applied in the reverse order.
+///
+/// The stretch is a symmetric matrix holding the scale and the shear of the 
+/// transformation. Interpolating the parts separately keeps rotating objects 
+/// rigid between keyframes, which interpolating the matrices entry by entry
+/// would not.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DecomposedTransform {
+    pub translation: Vector3<f32>,
+    rotation: Rotation,
+    stretch: Matrix3,
+}
+
+impl DecomposedTransform {
+    /// Construct a transformation scaling along the coordinate axes by `scale`,
+    /// then rotating by the angle `angle` in radians about the axis `axis`, 
+    /// then translating by `translation`.
+    pub fn new(translation: Vector3<f32>, axis: Vector3<f32>, angle: f32, scale: Vector3<f32>) -> Self {
+        let stretch = [
+            Vector3::new(scale.x, 0_f32, 0_f32),
+            Vector3::new(0_f32, scale.y, 0_f32),
+            Vector3::new(0_f32, 0_f32, scale.z),
+        ];
+        let rotation = if angle == 0_f32 {
+            Rotation::identity()
+        } else {
+            Rotation::from_axis_angle(&axis, angle)
+        };
+
+        Self { translation, rotation, stretch, }
+    }
+
+    /// Decompose an affine transformation matrix.
+    ///
+    /// The rotation comes from the polar decomposition of the linear part of the
+    /// matrix, which averages the matrix with its inverse transpose until they 
+    /// agree. A reflection ends up in the stretch.
+    pub fn from_matrix(matrix: &Matrix4x4<f32>) -> Self {
+        let translation = (matrix * Vector3::zero().extend(1_f32)).contract();
+        let linear: Matrix3 = [
+            (matrix * Vector3::unit_x().extend(0_f32)).contract(),
+            (matrix * Vector3::unit_y().extend(0_f32)).contract(),
+            (matrix * Vector3::unit_z().extend(0_f32)).contract(),
+        ];
+
+        let mut rotation_matrix = if matrix3_determinant(&linear) < 0_f32 {
+            [-linear[0], -linear[1], -linear[2]]
+        } else {
+            linear
+        };
+        for _ in 0..MAX_POLAR_DECOMPOSITION_ITERATIONS {
+            let inverse_transpose = matrix3_inverse_transpose(&rotation_matrix);
+            let next = [
+                (rotation_matrix[0] + inverse_transpose[0]) * 0.5,
+                (rotation_matrix[1] + inverse_transpose[1]) * 0.5,
+                (rotation_matrix[2] + inverse_transpose[2]) * 0.5,
+            ];
+            let change = (0..3)
+                .map(|i| (next[i] - rotation_matrix[i]).magnitude())
+                .fold(0_f32, f32::max);
+            rotation_matrix = next;
+            if change < 1e-6 {
+                break;
+            }
+        }
+
+        // The stretch is the rotation undone from the linear part.
+        let stretch = [0, 1, 2].map(|c| {
+            Vector3::new(
+                rotation_matrix[0].dot(&linear[c]),
+                rotation_matrix[1].dot(&linear[c]),
+                rotation_matrix[2].dot(&linear[c]),
+            )
+        });
+
+        Self { translation, rotation: Rotation::from_matrix(&rotation_matrix), stretch, }
+    }
+
+    /// Compose the parts of the transformation back into a matrix.
+    pub fn to_matrix(&self) -> Matrix4x4<f32> {
+        let rotation = self.rotation.to_matrix();
+        let linear = self.stretch.map(|column| matrix3_mul_vector(&rotation, &column));
+
+        Matrix4x4::new(
+            linear[0].x, linear[0].y, linear[0].z, 0_f32,
+            linear[1].x, linear[1].y, linear[1].z, 0_f32,
+            linear[2].x, linear[2].y, linear[2].z, 0_f32,
+            self.translation.x, self.translation.y, self.translation.z, 1_f32,
+        )
+    }
+
+    /// Interpolate between two transformations, linearly in the translation and
+    /// the stretch, and spherically in the rotation.
+    pub fn interpolate(&self, other: &Self, s: f32) -> Self {
+        let translation = self.translation + (other.translation - self.translation) * s;
+        let rotation = self.rotation.slerp(&other.rotation, s);
+        let stretch = [0, 1, 2].map(|c| self.stretch[c] + (other.stretch[c] - self.stretch[c]) * s);
+
+        Self { translation, rotation, stretch, }
+    }
+}
+
+/// A transformation animated by keyframes.
+///
+/// Between two keyframes the transformation interpolates the decomposed 
+/// keyframe transformations, and before the first and after the last keyframe 
+/// it holds still. A rotation between two keyframes takes the shorter way 
+/// around, so turns of half a revolution or more need more keyframes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimatedTransform {
+    times: Vec<f32>,
+    matrices: Vec<Matrix4x4<f32>>,
+    decompositions: Vec<DecomposedTransform>,
+}
+
+impl AnimatedTransform {
+    /// Construct a transformation that holds still at the matrix `matrix`.
+    pub fn new(matrix: Matrix4x4<f32>) -> Self {
+        Self::from_keyframes(vec![(0_f32, matrix)])
+    }
+
+    /// Construct an animated transformation from a list of keyframes, each a 
+    /// time together with the transformation matrix at that time.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if there are no keyframes.
+    pub fn from_keyframes(mut keyframes: Vec<(f32, Matrix4x4<f32>)>) -> Self {
+        assert!(!keyframes.is_empty(), "An animated transformation needs at least one keyframe.");
+        keyframes.sort_by(|keyframe1, keyframe2| keyframe1.0.total_cmp(&keyframe2.0));
+        let times = keyframes.iter().map(|keyframe| keyframe.0).collect::<Vec<f32>>();
+        let matrices = keyframes.iter().map(|keyframe| keyframe.1).collect::<Vec<Matrix4x4<f32>>>();
+        let decompositions = matrices
+            .iter()
+            .map(DecomposedTransform::from_matrix)
+            .collect::<Vec<DecomposedTransform>>();
+
+        Self { times, matrices, decompositions, }
+    }
+
+    /// Determine whether the transformation changes over time.
+    #[inline]
+    pub fn is_animated(&self) -> bool {
+        self.matrices.len() > 1
+    }
+
+    /// Returns the transformation matrix at the first keyframe.
+    #[inline]
+    pub fn start_matrix(&self) -> Matrix4x4<f32> {
+        self.matrices[0]
+    }
+
+    /// Find the keyframe interval containing a time, and the fraction of the 
+    /// interval elapsed at that time. Returns `None` outside the keyframes.
+    fn interval(&self, time: f32) -> Option<(usize, f32)> {
+        let last = self.times.len() - 1;
+        if time <= self.times[0] || time >= self.times[last] {
+            return None;
+        }
+
+        let index = self.times.partition_point(|&keyframe_time| keyframe_time <= time) - 1;
+        let duration = self.times[index + 1] - self.times[index];
+
+        Some((index, (time - self.times[index]) / duration))
+    }
+
+    /// Compute the transformation matrix at a time.
+    pub fn matrix_at(&self, time: f32) -> Matrix4x4<f32> {
+        match self.interval(time) {
+            Some((index, s)) => {
+                self.decompositions[index].interpolate(&self.decompositions[index + 1], s).to_matrix()
+            }
+            None if time <= self.times[0] => self.matrices[0],
+            None => self.matrices[self.matrices.len() - 1],
+        }
+    }
+
+    /// Compute a bounding box containing the image of a bounding box at all 
+    /// times.
+    ///
+    /// The bounds are sampled at steps along each keyframe interval, and padded
+    /// by how far a rotating corner strays from the line between two steps.
+    pub fn bounds(&self, bounds: &BoundingBox) -> BoundingBox {
+        if !self.is_animated() || bounds.is_empty() || bounds.is_infinite() {
+            return bounds.transform(&self.matrices[0]);
+        }
+
+        let mut result = BoundingBox::empty();
+        for index in 0..(self.times.len() - 1) {
+            let start = &self.decompositions[index];
+            let end = &self.decompositions[index + 1];
+            for step in 0..=MOTION_BOUNDS_STEPS {
+                let s = (step as f32) / (MOTION_BOUNDS_STEPS as f32);
+                result = result.union(&bounds.transform(&start.interpolate(end, s).to_matrix()));
+            }
+
+            // The stretched corners lie no farther from the center of rotation 
+            // than they do at either end of the interval.
+            let radius = [start, end]
+                .iter()
+                .map(|decomposed| {
+                    let linear = DecomposedTransform { translation: Vector3::zero(), ..**decomposed }.to_matrix();
+                    let corners = bounds.transform(&linear);
+                    let farthest_corner = Vector3::new(
+                        f32::max(f32::abs(corners.min.x), f32::abs(corners.max.x)),
+                        f32::max(f32::abs(corners.min.y), f32::abs(corners.max.y)),
+                        f32::max(f32::abs(corners.min.z), f32::abs(corners.max.z)),
+                    );
+
+                    farthest_corner.magnitude()
+                })
+                .fold(0_f32, f32::max);
+            let step_angle = start.rotation.angle_to(&end.rotation) / (MOTION_BOUNDS_STEPS as f32);
+            let padding = Vector3::from_fill(radius * (1_f32 - f32::cos(0.5 * step_angle)));
+            result = BoundingBox::new(result.min - padding, result.max + padding);
+        }
+
+        result
+    }
+}
+
@@ -0,0 +1,168 @@
+use crate::core::*;
+use cglinalg::{
+    Vector3,
+    Matrix4x4,
+};
+
+
+/// An axis-aligned bounding box.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BoundingBox {
+    /// The corner of the box with the smallest coordinates.
+    pub min: Vector3<f32>,
+    /// The corner of the box with the largest coordinates.
+    pub max: Vector3<f32>,
+}
+
+impl BoundingBox {
+    /// Construct the bounding box of two opposite corners, in any order.
+    pub fn new(corner1: Vector3<f32>, corner2: Vector3<f32>) -> Self {
+        let min = Vector3::new(
+            f32::min(corner1.x, corner2.x),
+            f32::min(corner1.y, corner2.y),
+            f32::min(corner1.z, corner2.z),
+        );
+        let max = Vector3::new(
+            f32::max(corner1.x, corner2.x),
+            f32::max(corner1.y, corner2.y),
+            f32::max(corner1.z, corner2.z),
+        );
+
+        Self { min, max, }
+    }
+
+    /// Construct a bounding box that contains no points. It is the identity of
+    /// [`BoundingBox::union`].
+    pub fn empty() -> Self {
+        Self { 
+            min: Vector3::from_fill(f32::INFINITY), 
+            max: Vector3::from_fill(f32::NEG_INFINITY),
+        }
+    }
+
+    /// Construct a bounding box that contains every point.
+    pub fn infinite() -> Self {
+        Self {
+            min: Vector3::from_fill(f32::NEG_INFINITY),
+            max: Vector3::from_fill(f32::INFINITY),
+        }
+    }
+
+    /// Determine whether a bounding box contains no points.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    /// Determine whether a bounding box extends to infinity along some axis.
+    #[inline]
+    pub fn is_infinite(&self) -> bool {
+        !(self.min.x.is_finite() && self.min.y.is_finite() && self.min.z.is_finite() &&
+            self.max.x.is_finite() && self.max.y.is_finite() && self.max.z.is_finite())
+    }
+
+    #[inline]
+    pub fn center(&self) -> Vector3<f32> {
+        (self.min + self.max) * 0.5
+    }
+
+    /// Returns the lengths of the sides of the box.
+    #[inline]
+    pub fn extent(&self) -> Vector3<f32> {
+        self.max - self.min
+    }
+
+    #[inline]
+    pub fn contains(&self, point: &Vector3<f32>) -> bool {
+        point.x >= self.min.x && point.x <= self.max.x &&
+            point.y >= self.min.y && point.y <= self.max.y &&
+            point.z >= self.min.z && point.z <= self.max.z
+    }
+
+    /// Construct the smallest bounding box containing two bounding boxes.
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox {
+            min: Vector3::new(
+                f32::min(self.min.x, other.min.x),
+                f32::min(self.min.y, other.min.y),
+                f32::min(self.min.z, other.min.z),
+            ),
+            max: Vector3::new(
+                f32::max(self.max.x, other.max.x),
+                f32::max(self.max.y, other.max.y),
+                f32::max(self.max.z, other.max.z),
+            ),
+        }
+    }
+
+    /// Construct the largest bounding box contained in two bounding boxes. The
+    /// result is empty when the boxes do not overlap.
+    pub fn intersection(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox {
+            min: Vector3::new(
+                f32::max(self.min.x, other.min.x),
+                f32::max(self.min.y, other.min.y),
+                f32::max(self.min.z, other.min.z),
+            ),
+            max: Vector3::new(
+                f32::min(self.max.x, other.max.x),
+                f32::min(self.max.y, other.max.y),
+                f32::min(self.max.z, other.max.z),
+            ),
+        }
+    }
+
+    /// Construct the smallest bounding box containing a bounding box and a point.
+    pub fn union_point(&self, point: &Vector3<f32>) -> BoundingBox {
+        self.union(&BoundingBox { min: *point, max: *point })
+    }
+
+    /// Construct the bounding box of the image of a bounding box under an affine
+    /// transformation.
+    pub fn transform(&self, matrix: &Matrix4x4<f32>) -> BoundingBox {
+        if self.is_empty() || self.is_infinite() {
+            return *self;
+        }
+
+        let mut result = BoundingBox::empty();
+        for i in 0..8 {
+            let corner = Vector3::new(
+                if i & 1 == 0 { self.min.x } else { self.max.x },
+                if i & 2 == 0 { self.min.y } else { self.max.y },
+                if i & 4 == 0 { self.min.z } else { self.max.z },
+            );
+            let corner_transformed = ((*matrix) * corner.extend(1_f32)).contract();
+            result = result.union_point(&corner_transformed);
+        }
+
+        result
+    }
+
+    /// Compute the range of ray parameters in `[t_min, t_max]` over which a ray 
+    /// lies inside the bounding box, or `None` if the ray misses the box.
+    pub fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<(f32, f32)> {
+        let mut t_enter = t_min;
+        let mut t_exit = t_max;
+        for axis in 0..3 {
+            let inverse_direction = 1_f32 / ray.direction[axis];
+            let mut t_near = (self.min[axis] - ray.origin[axis]) * inverse_direction;
+            let mut t_far = (self.max[axis] - ray.origin[axis]) * inverse_direction;
+            if t_near > t_far {
+                std::mem::swap(&mut t_near, &mut t_far);
+            }
+            // A ray parallel to a slab and lying on one of its planes gives `NaN` 
+            // here, which the comparisons below ignore.
+            if t_near > t_enter {
+                t_enter = t_near;
+            }
+            if t_far < t_exit {
+                t_exit = t_far;
+            }
+            if t_enter > t_exit {
+                return None;
+            }
+        }
+
+        Some((t_enter, t_exit))
+    }
+}
@@ -1,23 +1,159 @@
 use crate::core::*;
 use cglinalg::{
+    Vector2,
     Vector3,
 };
+use std::ops;
 
 
-pub trait BsdfMapping: std::fmt::Debug {
-    fn sample(&self, query: &BsdfQuery) -> BsdfResult;
+/// The set of scattering lobes a scattering function contains, or the lobe a
+/// scattering sample was drawn from.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BsdfFlags {
+    bits: u32,
 }
 
-pub trait BsdfQuerySampler: std::fmt::Debug {
-    type Bsdf: BsdfMapping;
+impl BsdfFlags {
+    /// The scattering function reflects light back into the hemisphere it arrived from.
+    pub const REFLECTION: BsdfFlags = BsdfFlags { bits: 1 << 0 };
+    /// The scattering function transmits light through the surface.
+    pub const TRANSMISSION: BsdfFlags = BsdfFlags { bits: 1 << 1 };
+    /// The scattering function scatters light uniformly over the hemisphere.
+    pub const DIFFUSE: BsdfFlags = BsdfFlags { bits: 1 << 2 };
+    /// The scattering function scatters light about a preferred direction.
+    pub const GLOSSY: BsdfFlags = BsdfFlags { bits: 1 << 3 };
+    /// The scattering function scatters light along a discrete set of directions
+    /// described by a Dirac delta distribution.
+    pub const DELTA: BsdfFlags = BsdfFlags { bits: 1 << 4 };
+    /// The scattering function transmits light through the surface without 
+    /// changing its direction, so that shadow rays cross the surface. Such 
+    /// surfaces mark the boundaries of participating media.
+    pub const PASS_THROUGH: BsdfFlags = BsdfFlags { bits: 1 << 5 };
 
-    fn sample(
-        &self, 
-        bsdf: &Self::Bsdf, 
-        ray_incoming: &Vector3<f32>, 
-        normal: &Vector3<f32>, 
-        point: &Vector3<f32>, 
-        sampler: &mut SphereSampler) -> BsdfQuery;
+    /// Construct an empty set of flags.
+    #[inline]
+    pub const fn empty() -> Self {
+        Self { bits: 0 }
+    }
+
+    /// Returns the raw bit representation of the flags.
+    #[inline]
+    pub const fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    /// Determine whether no flags are set.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    /// Determine whether every flag in `other` is also set in `self`.
+    #[inline]
+    pub const fn contains(&self, other: BsdfFlags) -> bool {
+        (self.bits & other.bits) == other.bits
+    }
+
+    /// Determine whether any flag in `other` is also set in `self`.
+    #[inline]
+    pub const fn intersects(&self, other: BsdfFlags) -> bool {
+        (self.bits & other.bits) != 0
+    }
+
+    #[inline]
+    pub const fn is_reflective(&self) -> bool {
+        self.intersects(Self::REFLECTION)
+    }
+
+    #[inline]
+    pub const fn is_transmissive(&self) -> bool {
+        self.intersects(Self::TRANSMISSION)
+    }
+
+    #[inline]
+    pub const fn is_diffuse(&self) -> bool {
+        self.intersects(Self::DIFFUSE)
+    }
+
+    #[inline]
+    pub const fn is_glossy(&self) -> bool {
+        self.intersects(Self::GLOSSY)
+    }
+
+    #[inline]
+    pub const fn is_delta(&self) -> bool {
+        self.intersects(Self::DELTA)
+    }
+
+    #[inline]
+    pub const fn is_pass_through(&self) -> bool {
+        self.intersects(Self::PASS_THROUGH)
+    }
+
+    /// Determine whether a scattering function with these flags has a scattering
+    /// distribution that can be evaluated for arbitrary pairs of directions.
+    #[inline]
+    pub const fn is_non_delta(&self) -> bool {
+        self.intersects(BsdfFlags { bits: Self::DIFFUSE.bits | Self::GLOSSY.bits })
+    }
+}
+
+impl ops::BitOr for BsdfFlags {
+    type Output = BsdfFlags;
+
+    #[inline]
+    fn bitor(self, other: BsdfFlags) -> Self::Output {
+        BsdfFlags { bits: self.bits | other.bits }
+    }
+}
+
+impl ops::BitOrAssign for BsdfFlags {
+    #[inline]
+    fn bitor_assign(&mut self, other: BsdfFlags) {
+        self.bits |= other.bits;
+    }
+}
+
+impl ops::BitAnd for BsdfFlags {
+    type Output = BsdfFlags;
+
+    #[inline]
+    fn bitand(self, other: BsdfFlags) -> Self::Output {
+        BsdfFlags { bits: self.bits & other.bits }
+    }
+}
+
+/// A bidirectional scattering distribution function.
+///
+/// Every direction a scattering function receives or returns is a unit vector
+/// expressed in the local shading frame of the surface, in which the shading 
+/// normal is the **z-axis**. Following the convention of the rest of the
+/// renderer, the incoming direction points along the direction of travel of the
+/// incident ray, i.e. towards the surface, and the outgoing direction points away
+/// from the surface along the direction of travel of the scattered ray.
+pub trait Bsdf: std::fmt::Debug {
+    /// Returns the set of lobes the scattering function contains.
+    fn flags(&self) -> BsdfFlags;
+
+    /// Evaluate the value of the scattering distribution for the pair of
+    /// directions in the query.
+    ///
+    /// Lobes described by a Dirac delta distribution contribute nothing to the
+    /// result.
+    fn evaluate(&self, query: &BsdfQuery) -> Vector3<f32>;
+
+    /// Evaluate the probability density, with respect to solid angle, that
+    /// [`Bsdf::sample`] generates the outgoing direction in the query given the
+    /// incoming direction.
+    ///
+    /// Lobes described by a Dirac delta distribution contribute nothing to the
+    /// result.
+    fn pdf(&self, query: &BsdfQuery) -> f32;
+
+    /// Sample an outgoing direction given an incoming direction.
+    ///
+    /// Returns `None` when the incident light is absorbed.
+    fn sample(&self, query: &BsdfSampleQuery, sampler: &mut SphereSampler) -> Option<BsdfResult>;
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -25,33 +161,97 @@ pub struct BsdfQuery {
     pub ray_incoming: Vector3<f32>,
     pub ray_outgoing: Vector3<f32>,
     pub point: Vector3<f32>,
-    pub normal: Vector3<f32>,
+    /// The scattering point in world space, for evaluating textures fixed in the
+    /// scene. This is the same as `point` unless set otherwise.
+    pub world_point: Vector3<f32>,
+    pub uv: Vector2<f32>,
 }
 
 impl BsdfQuery {
-    pub fn new(ray_incoming: Vector3<f32>, ray_outgoing: Vector3<f32>, point: Vector3<f32>, normal: Vector3<f32>) -> Self {
-        Self { ray_incoming, ray_outgoing, point, normal, }
+    pub fn new(ray_incoming: Vector3<f32>, ray_outgoing: Vector3<f32>, point: Vector3<f32>, uv: Vector2<f32>) -> Self {
+        Self { ray_incoming, ray_outgoing, point, world_point: point, uv, }
+    }
+
+    /// Construct the query for evaluating textured parameters at the scattering 
+    /// point.
+    #[inline]
+    pub fn texture_query(&self) -> TextureQuery {
+        TextureQuery::from_points(self.point, self.world_point, self.uv)
     }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
-pub struct BsdfResult {
+pub struct BsdfSampleQuery {
     pub ray_incoming: Vector3<f32>,
-    pub ray_outgoing: Vector3<f32>,
     pub point: Vector3<f32>,
-    pub normal: Vector3<f32>,
+    /// The scattering point in world space, for evaluating textures fixed in the
+    /// scene. This is the same as `point` unless set otherwise.
+    pub world_point: Vector3<f32>,
+    pub uv: Vector2<f32>,
+}
+
+impl BsdfSampleQuery {
+    pub fn new(ray_incoming: Vector3<f32>, point: Vector3<f32>, uv: Vector2<f32>) -> Self {
+        Self { ray_incoming, point, world_point: point, uv, }
+    }
+
+    /// Construct the query for evaluating textured parameters at the scattering 
+    /// point.
+    #[inline]
+    pub fn texture_query(&self) -> TextureQuery {
+        TextureQuery::from_points(self.point, self.world_point, self.uv)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BsdfResult {
+    /// The sampled outgoing direction in the local shading frame.
+    pub ray_outgoing: Vector3<f32>,
+    /// The throughput weight `f * |cos(theta)| / pdf` of the sample.
     pub scattering_fraction: Vector3<f32>,
+    /// The probability density of the sampled direction. For lobes described by
+    /// a Dirac delta distribution, this is the discrete probability of selecting
+    /// the lobe.
+    pub pdf: f32,
+    /// The lobe the sample was drawn from.
+    pub flags: BsdfFlags,
 }
 
 impl BsdfResult {
     pub fn new(
-        ray_incoming: Vector3<f32>,
         ray_outgoing: Vector3<f32>,
-        point: Vector3<f32>,
-        normal: Vector3<f32>,
-        scattering_fraction: Vector3<f32>) -> Self 
+        scattering_fraction: Vector3<f32>,
+        pdf: f32,
+        flags: BsdfFlags) -> Self
     {
-        Self { ray_incoming, ray_outgoing, point, normal, scattering_fraction, }
+        Self { ray_outgoing, scattering_fraction, pdf, flags, }
     }
 }
 
+/// Returns the cosine of the angle between a local shading frame direction and
+/// the shading normal.
+#[inline]
+pub fn cos_theta(direction: &Vector3<f32>) -> f32 {
+    direction.z
+}
+
+/// Returns the absolute value of the cosine of the angle between a local shading
+/// frame direction and the shading normal.
+#[inline]
+pub fn abs_cos_theta(direction: &Vector3<f32>) -> f32 {
+    f32::abs(direction.z)
+}
+
+/// Determine whether a query in the local shading frame describes a reflection,
+/// i.e. whether the outgoing ray leaves from the side of the surface the incoming
+/// ray arrives from.
+#[inline]
+pub fn is_reflection(ray_incoming: &Vector3<f32>, ray_outgoing: &Vector3<f32>) -> bool {
+    ray_incoming.z * ray_outgoing.z < 0_f32
+}
+
+/// Reflect a local shading frame direction about the shading normal.
+#[inline]
+pub fn reflect_local(direction: &Vector3<f32>) -> Vector3<f32> {
+    Vector3::new(direction.x, direction.y, -direction.z)
+}
@@ -0,0 +1,189 @@
+use crate::core::bounding_box::*;
+use crate::core::query::*;
+
+
+/// The largest number of items in a leaf of a bounding volume hierarchy.
+const MAX_LEAF_SIZE: usize = 4;
+
+/// The largest depth of a bounding volume hierarchy. Splitting at the median 
+/// keeps the depth logarithmic in the number of items, far below this limit.
+const MAX_DEPTH: usize = 64;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum BvhNodeKind {
+    /// A leaf holding the items `indices[start..(start + count)]`.
+    Leaf { start: usize, count: usize },
+    /// An interior node split along the axis `axis`. The first child directly
+    /// follows the node, and the second child sits at `second_child`.
+    Interior { second_child: usize, axis: usize },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct BvhNode {
+    bounds: BoundingBox,
+    kind: BvhNodeKind,
+}
+
+/// A bounding volume hierarchy over a list of items with bounding boxes, for 
+/// finding the items a ray might hit without testing all of them.
+///
+/// The hierarchy stores the indices of the items rather than the items 
+/// themselves, so the same hierarchy serves the objects of a scene and the 
+/// children of a group of geometry alike. Items with infinite bounding boxes
+/// do not fit in the hierarchy, so every traversal visits them. Items with 
+/// empty bounding boxes cannot be hit and are left out.
+#[derive(Clone, Debug, Default)]
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    indices: Vec<usize>,
+    unbounded: Vec<usize>,
+}
+
+impl Bvh {
+    /// Construct a bounding volume hierarchy over the items with the bounding 
+    /// boxes `bounds`, splitting nodes at the median of the centers of the items
+    /// along the axis of their largest spread.
+    pub fn new(bounds: &[BoundingBox]) -> Self {
+        let mut bounded = Vec::with_capacity(bounds.len());
+        let mut unbounded = Vec::new();
+        for (index, item_bounds) in bounds.iter().enumerate() {
+            if item_bounds.is_empty() {
+                continue;
+            }
+
+            if item_bounds.is_infinite() {
+                unbounded.push(index);
+            } else {
+                bounded.push(index);
+            }
+        }
+
+        let mut bvh = Self {
+            nodes: Vec::with_capacity(2 * bounded.len()),
+            indices: Vec::with_capacity(bounded.len()),
+            unbounded,
+        };
+        if !bounded.is_empty() {
+            bvh.build(bounds, &mut bounded, 0);
+        }
+
+        bvh
+    }
+
+    fn build(&mut self, bounds: &[BoundingBox], items: &mut [usize], depth: usize) {
+        let node_bounds = items
+            .iter()
+            .fold(BoundingBox::empty(), |node_bounds, &index| node_bounds.union(&bounds[index]));
+        let center_bounds = items
+            .iter()
+            .fold(BoundingBox::empty(), |center_bounds, &index| center_bounds.union_point(&bounds[index].center()));
+        let extent = center_bounds.extent();
+        let axis = if extent.x >= extent.y && extent.x >= extent.z {
+            0
+        } else if extent.y >= extent.z {
+            1
+        } else {
+            2
+        };
+
+        // Items whose centers coincide cannot be separated by splitting.
+        if items.len() <= MAX_LEAF_SIZE || extent[axis] <= 0_f32 || depth + 1 >= MAX_DEPTH {
+            let start = self.indices.len();
+            self.indices.extend_from_slice(items);
+            self.nodes.push(BvhNode { bounds: node_bounds, kind: BvhNodeKind::Leaf { start, count: items.len(), }, });
+
+            return;
+        }
+
+        let middle = items.len() / 2;
+        items.select_nth_unstable_by(middle, |&index1, &index2| {
+            bounds[index1].center()[axis].total_cmp(&bounds[index2].center()[axis])
+        });
+
+        let node_index = self.nodes.len();
+        self.nodes.push(BvhNode { bounds: node_bounds, kind: BvhNodeKind::Interior { second_child: 0, axis, }, });
+        let (first_items, second_items) = items.split_at_mut(middle);
+        self.build(bounds, first_items, depth + 1);
+        let second_child = self.nodes.len();
+        self.build(bounds, second_items, depth + 1);
+        self.nodes[node_index].kind = BvhNodeKind::Interior { second_child, axis, };
+    }
+
+    /// Returns the number of nodes in the hierarchy.
+    #[inline]
+    pub fn len_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Determine whether the hierarchy contains no items.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty() && self.unbounded.is_empty()
+    }
+
+    /// Visit the items whose bounding boxes a ray might hit within the range 
+    /// `[t_min, t_max]`, nearest nodes first.
+    ///
+    /// The closure `visit` receives the index of an item and the end of the range
+    /// of the ray so far, and returns the new end of the range, which is the ray 
+    /// parameter of the closest hit found so far. The traversal skips the nodes 
+    /// beyond the closest hit. Returning a value less than `t_min` ends the 
+    /// traversal early, e.g. once any hit will do. Returns the number of 
+    /// bounding boxes tested.
+    pub fn traverse<F>(&self, ray: &Ray, t_min: f32, t_max: f32, mut visit: F) -> usize
+    where
+        F: FnMut(usize, f32) -> f32,
+    {
+        let mut t_closest = t_max;
+        for &index in self.unbounded.iter() {
+            t_closest = visit(index, t_closest);
+            if t_closest < t_min {
+                return 0;
+            }
+        }
+
+        if self.nodes.is_empty() {
+            return 0;
+        }
+
+        let mut bounds_tests = 0;
+        let mut stack = [0_usize; MAX_DEPTH];
+        let mut stack_len = 1;
+        while stack_len > 0 {
+            stack_len -= 1;
+            let node_index = stack[stack_len];
+            let node = &self.nodes[node_index];
+            bounds_tests += 1;
+            if node.bounds.intersect(ray, t_min, t_closest).is_none() {
+                continue;
+            }
+
+            match node.kind {
+                BvhNodeKind::Leaf { start, count } => {
+                    for &index in self.indices[start..(start + count)].iter() {
+                        t_closest = visit(index, t_closest);
+                        if t_closest < t_min {
+                            return bounds_tests;
+                        }
+                    }
+                }
+                BvhNodeKind::Interior { second_child, axis } => {
+                    // Visit the child nearer to the ray origin first, so that the
+                    // hits found there cut off the search of the other one.
+                    let first_child = node_index + 1;
+                    let (near_child, far_child) = if ray.direction[axis] < 0_f32 {
+                        (second_child, first_child)
+                    } else {
+                        (first_child, second_child)
+                    };
+                    stack[stack_len] = far_child;
+                    stack[stack_len + 1] = near_child;
+                    stack_len += 2;
+                }
+            }
+        }
+
+        bounds_tests
+    }
+}
+
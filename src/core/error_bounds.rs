@@ -0,0 +1,117 @@
+use crate::core::*;
+use cglinalg::{
+    Vector3,
+    Matrix4x4,
+};
+
+
+/// The largest relative error of rounding a real number to the nearest single
+/// precision floating point number.
+pub const MACHINE_EPSILON: f32 = f32::EPSILON * 0.5;
+
+/// The fraction of a line segment between two points at its far end left out
+/// of visibility tests, so that a test does not hit the surface at the far
+/// end of the segment.
+pub const SHADOW_EPSILON: f32 = 0.0001;
+
+/// Compute a bound on the relative error of a sequence of `n` floating point
+/// operations.
+#[inline]
+pub fn gamma(n: i32) -> f32 {
+    let n_epsilon = (n as f32) * MACHINE_EPSILON;
+
+    n_epsilon / (1_f32 - n_epsilon)
+}
+
+/// Returns the smallest floating point number greater than `value`.
+#[inline]
+pub fn next_float_up(value: f32) -> f32 {
+    if value.is_infinite() && value > 0_f32 {
+        return value;
+    }
+
+    // Skip negative zero so that stepping up from zero gives a positive number.
+    let value = if value == -0_f32 { 0_f32 } else { value };
+    let bits = value.to_bits();
+    let next_bits = if value >= 0_f32 { bits + 1 } else { bits - 1 };
+
+    f32::from_bits(next_bits)
+}
+
+/// Returns the largest floating point number less than `value`.
+#[inline]
+pub fn next_float_down(value: f32) -> f32 {
+    if value.is_infinite() && value < 0_f32 {
+        return value;
+    }
+
+    let value = if value == 0_f32 { -0_f32 } else { value };
+    let bits = value.to_bits();
+    let next_bits = if value > 0_f32 { bits - 1 } else { bits + 1 };
+
+    f32::from_bits(next_bits)
+}
+
+#[inline]
+fn abs(vector: &Vector3<f32>) -> Vector3<f32> {
+    Vector3::new(f32::abs(vector.x), f32::abs(vector.y), f32::abs(vector.z))
+}
+
+/// Compute a bound on the absolute error of a point computed by evaluating the
+/// equation of the ray `ray` at a ray parameter `t` with a small relative error.
+#[inline]
+pub fn ray_point_error(ray: &Ray, t: f32) -> Vector3<f32> {
+    (abs(&ray.origin) + abs(&ray.direction) * f32::abs(t)) * gamma(7)
+}
+
+/// Compute a bound on the absolute error of a point computed exactly up to
+/// rounding error, e.g. by reprojecting it onto the surface it lies on.
+#[inline]
+pub fn point_error(point: &Vector3<f32>) -> Vector3<f32> {
+    abs(point) * gamma(5)
+}
+
+/// Compute a bound on the absolute error of a point with the absolute error
+/// `point_error` after transforming it by the affine matrix `matrix`.
+pub fn transform_point_error(matrix: &Matrix4x4<f32>, point: &Vector3<f32>, point_error: &Vector3<f32>) -> Vector3<f32> {
+    let column = |axis: Vector3<f32>| abs(&(matrix * axis.extend(0_f32)).contract());
+    let columns = [column(Vector3::unit_x()), column(Vector3::unit_y()), column(Vector3::unit_z())];
+    let translation = abs(&(matrix * Vector3::zero().extend(1_f32)).contract());
+    let abs_transform = |vector: &Vector3<f32>| columns[0] * vector.x + columns[1] * vector.y + columns[2] * vector.z;
+    let transformed_error = abs_transform(point_error) * (gamma(3) + 1_f32);
+    let rounding_error = (abs_transform(&abs(point)) + translation) * gamma(3);
+
+    transformed_error + rounding_error
+}
+
+/// Offset the origin of a ray leaving the point `point` on a surface with the
+/// normal `normal` in the direction `direction`, so that the ray cannot hit the
+/// surface it leaves again.
+///
+/// The point lies within the box of half extent `point_error` around its
+/// exact position. The origin is pushed along the normal to the side of the
+/// surface the ray leaves towards, far enough to clear the box, then rounded
+/// away from the point.
+pub fn offset_ray_origin(
+    point: &Vector3<f32>,
+    point_error: &Vector3<f32>,
+    normal: &Vector3<f32>,
+    direction: &Vector3<f32>) -> Vector3<f32>
+{
+    let distance = abs(normal).dot(point_error);
+    let offset = if direction.dot(normal) < 0_f32 {
+        -(normal * distance)
+    } else {
+        normal * distance
+    };
+    let mut origin = point + offset;
+    for i in 0..3 {
+        if offset[i] > 0_f32 {
+            origin[i] = next_float_up(origin[i]);
+        } else if offset[i] < 0_f32 {
+            origin[i] = next_float_down(origin[i]);
+        }
+    }
+
+    origin
+}
@@ -0,0 +1,77 @@
+use cglinalg::{
+    Vector3,
+    Magnitude,
+};
+
+
+/// An orthonormal coordinate frame at a point on a surface.
+///
+/// The frame's **z-axis** is the surface normal, and its **x-axis** and **y-axis** 
+/// span the tangent plane. Scattering functions are evaluated in the local 
+/// coordinates of the shading frame of a surface.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Frame {
+    /// The **x-axis** of the frame.
+    pub tangent: Vector3<f32>,
+    /// The **y-axis** of the frame.
+    pub bitangent: Vector3<f32>,
+    /// The **z-axis** of the frame.
+    pub normal: Vector3<f32>,
+}
+
+impl Frame {
+    /// Construct a frame from three mutually orthogonal unit vectors.
+    pub fn new(tangent: Vector3<f32>, bitangent: Vector3<f32>, normal: Vector3<f32>) -> Self {
+        Self { tangent, bitangent, normal, }
+    }
+
+    /// Construct a frame about a unit normal vector, choosing an arbitrary 
+    /// tangent.
+    pub fn from_normal(normal: &Vector3<f32>) -> Self {
+        // Construct an orthonormal basis about the normal without branching on the
+        // normal's largest component. See Duff et al., "Building an Orthonormal Basis,
+        // Revisited", JCGT 2017.
+        let sign = f32::copysign(1_f32, normal.z);
+        let a = -1_f32 / (sign + normal.z);
+        let b = normal.x * normal.y * a;
+        let tangent = Vector3::new(1_f32 + sign * normal.x * normal.x * a, sign * b, -sign * normal.x);
+        let bitangent = Vector3::new(b, sign + normal.y * normal.y * a, -normal.y);
+
+        Self::new(tangent, bitangent, *normal)
+    }
+
+    /// Construct a frame about a unit normal vector whose **x-axis** points along
+    /// the component of `tangent` orthogonal to the normal.
+    ///
+    /// When `tangent` is degenerate, i.e. zero or parallel to the normal, the 
+    /// tangent is chosen as in [`Frame::from_normal`].
+    pub fn from_normal_tangent(normal: &Vector3<f32>, tangent: &Vector3<f32>) -> Self {
+        let projected_tangent = tangent - normal * normal.dot(tangent);
+        let magnitude_squared = projected_tangent.magnitude_squared();
+        if magnitude_squared <= 1e-12 || !magnitude_squared.is_finite() {
+            return Self::from_normal(normal);
+        }
+
+        let unit_tangent = projected_tangent / f32::sqrt(magnitude_squared);
+        let bitangent = normal.cross(&unit_tangent);
+
+        Self::new(unit_tangent, bitangent, *normal)
+    }
+
+    /// Express a vector in the local coordinates of the frame.
+    #[inline]
+    pub fn to_local(&self, vector: &Vector3<f32>) -> Vector3<f32> {
+        Vector3::new(
+            vector.dot(&self.tangent), 
+            vector.dot(&self.bitangent), 
+            vector.dot(&self.normal)
+        )
+    }
+
+    /// Express a vector in the local coordinates of the frame in the coordinate 
+    /// system the frame is defined in.
+    #[inline]
+    pub fn to_world(&self, vector: &Vector3<f32>) -> Vector3<f32> {
+        self.tangent * vector.x + self.bitangent * vector.y + self.normal * vector.z
+    }
+}
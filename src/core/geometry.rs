@@ -1,8 +1,58 @@
+use crate::core::bounding_box::*;
 use crate::core::query::*;
+use crate::core::sampler::*;
 use cglinalg::{
     Vector3,
 };
 
+
+/// A point sampled on the surface of a geometry.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SurfaceSample {
+    /// The sampled point.
+    pub point: Vector3<f32>,
+    /// The unit geometric normal of the surface at the sampled point.
+    pub normal: Vector3<f32>,
+    /// The probability density of the sampled point with respect to surface area.
+    pub pdf: f32,
+}
+
+impl SurfaceSample {
+    pub fn new(point: Vector3<f32>, normal: Vector3<f32>, pdf: f32) -> Self {
+        Self { point, normal, pdf, }
+    }
+}
+
+/// An interval of a ray inside a solid.
+///
+/// The ends of an interval are crossings of the boundary of the solid, except
+/// where the interval is cut off by the range of the query that produced it. 
+/// An interval whose ray starts inside the solid has no entry crossing, and an 
+/// interval whose ray is still inside the solid at the end of the range has no 
+/// exit crossing.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RayInterval {
+    /// The ray parameter where the ray enters the solid.
+    pub t_entry: f32,
+    /// The ray parameter where the ray leaves the solid.
+    pub t_exit: f32,
+    /// The intersection data where the ray crosses into the solid.
+    pub entry: Option<IntersectionData>,
+    /// The intersection data where the ray crosses out of the solid.
+    pub exit: Option<IntersectionData>,
+}
+
+impl RayInterval {
+    pub fn new(t_entry: f32, t_exit: f32, entry: Option<IntersectionData>, exit: Option<IntersectionData>) -> Self {
+        Self { t_entry, t_exit, entry, exit, }
+    }
+}
+
+/// The largest number of boundary crossings along a ray that the default 
+/// interval query visits, which guards against surfaces that report the same
+/// crossing over and over.
+const MAX_BOUNDARY_CROSSINGS: usize = 64;
+
 pub trait Intersection {
     fn intersect(&self, query: &IntersectionQuery) -> IntersectionResult;
 }
@@ -10,5 +60,63 @@ pub trait Geometry: std::fmt::Debug + Intersection {
     fn center(&self) -> Vector3<f32>;
 
     fn contains(&self, point: &Vector3<f32>) -> bool;
-}
 
+    /// Returns the bounding box of the geometry in model space.
+    fn bounds(&self) -> BoundingBox;
+
+    /// Returns the surface area of the geometry in model space. Unbounded 
+    /// surfaces have infinite area.
+    fn area(&self) -> f32;
+
+    /// Sample a point on the surface of the geometry in model space uniformly 
+    /// with respect to surface area, or return `None` when the surface is 
+    /// unbounded.
+    fn sample_surface(&self, sampler: &mut SphereSampler) -> Option<SurfaceSample>;
+
+    /// Enumerate the intervals of a ray inside the geometry within the range of
+    /// the query, in increasing order along the ray.
+    ///
+    /// The default implementation walks along the ray from one boundary crossing
+    /// to the next with the nearest hit query, and tells entries from exits by 
+    /// the side of the outward normal the ray crosses from. The intervals are 
+    /// only meaningful for geometry that encloses a solid.
+    fn intersect_intervals(&self, query: &IntersectionQuery) -> Vec<RayInterval> {
+        let mut intervals = Vec::new();
+        let mut current_entry = if self.contains(&query.ray.interpolate(query.t_min)) {
+            Some((query.t_min, None))
+        } else {
+            None
+        };
+        let mut t = query.t_min;
+        for _ in 0..MAX_BOUNDARY_CROSSINGS {
+            let crossing_query = IntersectionQuery::new(query.ray, t, query.t_max);
+            let crossing = match self.intersect(&crossing_query) {
+                IntersectionResult::Hit(crossing) => crossing,
+                IntersectionResult::Tangent(crossing) => {
+                    // A ray grazing the surface neither enters nor leaves the solid.
+                    t = crossing.t;
+                    continue;
+                }
+                IntersectionResult::Miss(_) => break,
+            };
+            let is_entering = crossing.normal.dot(&query.ray.direction) < 0_f32;
+            match current_entry {
+                None if is_entering => {
+                    current_entry = Some((crossing.t, Some(crossing)));
+                }
+                Some((t_entry, entry)) if !is_entering => {
+                    intervals.push(RayInterval::new(t_entry, crossing.t, entry, Some(crossing)));
+                    current_entry = None;
+                }
+                _ => {}
+            }
+            t = crossing.t;
+        }
+
+        if let Some((t_entry, entry)) = current_entry {
+            intervals.push(RayInterval::new(t_entry, query.t_max, entry, None));
+        }
+
+        intervals
+    }
+}
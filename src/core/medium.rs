@@ -0,0 +1,89 @@
+use crate::core::*;
+use cglinalg::{
+    Vector3,
+};
+
+
+/// The angular distribution of light scattered inside a participating medium.
+///
+/// Following the convention of the rest of the renderer, the incoming direction 
+/// points along the direction of travel of the incident ray, and the outgoing 
+/// direction points along the direction of travel of the scattered ray. Both 
+/// directions are unit vectors in world space.
+pub trait PhaseFunction: std::fmt::Debug {
+    /// Evaluate the phase function for a pair of directions. Phase functions are
+    /// normalized over the sphere of directions.
+    fn evaluate(&self, ray_incoming: &Vector3<f32>, ray_outgoing: &Vector3<f32>) -> f32;
+
+    /// Sample an outgoing direction given an incoming direction.
+    fn sample(&self, ray_incoming: &Vector3<f32>, sampler: &mut SphereSampler) -> PhaseFunctionResult;
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PhaseFunctionResult {
+    /// The sampled outgoing direction.
+    pub ray_outgoing: Vector3<f32>,
+    /// The value of the phase function for the sampled direction.
+    pub value: f32,
+    /// The probability density of the sampled direction with respect to solid
+    /// angle.
+    pub pdf: f32,
+}
+
+impl PhaseFunctionResult {
+    pub fn new(ray_outgoing: Vector3<f32>, value: f32, pdf: f32) -> Self {
+        Self { ray_outgoing, value, pdf, }
+    }
+}
+
+/// A participating medium that absorbs and scatters light travelling through it.
+///
+/// Positions along a ray are measured by the ray parameter `t`, so the distance
+/// travelled through the medium accounts for the length of the ray direction.
+pub trait Medium: std::fmt::Debug {
+    /// Returns the phase function of the medium.
+    fn phase_function(&self) -> &dyn PhaseFunction;
+
+    /// Estimate the fraction of light that travels along a ray from its origin to 
+    /// the point at parameter `t_max` without being absorbed or scattered.
+    fn transmittance(&self, ray: &Ray, t_max: f32, sampler: &mut SphereSampler) -> Vector3<f32>;
+
+    /// Sample the distance a ray travels through the medium before it scatters, 
+    /// up to the point at parameter `t_max`.
+    fn sample(&self, ray: &Ray, t_max: f32, sampler: &mut SphereSampler) -> MediumResult;
+}
+
+/// A point at which a ray scatters inside a participating medium.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MediumInteraction {
+    /// The ray parameter of the scattering point.
+    pub t: f32,
+    pub point: Vector3<f32>,
+}
+
+impl MediumInteraction {
+    pub fn new(t: f32, point: Vector3<f32>) -> Self {
+        Self { t, point, }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MediumResult {
+    /// The point at which the ray scatters, or `None` when the ray passes through
+    /// the medium up to the end of the sampled segment.
+    pub interaction: Option<MediumInteraction>,
+    /// The throughput weight of the sample. For a scattering event this is 
+    /// `sigma_s * transmittance / pdf`, and otherwise `transmittance / pdf`.
+    pub weight: Vector3<f32>,
+}
+
+impl MediumResult {
+    pub fn new(interaction: Option<MediumInteraction>, weight: Vector3<f32>) -> Self {
+        Self { interaction, weight, }
+    }
+
+    #[inline]
+    pub fn is_scattered(&self) -> bool {
+        self.interaction.is_some()
+    }
+}
@@ -1,13 +1,29 @@
+mod animated_transform;
+mod bounding_box;
 mod bsdf;
+mod bvh;
+mod error_bounds;
+mod frame;
 mod geometry;
 mod light;
+mod medium;
+mod normal_mapping;
 mod query;
 mod sampler;
+mod texture;
 
 
+pub use animated_transform::*;
+pub use bounding_box::*;
 pub use bsdf::*;
+pub use bvh::*;
+pub use error_bounds::*;
+pub use frame::*;
 pub use geometry::*;
 pub use light::*;
+pub use medium::*;
+pub use normal_mapping::*;
 pub use query::*;
 pub use sampler::*;
+pub use texture::*;
 
@@ -0,0 +1,39 @@
+use crate::core::*;
+use cglinalg::{
+    Vector3,
+    Magnitude,
+};
+
+
+/// The smallest cosine of the angle between a perturbed shading normal and the
+/// geometric normal that [`safeguard_shading_normal`] permits.
+const MIN_COS_SHADING_NORMAL: f32 = 0.01;
+
+/// A perturbation of the shading normal of a surface, such as a normal map or a 
+/// bump map.
+pub trait NormalMapping: std::fmt::Debug {
+    /// Compute the perturbed shading normal at a point of intersection.
+    fn shading_normal(&self, hit: &IntersectionData) -> Vector3<f32>;
+}
+
+/// Correct a perturbed shading normal that points below the surface.
+///
+/// A shading normal in the opposite hemisphere to the geometric normal makes 
+/// scattering functions send light through opaque surfaces. The returned normal
+/// lies strictly in the hemisphere about the geometric normal.
+pub fn safeguard_shading_normal(normal: &Vector3<f32>, shading_normal: &Vector3<f32>) -> Vector3<f32> {
+    let cos_theta = shading_normal.dot(normal);
+    if cos_theta >= MIN_COS_SHADING_NORMAL {
+        return *shading_normal;
+    }
+
+    // Tilt the shading normal towards the geometric normal until it lies above the
+    // surface again.
+    let corrected_normal = shading_normal + normal * (MIN_COS_SHADING_NORMAL - cos_theta);
+    let magnitude = corrected_normal.magnitude();
+    if magnitude > 0_f32 && magnitude.is_finite() {
+        corrected_normal / magnitude
+    } else {
+        *normal
+    }
+}
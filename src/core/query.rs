@@ -1,16 +1,29 @@
+use crate::core::*;
 use cglinalg::{
+    Vector2,
     Vector3,
+    Magnitude,
 };
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Ray {
     pub origin: Vector3<f32>,
     pub direction: Vector3<f32>,
+    /// The time at which the ray travels through the scene, within the shutter
+    /// interval of the camera.
+    pub time: f32,
 }
 
 impl Ray {
     pub fn new(origin: Vector3<f32>, direction: Vector3<f32>) -> Self {
-        Ray { origin, direction }
+        Ray { origin, direction, time: 0_f32, }
+    }
+
+    /// Set the time at which the ray travels through the scene.
+    #[inline]
+    pub fn with_time(mut self, time: f32) -> Self {
+        self.time = time;
+        self
     }
 
     pub fn interpolate(&self, t: f32) -> Vector3<f32> {
@@ -32,6 +45,48 @@ impl IntersectionQuery {
     }
 }
 
+/// A query for whether any surface blocks a ray before it travels a distance 
+/// `distance` from its origin.
+///
+/// Unlike an intersection query, a shadow query only needs to find some hit
+/// along the ray rather than the closest one. The origin of the ray is offset 
+/// from the surface it leaves, if any, so the query starts at the origin.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ShadowQuery {
+    pub ray: Ray,
+    pub distance: f32,
+}
+
+impl ShadowQuery {
+    pub fn new(ray: Ray, distance: f32) -> Self {
+        Self { ray, distance, }
+    }
+
+    /// Construct a shadow query along the line segment between two points at 
+    /// the time `time`.
+    ///
+    /// The query stops short of the far end of the segment by the fraction 
+    /// [`SHADOW_EPSILON`] of its length, so that a point on a surface at the far
+    /// end, e.g. a light source, does not block itself.
+    pub fn between(from_location: &Vector3<f32>, to_location: &Vector3<f32>, time: f32) -> Self {
+        let offset = to_location - from_location;
+        let ray = Ray::new(*from_location, offset).with_time(time);
+
+        Self::new(ray, offset.magnitude() * (1_f32 - SHADOW_EPSILON))
+    }
+
+    /// Returns the ray parameter at which the ray reaches the end of the query.
+    #[inline]
+    pub fn t_max(&self) -> f32 {
+        let speed = self.ray.direction.magnitude();
+        if speed == 0_f32 {
+            0_f32
+        } else {
+            self.distance / speed
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Reason {
     HitBeforeMin { t_got: f32 },
@@ -118,14 +173,148 @@ impl IntersectionResult {
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct IntersectionData {
+    /// The ray parameter at the point of intersection.
     pub t: f32,
+    /// The point of intersection.
     pub point: Vector3<f32>,
+    /// A bound on the absolute error of each coordinate of the point of 
+    /// intersection.
+    pub point_error: Vector3<f32>,
+    /// The geometric normal of the surface at the point of intersection.
     pub normal: Vector3<f32>,
+    /// The normal used for shading at the point of intersection. It differs from
+    /// the geometric normal when the shading of the surface is perturbed, e.g. 
+    /// by interpolated vertex normals or normal maps.
+    pub shading_normal: Vector3<f32>,
+    /// The partial derivative of the surface position with respect to the **u**
+    /// surface parameter.
+    pub dpdu: Vector3<f32>,
+    /// The partial derivative of the surface position with respect to the **v**
+    /// surface parameter.
+    pub dpdv: Vector3<f32>,
+    /// The surface parameters of the point of intersection.
+    pub uv: Vector2<f32>,
+    /// The barycentric coordinates of the point of intersection with respect to
+    /// the vertices of a triangle, or `None` when the surface is not a triangle.
+    pub barycentrics: Option<Vector3<f32>>,
+    /// The time of the ray that hit the surface. Rays leaving the point of 
+    /// intersection travel at the same time.
+    pub time: f32,
+    /// Whether the ray hit the surface from the side the geometric normal points
+    /// to.
+    pub is_front_face: bool,
 }
 
-impl<'a> IntersectionData {
+impl IntersectionData {
+    /// Construct the intersection data for a surface without a parameterization.
+    /// 
+    /// The shading normal is the geometric normal, the surface parameters are 
+    /// zero, and the tangents are an arbitrary orthonormal pair perpendicular to
+    /// the normal.
     pub fn new(t: f32, point: Vector3<f32>, normal: Vector3<f32>) -> Self {
-        IntersectionData { t, point, normal, }
+        let frame = Frame::from_normal(&normal);
+
+        Self {
+            t, 
+            point, 
+            point_error: point_error(&point),
+            normal,
+            shading_normal: normal,
+            dpdu: frame.tangent,
+            dpdv: frame.bitangent,
+            uv: Vector2::zero(),
+            barycentrics: None,
+            time: 0_f32,
+            is_front_face: true,
+        }
+    }
+
+    /// Construct the intersection data for a parameterized surface.
+    ///
+    /// The point of intersection is assumed to be exact up to rounding error. 
+    /// Geometries computing it with a larger error set the error with 
+    /// [`IntersectionData::with_point_error`].
+    pub fn from_surface(
+        t: f32,
+        point: Vector3<f32>,
+        normal: Vector3<f32>,
+        shading_normal: Vector3<f32>,
+        dpdu: Vector3<f32>,
+        dpdv: Vector3<f32>,
+        uv: Vector2<f32>) -> Self
+    {
+        Self { 
+            t, 
+            point, 
+            point_error: point_error(&point), 
+            normal, 
+            shading_normal, 
+            dpdu, 
+            dpdv, 
+            uv, 
+            barycentrics: None,
+            time: 0_f32, 
+            is_front_face: true, 
+        }
+    }
+
+    /// Set the bound on the absolute error of the point of intersection.
+    #[inline]
+    pub fn with_point_error(mut self, point_error: Vector3<f32>) -> Self {
+        self.point_error = point_error;
+        self
+    }
+
+    /// Record the barycentric coordinates of the point of intersection inside a
+    /// triangle.
+    #[inline]
+    pub fn with_barycentrics(mut self, barycentrics: Vector3<f32>) -> Self {
+        self.barycentrics = Some(barycentrics);
+        self
+    }
+
+    /// Compute the origin of a ray leaving the point of intersection in the 
+    /// direction `direction`, offset from the surface far enough that the ray 
+    /// does not hit the surface again.
+    #[inline]
+    pub fn spawn_origin(&self, direction: &Vector3<f32>) -> Vector3<f32> {
+        offset_ray_origin(&self.point, &self.point_error, &self.normal, direction)
+    }
+
+    /// Construct a ray leaving the point of intersection in the direction 
+    /// `direction` at the time of the intersection. 
+    ///
+    /// The ray starts off the surface, so it needs no minimum ray parameter to
+    /// avoid hitting the surface it leaves.
+    #[inline]
+    pub fn spawn_ray(&self, direction: &Vector3<f32>) -> Ray {
+        Ray::new(self.spawn_origin(direction), *direction).with_time(self.time)
+    }
+
+    /// Construct a ray leaving the point of intersection towards the point 
+    /// `target`, which the ray reaches at the ray parameter one.
+    #[inline]
+    pub fn spawn_ray_to(&self, target: &Vector3<f32>) -> Ray {
+        let origin = self.spawn_origin(&(target - self.point));
+
+        Ray::new(origin, target - origin).with_time(self.time)
+    }
+
+    /// Record which side of the surface a ray travelling in the direction 
+    /// `ray_direction` hit. The normals of the surface are left as they are.
+    #[inline]
+    pub fn with_face_orientation(mut self, ray_direction: &Vector3<f32>) -> Self {
+        self.is_front_face = self.normal.dot(ray_direction) <= 0_f32;
+        self
+    }
+
+    /// Construct the shading frame at the point of intersection.
+    ///
+    /// The **z-axis** of the frame is the shading normal, and its **x-axis** is
+    /// aligned with the **u** tangent of the surface.
+    #[inline]
+    pub fn shading_frame(&self) -> Frame {
+        Frame::from_normal_tangent(&self.shading_normal, &self.dpdu)
     }
 }
 
@@ -134,11 +323,50 @@ impl<'a> IntersectionData {
 pub struct ScatteringQuery {
     pub ray_incoming: Vector3<f32>,
     pub point: Vector3<f32>,
+    /// The scattering point in world space. This is the same as `point` until the
+    /// query is transformed into the model space of an object.
+    pub world_point: Vector3<f32>,
+    pub uv: Vector2<f32>,
+    pub shading_frame: Frame,
+    /// The geometric normal of the surface at the scattering point.
+    pub normal: Vector3<f32>,
+    /// Whether the incoming ray arrived from the side of the surface the 
+    /// geometric normal points to.
+    pub is_front_face: bool,
+    /// The time at which the scattering happens.
+    pub time: f32,
 }
 
 impl ScatteringQuery {
-    pub fn new(ray_incoming: Vector3<f32>, point: Vector3<f32>) -> Self {
-        Self { ray_incoming, point, }
+    /// Construct a scattering query for a surface whose geometric normal is the
+    /// normal of its shading frame.
+    pub fn new(ray_incoming: Vector3<f32>, point: Vector3<f32>, uv: Vector2<f32>, shading_frame: Frame) -> Self {
+        let normal = shading_frame.normal;
+        let is_front_face = normal.dot(&ray_incoming) <= 0_f32;
+
+        Self { ray_incoming, point, world_point: point, uv, shading_frame, normal, is_front_face, time: 0_f32, }
+    }
+
+    /// Construct a scattering query for a ray travelling in the direction 
+    /// `ray_incoming` from the full record of the intersection it scatters at.
+    pub fn from_hit(ray_incoming: Vector3<f32>, hit: &IntersectionData) -> Self {
+        Self {
+            ray_incoming,
+            point: hit.point,
+            world_point: hit.point,
+            uv: hit.uv,
+            shading_frame: hit.shading_frame(),
+            normal: hit.normal,
+            is_front_face: hit.is_front_face,
+            time: hit.time,
+        }
+    }
+
+    /// Set the time at which the scattering happens.
+    #[inline]
+    pub fn with_time(mut self, time: f32) -> Self {
+        self.time = time;
+        self
     }
 }
 
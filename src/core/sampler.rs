@@ -4,25 +4,168 @@ use cglinalg::{
     Vector3
 };
 
+/// A coordinate of a point in primary sample space, the unit hypercube of random
+/// numbers a path is generated from.
+#[derive(Copy, Clone, Debug)]
+struct PrimarySample {
+    value: f32,
+    /// The iteration in which the value last changed.
+    modified: usize,
+    /// The value before the mutation of the current iteration.
+    backup_value: f32,
+    backup_modified: usize,
+}
+
+/// The state of a sampler whose random numbers are the coordinates of a point in 
+/// primary sample space, mutated from one iteration of a Markov chain to the 
+/// next in the manner of Kelemen et al.
+///
+/// The coordinates are mutated lazily, when they are next drawn. A large step 
+/// replaces every coordinate with a fresh uniform random number, and a small step
+/// perturbs every coordinate with a normally distributed offset, wrapping around 
+/// the unit interval.
+#[derive(Clone, Debug)]
+struct PrimarySampleSpace {
+    samples: Vec<PrimarySample>,
+    sigma: f32,
+    large_step_probability: f32,
+    iteration: usize,
+    last_large_step_iteration: usize,
+    is_large_step: bool,
+    index: usize,
+}
+
+impl PrimarySampleSpace {
+    fn new(sigma: f32, large_step_probability: f32) -> Self {
+        Self {
+            samples: Vec::new(),
+            sigma,
+            large_step_probability,
+            iteration: 0,
+            last_large_step_iteration: 0,
+            is_large_step: true,
+            index: 0,
+        }
+    }
+
+    fn next(&mut self, rng: &mut rand_isaac::Isaac64Rng) -> f32 {
+        let iteration = self.iteration;
+        let index = self.index;
+        self.index += 1;
+        if index >= self.samples.len() {
+            let value = rng.gen::<f32>();
+            self.samples.push(PrimarySample {
+                value,
+                modified: iteration,
+                backup_value: value,
+                backup_modified: iteration.saturating_sub(1),
+            });
+
+            return value;
+        }
+
+        let sample = &mut self.samples[index];
+        if sample.modified < self.last_large_step_iteration {
+            // A large step replaced the coordinate since it was last drawn.
+            sample.value = rng.gen::<f32>();
+            sample.modified = self.last_large_step_iteration;
+        }
+
+        sample.backup_value = sample.value;
+        sample.backup_modified = sample.modified;
+        if self.is_large_step {
+            sample.value = rng.gen::<f32>();
+        } else {
+            // Apply every small step the coordinate missed at once, since the sum 
+            // of normally distributed offsets is normally distributed.
+            let small_step_count = (iteration - sample.modified) as f32;
+            let sigma = self.sigma * f32::sqrt(small_step_count);
+            let u1 = f32::max(rng.gen::<f32>(), f32::MIN_POSITIVE);
+            let u2 = rng.gen::<f32>();
+            let offset = sigma * f32::sqrt(-2_f32 * f32::ln(u1)) * f32::cos(2_f32 * std::f32::consts::PI * u2);
+            let value = sample.value + offset;
+            sample.value = value - f32::floor(value);
+            if sample.value >= 1_f32 {
+                sample.value = 0_f32;
+            }
+        }
+        sample.modified = iteration;
+
+        sample.value
+    }
+}
+
 pub struct SphereSampler {
     pub rng: rand_isaac::Isaac64Rng,
+    primary_sample_space: Option<PrimarySampleSpace>,
 }
 
 impl SphereSampler {
     pub fn new(rng: rand_isaac::Isaac64Rng) -> Self {
-        Self { rng, }
+        Self { rng, primary_sample_space: None, }
+    }
+
+    /// Draw the random numbers of the sampler from a point in primary sample 
+    /// space for Metropolis light transport. 
+    ///
+    /// Small steps perturb the point with normally distributed offsets with 
+    /// standard deviation `sigma`, and each iteration is a large step with 
+    /// probability `large_step_probability`. Before the first call to 
+    /// [`SphereSampler::start_iteration`], the sampler draws the same numbers as 
+    /// a sampler constructed from the same random number generator.
+    pub fn with_primary_sample_space(mut self, sigma: f32, large_step_probability: f32) -> Self {
+        self.primary_sample_space = Some(PrimarySampleSpace::new(sigma, large_step_probability));
+        self
+    }
+
+    /// Propose a mutation of the point in primary sample space the sampler draws 
+    /// its random numbers from. Has no effect on a sampler drawing independent 
+    /// random numbers.
+    pub fn start_iteration(&mut self) {
+        if let Some(primary_sample_space) = self.primary_sample_space.as_mut() {
+            primary_sample_space.iteration += 1;
+            primary_sample_space.is_large_step = self.rng.gen::<f32>() < primary_sample_space.large_step_probability;
+            primary_sample_space.index = 0;
+        }
+    }
+
+    /// Accept the mutation proposed by the current iteration.
+    pub fn accept(&mut self) {
+        if let Some(primary_sample_space) = self.primary_sample_space.as_mut() {
+            if primary_sample_space.is_large_step {
+                primary_sample_space.last_large_step_iteration = primary_sample_space.iteration;
+            }
+        }
+    }
+
+    /// Reject the mutation proposed by the current iteration, restoring the point
+    /// in primary sample space from before the iteration.
+    pub fn reject(&mut self) {
+        if let Some(primary_sample_space) = self.primary_sample_space.as_mut() {
+            let iteration = primary_sample_space.iteration;
+            for sample in primary_sample_space.samples.iter_mut() {
+                if sample.modified == iteration {
+                    sample.value = sample.backup_value;
+                    sample.modified = sample.backup_modified;
+                }
+            }
+            primary_sample_space.iteration -= 1;
+        }
     }
 
     #[inline]
     pub fn sample_f32(&mut self) -> f32 {
-        self.rng.gen::<f32>()
+        match self.primary_sample_space.as_mut() {
+            Some(primary_sample_space) => primary_sample_space.next(&mut self.rng),
+            None => self.rng.gen::<f32>(),
+        }
     }
 
     pub fn sample_unit_sphere(&mut self) -> Vector3<f32> {
         loop {
-            let a = self.rng.gen::<f32>();
-            let b = self.rng.gen::<f32>();
-            let c = self.rng.gen::<f32>();
+            let a = self.sample_f32();
+            let b = self.sample_f32();
+            let c = self.sample_f32();
             let p = Vector3::new(a, b, c) * 2_f32 - Vector3::new(1_f32, 1_f32, 1_f32);
     
             // If the sample falls inside the unit sphere, we can return.
@@ -32,11 +175,24 @@ impl SphereSampler {
         }
     }
 
+    /// Sample a direction uniformly from the unit sphere.
+    ///
+    /// The probability density of every sample is `1 / (4 * pi)`.
+    pub fn sample_uniform_direction(&mut self) -> Vector3<f32> {
+        let u1 = self.sample_f32();
+        let u2 = self.sample_f32();
+        let z = 1_f32 - 2_f32 * u1;
+        let r = f32::sqrt(f32::max(0_f32, 1_f32 - z * z));
+        let phi = 2_f32 * std::f32::consts::PI * u2;
+
+        Vector3::new(r * f32::cos(phi), r * f32::sin(phi), z)
+    }
+
     pub fn sample_unit_disk(&mut self) -> Vector3<f32> {
         loop {
             let p = Vector3::new(
-                2_f32 * self.rng.gen::<f32>() - 1_f32,
-                2_f32 * self.rng.gen::<f32>() - 1_f32,
+                2_f32 * self.sample_f32() - 1_f32,
+                2_f32 * self.sample_f32() - 1_f32,
                 0_f32,
             );
     
@@ -46,5 +202,20 @@ impl SphereSampler {
             }
         }
     }
+
+    /// Sample a direction from the unit hemisphere about the **z-axis** with 
+    /// probability density proportional to the cosine of the angle between the 
+    /// direction and the **z-axis**.
+    ///
+    /// The probability density of a sample `w` is `w.z / pi`.
+    pub fn sample_cosine_hemisphere(&mut self) -> Vector3<f32> {
+        let u1 = self.sample_f32();
+        let u2 = self.sample_f32();
+        let r = f32::sqrt(u1);
+        let phi = 2_f32 * std::f32::consts::PI * u2;
+        let z = f32::sqrt(f32::max(0_f32, 1_f32 - u1));
+
+        Vector3::new(r * f32::cos(phi), r * f32::sin(phi), z)
+    }
 }
 
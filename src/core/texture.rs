@@ -0,0 +1,50 @@
+use cglinalg::{
+    Vector2,
+    Vector3,
+};
+
+
+/// A function of position on a surface that supplies the value of a material 
+/// parameter.
+pub trait Texture<T>: std::fmt::Debug {
+    fn evaluate(&self, query: &TextureQuery) -> T;
+}
+
+/// The coordinate system a solid texture is evaluated in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TextureSpace {
+    /// The pattern is attached to the object and moves with it.
+    Object,
+    /// The pattern is fixed in the scene and objects move through it.
+    World,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TextureQuery {
+    /// The point on the surface in model space.
+    pub point: Vector3<f32>,
+    /// The point on the surface in world space. Queries made without knowledge
+    /// of the placement of the object in the scene use the model space point.
+    pub world_point: Vector3<f32>,
+    /// The surface parameters of the point.
+    pub uv: Vector2<f32>,
+}
+
+impl TextureQuery {
+    pub fn new(point: Vector3<f32>, uv: Vector2<f32>) -> Self {
+        Self { point, world_point: point, uv, }
+    }
+
+    pub fn from_points(point: Vector3<f32>, world_point: Vector3<f32>, uv: Vector2<f32>) -> Self {
+        Self { point, world_point, uv, }
+    }
+
+    /// Returns the point on the surface in the coordinate system `space`.
+    #[inline]
+    pub fn point_in(&self, space: TextureSpace) -> Vector3<f32> {
+        match space {
+            TextureSpace::Object => self.point,
+            TextureSpace::World => self.world_point,
+        }
+    }
+}
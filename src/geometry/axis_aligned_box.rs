@@ -0,0 +1,145 @@
+use crate::core::*;
+use cglinalg::{
+    Vector2,
+    Vector3,
+};
+
+
+/// A box whose faces are perpendicular to the coordinate axes of model space.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AxisAlignedBox {
+    /// The corner of the box with the smallest coordinates in model space.
+    pub min: Vector3<f32>,
+    /// The corner of the box with the largest coordinates in model space.
+    pub max: Vector3<f32>,
+}
+
+impl AxisAlignedBox {
+    /// Construct a new model space box from two opposite corners, in any order.
+    pub fn new(corner1: Vector3<f32>, corner2: Vector3<f32>) -> Self {
+        let bounds = BoundingBox::new(corner1, corner2);
+
+        Self { min: bounds.min, max: bounds.max, }
+    }
+
+    /// Construct a new model space box filling a bounding box.
+    pub fn from_bounds(bounds: &BoundingBox) -> Self {
+        Self { min: bounds.min, max: bounds.max, }
+    }
+
+    /// Compute the intersection data at a point on the surface of the box.
+    ///
+    /// Each face is parameterized over `[0, 1]` by the two coordinates the face
+    /// spans, in the order `(z, y)`, `(x, z)`, and `(x, y)` for the faces 
+    /// perpendicular to the **x-axis**, the **y-axis**, and the **z-axis**.
+    fn surface_data(&self, t: f32, point: Vector3<f32>) -> IntersectionData {
+        let center = (self.min + self.max) * 0.5;
+        let half_extent = (self.max - self.min) * 0.5;
+        // The face containing the point is the one along whose axis the point lies
+        // farthest from the center, relative to the size of the box.
+        let mut axis = 0;
+        let mut distance_max = f32::NEG_INFINITY;
+        for i in 0..3 {
+            let distance = f32::abs((point[i] - center[i]) / half_extent[i]);
+            if distance > distance_max {
+                distance_max = distance;
+                axis = i;
+            }
+        }
+
+        let mut normal = Vector3::zero();
+        normal[axis] = f32::signum(point[axis] - center[axis]);
+        let (u_axis, v_axis) = match axis {
+            0 => (2, 1),
+            1 => (0, 2),
+            _ => (0, 1),
+        };
+        let extent = self.max - self.min;
+        let u = (point[u_axis] - self.min[u_axis]) / extent[u_axis];
+        let v = (point[v_axis] - self.min[v_axis]) / extent[v_axis];
+        let mut dpdu = Vector3::zero();
+        dpdu[u_axis] = extent[u_axis];
+        let mut dpdv = Vector3::zero();
+        dpdv[v_axis] = extent[v_axis];
+
+        IntersectionData::from_surface(t, point, normal, normal, dpdu, dpdv, Vector2::new(u, v))
+    }
+}
+
+impl Intersection for AxisAlignedBox {
+    fn intersect(&self, query: &IntersectionQuery) -> IntersectionResult {
+        let bounds = BoundingBox { min: self.min, max: self.max, };
+        if let Some((t_enter, t_exit)) = bounds.intersect(&query.ray, f32::NEG_INFINITY, f32::INFINITY) {
+            for t in [t_enter, t_exit] {
+                if t > query.t_min && t < query.t_max {
+                    let point_of_intersection = query.ray.interpolate(t);
+                    let point_error = ray_point_error(&query.ray, t);
+                    let data = self.surface_data(t, point_of_intersection).with_point_error(point_error);
+
+                    return if t_enter == t_exit {
+                        IntersectionResult::Tangent(data)
+                    } else {
+                        IntersectionResult::Hit(data)
+                    };
+                }
+            }
+        }
+
+        IntersectionResult::new_miss(Reason::NoIntersection)
+    }
+}
+
+impl Geometry for AxisAlignedBox {
+    #[inline]
+    fn center(&self) -> Vector3<f32> {
+        (self.min + self.max) * 0.5
+    }
+
+    fn contains(&self, point: &Vector3<f32>) -> bool {
+        BoundingBox { min: self.min, max: self.max, }.contains(point)
+    }
+
+    #[inline]
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox { min: self.min, max: self.max, }
+    }
+
+    fn area(&self) -> f32 {
+        let extent = self.max - self.min;
+
+        2_f32 * (extent.x * extent.y + extent.y * extent.z + extent.z * extent.x)
+    }
+
+    fn sample_surface(&self, sampler: &mut SphereSampler) -> Option<SurfaceSample> {
+        let extent = self.max - self.min;
+        // Choose the axis of the face in proportion to the area of the faces 
+        // perpendicular to it, then one of the two faces.
+        let face_areas = [extent.y * extent.z, extent.z * extent.x, extent.x * extent.y];
+        let mut choice = sampler.sample_f32() * (face_areas[0] + face_areas[1] + face_areas[2]);
+        let mut axis = 2;
+        for (i, face_area) in face_areas.iter().enumerate() {
+            if choice < *face_area {
+                axis = i;
+                break;
+            }
+
+            choice -= face_area;
+        }
+        let is_max_face = sampler.sample_f32() < 0.5;
+
+        let mut point = Vector3::zero();
+        for i in 0..3 {
+            point[i] = self.min[i] + sampler.sample_f32() * extent[i];
+        }
+        let mut normal = Vector3::zero();
+        if is_max_face {
+            point[axis] = self.max[axis];
+            normal[axis] = 1_f32;
+        } else {
+            point[axis] = self.min[axis];
+            normal[axis] = -1_f32;
+        }
+
+        Some(SurfaceSample::new(point, normal, 1_f32 / self.area()))
+    }
+}
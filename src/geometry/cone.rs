@@ -0,0 +1,126 @@
+use crate::core::*;
+use crate::geometry::roots::*;
+use cglinalg::{
+    Vector2,
+    Vector3,
+    Magnitude,
+};
+
+
+/// An open cone about an axis parallel to the **y-axis** of model space, with
+/// its apex above its base. A disk closes the base of a cone.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Cone {
+    /// The center of the base of the cone in model space.
+    pub base: Vector3<f32>,
+    /// The radius of the base of the cone in model space.
+    pub radius: f32,
+    /// The distance from the base of the cone to its apex in model space.
+    pub height: f32,
+}
+
+impl Cone {
+    /// Construct a new model space cone.
+    pub fn new(base: Vector3<f32>, radius: f32, height: f32) -> Self {
+        Self { base, radius, height, }
+    }
+
+    /// Returns the apex of the cone in model space.
+    #[inline]
+    pub fn apex(&self) -> Vector3<f32> {
+        self.base + Vector3::new(0_f32, self.height, 0_f32)
+    }
+
+    /// Compute the intersection data at a point on the surface of the cone.
+    ///
+    /// The cone is parameterized by the angle **u** about its axis and the height
+    /// **v** from the base to the apex, both in `[0, 1]`.
+    fn surface_data(&self, t: f32, point: Vector3<f32>) -> IntersectionData {
+        let offset = point - self.base;
+        let phi = f32::atan2(offset.z, offset.x).rem_euclid(2_f32 * std::f32::consts::PI);
+        let (sin_phi, cos_phi) = f32::sin_cos(phi);
+        let u = phi / (2_f32 * std::f32::consts::PI);
+        let v = offset.y / self.height;
+        let distance = self.radius * (1_f32 - v);
+        // The normal tilts up from the horizontal by the half angle of the apex.
+        let normal = Vector3::new(self.height * cos_phi, self.radius, self.height * sin_phi).normalize();
+        let dpdu = Vector3::new(-distance * sin_phi, 0_f32, distance * cos_phi) * (2_f32 * std::f32::consts::PI);
+        let dpdv = Vector3::new(-self.radius * cos_phi, self.height, -self.radius * sin_phi);
+
+        IntersectionData::from_surface(t, point, normal, normal, dpdu, dpdv, Vector2::new(u, v))
+    }
+}
+
+impl Intersection for Cone {
+    fn intersect(&self, query: &IntersectionQuery) -> IntersectionResult {
+        // Points on the cone satisfy `x^2 + z^2 = (k * (height - y))^2` relative to
+        // the base, where `k` is the slope of the cone.
+        let k = self.radius / self.height;
+        let k_squared = k * k;
+        let origin = query.ray.origin - self.base;
+        let direction = query.ray.direction;
+        let height_above = self.height - origin.y;
+        let a = direction.x * direction.x + direction.z * direction.z - k_squared * direction.y * direction.y;
+        let b = 2_f32 * (origin.x * direction.x + origin.z * direction.z + k_squared * height_above * direction.y);
+        let c = origin.x * origin.x + origin.z * origin.z - k_squared * height_above * height_above;
+        if let Some((t0, t1)) = solve_quadratic(a, b, c) {
+            for t in [t0, t1] {
+                let y = origin.y + t * direction.y;
+                if t > query.t_min && t < query.t_max && (0_f32..=self.height).contains(&y) {
+                    let point_of_intersection = query.ray.interpolate(t);
+                    let point_error = ray_point_error(&query.ray, t);
+
+                    return IntersectionResult::Hit(self.surface_data(t, point_of_intersection).with_point_error(point_error));
+                }
+            }
+        }
+
+        IntersectionResult::new_miss(Reason::NoIntersection)
+    }
+}
+
+impl Geometry for Cone {
+    fn center(&self) -> Vector3<f32> {
+        self.base + Vector3::new(0_f32, 0.5 * self.height, 0_f32)
+    }
+
+    /// A cone contains the solid it would bound if its base were closed.
+    fn contains(&self, point: &Vector3<f32>) -> bool {
+        let offset = point - self.base;
+        if !(0_f32..=self.height).contains(&offset.y) {
+            return false;
+        }
+
+        let radius = self.radius * (1_f32 - offset.y / self.height);
+
+        offset.x * offset.x + offset.z * offset.z <= radius * radius
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        let min = self.base - Vector3::new(self.radius, 0_f32, self.radius);
+        let max = self.base + Vector3::new(self.radius, self.height, self.radius);
+
+        BoundingBox::new(min, max)
+    }
+
+    #[inline]
+    fn area(&self) -> f32 {
+        let slant_height = f32::sqrt(self.radius * self.radius + self.height * self.height);
+
+        std::f32::consts::PI * self.radius * slant_height
+    }
+
+    fn sample_surface(&self, sampler: &mut SphereSampler) -> Option<SurfaceSample> {
+        // The area of the cone within a distance of the axis grows with the
+        // square of the distance.
+        let distance = self.radius * f32::sqrt(sampler.sample_f32());
+        let phi = 2_f32 * std::f32::consts::PI * sampler.sample_f32();
+        let (sin_phi, cos_phi) = f32::sin_cos(phi);
+        let y = self.height * (1_f32 - distance / self.radius);
+        let point = self.base + Vector3::new(distance * cos_phi, y, distance * sin_phi);
+        let normal = Vector3::new(self.height * cos_phi, self.radius, self.height * sin_phi).normalize();
+
+        Some(SurfaceSample::new(point, normal, 1_f32 / self.area()))
+    }
+}
+
@@ -0,0 +1,301 @@
+use crate::core::*;
+use cglinalg::{
+    Vector3,
+};
+use rand::prelude::*;
+
+
+/// The number of surface samples used to estimate the surface area of a 
+/// constructive solid geometry node.
+const AREA_ESTIMATE_SAMPLES: usize = 4096;
+
+/// The number of surface samples of the children of a constructive solid 
+/// geometry node tried before sampling a point on its surface fails.
+const MAX_SURFACE_SAMPLE_ATTEMPTS: usize = 64;
+
+/// A boolean set operation combining two solids.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CsgOperation {
+    /// The points inside either solid.
+    Union,
+    /// The points inside both solids.
+    Intersection,
+    /// The points inside the first solid but not the second.
+    Difference,
+}
+
+impl CsgOperation {
+    /// Determine whether a point lies inside the combined solid, given whether it
+    /// lies inside each of the two solids.
+    #[inline]
+    fn apply(self, inside_left: bool, inside_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => inside_left || inside_right,
+            CsgOperation::Intersection => inside_left && inside_right,
+            CsgOperation::Difference => inside_left && !inside_right,
+        }
+    }
+}
+
+/// A crossing of the boundary of one of the children of a constructive solid 
+/// geometry node along a ray.
+#[derive(Copy, Clone, Debug)]
+struct BoundaryCrossing {
+    t: f32,
+    data: Option<IntersectionData>,
+    is_left: bool,
+    is_entering: bool,
+}
+
+/// A constructive solid geometry node, combining two solids with a boolean set 
+/// operation.
+///
+/// The children may themselves be constructive solid geometry nodes, so a tree
+/// of nodes models an arbitrary boolean combination of solids. The children 
+/// must enclose solids, with outward facing normals, for the node to be well 
+/// defined. The surface of the difference of two solids includes the part of 
+/// the surface of the second solid inside the first one, with its normals 
+/// flipped to face out of the difference.
+#[derive(Debug)]
+pub struct Csg {
+    operation: CsgOperation,
+    left: Box<dyn Geometry>,
+    right: Box<dyn Geometry>,
+    area: f32,
+}
+
+impl Csg {
+    /// Construct a new constructive solid geometry node combining the solids 
+    /// `left` and `right` with the operation `operation`.
+    pub fn new(operation: CsgOperation, left: Box<dyn Geometry>, right: Box<dyn Geometry>) -> Self {
+        let mut csg = Self { operation, left, right, area: 0_f32, };
+        csg.area = csg.estimate_area();
+
+        csg
+    }
+
+    /// Construct the union of two solids.
+    pub fn union(left: Box<dyn Geometry>, right: Box<dyn Geometry>) -> Self {
+        Self::new(CsgOperation::Union, left, right)
+    }
+
+    /// Construct the intersection of two solids.
+    pub fn intersection(left: Box<dyn Geometry>, right: Box<dyn Geometry>) -> Self {
+        Self::new(CsgOperation::Intersection, left, right)
+    }
+
+    /// Construct the difference of two solids, removing the second solid from 
+    /// the first one.
+    pub fn difference(left: Box<dyn Geometry>, right: Box<dyn Geometry>) -> Self {
+        Self::new(CsgOperation::Difference, left, right)
+    }
+
+    /// Returns the operation combining the children of the node.
+    #[inline]
+    pub fn operation(&self) -> CsgOperation {
+        self.operation
+    }
+
+    /// Determine whether a point on the surface of one of the children lies on 
+    /// the surface of the combined solid.
+    fn is_on_surface(&self, point: &Vector3<f32>, is_left: bool) -> bool {
+        let inside_other = if is_left {
+            self.right.contains(point)
+        } else {
+            self.left.contains(point)
+        };
+
+        match (self.operation, is_left) {
+            (CsgOperation::Union, _) => !inside_other,
+            (CsgOperation::Intersection, _) => inside_other,
+            (CsgOperation::Difference, true) => !inside_other,
+            (CsgOperation::Difference, false) => inside_other,
+        }
+    }
+
+    /// Determine whether the normals of a child face the opposite way on the 
+    /// surface of the combined solid.
+    #[inline]
+    fn is_flipped(&self, is_left: bool) -> bool {
+        self.operation == CsgOperation::Difference && !is_left
+    }
+
+    /// Sample a point on the surface of one of the children, chosen in 
+    /// proportion to their surface areas, and report which child it came from.
+    fn sample_child_surface(&self, sampler: &mut SphereSampler) -> Option<(SurfaceSample, bool)> {
+        let left_area = self.left.area();
+        let right_area = self.right.area();
+        let is_left = sampler.sample_f32() * (left_area + right_area) < left_area;
+        let sample = if is_left {
+            self.left.sample_surface(sampler)
+        } else {
+            self.right.sample_surface(sampler)
+        };
+
+        sample.map(|sample| (sample, is_left))
+    }
+
+    /// Estimate the surface area of the combined solid from the fraction of the 
+    /// surfaces of the children that lies on its surface.
+    fn estimate_area(&self) -> f32 {
+        let total_area = self.left.area() + self.right.area();
+        if !total_area.is_finite() {
+            return f32::INFINITY;
+        }
+
+        let mut sampler = SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0));
+        let mut count = 0;
+        for _ in 0..AREA_ESTIMATE_SAMPLES {
+            if let Some((sample, is_left)) = self.sample_child_surface(&mut sampler) {
+                if self.is_on_surface(&sample.point, is_left) {
+                    count += 1;
+                }
+            }
+        }
+
+        total_area * (count as f32) / (AREA_ESTIMATE_SAMPLES as f32)
+    }
+
+    /// Flip the orientation of intersection data on the surface of a child whose
+    /// normals face into the combined solid.
+    fn flip(data: &IntersectionData) -> IntersectionData {
+        let mut flipped = *data;
+        flipped.normal = -data.normal;
+        flipped.shading_normal = -data.shading_normal;
+
+        flipped
+    }
+
+    /// Collect the boundary crossings of the intervals of a child along a ray.
+    /// The crossings where the intervals are cut off by the range of the query 
+    /// are left out.
+    fn boundary_crossings(intervals: &[RayInterval], is_left: bool, crossings: &mut Vec<BoundaryCrossing>) {
+        for interval in intervals.iter() {
+            if let Some(entry) = interval.entry {
+                crossings.push(BoundaryCrossing { t: interval.t_entry, data: Some(entry), is_left, is_entering: true, });
+            }
+            if let Some(exit) = interval.exit {
+                crossings.push(BoundaryCrossing { t: interval.t_exit, data: Some(exit), is_left, is_entering: false, });
+            }
+        }
+    }
+}
+
+impl Intersection for Csg {
+    fn intersect(&self, query: &IntersectionQuery) -> IntersectionResult {
+        for interval in self.intersect_intervals(query) {
+            if let Some(entry) = interval.entry {
+                return IntersectionResult::Hit(entry);
+            }
+            if let Some(exit) = interval.exit {
+                return IntersectionResult::Hit(exit);
+            }
+        }
+
+        IntersectionResult::new_miss(Reason::NoIntersection)
+    }
+}
+
+impl Geometry for Csg {
+    fn center(&self) -> Vector3<f32> {
+        let bounds = self.bounds();
+        if bounds.is_empty() || bounds.is_infinite() {
+            self.left.center()
+        } else {
+            bounds.center()
+        }
+    }
+
+    #[inline]
+    fn contains(&self, point: &Vector3<f32>) -> bool {
+        self.operation.apply(self.left.contains(point), self.right.contains(point))
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        match self.operation {
+            CsgOperation::Union => self.left.bounds().union(&self.right.bounds()),
+            CsgOperation::Intersection => self.left.bounds().intersection(&self.right.bounds()),
+            CsgOperation::Difference => self.left.bounds(),
+        }
+    }
+
+    /// The surface area of a constructive solid geometry node is a Monte Carlo 
+    /// estimate made when the node is constructed.
+    #[inline]
+    fn area(&self) -> f32 {
+        self.area
+    }
+
+    /// Sample the surfaces of the children until a point lands on the surface of
+    /// the combined solid. Sampling fails when the children are unbounded, or 
+    /// when no sample lands on the surface after a fixed number of attempts.
+    fn sample_surface(&self, sampler: &mut SphereSampler) -> Option<SurfaceSample> {
+        if !self.area.is_finite() || self.area <= 0_f32 {
+            return None;
+        }
+
+        for _ in 0..MAX_SURFACE_SAMPLE_ATTEMPTS {
+            let (sample, is_left) = self.sample_child_surface(sampler)?;
+            if self.is_on_surface(&sample.point, is_left) {
+                let normal = if self.is_flipped(is_left) { -sample.normal } else { sample.normal };
+
+                return Some(SurfaceSample::new(sample.point, normal, 1_f32 / self.area));
+            }
+        }
+
+        None
+    }
+
+    /// Combine the intervals of the children by sweeping over their boundary 
+    /// crossings in order along the ray, keeping track of which children the 
+    /// ray is inside of.
+    fn intersect_intervals(&self, query: &IntersectionQuery) -> Vec<RayInterval> {
+        let left_intervals = self.left.intersect_intervals(query);
+        let right_intervals = self.right.intersect_intervals(query);
+        let starts_inside = |intervals: &[RayInterval]| {
+            intervals.first().is_some_and(|interval| interval.entry.is_none())
+        };
+        let mut inside_left = starts_inside(&left_intervals);
+        let mut inside_right = starts_inside(&right_intervals);
+
+        let mut crossings = Vec::with_capacity(2 * (left_intervals.len() + right_intervals.len()));
+        Self::boundary_crossings(&left_intervals, true, &mut crossings);
+        Self::boundary_crossings(&right_intervals, false, &mut crossings);
+        crossings.sort_by(|crossing1, crossing2| crossing1.t.total_cmp(&crossing2.t));
+
+        let mut intervals = Vec::new();
+        let mut inside = self.operation.apply(inside_left, inside_right);
+        let mut current_entry = if inside { Some((query.t_min, None)) } else { None };
+        for crossing in crossings.iter() {
+            if crossing.is_left {
+                inside_left = crossing.is_entering;
+            } else {
+                inside_right = crossing.is_entering;
+            }
+
+            let now_inside = self.operation.apply(inside_left, inside_right);
+            if now_inside == inside {
+                continue;
+            }
+
+            let data = if self.is_flipped(crossing.is_left) {
+                crossing.data.map(|data| Self::flip(&data))
+            } else {
+                crossing.data
+            };
+            if now_inside {
+                current_entry = Some((crossing.t, data));
+            } else if let Some((t_entry, entry)) = current_entry.take() {
+                intervals.push(RayInterval::new(t_entry, crossing.t, entry, data));
+            }
+            inside = now_inside;
+        }
+
+        if let Some((t_entry, entry)) = current_entry {
+            intervals.push(RayInterval::new(t_entry, query.t_max, entry, None));
+        }
+
+        intervals
+    }
+}
+
@@ -0,0 +1,102 @@
+use crate::core::*;
+use crate::geometry::roots::*;
+use cglinalg::{
+    Vector2,
+    Vector3,
+};
+
+
+/// An open cylinder about an axis parallel to the **y-axis** of model space, 
+/// without caps. Disks cap the ends of a cylinder.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Cylinder {
+    /// The point in the middle of the axis of the cylinder in model space.
+    pub center: Vector3<f32>,
+    /// The radius of the cylinder in model space.
+    pub radius: f32,
+    /// The length of the cylinder along the **y-axis** in model space.
+    pub height: f32,
+}
+
+impl Cylinder {
+    /// Construct a new model space cylinder.
+    pub fn new(center: Vector3<f32>, radius: f32, height: f32) -> Self {
+        Self { center, radius, height, }
+    }
+
+    /// Compute the intersection data at a point on the surface of the cylinder.
+    ///
+    /// The cylinder is parameterized by the angle **u** about its axis and the 
+    /// height **v** from the bottom to the top, both in `[0, 1]`.
+    fn surface_data(&self, t: f32, point: Vector3<f32>) -> IntersectionData {
+        let offset = point - self.center;
+        let phi = f32::atan2(offset.z, offset.x).rem_euclid(2_f32 * std::f32::consts::PI);
+        let (sin_phi, cos_phi) = f32::sin_cos(phi);
+        let u = phi / (2_f32 * std::f32::consts::PI);
+        let v = (offset.y + 0.5 * self.height) / self.height;
+        let normal = Vector3::new(cos_phi, 0_f32, sin_phi);
+        let dpdu = Vector3::new(-sin_phi, 0_f32, cos_phi) * (2_f32 * std::f32::consts::PI * self.radius);
+        let dpdv = Vector3::new(0_f32, self.height, 0_f32);
+
+        IntersectionData::from_surface(t, point, normal, normal, dpdu, dpdv, Vector2::new(u, v))
+    }
+}
+
+impl Intersection for Cylinder {
+    fn intersect(&self, query: &IntersectionQuery) -> IntersectionResult {
+        let origin = query.ray.origin - self.center;
+        let direction = query.ray.direction;
+        let a = direction.x * direction.x + direction.z * direction.z;
+        let b = 2_f32 * (origin.x * direction.x + origin.z * direction.z);
+        let c = origin.x * origin.x + origin.z * origin.z - self.radius * self.radius;
+        if let Some((t0, t1)) = solve_quadratic(a, b, c) {
+            for t in [t0, t1] {
+                let y = origin.y + t * direction.y;
+                if t > query.t_min && t < query.t_max && f32::abs(y) <= 0.5 * self.height {
+                    let point_of_intersection = query.ray.interpolate(t);
+                    let point_error = ray_point_error(&query.ray, t);
+
+                    return IntersectionResult::Hit(self.surface_data(t, point_of_intersection).with_point_error(point_error));
+                }
+            }
+        }
+
+        IntersectionResult::new_miss(Reason::NoIntersection)
+    }
+}
+
+impl Geometry for Cylinder {
+    #[inline]
+    fn center(&self) -> Vector3<f32> {
+        self.center
+    }
+
+    /// A cylinder contains the solid it would bound if it had caps.
+    fn contains(&self, point: &Vector3<f32>) -> bool {
+        let offset = point - self.center;
+
+        offset.x * offset.x + offset.z * offset.z <= self.radius * self.radius && 
+            f32::abs(offset.y) <= 0.5 * self.height
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        let extent = Vector3::new(self.radius, 0.5 * self.height, self.radius);
+
+        BoundingBox::new(self.center - extent, self.center + extent)
+    }
+
+    #[inline]
+    fn area(&self) -> f32 {
+        2_f32 * std::f32::consts::PI * self.radius * self.height
+    }
+
+    fn sample_surface(&self, sampler: &mut SphereSampler) -> Option<SurfaceSample> {
+        let phi = 2_f32 * std::f32::consts::PI * sampler.sample_f32();
+        let y = (sampler.sample_f32() - 0.5) * self.height;
+        let normal = Vector3::new(f32::cos(phi), 0_f32, f32::sin(phi));
+        let point = self.center + normal * self.radius + Vector3::new(0_f32, y, 0_f32);
+
+        Some(SurfaceSample::new(point, normal, 1_f32 / self.area()))
+    }
+}
+
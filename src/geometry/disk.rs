@@ -0,0 +1,107 @@
+use crate::core::*;
+use cglinalg::{
+    Vector2,
+    Vector3,
+};
+
+
+/// A flat disk, or an annulus when it has a hole in the middle, perpendicular to
+/// the **y-axis** of model space and facing the **positive y-axis**.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Disk {
+    /// The center of the disk in model space.
+    pub center: Vector3<f32>,
+    /// The outer radius of the disk in model space.
+    pub radius: f32,
+    /// The radius of the hole in the middle of the disk in model space.
+    pub inner_radius: f32,
+}
+
+impl Disk {
+    /// Construct a new model space disk without a hole.
+    pub fn new(center: Vector3<f32>, radius: f32) -> Self {
+        Self { center, radius, inner_radius: 0_f32, }
+    }
+
+    /// Cut a hole of radius `inner_radius` out of the middle of the disk.
+    pub fn with_inner_radius(mut self, inner_radius: f32) -> Self {
+        self.inner_radius = inner_radius;
+        self
+    }
+
+    /// Compute the intersection data at a point on the disk.
+    ///
+    /// The disk is parameterized by the angle **u** about the **y-axis** and the
+    /// distance **v** from the inner radius to the outer radius, both in `[0, 1]`.
+    fn surface_data(&self, t: f32, point: Vector3<f32>) -> IntersectionData {
+        let offset = point - self.center;
+        let distance = f32::sqrt(offset.x * offset.x + offset.z * offset.z);
+        let phi = f32::atan2(offset.z, offset.x).rem_euclid(2_f32 * std::f32::consts::PI);
+        let (sin_phi, cos_phi) = f32::sin_cos(phi);
+        let width = self.radius - self.inner_radius;
+        let u = phi / (2_f32 * std::f32::consts::PI);
+        let v = (distance - self.inner_radius) / width;
+        let dpdu = Vector3::new(-distance * sin_phi, 0_f32, distance * cos_phi) * (2_f32 * std::f32::consts::PI);
+        let dpdv = Vector3::new(cos_phi, 0_f32, sin_phi) * width;
+        let normal = Vector3::unit_y();
+
+        IntersectionData::from_surface(t, point, normal, normal, dpdu, dpdv, Vector2::new(u, v))
+    }
+}
+
+impl Intersection for Disk {
+    fn intersect(&self, query: &IntersectionQuery) -> IntersectionResult {
+        if query.ray.direction.y == 0_f32 {
+            return IntersectionResult::new_miss(Reason::NoIntersection);
+        }
+
+        let t = (self.center.y - query.ray.origin.y) / query.ray.direction.y;
+        if t > query.t_min && t < query.t_max {
+            let point_of_intersection = query.ray.interpolate(t);
+            let offset = point_of_intersection - self.center;
+            let distance_squared = offset.x * offset.x + offset.z * offset.z;
+            if distance_squared <= self.radius * self.radius && distance_squared >= self.inner_radius * self.inner_radius {
+                let point_error = ray_point_error(&query.ray, t);
+
+                return IntersectionResult::Hit(self.surface_data(t, point_of_intersection).with_point_error(point_error));
+            }
+        }
+
+        IntersectionResult::new_miss(Reason::NoIntersection)
+    }
+}
+
+impl Geometry for Disk {
+    #[inline]
+    fn center(&self) -> Vector3<f32> {
+        self.center
+    }
+
+    /// A disk has no interior.
+    #[inline]
+    fn contains(&self, _point: &Vector3<f32>) -> bool {
+        false
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        let extent = Vector3::new(self.radius, 0_f32, self.radius);
+
+        BoundingBox::new(self.center - extent, self.center + extent)
+    }
+
+    #[inline]
+    fn area(&self) -> f32 {
+        std::f32::consts::PI * (self.radius * self.radius - self.inner_radius * self.inner_radius)
+    }
+
+    fn sample_surface(&self, sampler: &mut SphereSampler) -> Option<SurfaceSample> {
+        let inner_radius_squared = self.inner_radius * self.inner_radius;
+        let radius_squared = self.radius * self.radius;
+        let distance = f32::sqrt(inner_radius_squared + sampler.sample_f32() * (radius_squared - inner_radius_squared));
+        let phi = 2_f32 * std::f32::consts::PI * sampler.sample_f32();
+        let point = self.center + Vector3::new(distance * f32::cos(phi), 0_f32, distance * f32::sin(phi));
+
+        Some(SurfaceSample::new(point, Vector3::unit_y(), 1_f32 / self.area()))
+    }
+}
+
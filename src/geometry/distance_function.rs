@@ -0,0 +1,263 @@
+use crate::core::*;
+use cglinalg::{
+    Vector3,
+    Magnitude,
+};
+
+
+/// A signed distance function describing an implicit surface.
+///
+/// The signed distance is negative inside the surface and positive outside of
+/// it. Sphere tracing only needs a bound on the distance to the surface, so a 
+/// distance function may underestimate the distance, and a function that 
+/// overestimates it by at most a constant factor reports that factor as its 
+/// Lipschitz constant.
+pub trait DistanceFunction: std::fmt::Debug {
+    /// Compute the signed distance from a point to the surface.
+    fn distance(&self, point: &Vector3<f32>) -> f32;
+
+    /// Returns a bounding box containing the surface.
+    fn bounds(&self) -> BoundingBox;
+
+    /// Returns a bound on the rate of change of the distance function. Sphere 
+    /// tracing divides its steps by this constant.
+    #[inline]
+    fn lipschitz_constant(&self) -> f32 {
+        1_f32
+    }
+}
+
+/// The distance function of a sphere.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SphereDistance {
+    pub center: Vector3<f32>,
+    pub radius: f32,
+}
+
+impl SphereDistance {
+    pub fn new(center: Vector3<f32>, radius: f32) -> Self {
+        Self { center, radius, }
+    }
+}
+
+impl DistanceFunction for SphereDistance {
+    #[inline]
+    fn distance(&self, point: &Vector3<f32>) -> f32 {
+        (point - self.center).magnitude() - self.radius
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        let extent = Vector3::from_fill(self.radius);
+
+        BoundingBox::new(self.center - extent, self.center + extent)
+    }
+}
+
+/// The distance function of a box with sides parallel to the coordinate axes.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BoxDistance {
+    pub center: Vector3<f32>,
+    /// Half the lengths of the sides of the box.
+    pub half_extent: Vector3<f32>,
+}
+
+impl BoxDistance {
+    pub fn new(center: Vector3<f32>, half_extent: Vector3<f32>) -> Self {
+        Self { center, half_extent, }
+    }
+}
+
+impl DistanceFunction for BoxDistance {
+    fn distance(&self, point: &Vector3<f32>) -> f32 {
+        let offset = point - self.center;
+        let q = Vector3::new(
+            f32::abs(offset.x) - self.half_extent.x,
+            f32::abs(offset.y) - self.half_extent.y,
+            f32::abs(offset.z) - self.half_extent.z,
+        );
+        let outside = Vector3::new(f32::max(q.x, 0_f32), f32::max(q.y, 0_f32), f32::max(q.z, 0_f32));
+        let inside = f32::min(f32::max(q.x, f32::max(q.y, q.z)), 0_f32);
+
+        outside.magnitude() + inside
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(self.center - self.half_extent, self.center + self.half_extent)
+    }
+}
+
+/// The distance function of a torus about an axis parallel to the **y-axis**.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TorusDistance {
+    pub center: Vector3<f32>,
+    pub major_radius: f32,
+    pub minor_radius: f32,
+}
+
+impl TorusDistance {
+    pub fn new(center: Vector3<f32>, major_radius: f32, minor_radius: f32) -> Self {
+        Self { center, major_radius, minor_radius, }
+    }
+}
+
+impl DistanceFunction for TorusDistance {
+    fn distance(&self, point: &Vector3<f32>) -> f32 {
+        let offset = point - self.center;
+        let distance_to_ring = f32::sqrt(offset.x * offset.x + offset.z * offset.z) - self.major_radius;
+
+        f32::sqrt(distance_to_ring * distance_to_ring + offset.y * offset.y) - self.minor_radius
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        let extent = self.major_radius + self.minor_radius;
+        let extent = Vector3::new(extent, self.minor_radius, extent);
+
+        BoundingBox::new(self.center - extent, self.center + extent)
+    }
+}
+
+/// The union of two implicit surfaces, blended together where they come within
+/// a distance `smoothness` of each other. A smoothness of zero gives the exact
+/// union.
+#[derive(Debug)]
+pub struct SmoothUnion {
+    left: Box<dyn DistanceFunction>,
+    right: Box<dyn DistanceFunction>,
+    smoothness: f32,
+}
+
+impl SmoothUnion {
+    pub fn new(left: Box<dyn DistanceFunction>, right: Box<dyn DistanceFunction>, smoothness: f32) -> Self {
+        Self { left, right, smoothness, }
+    }
+}
+
+impl DistanceFunction for SmoothUnion {
+    fn distance(&self, point: &Vector3<f32>) -> f32 {
+        let distance_left = self.left.distance(point);
+        let distance_right = self.right.distance(point);
+        if self.smoothness <= 0_f32 {
+            return f32::min(distance_left, distance_right);
+        }
+
+        // The polynomial smooth minimum.
+        let h = (0.5 + 0.5 * (distance_right - distance_left) / self.smoothness).clamp(0_f32, 1_f32);
+
+        distance_right + (distance_left - distance_right) * h - self.smoothness * h * (1_f32 - h)
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        // The blend pushes the surface out by at most a quarter of the smoothness.
+        let extent = Vector3::from_fill(0.25 * f32::max(self.smoothness, 0_f32));
+        let bounds = self.left.bounds().union(&self.right.bounds());
+
+        BoundingBox::new(bounds.min - extent, bounds.max + extent)
+    }
+
+    #[inline]
+    fn lipschitz_constant(&self) -> f32 {
+        f32::max(self.left.lipschitz_constant(), self.right.lipschitz_constant())
+    }
+}
+
+/// An implicit surface twisted about the **y-axis**, by the angle `rate` in 
+/// radians per unit of height.
+#[derive(Debug)]
+pub struct Twist {
+    inner: Box<dyn DistanceFunction>,
+    rate: f32,
+}
+
+impl Twist {
+    pub fn new(inner: Box<dyn DistanceFunction>, rate: f32) -> Self {
+        Self { inner, rate, }
+    }
+
+    /// Returns the largest distance of the bounds of the twisted surface from 
+    /// the **y-axis**.
+    fn radius(&self) -> f32 {
+        let bounds = self.inner.bounds();
+        let x = f32::max(f32::abs(bounds.min.x), f32::abs(bounds.max.x));
+        let z = f32::max(f32::abs(bounds.min.z), f32::abs(bounds.max.z));
+
+        f32::sqrt(x * x + z * z)
+    }
+}
+
+impl DistanceFunction for Twist {
+    fn distance(&self, point: &Vector3<f32>) -> f32 {
+        let (sin_angle, cos_angle) = f32::sin_cos(-self.rate * point.y);
+        let untwisted = Vector3::new(
+            cos_angle * point.x - sin_angle * point.z,
+            point.y,
+            sin_angle * point.x + cos_angle * point.z,
+        );
+
+        self.inner.distance(&untwisted)
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        let bounds = self.inner.bounds();
+        let radius = self.radius();
+
+        BoundingBox::new(
+            Vector3::new(-radius, bounds.min.y, -radius),
+            Vector3::new(radius, bounds.max.y, radius),
+        )
+    }
+
+    /// Twisting shears the space around the axis, stretching distances by up to
+    /// the rate of twist times the distance from the axis.
+    fn lipschitz_constant(&self) -> f32 {
+        let stretch = self.rate * self.radius();
+
+        self.inner.lipschitz_constant() * f32::sqrt(1_f32 + stretch * stretch)
+    }
+}
+
+/// An implicit surface repeated infinitely on a grid with cells of size 
+/// `period`, centered at the origin. An axis with a period of zero is not 
+/// repeated. The surface must fit inside the cell centered at the origin.
+#[derive(Debug)]
+pub struct Repetition {
+    inner: Box<dyn DistanceFunction>,
+    period: Vector3<f32>,
+}
+
+impl Repetition {
+    pub fn new(inner: Box<dyn DistanceFunction>, period: Vector3<f32>) -> Self {
+        Self { inner, period, }
+    }
+}
+
+impl DistanceFunction for Repetition {
+    fn distance(&self, point: &Vector3<f32>) -> f32 {
+        let mut local_point = *point;
+        for axis in 0..3 {
+            let period = self.period[axis];
+            if period > 0_f32 {
+                local_point[axis] -= period * f32::round(point[axis] / period);
+            }
+        }
+
+        self.inner.distance(&local_point)
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        let mut bounds = self.inner.bounds();
+        for axis in 0..3 {
+            if self.period[axis] > 0_f32 {
+                bounds.min[axis] = f32::NEG_INFINITY;
+                bounds.max[axis] = f32::INFINITY;
+            }
+        }
+
+        bounds
+    }
+
+    #[inline]
+    fn lipschitz_constant(&self) -> f32 {
+        self.inner.lipschitz_constant()
+    }
+}
+
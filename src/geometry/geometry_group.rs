@@ -0,0 +1,122 @@
+use crate::core::*;
+use cglinalg::{
+    Vector3,
+};
+
+
+/// A group of geometry acting as a single geometry, such as the pieces of a 
+/// model made of many primitives.
+///
+/// A group finds the child a ray hits with a bounding volume hierarchy of its
+/// own, so a group shared between many instances in a scene forms the bottom 
+/// level of a two level hierarchy, below the hierarchy of the instances in the
+/// scene. The surface of a group is the union of the surfaces of its children,
+/// and its interior is the union of their interiors.
+#[derive(Debug)]
+pub struct GeometryGroup {
+    children: Vec<Box<dyn Geometry>>,
+    bvh: Bvh,
+    bounds: BoundingBox,
+    /// The running totals of the areas of the children, for choosing a child to
+    /// sample in proportion to its area.
+    cumulative_areas: Vec<f32>,
+}
+
+impl GeometryGroup {
+    /// Construct a new group from the geometry `children`.
+    pub fn new(children: Vec<Box<dyn Geometry>>) -> Self {
+        let child_bounds = children
+            .iter()
+            .map(|child| child.bounds())
+            .collect::<Vec<BoundingBox>>();
+        let bvh = Bvh::new(&child_bounds);
+        let bounds = child_bounds
+            .iter()
+            .fold(BoundingBox::empty(), |bounds, child_bounds| bounds.union(child_bounds));
+        let mut total_area = 0_f32;
+        let cumulative_areas = children
+            .iter()
+            .map(|child| {
+                total_area += child.area();
+                total_area
+            })
+            .collect::<Vec<f32>>();
+
+        Self { children, bvh, bounds, cumulative_areas, }
+    }
+
+    /// Returns the number of children in the group.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.children.len()
+    }
+
+    /// Determine whether the group has no children.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+impl Intersection for GeometryGroup {
+    fn intersect(&self, query: &IntersectionQuery) -> IntersectionResult {
+        let mut closest_result = IntersectionResult::new_miss(Reason::NoIntersection);
+        self.bvh.traverse(&query.ray, query.t_min, query.t_max, |index, t_closest| {
+            let child_query = IntersectionQuery::new(query.ray, query.t_min, t_closest);
+            match self.children[index].intersect(&child_query) {
+                result @ (IntersectionResult::Hit(data) | IntersectionResult::Tangent(data)) if data.t < t_closest => {
+                    closest_result = result;
+
+                    data.t
+                }
+                _ => t_closest,
+            }
+        });
+
+        closest_result
+    }
+}
+
+impl Geometry for GeometryGroup {
+    fn center(&self) -> Vector3<f32> {
+        if self.bounds.is_empty() || self.bounds.is_infinite() {
+            Vector3::zero()
+        } else {
+            self.bounds.center()
+        }
+    }
+
+    fn contains(&self, point: &Vector3<f32>) -> bool {
+        self.children.iter().any(|child| child.contains(point))
+    }
+
+    #[inline]
+    fn bounds(&self) -> BoundingBox {
+        self.bounds
+    }
+
+    /// The area of a group is the total area of its children, counting the 
+    /// parts of overlapping children hidden inside each other.
+    #[inline]
+    fn area(&self) -> f32 {
+        self.cumulative_areas.last().copied().unwrap_or(0_f32)
+    }
+
+    fn sample_surface(&self, sampler: &mut SphereSampler) -> Option<SurfaceSample> {
+        let total_area = self.area();
+        if !total_area.is_finite() || total_area <= 0_f32 {
+            return None;
+        }
+
+        let choice = sampler.sample_f32() * total_area;
+        let index = usize::min(
+            self.cumulative_areas.partition_point(|&area| area <= choice),
+            self.children.len() - 1
+        );
+
+        self.children[index]
+            .sample_surface(sampler)
+            .map(|sample| SurfaceSample::new(sample.point, sample.normal, 1_f32 / total_area))
+    }
+}
+
@@ -1,5 +1,30 @@
+mod axis_aligned_box;
+mod cone;
+mod csg;
+mod cylinder;
+mod disk;
+mod distance_function;
+mod geometry_group;
+mod plane;
+mod rectangle;
+mod roots;
+mod signed_distance;
 mod sphere;
+mod torus;
+mod triangle;
 
 
+pub use axis_aligned_box::*;
+pub use cone::*;
+pub use csg::*;
+pub use cylinder::*;
+pub use disk::*;
+pub use distance_function::*;
+pub use geometry_group::*;
+pub use plane::*;
+pub use rectangle::*;
+pub use signed_distance::*;
 pub use sphere::*;
+pub use torus::*;
+pub use triangle::*;
 
@@ -0,0 +1,84 @@
+use crate::core::*;
+use cglinalg::{
+    Vector2,
+    Vector3,
+    Magnitude,
+};
+
+
+/// An infinite plane through a point, facing the direction of its normal.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Plane {
+    /// A point on the plane in model space.
+    pub point: Vector3<f32>,
+    /// The unit normal of the plane in model space.
+    pub normal: Vector3<f32>,
+}
+
+impl Plane {
+    /// Construct a new model space plane through the point `point` with the 
+    /// normal `normal`, which need not have unit length.
+    pub fn new(point: Vector3<f32>, normal: Vector3<f32>) -> Self {
+        Self { point, normal: normal.normalize(), }
+    }
+
+    /// Compute the intersection data at a point on the plane.
+    ///
+    /// The plane is parameterized by the distances from its reference point 
+    /// along the tangent and the bitangent of the frame of its normal, so the 
+    /// surface parameters are unbounded.
+    fn surface_data(&self, t: f32, point: Vector3<f32>) -> IntersectionData {
+        let frame = Frame::from_normal(&self.normal);
+        let offset = point - self.point;
+        let uv = Vector2::new(offset.dot(&frame.tangent), offset.dot(&frame.bitangent));
+
+        IntersectionData::from_surface(t, point, self.normal, self.normal, frame.tangent, frame.bitangent, uv)
+    }
+}
+
+impl Intersection for Plane {
+    fn intersect(&self, query: &IntersectionQuery) -> IntersectionResult {
+        let denominator = query.ray.direction.dot(&self.normal);
+        if denominator == 0_f32 {
+            return IntersectionResult::new_miss(Reason::NoIntersection);
+        }
+
+        let t = (self.point - query.ray.origin).dot(&self.normal) / denominator;
+        if t > query.t_min && t < query.t_max {
+            let point_of_intersection = query.ray.interpolate(t);
+            let point_error = ray_point_error(&query.ray, t);
+
+            return IntersectionResult::Hit(self.surface_data(t, point_of_intersection).with_point_error(point_error));
+        }
+
+        IntersectionResult::new_miss(Reason::NoIntersection)
+    }
+}
+
+impl Geometry for Plane {
+    #[inline]
+    fn center(&self) -> Vector3<f32> {
+        self.point
+    }
+
+    /// A plane contains the half-space behind it.
+    fn contains(&self, point: &Vector3<f32>) -> bool {
+        (point - self.point).dot(&self.normal) <= 0_f32
+    }
+
+    #[inline]
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::infinite()
+    }
+
+    #[inline]
+    fn area(&self) -> f32 {
+        f32::INFINITY
+    }
+
+    #[inline]
+    fn sample_surface(&self, _sampler: &mut SphereSampler) -> Option<SurfaceSample> {
+        None
+    }
+}
+
@@ -0,0 +1,103 @@
+use crate::core::*;
+use cglinalg::{
+    Vector2,
+    Vector3,
+};
+
+
+/// A flat rectangle perpendicular to the **y-axis** of model space, facing the
+/// **positive y-axis**.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Rectangle {
+    /// The center of the rectangle in model space.
+    pub center: Vector3<f32>,
+    /// The length of the rectangle along the **x-axis** in model space.
+    pub width: f32,
+    /// The length of the rectangle along the **z-axis** in model space.
+    pub depth: f32,
+}
+
+impl Rectangle {
+    /// Construct a new model space rectangle.
+    pub fn new(center: Vector3<f32>, width: f32, depth: f32) -> Self {
+        Self { center, width, depth, }
+    }
+
+    /// Returns the corner of the rectangle with the smallest coordinates.
+    #[inline]
+    fn min(&self) -> Vector3<f32> {
+        self.center - Vector3::new(self.width, 0_f32, self.depth) * 0.5
+    }
+
+    /// Compute the intersection data at a point on the rectangle.
+    ///
+    /// The rectangle is parameterized over `[0, 1]` by its **x-coordinate** and 
+    /// **z-coordinate**.
+    fn surface_data(&self, t: f32, point: Vector3<f32>) -> IntersectionData {
+        let min = self.min();
+        let u = (point.x - min.x) / self.width;
+        let v = (point.z - min.z) / self.depth;
+        let dpdu = Vector3::new(self.width, 0_f32, 0_f32);
+        let dpdv = Vector3::new(0_f32, 0_f32, self.depth);
+        let normal = Vector3::unit_y();
+
+        IntersectionData::from_surface(t, point, normal, normal, dpdu, dpdv, Vector2::new(u, v))
+    }
+}
+
+impl Intersection for Rectangle {
+    fn intersect(&self, query: &IntersectionQuery) -> IntersectionResult {
+        if query.ray.direction.y == 0_f32 {
+            return IntersectionResult::new_miss(Reason::NoIntersection);
+        }
+
+        let t = (self.center.y - query.ray.origin.y) / query.ray.direction.y;
+        if t > query.t_min && t < query.t_max {
+            let point_of_intersection = query.ray.interpolate(t);
+            let offset = point_of_intersection - self.center;
+            if f32::abs(offset.x) <= 0.5 * self.width && f32::abs(offset.z) <= 0.5 * self.depth {
+                let point_error = ray_point_error(&query.ray, t);
+
+                return IntersectionResult::Hit(self.surface_data(t, point_of_intersection).with_point_error(point_error));
+            }
+        }
+
+        IntersectionResult::new_miss(Reason::NoIntersection)
+    }
+}
+
+impl Geometry for Rectangle {
+    #[inline]
+    fn center(&self) -> Vector3<f32> {
+        self.center
+    }
+
+    /// A rectangle has no interior.
+    #[inline]
+    fn contains(&self, _point: &Vector3<f32>) -> bool {
+        false
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        let extent = Vector3::new(self.width, 0_f32, self.depth) * 0.5;
+
+        BoundingBox::new(self.center - extent, self.center + extent)
+    }
+
+    #[inline]
+    fn area(&self) -> f32 {
+        self.width * self.depth
+    }
+
+    fn sample_surface(&self, sampler: &mut SphereSampler) -> Option<SurfaceSample> {
+        let min = self.min();
+        let point = Vector3::new(
+            min.x + sampler.sample_f32() * self.width, 
+            self.center.y, 
+            min.z + sampler.sample_f32() * self.depth
+        );
+
+        Some(SurfaceSample::new(point, Vector3::unit_y(), 1_f32 / self.area()))
+    }
+}
+
@@ -0,0 +1,144 @@
+/// Find the real roots of the quadratic polynomial `a * t^2 + b * t + c` in
+/// increasing order, or return `None` when it has no real roots.
+///
+/// A linear polynomial returns its root twice.
+pub(crate) fn solve_quadratic(a: f32, b: f32, c: f32) -> Option<(f32, f32)> {
+    if a == 0_f32 {
+        if b == 0_f32 {
+            return None;
+        }
+
+        let t = -c / b;
+
+        return Some((t, t));
+    }
+
+    let discriminant = b * b - 4_f32 * a * c;
+    if discriminant < 0_f32 {
+        return None;
+    }
+
+    // Avoid the cancellation of subtracting nearly equal numbers by computing
+    // one root from the other.
+    let root_discriminant = f32::sqrt(discriminant);
+    let q = if b < 0_f32 {
+        -0.5 * (b - root_discriminant)
+    } else {
+        -0.5 * (b + root_discriminant)
+    };
+    if q == 0_f32 {
+        return Some((0_f32, 0_f32));
+    }
+
+    let t0 = q / a;
+    let t1 = c / q;
+
+    Some((f32::min(t0, t1), f32::max(t0, t1)))
+}
+
+/// Find the real roots of the quadratic polynomial `t^2 + b * t + c`.
+fn solve_monic_quadratic_f64(b: f64, c: f64, roots: &mut Vec<f64>) {
+    let discriminant = b * b - 4_f64 * c;
+    if discriminant < 0_f64 {
+        return;
+    }
+
+    let root_discriminant = f64::sqrt(discriminant);
+    let q = if b < 0_f64 {
+        -0.5 * (b - root_discriminant)
+    } else {
+        -0.5 * (b + root_discriminant)
+    };
+    if q == 0_f64 {
+        roots.push(0_f64);
+        return;
+    }
+
+    roots.push(q);
+    roots.push(c / q);
+}
+
+/// Find a positive root of the resolvent cubic of a depressed quartic,
+/// `8 * m^3 + 8 * p * m^2 + (2 * p^2 - 8 * r) * m - q^2`, which is negative at
+/// zero and grows without bound.
+fn solve_resolvent_cubic(p: f64, q: f64, r: f64) -> f64 {
+    let cubic = |m: f64| ((8_f64 * m + 8_f64 * p) * m + (2_f64 * p * p - 8_f64 * r)) * m - q * q;
+    let mut low = 0_f64;
+    let mut high = 1_f64;
+    while cubic(high) < 0_f64 {
+        low = high;
+        high *= 2_f64;
+    }
+    for _ in 0..100 {
+        let middle = 0.5 * (low + high);
+        if cubic(middle) < 0_f64 {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    0.5 * (low + high)
+}
+
+/// Find the real roots of the quartic polynomial with the coefficients
+/// `coefficients`, from the coefficient of `t^4` down to the constant term,
+/// in increasing order.
+///
+/// The roots come from Ferrari's method, polished with Newton's method to
+/// recover the precision the method loses.
+pub(crate) fn solve_quartic(coefficients: [f64; 5]) -> Vec<f64> {
+    let [a, b, c, d, e] = coefficients;
+    if a == 0_f64 {
+        return Vec::new();
+    }
+
+    // Depress the quartic with the substitution `t = y - b / 4`.
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+    let p = c - 3_f64 * b * b / 8_f64;
+    let q = d - b * c / 2_f64 + b * b * b / 8_f64;
+    let r = e - b * d / 4_f64 + b * b * c / 16_f64 - 3_f64 * b * b * b * b / 256_f64;
+
+    let mut roots = Vec::with_capacity(4);
+    if f64::abs(q) < 1e-12 {
+        // The quartic is a quadratic in `y^2`.
+        let mut squares = Vec::with_capacity(2);
+        solve_monic_quadratic_f64(p, r, &mut squares);
+        for square in squares {
+            if square >= 0_f64 {
+                let y = f64::sqrt(square);
+                roots.push(y);
+                roots.push(-y);
+            }
+        }
+    } else {
+        let m = solve_resolvent_cubic(p, q, r);
+        let root_2m = f64::sqrt(2_f64 * m);
+        let offset = q / (2_f64 * root_2m);
+        solve_monic_quadratic_f64(root_2m, p / 2_f64 + m - offset, &mut roots);
+        solve_monic_quadratic_f64(-root_2m, p / 2_f64 + m + offset, &mut roots);
+    }
+
+    let polynomial = |t: f64| (((t + b) * t + c) * t + d) * t + e;
+    let derivative = |t: f64| ((4_f64 * t + 3_f64 * b) * t + 2_f64 * c) * t + d;
+    let mut roots = roots
+        .into_iter()
+        .map(|y| {
+            let mut t = y - b / 4_f64;
+            for _ in 0..4 {
+                let slope = derivative(t);
+                if slope == 0_f64 {
+                    break;
+                }
+
+                t -= polynomial(t) / slope;
+            }
+
+            t
+        })
+        .collect::<Vec<f64>>();
+    roots.sort_by(|t0, t1| t0.total_cmp(t1));
+
+    roots
+}
+
@@ -0,0 +1,245 @@
+use crate::core::*;
+use crate::geometry::distance_function::*;
+use cglinalg::{
+    Vector3,
+    Magnitude,
+};
+use rand::prelude::*;
+
+
+/// The number of points used to estimate the surface area of an implicit 
+/// surface.
+const AREA_ESTIMATE_SAMPLES: usize = 262144;
+
+/// The half width of the band about an implicit surface used for estimating 
+/// and sampling its area, relative to the size of its bounding box.
+const SURFACE_BAND_WIDTH: f32 = 0.01;
+
+/// The number of points tried before sampling a point on an implicit surface 
+/// fails.
+const MAX_SURFACE_SAMPLE_ATTEMPTS: usize = 4096;
+
+/// A geometry bounded by the zero set of a signed distance function, rendered 
+/// by sphere tracing.
+///
+/// Sphere tracing marches along a ray in steps as long as the distance to the 
+/// surface, which cannot overshoot the surface, until the distance falls below 
+/// a threshold. The normals are the normalized gradient of the distance 
+/// function, estimated by central differences. Implicit surfaces have no 
+/// natural parameterization, so the surface parameters are zero.
+#[derive(Debug)]
+pub struct SignedDistanceGeometry {
+    function: Box<dyn DistanceFunction>,
+    max_steps: usize,
+    epsilon: f32,
+    area: f32,
+}
+
+impl SignedDistanceGeometry {
+    /// Construct a new geometry from the distance function `function`.
+    pub fn new(function: Box<dyn DistanceFunction>) -> Self {
+        let mut geometry = Self { function, max_steps: 256, epsilon: 1e-4, area: 0_f32, };
+        geometry.area = geometry.estimate_area();
+
+        geometry
+    }
+
+    /// Set the largest number of steps sphere tracing takes along a ray before 
+    /// giving up on finding the surface.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Set the distance from the surface at which sphere tracing considers a 
+    /// point to lie on it. The threshold also sets the step size of the 
+    /// finite differences of the normals.
+    pub fn with_epsilon(mut self, epsilon: f32) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    /// Returns the distance function of the geometry.
+    #[inline]
+    pub fn function(&self) -> &dyn DistanceFunction {
+        &*self.function
+    }
+
+    /// Estimate the gradient of the distance function at a point by central 
+    /// differences.
+    fn gradient(&self, point: &Vector3<f32>) -> Vector3<f32> {
+        let h = self.epsilon;
+        let dx = Vector3::new(h, 0_f32, 0_f32);
+        let dy = Vector3::new(0_f32, h, 0_f32);
+        let dz = Vector3::new(0_f32, 0_f32, h);
+
+        Vector3::new(
+            self.function.distance(&(point + dx)) - self.function.distance(&(point - dx)),
+            self.function.distance(&(point + dy)) - self.function.distance(&(point - dy)),
+            self.function.distance(&(point + dz)) - self.function.distance(&(point - dz)),
+        ) / (2_f32 * h)
+    }
+
+    /// Compute the unit normal of the surface at a point.
+    fn normal(&self, point: &Vector3<f32>) -> Vector3<f32> {
+        let gradient = self.gradient(point);
+        let magnitude = gradient.magnitude();
+        if magnitude > 0_f32 {
+            gradient / magnitude
+        } else {
+            Vector3::unit_y()
+        }
+    }
+
+    /// Returns the half width of the band about the surface used for estimating
+    /// and sampling its area.
+    fn band_width(bounds: &BoundingBox) -> f32 {
+        let extent = bounds.extent();
+
+        SURFACE_BAND_WIDTH * f32::max(extent.x, f32::max(extent.y, extent.z))
+    }
+
+    /// Sample a point uniformly inside a bounding box.
+    fn sample_bounds(bounds: &BoundingBox, sampler: &mut SphereSampler) -> Vector3<f32> {
+        let extent = bounds.extent();
+
+        bounds.min + Vector3::new(
+            extent.x * sampler.sample_f32(),
+            extent.y * sampler.sample_f32(),
+            extent.z * sampler.sample_f32(),
+        )
+    }
+
+    /// Estimate the surface area from the volume of a thin band about the 
+    /// surface, which is twice the width of the band times the area of the 
+    /// surface for an exact distance function.
+    fn estimate_area(&self) -> f32 {
+        let bounds = self.function.bounds();
+        if bounds.is_empty() {
+            return 0_f32;
+        }
+        if bounds.is_infinite() {
+            return f32::INFINITY;
+        }
+
+        // Pad the bounds so that the band around the surface fits inside them.
+        let band_width = Self::band_width(&bounds);
+        let padding = Vector3::from_fill(band_width);
+        let bounds = BoundingBox::new(bounds.min - padding, bounds.max + padding);
+        let extent = bounds.extent();
+        let volume = extent.x * extent.y * extent.z;
+        let mut sampler = SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0));
+        let count = (0..AREA_ESTIMATE_SAMPLES)
+            .filter(|_| {
+                let point = Self::sample_bounds(&bounds, &mut sampler);
+
+                f32::abs(self.function.distance(&point)) < band_width
+            })
+            .count();
+
+        volume * (count as f32) / (AREA_ESTIMATE_SAMPLES as f32) / (2_f32 * band_width)
+    }
+}
+
+impl Intersection for SignedDistanceGeometry {
+    fn intersect(&self, query: &IntersectionQuery) -> IntersectionResult {
+        let direction_magnitude = query.ray.direction.magnitude();
+        if direction_magnitude == 0_f32 {
+            return IntersectionResult::new_miss(Reason::NoIntersection);
+        }
+
+        let (t_start, t_end) = match self.function.bounds().intersect(&query.ray, query.t_min, query.t_max) {
+            Some(range) => range,
+            None => return IntersectionResult::new_miss(Reason::NoIntersection),
+        };
+        // The distances are measured in model space, and the ray parameter in 
+        // multiples of the ray direction.
+        let step_scale = 1_f32 / (self.function.lipschitz_constant() * direction_magnitude);
+        let mut t = t_start;
+        // A ray leaving the surface starts out within the threshold of it, so it 
+        // steps off the surface before it looks for the next hit.
+        let mut is_leaving_surface = t_start <= query.t_min;
+        for _ in 0..self.max_steps {
+            if t > t_end {
+                break;
+            }
+
+            let point = query.ray.interpolate(t);
+            let distance = f32::abs(self.function.distance(&point));
+            if distance >= self.epsilon {
+                is_leaving_surface = false;
+                t += distance * step_scale;
+            } else if is_leaving_surface {
+                t += 2_f32 * self.epsilon / direction_magnitude;
+            } else {
+                let normal = self.normal(&point);
+
+                // Sphere tracing stops anywhere within the threshold distance of
+                // the surface.
+                let point_error = Vector3::from_fill(self.epsilon);
+
+                return IntersectionResult::Hit(IntersectionData::new(t, point, normal).with_point_error(point_error));
+            }
+        }
+
+        IntersectionResult::new_miss(Reason::NoIntersection)
+    }
+}
+
+impl Geometry for SignedDistanceGeometry {
+    fn center(&self) -> Vector3<f32> {
+        let bounds = self.function.bounds();
+        if bounds.is_empty() || bounds.is_infinite() {
+            Vector3::zero()
+        } else {
+            bounds.center()
+        }
+    }
+
+    #[inline]
+    fn contains(&self, point: &Vector3<f32>) -> bool {
+        self.function.distance(point) < 0_f32
+    }
+
+    #[inline]
+    fn bounds(&self) -> BoundingBox {
+        self.function.bounds()
+    }
+
+    /// The surface area of an implicit surface is a Monte Carlo estimate made 
+    /// when the geometry is constructed.
+    #[inline]
+    fn area(&self) -> f32 {
+        self.area
+    }
+
+    /// Sample points in a thin band about the surface until one lands inside the
+    /// band, then project it onto the surface along the gradient. Sampling is 
+    /// approximately uniform with respect to area for an exact distance function.
+    fn sample_surface(&self, sampler: &mut SphereSampler) -> Option<SurfaceSample> {
+        if !self.area.is_finite() || self.area <= 0_f32 {
+            return None;
+        }
+
+        let bounds = self.function.bounds();
+        let band_width = Self::band_width(&bounds);
+        let padding = Vector3::from_fill(band_width);
+        let bounds = BoundingBox::new(bounds.min - padding, bounds.max + padding);
+        for _ in 0..MAX_SURFACE_SAMPLE_ATTEMPTS {
+            let mut point = Self::sample_bounds(&bounds, sampler);
+            if f32::abs(self.function.distance(&point)) >= band_width {
+                continue;
+            }
+
+            for _ in 0..4 {
+                point -= self.normal(&point) * self.function.distance(&point);
+            }
+            let normal = self.normal(&point);
+
+            return Some(SurfaceSample::new(point, normal, 1_f32 / self.area));
+        }
+
+        None
+    }
+}
+
@@ -1,6 +1,8 @@
 use crate::core::*;
 use cglinalg::{
+    Vector2,
     Vector3,
+    Magnitude,
 };
 
 
@@ -31,6 +33,39 @@ impl Sphere {
     pub fn diameter(&self) -> f32 {
         self.radius + self.radius
     }
+
+    /// Compute the intersection data at a point on the surface of the sphere.
+    ///
+    /// The sphere is parameterized by longitude **u** about the **y-axis** and 
+    /// latitude **v** from the south pole to the north pole, both in `[0, 1]`.
+    ///
+    /// The point is reprojected onto the sphere first, which bounds its error
+    /// however inaccurately the ray parameter of the intersection was computed.
+    fn surface_data(&self, t: f32, point: Vector3<f32>) -> IntersectionData {
+        let offset = point - self.center;
+        let offset = offset * (self.radius / offset.magnitude());
+        let point = self.center + offset;
+        let normal = offset / self.radius;
+        let theta = f32::acos(f32::clamp(-normal.y, -1_f32, 1_f32));
+        let phi = f32::atan2(-normal.z, normal.x) + std::f32::consts::PI;
+        let u = phi / (2_f32 * std::f32::consts::PI);
+        let v = theta / std::f32::consts::PI;
+        let (sin_theta, cos_theta) = f32::sin_cos(theta);
+        let (sin_phi, cos_phi) = f32::sin_cos(phi);
+        let dpdu = Vector3::new(
+            sin_phi * sin_theta, 
+            0_f32, 
+            cos_phi * sin_theta
+        ) * (2_f32 * std::f32::consts::PI * self.radius);
+        let dpdv = Vector3::new(
+            -cos_phi * cos_theta, 
+            sin_theta, 
+            sin_phi * cos_theta
+        ) * (std::f32::consts::PI * self.radius);
+
+        IntersectionData::from_surface(t, point, normal, normal, dpdu, dpdv, Vector2::new(u, v))
+            .with_point_error(point_error(&offset) + point_error(&self.center))
+    }
 }
 
 impl Intersection for Sphere {
@@ -44,21 +79,13 @@ impl Intersection for Sphere {
             let t_intersect1 = (-b - f32::sqrt(b * b - a * c)) / a; // 4 * a * c?
             if t_intersect1 > query.t_min && t_intersect1 < query.t_max {
                 let point_of_intersection = query.ray.interpolate(t_intersect1);
-                return IntersectionResult::new_hit(
-                    t_intersect1,
-                    point_of_intersection,
-                    (point_of_intersection - self.center) / self.radius,
-                );
+                return IntersectionResult::Hit(self.surface_data(t_intersect1, point_of_intersection));
             }
 
             let t_intersect2 = (-b + f32::sqrt(b * b - a * c)) / a; // 4 * a * c?
             if t_intersect2 > query.t_min && t_intersect2 < query.t_max {
                 let point_of_intersection = query.ray.interpolate(t_intersect2);
-                return IntersectionResult::new_hit(
-                    t_intersect2,
-                    point_of_intersection,
-                    (point_of_intersection - self.center) / self.radius,
-                );
+                return IntersectionResult::Hit(self.surface_data(t_intersect2, point_of_intersection));
             }
         } 
         
@@ -66,21 +93,13 @@ impl Intersection for Sphere {
             let t_intersect1 = (-b - f32::sqrt(b * b - a * c)) / a; // 4 * a * c?
             if t_intersect1 > query.t_min && t_intersect1 < query.t_max {
                 let point_of_intersection = query.ray.interpolate(t_intersect1);
-                return IntersectionResult::new_tangent(
-                    t_intersect1,
-                    point_of_intersection,
-                    (point_of_intersection - self.center) / self.radius,
-                );
+                return IntersectionResult::Tangent(self.surface_data(t_intersect1, point_of_intersection));
             }
 
             let t_intersect2 = (-b + f32::sqrt(b * b - a * c)) / a; // 4 * a * c?
             if t_intersect2 > query.t_min && t_intersect2 < query.t_max {
                 let point_of_intersection = query.ray.interpolate(t_intersect2);
-                return IntersectionResult::new_tangent(
-                    t_intersect2,
-                    point_of_intersection,
-                    (point_of_intersection - self.center) / self.radius,
-                );
+                return IntersectionResult::Tangent(self.surface_data(t_intersect2, point_of_intersection));
             }
         }
         
@@ -99,5 +118,23 @@ impl Geometry for Sphere {
 
         diff.dot(&diff) <= self.radius * self.radius
     }
+
+    fn bounds(&self) -> BoundingBox {
+        let radius = Vector3::from_fill(f32::abs(self.radius));
+
+        BoundingBox::new(self.center - radius, self.center + radius)
+    }
+
+    #[inline]
+    fn area(&self) -> f32 {
+        4_f32 * std::f32::consts::PI * self.radius * self.radius
+    }
+
+    fn sample_surface(&self, sampler: &mut SphereSampler) -> Option<SurfaceSample> {
+        let normal = sampler.sample_uniform_direction();
+        let point = self.center + normal * self.radius;
+
+        Some(SurfaceSample::new(point, normal, 1_f32 / self.area()))
+    }
 }
 
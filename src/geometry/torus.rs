@@ -0,0 +1,140 @@
+use crate::core::*;
+use crate::geometry::roots::*;
+use cglinalg::{
+    Vector2,
+    Vector3,
+    Magnitude,
+};
+
+
+/// A ring torus about an axis parallel to the **y-axis** of model space.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Torus {
+    /// The center of the torus in model space.
+    pub center: Vector3<f32>,
+    /// The distance from the center of the torus to the center of its tube in 
+    /// model space.
+    pub major_radius: f32,
+    /// The radius of the tube of the torus in model space.
+    pub minor_radius: f32,
+}
+
+impl Torus {
+    /// Construct a new model space torus.
+    pub fn new(center: Vector3<f32>, major_radius: f32, minor_radius: f32) -> Self {
+        Self { center, major_radius, minor_radius, }
+    }
+
+    /// Compute the intersection data at a point on the surface of the torus.
+    ///
+    /// The torus is parameterized by the angle **u** about the **y-axis** and the
+    /// angle **v** about the center of the tube, both in `[0, 1]`.
+    fn surface_data(&self, t: f32, point: Vector3<f32>) -> IntersectionData {
+        let offset = point - self.center;
+        let distance = f32::sqrt(offset.x * offset.x + offset.z * offset.z);
+        let phi = f32::atan2(offset.z, offset.x).rem_euclid(2_f32 * std::f32::consts::PI);
+        let theta = f32::atan2(offset.y, distance - self.major_radius).rem_euclid(2_f32 * std::f32::consts::PI);
+        let (sin_phi, cos_phi) = f32::sin_cos(phi);
+        let (sin_theta, cos_theta) = f32::sin_cos(theta);
+        let u = phi / (2_f32 * std::f32::consts::PI);
+        let v = theta / (2_f32 * std::f32::consts::PI);
+        let normal = Vector3::new(cos_theta * cos_phi, sin_theta, cos_theta * sin_phi);
+        let dpdu = Vector3::new(-distance * sin_phi, 0_f32, distance * cos_phi) * (2_f32 * std::f32::consts::PI);
+        let dpdv = Vector3::new(-sin_theta * cos_phi, cos_theta, -sin_theta * sin_phi) * 
+            (2_f32 * std::f32::consts::PI * self.minor_radius);
+
+        IntersectionData::from_surface(t, point, normal, normal, dpdu, dpdv, Vector2::new(u, v))
+    }
+}
+
+impl Intersection for Torus {
+    fn intersect(&self, query: &IntersectionQuery) -> IntersectionResult {
+        let direction_squared = query.ray.direction.magnitude_squared();
+        if direction_squared == 0_f32 {
+            return IntersectionResult::new_miss(Reason::NoIntersection);
+        }
+
+        // The coefficients of the quartic lose precision with the distance of the 
+        // ray origin from the torus, so solve from the point of the ray closest to
+        // the bounding sphere instead.
+        let extent = self.major_radius + self.minor_radius;
+        let t_closest = (self.center - query.ray.origin).dot(&query.ray.direction) / direction_squared;
+        let t_shift = f32::max(0_f32, t_closest - extent / f32::sqrt(direction_squared));
+        let origin = query.ray.interpolate(t_shift) - self.center;
+        let direction = query.ray.direction;
+
+        let (ox, oy, oz) = (origin.x as f64, origin.y as f64, origin.z as f64);
+        let (dx, dy, dz) = (direction.x as f64, direction.y as f64, direction.z as f64);
+        let major_radius_squared = (self.major_radius as f64) * (self.major_radius as f64);
+        let minor_radius_squared = (self.minor_radius as f64) * (self.minor_radius as f64);
+        let a = dx * dx + dy * dy + dz * dz;
+        let b = 2_f64 * (ox * dx + oy * dy + oz * dz);
+        let c = ox * ox + oy * oy + oz * oz + major_radius_squared - minor_radius_squared;
+        let coefficients = [
+            a * a,
+            2_f64 * a * b,
+            b * b + 2_f64 * a * c - 4_f64 * major_radius_squared * (dx * dx + dz * dz),
+            2_f64 * b * c - 8_f64 * major_radius_squared * (ox * dx + oz * dz),
+            c * c - 4_f64 * major_radius_squared * (ox * ox + oz * oz),
+        ];
+        for root in solve_quartic(coefficients) {
+            let t = t_shift + root as f32;
+            if t > query.t_min && t < query.t_max {
+                let point_of_intersection = query.ray.interpolate(t);
+                let point_error = ray_point_error(&query.ray, t);
+
+                return IntersectionResult::Hit(self.surface_data(t, point_of_intersection).with_point_error(point_error));
+            }
+        }
+
+        IntersectionResult::new_miss(Reason::NoIntersection)
+    }
+}
+
+impl Geometry for Torus {
+    #[inline]
+    fn center(&self) -> Vector3<f32> {
+        self.center
+    }
+
+    fn contains(&self, point: &Vector3<f32>) -> bool {
+        let offset = point - self.center;
+        let distance = f32::sqrt(offset.x * offset.x + offset.z * offset.z) - self.major_radius;
+
+        distance * distance + offset.y * offset.y <= self.minor_radius * self.minor_radius
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        let extent = self.major_radius + self.minor_radius;
+        let extent = Vector3::new(extent, self.minor_radius, extent);
+
+        BoundingBox::new(self.center - extent, self.center + extent)
+    }
+
+    #[inline]
+    fn area(&self) -> f32 {
+        4_f32 * std::f32::consts::PI * std::f32::consts::PI * self.major_radius * self.minor_radius
+    }
+
+    fn sample_surface(&self, sampler: &mut SphereSampler) -> Option<SurfaceSample> {
+        // The outside of the tube has more area than the inside, in proportion to
+        // the distance from the axis of the torus.
+        let outer_distance = self.major_radius + self.minor_radius;
+        let theta = loop {
+            let theta = 2_f32 * std::f32::consts::PI * sampler.sample_f32();
+            let distance = self.major_radius + self.minor_radius * f32::cos(theta);
+            if sampler.sample_f32() * outer_distance <= distance {
+                break theta;
+            }
+        };
+        let phi = 2_f32 * std::f32::consts::PI * sampler.sample_f32();
+        let (sin_phi, cos_phi) = f32::sin_cos(phi);
+        let (sin_theta, cos_theta) = f32::sin_cos(theta);
+        let normal = Vector3::new(cos_theta * cos_phi, sin_theta, cos_theta * sin_phi);
+        let distance = self.major_radius + self.minor_radius * cos_theta;
+        let point = self.center + Vector3::new(distance * cos_phi, self.minor_radius * sin_theta, distance * sin_phi);
+
+        Some(SurfaceSample::new(point, normal, 1_f32 / self.area()))
+    }
+}
+
@@ -0,0 +1,131 @@
+use crate::core::*;
+use cglinalg::{
+    Vector2,
+    Vector3,
+    Magnitude,
+};
+
+
+/// A flat triangle in model space, facing the side from which its vertices run
+/// counterclockwise.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Triangle {
+    /// The vertices of the triangle in model space.
+    pub vertices: [Vector3<f32>; 3],
+}
+
+impl Triangle {
+    /// Construct a new model space triangle from its vertices.
+    pub fn new(vertex0: Vector3<f32>, vertex1: Vector3<f32>, vertex2: Vector3<f32>) -> Self {
+        Self { vertices: [vertex0, vertex1, vertex2], }
+    }
+
+    /// Returns the unit normal of the triangle.
+    #[inline]
+    fn normal(&self) -> Vector3<f32> {
+        let edge1 = self.vertices[1] - self.vertices[0];
+        let edge2 = self.vertices[2] - self.vertices[0];
+
+        edge1.cross(&edge2).normalize()
+    }
+
+    /// Compute the intersection data at the point of the triangle with the
+    /// barycentric coordinates `(1 - b1 - b2, b1, b2)`.
+    ///
+    /// The triangle is parameterized by the barycentric coordinates of its
+    /// second and third vertices.
+    fn surface_data(&self, t: f32, point: Vector3<f32>, b1: f32, b2: f32) -> IntersectionData {
+        let dpdu = self.vertices[1] - self.vertices[0];
+        let dpdv = self.vertices[2] - self.vertices[0];
+        let normal = self.normal();
+        let barycentrics = Vector3::new(1_f32 - b1 - b2, b1, b2);
+
+        IntersectionData::from_surface(t, point, normal, normal, dpdu, dpdv, Vector2::new(b1, b2))
+            .with_barycentrics(barycentrics)
+    }
+}
+
+impl Intersection for Triangle {
+    fn intersect(&self, query: &IntersectionQuery) -> IntersectionResult {
+        // Solve for the ray parameter and the barycentric coordinates together 
+        // with the Möller-Trumbore algorithm. Rays hit the triangle from either
+        // side.
+        let edge1 = self.vertices[1] - self.vertices[0];
+        let edge2 = self.vertices[2] - self.vertices[0];
+        let p = query.ray.direction.cross(&edge2);
+        let determinant = edge1.dot(&p);
+        if determinant == 0_f32 {
+            return IntersectionResult::new_miss(Reason::NoIntersection);
+        }
+
+        let determinant_inv = 1_f32 / determinant;
+        let s = query.ray.origin - self.vertices[0];
+        let b1 = s.dot(&p) * determinant_inv;
+        if !(0_f32..=1_f32).contains(&b1) {
+            return IntersectionResult::new_miss(Reason::NoIntersection);
+        }
+
+        let q = s.cross(&edge1);
+        let b2 = query.ray.direction.dot(&q) * determinant_inv;
+        if b2 < 0_f32 || b1 + b2 > 1_f32 {
+            return IntersectionResult::new_miss(Reason::NoIntersection);
+        }
+
+        let t = edge2.dot(&q) * determinant_inv;
+        if t > query.t_min && t < query.t_max {
+            let point_of_intersection = query.ray.interpolate(t);
+            let point_error = ray_point_error(&query.ray, t);
+
+            return IntersectionResult::Hit(self.surface_data(t, point_of_intersection, b1, b2).with_point_error(point_error));
+        }
+
+        IntersectionResult::new_miss(Reason::NoIntersection)
+    }
+}
+
+impl Geometry for Triangle {
+    #[inline]
+    fn center(&self) -> Vector3<f32> {
+        (self.vertices[0] + self.vertices[1] + self.vertices[2]) / 3_f32
+    }
+
+    /// A triangle has no interior.
+    #[inline]
+    fn contains(&self, _point: &Vector3<f32>) -> bool {
+        false
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        let [vertex0, vertex1, vertex2] = self.vertices;
+        let min = Vector3::new(
+            f32::min(f32::min(vertex0.x, vertex1.x), vertex2.x),
+            f32::min(f32::min(vertex0.y, vertex1.y), vertex2.y),
+            f32::min(f32::min(vertex0.z, vertex1.z), vertex2.z),
+        );
+        let max = Vector3::new(
+            f32::max(f32::max(vertex0.x, vertex1.x), vertex2.x),
+            f32::max(f32::max(vertex0.y, vertex1.y), vertex2.y),
+            f32::max(f32::max(vertex0.z, vertex1.z), vertex2.z),
+        );
+
+        BoundingBox::new(min, max)
+    }
+
+    #[inline]
+    fn area(&self) -> f32 {
+        let edge1 = self.vertices[1] - self.vertices[0];
+        let edge2 = self.vertices[2] - self.vertices[0];
+
+        0.5 * edge1.cross(&edge2).magnitude()
+    }
+
+    fn sample_surface(&self, sampler: &mut SphereSampler) -> Option<SurfaceSample> {
+        let sqrt_u = f32::sqrt(sampler.sample_f32());
+        let b0 = 1_f32 - sqrt_u;
+        let b1 = sampler.sample_f32() * sqrt_u;
+        let b2 = 1_f32 - b0 - b1;
+        let point = self.vertices[0] * b0 + self.vertices[1] * b1 + self.vertices[2] * b2;
+
+        Some(SurfaceSample::new(point, self.normal(), 1_f32 / self.area()))
+    }
+}
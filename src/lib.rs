@@ -1,3 +1,4 @@
+mod aperture;
 mod camera;
 mod canvas;
 
@@ -5,9 +6,12 @@ pub mod core;
 pub mod geometry;
 pub mod bsdf;
 pub mod light;
+pub mod medium;
 pub mod scene;
+pub mod texture;
 pub mod renderer;
 
+pub use aperture::*;
 pub use camera::*;
 pub use canvas::*;
 
@@ -0,0 +1,183 @@
+use cglinalg::{
+    Vector3,
+};
+use std::fs::File;
+use std::io;
+use std::io::{
+    BufReader,
+    Read,
+};
+use std::path::Path;
+
+
+/// The magic number at the start of a density grid file.
+const GRID_MAGIC: &[u8; 4] = b"GRID";
+
+/// A three dimensional array of density values sampled on a regular grid.
+///
+/// Values are stored with the **x** index varying fastest, followed by the **y**
+/// index, then the **z** index.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DensityGrid {
+    dimensions: [usize; 3],
+    data: Vec<f32>,
+    max_value: f32,
+}
+
+impl DensityGrid {
+    /// Construct a density grid from its values.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if any dimension of the grid is zero, or if the number
+    /// of values does not match the dimensions of the grid.
+    pub fn new(width: usize, height: usize, depth: usize, data: Vec<f32>) -> Self {
+        assert!(
+            width > 0 && height > 0 && depth > 0, 
+            "A density grid must have at least one value along each axis, but has dimensions {}x{}x{}", width, height, depth
+        );
+        assert_eq!(
+            data.len(), width * height * depth, 
+            "A density grid of dimensions {}x{}x{} must have {} values", width, height, depth, width * height * depth
+        );
+        let max_value = data.iter().copied().fold(0_f32, f32::max);
+
+        Self { dimensions: [width, height, depth], data, max_value, }
+    }
+
+    /// Construct a density grid whose values are all the same.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if any dimension of the grid is zero.
+    pub fn from_fill(width: usize, height: usize, depth: usize, value: f32) -> Self {
+        Self::new(width, height, depth, vec![value; width * height * depth])
+    }
+
+    /// Returns the number of values along each axis of the grid.
+    #[inline]
+    pub fn dimensions(&self) -> [usize; 3] {
+        self.dimensions
+    }
+
+    /// Returns the largest value in the grid. 
+    #[inline]
+    pub fn max_value(&self) -> f32 {
+        self.max_value
+    }
+
+    /// Returns the value at grid index `(x, y, z)`.
+    #[inline]
+    pub fn value(&self, x: usize, y: usize, z: usize) -> f32 {
+        let [width, height, _] = self.dimensions;
+
+        self.data[(z * height + y) * width + x]
+    }
+
+    /// Look up the trilinearly interpolated value of the grid at a point of the 
+    /// unit cube, over which the grid spans. The values lie at the centers of 
+    /// the cells of the grid, and points outside the unit cube have a value of
+    /// zero.
+    pub fn lookup(&self, point: &Vector3<f32>) -> f32 {
+        if !(0_f32..=1_f32).contains(&point.x) || 
+            !(0_f32..=1_f32).contains(&point.y) || 
+            !(0_f32..=1_f32).contains(&point.z) 
+        {
+            return 0_f32;
+        }
+
+        let [width, height, depth] = self.dimensions;
+        let x = point.x * width as f32 - 0.5;
+        let y = point.y * height as f32 - 0.5;
+        let z = point.z * depth as f32 - 0.5;
+        let x0 = f32::floor(x);
+        let y0 = f32::floor(y);
+        let z0 = f32::floor(z);
+        let dx = x - x0;
+        let dy = y - y0;
+        let dz = z - z0;
+        let clamp = |index: f32, size: usize| (index as i64).clamp(0, size as i64 - 1) as usize;
+        let xs = [clamp(x0, width), clamp(x0 + 1_f32, width)];
+        let ys = [clamp(y0, height), clamp(y0 + 1_f32, height)];
+        let zs = [clamp(z0, depth), clamp(z0 + 1_f32, depth)];
+        let lerp = |t: f32, a: f32, b: f32| a + t * (b - a);
+
+        lerp(dz,
+            lerp(dy,
+                lerp(dx, self.value(xs[0], ys[0], zs[0]), self.value(xs[1], ys[0], zs[0])),
+                lerp(dx, self.value(xs[0], ys[1], zs[0]), self.value(xs[1], ys[1], zs[0])),
+            ),
+            lerp(dy,
+                lerp(dx, self.value(xs[0], ys[0], zs[1]), self.value(xs[1], ys[0], zs[1])),
+                lerp(dx, self.value(xs[0], ys[1], zs[1]), self.value(xs[1], ys[1], zs[1])),
+            ),
+        )
+    }
+
+    /// Load a density grid from a grid file.
+    pub fn from_grid_file<P: AsRef<Path>>(path: P) -> io::Result<DensityGrid> {
+        let file = File::open(path)?;
+
+        Self::from_grid(BufReader::new(file))
+    }
+
+    /// Decode a density grid in grid format. 
+    ///
+    /// A grid file starts with the four bytes `GRID`, followed by the width, 
+    /// height, and depth of the grid as little-endian 32-bit unsigned integers, 
+    /// followed by the values of the grid as little-endian 32-bit floating point
+    /// numbers in the order of [`DensityGrid`].
+    pub fn from_grid<R: Read>(mut reader: R) -> io::Result<DensityGrid> {
+        let mut magic = [0_u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != GRID_MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid density grid: unknown magic number"));
+        }
+
+        let mut dimensions = [0_usize; 3];
+        for dimension in dimensions.iter_mut() {
+            let mut bytes = [0_u8; 4];
+            reader.read_exact(&mut bytes)?;
+            *dimension = u32::from_le_bytes(bytes) as usize;
+        }
+        let [width, height, depth] = dimensions;
+
+        Self::from_raw(reader, width, height, depth)
+    }
+
+    /// Load a density grid from a raw file of known dimensions.
+    pub fn from_raw_file<P: AsRef<Path>>(path: P, width: usize, height: usize, depth: usize) -> io::Result<DensityGrid> {
+        let file = File::open(path)?;
+
+        Self::from_raw(BufReader::new(file), width, height, depth)
+    }
+
+    /// Decode a density grid of known dimensions stored as raw little-endian 
+    /// 32-bit floating point numbers in the order of [`DensityGrid`], without a 
+    /// header.
+    pub fn from_raw<R: Read>(reader: R, width: usize, height: usize, depth: usize) -> io::Result<DensityGrid> {
+        if width == 0 || height == 0 || depth == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid density grid: a dimension is zero"));
+        }
+
+        let byte_len = width
+            .checked_mul(height)
+            .and_then(|len| len.checked_mul(depth))
+            .and_then(|len| len.checked_mul(4))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Invalid density grid: dimensions are too large"))?;
+        // Read no more than the data the reader holds, so that the dimensions of
+        // a malformed file cannot make us allocate a huge buffer up front.
+        let mut bytes = Vec::new();
+        reader.take(byte_len as u64).read_to_end(&mut bytes)?;
+        if bytes.len() != byte_len {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid density grid: data is truncated"));
+        }
+
+        let data = bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect();
+
+        Ok(Self::new(width, height, depth, data))
+    }
+}
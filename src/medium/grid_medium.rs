@@ -0,0 +1,133 @@
+use crate::core::*;
+use crate::medium::*;
+use cglinalg::{
+    Vector3,
+    Magnitude,
+};
+
+
+/// A participating medium whose density varies over a voxel grid.
+///
+/// The grid fills a bounding box in world space, and the medium is empty outside
+/// of it. The medium scales its extinction coefficient by the density, and 
+/// scatters the fraction `albedo` of the light it extinguishes. 
+///
+/// Free-flight distances are sampled with delta tracking and transmittance is
+/// estimated with ratio tracking, both of which bound the extinction coefficient
+/// by its value at the maximum density in the grid.
+#[derive(Clone, Debug)]
+pub struct GridMedium {
+    grid: DensityGrid,
+    bounds: BoundingBox,
+    /// The extinction coefficient per unit length at unit density.
+    sigma_t: f32,
+    /// The fraction of extinguished light that the medium scatters in each color
+    /// channel.
+    albedo: Vector3<f32>,
+    phase_function: HenyeyGreensteinPhaseFunction,
+}
+
+impl GridMedium {
+    pub fn new(grid: DensityGrid, bounds: BoundingBox, sigma_t: f32, albedo: Vector3<f32>, g: f32) -> Self {
+        Self { 
+            grid, 
+            bounds, 
+            sigma_t, 
+            albedo, 
+            phase_function: HenyeyGreensteinPhaseFunction::new(g),
+        }
+    }
+
+    /// Returns the bounding box of the medium in world space.
+    #[inline]
+    pub fn bounds(&self) -> BoundingBox {
+        self.bounds
+    }
+
+    /// Returns the density of the medium at a point in world space.
+    pub fn density(&self, point: &Vector3<f32>) -> f32 {
+        let extent = self.bounds.extent();
+        let offset = point - self.bounds.min;
+        let grid_point = Vector3::new(offset.x / extent.x, offset.y / extent.y, offset.z / extent.z);
+
+        self.grid.lookup(&grid_point)
+    }
+
+    /// Returns the bound on the extinction coefficient over the medium.
+    #[inline]
+    fn majorant(&self) -> f32 {
+        self.sigma_t * self.grid.max_value()
+    }
+
+    /// Clip a ray segment to the bounding box of the medium. The result is the 
+    /// range of ray parameters inside the medium, and the length of the ray 
+    /// direction.
+    fn clip(&self, ray: &Ray, t_max: f32) -> Option<(f32, f32, f32)> {
+        let ray_length = ray.direction.magnitude();
+        if ray_length == 0_f32 || self.majorant() <= 0_f32 {
+            return None;
+        }
+
+        self.bounds
+            .intersect(ray, 0_f32, t_max)
+            .map(|(t_enter, t_exit)| (t_enter, t_exit, ray_length))
+    }
+}
+
+impl Medium for GridMedium {
+    fn phase_function(&self) -> &dyn PhaseFunction {
+        &self.phase_function
+    }
+
+    fn transmittance(&self, ray: &Ray, t_max: f32, sampler: &mut SphereSampler) -> Vector3<f32> {
+        let (t_enter, t_exit, ray_length) = match self.clip(ray, t_max) {
+            Some(range) => range,
+            None => return Vector3::from_fill(1_f32),
+        };
+
+        // Ratio tracking: walk tentative collisions drawn from the majorant and
+        // weight the transmittance by the probability that each one is a null 
+        // collision.
+        let majorant = self.majorant();
+        let mut transmittance = 1_f32;
+        let mut t = t_enter;
+        loop {
+            t -= f32::ln(1_f32 - sampler.sample_f32()) / (majorant * ray_length);
+            if t >= t_exit {
+                break;
+            }
+
+            transmittance *= 1_f32 - self.sigma_t * self.density(&ray.interpolate(t)) / majorant;
+            if transmittance <= 0_f32 {
+                return Vector3::zero();
+            }
+        }
+
+        Vector3::from_fill(transmittance)
+    }
+
+    fn sample(&self, ray: &Ray, t_max: f32, sampler: &mut SphereSampler) -> MediumResult {
+        let (t_enter, t_exit, ray_length) = match self.clip(ray, t_max) {
+            Some(range) => range,
+            None => return MediumResult::new(None, Vector3::from_fill(1_f32)),
+        };
+
+        // Delta tracking: walk tentative collisions drawn from the majorant and
+        // accept each one as a real collision with probability proportional to 
+        // the density there.
+        let majorant = self.majorant();
+        let mut t = t_enter;
+        loop {
+            t -= f32::ln(1_f32 - sampler.sample_f32()) / (majorant * ray_length);
+            if t >= t_exit {
+                return MediumResult::new(None, Vector3::from_fill(1_f32));
+            }
+
+            let point = ray.interpolate(t);
+            if sampler.sample_f32() < self.sigma_t * self.density(&point) / majorant {
+                // The weight of a real collision is `sigma_s / sigma_t`.
+                return MediumResult::new(Some(MediumInteraction::new(t, point)), self.albedo);
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use crate::core::*;
+use cglinalg::{
+    Vector3,
+    Magnitude,
+};
+
+
+/// The Henyey-Greenstein phase function.
+///
+/// The asymmetry parameter `g` in `(-1, 1)` is the mean cosine of the scattering
+/// angle. Positive values favor forward scattering, negative values favor back
+/// scattering, and zero gives isotropic scattering.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HenyeyGreensteinPhaseFunction {
+    g: f32,
+}
+
+impl HenyeyGreensteinPhaseFunction {
+    pub fn new(g: f32) -> Self {
+        Self { g: f32::clamp(g, -0.99, 0.99), }
+    }
+
+    #[inline]
+    pub fn g(&self) -> f32 {
+        self.g
+    }
+
+    /// Evaluate the phase function for the cosine of the angle between the 
+    /// incoming and outgoing directions.
+    #[inline]
+    fn evaluate_cos_theta(&self, cos_theta: f32) -> f32 {
+        let g = self.g;
+        let denominator = 1_f32 + g * g - 2_f32 * g * cos_theta;
+
+        (1_f32 - g * g) / (4_f32 * std::f32::consts::PI * denominator * f32::sqrt(denominator))
+    }
+}
+
+impl PhaseFunction for HenyeyGreensteinPhaseFunction {
+    fn evaluate(&self, ray_incoming: &Vector3<f32>, ray_outgoing: &Vector3<f32>) -> f32 {
+        self.evaluate_cos_theta(ray_incoming.dot(ray_outgoing))
+    }
+
+    fn sample(&self, ray_incoming: &Vector3<f32>, sampler: &mut SphereSampler) -> PhaseFunctionResult {
+        let g = self.g;
+        let u1 = sampler.sample_f32();
+        let u2 = sampler.sample_f32();
+        // Invert the cumulative distribution of the cosine of the scattering angle,
+        // measured from the direction of travel of the incoming ray.
+        let cos_theta = if f32::abs(g) < 1e-3 {
+            1_f32 - 2_f32 * u1
+        } else {
+            let ratio = (1_f32 - g * g) / (1_f32 - g + 2_f32 * g * u1);
+
+            (1_f32 + g * g - ratio * ratio) / (2_f32 * g)
+        };
+        let cos_theta = f32::clamp(cos_theta, -1_f32, 1_f32);
+        let sin_theta = f32::sqrt(f32::max(0_f32, 1_f32 - cos_theta * cos_theta));
+        let phi = 2_f32 * std::f32::consts::PI * u2;
+        let frame = Frame::from_normal(&ray_incoming.normalize());
+        let ray_outgoing = frame.to_world(&Vector3::new(
+            sin_theta * f32::cos(phi),
+            sin_theta * f32::sin(phi),
+            cos_theta,
+        ));
+        let value = self.evaluate_cos_theta(cos_theta);
+
+        PhaseFunctionResult::new(ray_outgoing, value, value)
+    }
+}
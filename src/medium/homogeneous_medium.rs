@@ -0,0 +1,138 @@
+use crate::core::*;
+use crate::medium::*;
+use cglinalg::{
+    Vector3,
+    Magnitude,
+};
+
+
+/// A participating medium with the same optical properties everywhere.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HomogeneousMedium {
+    /// The absorption coefficient per unit length of each color channel.
+    sigma_a: Vector3<f32>,
+    /// The scattering coefficient per unit length of each color channel.
+    sigma_s: Vector3<f32>,
+    /// The extinction coefficient `sigma_a + sigma_s`.
+    sigma_t: Vector3<f32>,
+    phase_function: HenyeyGreensteinPhaseFunction,
+}
+
+impl HomogeneousMedium {
+    pub fn new(sigma_a: Vector3<f32>, sigma_s: Vector3<f32>, g: f32) -> Self {
+        Self {
+            sigma_a,
+            sigma_s,
+            sigma_t: sigma_a + sigma_s,
+            phase_function: HenyeyGreensteinPhaseFunction::new(g),
+        }
+    }
+
+    /// Construct a dense scattering medium for a subsurface scattering material 
+    /// from artist-friendly parameters.
+    ///
+    /// The `albedo` is the overall color of the material seen from outside after 
+    /// light has scattered many times inside it, and the `mean_free_path` is the 
+    /// average distance light of each color channel travels between scattering 
+    /// events, which controls how far light bleeds under the surface. The albedo
+    /// is converted to the single scattering albedo of the medium with the 
+    /// fit of Chiang, Kutz, and Burley (2016) for random walks.
+    pub fn from_albedo(albedo: Vector3<f32>, mean_free_path: Vector3<f32>, g: f32) -> Self {
+        #[inline]
+        fn single_scattering_albedo(albedo: f32) -> f32 {
+            let albedo = f32::clamp(albedo, 0_f32, 1_f32);
+            let root = 4.09712 + 4.20863 * albedo - f32::sqrt(9.59217 + 41.6808 * albedo + 17.7126 * albedo * albedo);
+
+            1_f32 - root * root
+        }
+
+        let sigma_t = Vector3::new(
+            1_f32 / mean_free_path.x,
+            1_f32 / mean_free_path.y,
+            1_f32 / mean_free_path.z,
+        );
+        let scattering_albedo = Vector3::new(
+            single_scattering_albedo(albedo.x),
+            single_scattering_albedo(albedo.y),
+            single_scattering_albedo(albedo.z),
+        );
+        let sigma_s = sigma_t.component_mul(&scattering_albedo);
+        let sigma_a = sigma_t - sigma_s;
+
+        Self::new(sigma_a, sigma_s, g)
+    }
+
+    #[inline]
+    pub fn sigma_a(&self) -> Vector3<f32> {
+        self.sigma_a
+    }
+
+    #[inline]
+    pub fn sigma_s(&self) -> Vector3<f32> {
+        self.sigma_s
+    }
+
+    #[inline]
+    pub fn sigma_t(&self) -> Vector3<f32> {
+        self.sigma_t
+    }
+
+    /// Evaluate the Beer-Lambert transmittance over a distance.
+    #[inline]
+    fn transmittance_over(&self, distance: f32) -> Vector3<f32> {
+        // Clamp the distance so that a ray leaving the scene does not multiply a
+        // zero extinction coefficient by infinity.
+        let distance = f32::min(distance, f32::MAX);
+
+        Vector3::new(
+            f32::exp(-self.sigma_t.x * distance),
+            f32::exp(-self.sigma_t.y * distance),
+            f32::exp(-self.sigma_t.z * distance),
+        )
+    }
+}
+
+impl Medium for HomogeneousMedium {
+    fn phase_function(&self) -> &dyn PhaseFunction {
+        &self.phase_function
+    }
+
+    fn transmittance(&self, ray: &Ray, t_max: f32, _sampler: &mut SphereSampler) -> Vector3<f32> {
+        self.transmittance_over(t_max * ray.direction.magnitude())
+    }
+
+    fn sample(&self, ray: &Ray, t_max: f32, sampler: &mut SphereSampler) -> MediumResult {
+        let ray_length = ray.direction.magnitude();
+        let distance_max = f32::min(t_max * ray_length, f32::MAX);
+        // Sample the free-flight distance from the extinction coefficient of a 
+        // uniformly chosen color channel, and weight the sample by the density of
+        // sampling it from any of the channels.
+        let channel = usize::min((sampler.sample_f32() * 3_f32) as usize, 2);
+        let sigma_t_channel = self.sigma_t[channel];
+        let distance_sampled = if sigma_t_channel > 0_f32 {
+            -f32::ln(1_f32 - sampler.sample_f32()) / sigma_t_channel
+        } else {
+            f32::INFINITY
+        };
+
+        if distance_sampled < distance_max {
+            let transmittance = self.transmittance_over(distance_sampled);
+            let density = self.sigma_t.component_mul(&transmittance);
+            let pdf = (density.x + density.y + density.z) / 3_f32;
+            let t = distance_sampled / ray_length;
+            let interaction = MediumInteraction::new(t, ray.interpolate(t));
+
+            MediumResult::new(Some(interaction), self.sigma_s.component_mul(&transmittance) / pdf)
+        } else {
+            let transmittance = self.transmittance_over(distance_max);
+            let pdf = (transmittance.x + transmittance.y + transmittance.z) / 3_f32;
+            let weight = if pdf > 0_f32 {
+                transmittance / pdf
+            } else {
+                Vector3::zero()
+            };
+
+            MediumResult::new(None, weight)
+        }
+    }
+}
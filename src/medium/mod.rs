@@ -0,0 +1,11 @@
+mod density_grid;
+mod grid_medium;
+mod henyey_greenstein_phase_function;
+mod homogeneous_medium;
+
+
+pub use density_grid::*;
+pub use grid_medium::*;
+pub use henyey_greenstein_phase_function::*;
+pub use homogeneous_medium::*;
+
@@ -0,0 +1,51 @@
+use crate::core::*;
+use crate::renderer::*;
+use crate::scene::*;
+use cglinalg::{
+    Vector3,
+    Magnitude,
+};
+
+
+/// An integrator estimating the fraction of the hemisphere above the surfaces 
+/// seen by the camera, weighted by the cosine to the surface normal, that no 
+/// other surface occludes within a maximum distance.
+///
+/// Surfaces appear white where they are unoccluded and black where they are 
+/// fully occluded. Rays escaping the scene are black.
+#[derive(Copy, Clone, Debug)]
+pub struct AmbientOcclusionIntegrator {
+    max_distance: f32,
+    t_max: f32,
+}
+
+impl AmbientOcclusionIntegrator {
+    pub fn new(max_distance: f32, t_max: f32) -> Self {
+        Self { max_distance, t_max, }
+    }
+}
+
+impl Integrator for AmbientOcclusionIntegrator {
+    fn radiance(&self, scene: &Scene, ray: &Ray, sampler: &mut SphereSampler) -> Vector3<f32> {
+        let query = IntersectionQuery::new(*ray, 0_f32, self.t_max);
+        let hit = match scene.intersect(&query) {
+            Some(hit) => hit,
+            None => return Vector3::zero(),
+        };
+        let intersection_result = hit.intersection_result.unwrap_hit_or_tangent();
+        // Sample the hemisphere on the side of the surface the ray arrived from.
+        let normal = intersection_result.normal.normalize();
+        let normal = if normal.dot(&ray.direction) > 0_f32 { -normal } else { normal };
+        let frame = Frame::from_normal_tangent(&normal, &intersection_result.dpdu);
+        let direction = frame.to_world(&sampler.sample_cosine_hemisphere());
+        let occlusion_ray = intersection_result.spawn_ray(&direction);
+        let occlusion_query = IntersectionQuery::new(occlusion_ray, 0_f32, self.max_distance);
+
+        if scene.intersect(&occlusion_query).is_none() {
+            Vector3::from_fill(1_f32)
+        } else {
+            Vector3::zero()
+        }
+    }
+}
+
@@ -0,0 +1,549 @@
+use crate::core::*;
+use crate::renderer::*;
+use crate::scene::*;
+use cglinalg::{
+    Vector3,
+    Magnitude,
+};
+
+
+/// The kind of point a vertex of a bidirectional path lies on.
+#[derive(Copy, Clone, Debug)]
+enum VertexKind<'a> {
+    /// A point on the lens of the camera.
+    Camera,
+    /// The position of a point light.
+    Light,
+    /// A point on the surface of an object in a scene.
+    Surface {
+        object: &'a SceneObject,
+        hit: IntersectionData,
+        /// The unit direction of travel of the subpath arriving at the vertex.
+        ray_incoming: Vector3<f32>,
+    },
+}
+
+/// A vertex of a camera subpath or a light subpath.
+///
+/// The probability densities of a vertex are measured with respect to area.
+/// The forward density is the density of sampling the vertex from the previous
+/// vertex of its own subpath, and the reverse density is the density of sampling
+/// it from the next vertex, i.e. of sampling it from the opposite end of the path.
+#[derive(Copy, Clone, Debug)]
+struct Vertex<'a> {
+    kind: VertexKind<'a>,
+    point: Vector3<f32>,
+    /// The time at which the path passes through the vertex.
+    time: f32,
+    /// The throughput of the subpath up to and including the vertex.
+    beta: Vector3<f32>,
+    /// Whether the subpath scattered from the vertex along a direction described
+    /// by a Dirac delta distribution.
+    is_delta: bool,
+    pdf_forward: f32,
+    pdf_reverse: f32,
+}
+
+impl<'a> Vertex<'a> {
+    fn new_camera(point: Vector3<f32>, time: f32, beta: Vector3<f32>) -> Self {
+        Self {
+            kind: VertexKind::Camera,
+            point,
+            time,
+            beta,
+            is_delta: false,
+            pdf_forward: 1_f32,
+            pdf_reverse: 0_f32,
+        }
+    }
+
+    fn new_light(point: Vector3<f32>, time: f32, beta: Vector3<f32>, pdf_forward: f32) -> Self {
+        Self {
+            kind: VertexKind::Light,
+            point,
+            time,
+            beta,
+            is_delta: false,
+            pdf_forward,
+            pdf_reverse: 0_f32,
+        }
+    }
+
+    fn new_surface(
+        object: &'a SceneObject,
+        hit: IntersectionData,
+        ray_incoming: Vector3<f32>,
+        beta: Vector3<f32>) -> Self
+    {
+        Self {
+            kind: VertexKind::Surface { object, hit, ray_incoming, },
+            point: hit.point,
+            time: hit.time,
+            beta,
+            is_delta: false,
+            pdf_forward: 0_f32,
+            pdf_reverse: 0_f32,
+        }
+    }
+
+    /// Determine whether a path can connect to the vertex with a deterministic
+    /// segment.
+    #[inline]
+    fn is_connectible(&self) -> bool {
+        match self.kind {
+            VertexKind::Camera | VertexKind::Light => true,
+            VertexKind::Surface { object, .. } => object.bsdf().flags().is_non_delta(),
+        }
+    }
+
+    /// Compute the end point of a segment from the vertex towards the point
+    /// `target`, offset from the surface the vertex lies on.
+    #[inline]
+    fn spawn_origin(&self, target: &Vector3<f32>) -> Vector3<f32> {
+        match self.kind {
+            VertexKind::Surface { hit, .. } => hit.spawn_origin(&(target - self.point)),
+            _ => self.point,
+        }
+    }
+
+    /// Returns the absolute value of the cosine between the shading normal at
+    /// the vertex and the unit direction `direction`, or one for vertices not
+    /// lying on a surface.
+    #[inline]
+    fn abs_cos_shading(&self, direction: &Vector3<f32>) -> f32 {
+        match self.kind {
+            VertexKind::Surface { hit, .. } => f32::abs(hit.shading_normal.normalize().dot(direction)),
+            _ => 1_f32,
+        }
+    }
+
+    /// Convert a probability density with respect to solid angle at the vertex
+    /// into a probability density with respect to area at the vertex `next`.
+    fn convert_density(&self, pdf: f32, next: &Vertex) -> f32 {
+        let offset = next.point - self.point;
+        let distance_squared = offset.magnitude_squared();
+        if distance_squared == 0_f32 {
+            return 0_f32;
+        }
+
+        let pdf_area = pdf / distance_squared;
+        match next.kind {
+            VertexKind::Surface { hit, .. } => {
+                let direction = offset / f32::sqrt(distance_squared);
+
+                pdf_area * f32::abs(hit.normal.normalize().dot(&direction))
+            }
+            _ => pdf_area,
+        }
+    }
+
+    /// Evaluate the scattering function at the vertex for the path arriving at
+    /// the vertex and leaving towards the vertex `next`.
+    fn evaluate(&self, next: &Vertex) -> Vector3<f32> {
+        match self.kind {
+            VertexKind::Surface { object, hit, ray_incoming, } => {
+                let ray_outgoing = (next.point - self.point).normalize();
+
+                object.evaluate_bsdf(&hit, &ray_incoming, &ray_outgoing)
+            }
+            _ => Vector3::zero(),
+        }
+    }
+
+    /// Evaluate the probability density with respect to area of sampling the
+    /// vertex `next` from the vertex, given the path arrived at the vertex from
+    /// the vertex `previous`.
+    fn pdf(&self, scene: &Scene, previous: Option<&Vertex>, next: &Vertex) -> f32 {
+        let pdf = match self.kind {
+            VertexKind::Camera => scene.camera.at_time(self.time).pdf_direction(&(next.point - self.point)),
+            VertexKind::Light => 1_f32 / (4_f32 * std::f32::consts::PI),
+            VertexKind::Surface { object, hit, .. } => {
+                let previous = match previous {
+                    Some(previous) => previous,
+                    None => return 0_f32,
+                };
+                let ray_incoming = (self.point - previous.point).normalize();
+                let ray_outgoing = (next.point - self.point).normalize();
+
+                object.pdf_bsdf(&hit, &ray_incoming, &ray_outgoing)
+            }
+        };
+
+        self.convert_density(pdf, next)
+    }
+}
+
+/// The estimate of a single connection strategy.
+#[derive(Copy, Clone, Debug)]
+struct Connection {
+    radiance: Vector3<f32>,
+    /// The film coordinates the estimate lands on, for strategies connecting to
+    /// a fresh point on the camera lens.
+    film_position: Option<(f32, f32)>,
+}
+
+/// A bidirectional path tracer.
+///
+/// For each camera sample, the bidirectional path tracer traces one subpath from
+/// the camera and one subpath from a point light chosen uniformly at random, and
+/// connects every prefix of one to every prefix of the other. The estimates of
+/// the different connection strategies are combined with multiple importance
+/// sampling using the balance heuristic. Connections ending at the camera lens
+/// land on arbitrary pixels, so they are splatted onto the film.
+///
+/// Emitting surfaces cannot be sampled from the light end of a path, so light
+/// they emit reaches the camera only through camera subpaths that hit them, and
+/// likewise for the background. The bidirectional path tracer treats the space
+/// between surfaces as a vacuum, so it ignores participating media, and treats
+/// the boundaries of media as specular surfaces. The renderer falls back to path
+/// tracing for scenes with media.
+#[derive(Copy, Clone, Debug)]
+pub struct BidirectionalPathTracer {
+    max_path_depth: usize,
+    t_max: f32,
+}
+
+impl BidirectionalPathTracer {
+    pub fn new(max_path_depth: usize, t_max: f32) -> Self {
+        Self { max_path_depth, t_max, }
+    }
+
+    /// Extend a subpath by tracing a random walk from its last vertex along the
+    /// ray `ray`, sampled with probability density `pdf` with respect to solid
+    /// angle. The walk visits at most `self.max_path_depth` surfaces.
+    ///
+    /// Returns the radiance of the background the walk reaches after leaving the
+    /// scene weighted by the throughput of the subpath. Only camera subpaths 
+    /// gather it.
+    fn random_walk<'a>(
+        &self,
+        scene: &'a Scene,
+        ray: Ray,
+        beta: Vector3<f32>,
+        pdf: f32,
+        sampler: &mut SphereSampler,
+        path: &mut Vec<Vertex<'a>>) -> Vector3<f32>
+    {
+        let mut ray = ray;
+        let mut beta = beta;
+        let mut pdf_forward = pdf;
+        let mut depth = 0;
+        loop {
+            let query = IntersectionQuery::new(ray, 0_f32, self.t_max);
+            let hit = match scene.intersect(&query) {
+                Some(hit) => hit,
+                None => return beta.component_mul(&background_radiance(&ray.direction)),
+            };
+            if depth >= self.max_path_depth {
+                return Vector3::zero();
+            }
+
+            let intersection_result = hit.intersection_result.unwrap_hit_or_tangent();
+            let ray_incoming = ray.direction.normalize();
+            let mut vertex = Vertex::new_surface(hit.object, intersection_result, ray_incoming, beta);
+            let previous = path.len() - 1;
+            vertex.pdf_forward = path[previous].convert_density(pdf_forward, &vertex);
+            path.push(vertex);
+            depth += 1;
+
+            let (ray_outgoing, bsdf_result) = match hit.object.sample_bsdf(&intersection_result, &ray_incoming, sampler) {
+                Some(sample) => sample,
+                None => return Vector3::zero(),
+            };
+            if bsdf_result.scattering_fraction == Vector3::zero() {
+                return Vector3::zero();
+            }
+
+            beta = beta.component_mul(&bsdf_result.scattering_fraction);
+            let pdf_reverse = if bsdf_result.flags.is_delta() {
+                path[previous + 1].is_delta = true;
+                pdf_forward = 0_f32;
+
+                0_f32
+            } else {
+                pdf_forward = bsdf_result.pdf;
+
+                hit.object.pdf_bsdf(&intersection_result, &(-ray_outgoing), &(-ray_incoming))
+            };
+            path[previous].pdf_reverse = path[previous + 1].convert_density(pdf_reverse, &path[previous]);
+
+            ray = intersection_result.spawn_ray(&ray_outgoing);
+        }
+    }
+
+    /// Trace a subpath starting from the camera ray `ray`.
+    fn generate_camera_subpath<'a>(
+        &self,
+        scene: &'a Scene,
+        ray: Ray,
+        sampler: &mut SphereSampler,
+        path: &mut Vec<Vertex<'a>>) -> Vector3<f32>
+    {
+        let beta = Vector3::from_fill(1_f32);
+        let pdf_direction = scene.camera.at_time(ray.time).pdf_direction(&ray.direction);
+        path.push(Vertex::new_camera(ray.origin, ray.time, beta));
+
+        self.random_walk(scene, ray, beta, pdf_direction, sampler, path)
+    }
+
+    /// Trace a subpath starting from a point light chosen uniformly at random, 
+    /// emitted at the time `time` of the camera subpath it connects to.
+    fn generate_light_subpath<'a>(&self, scene: &'a Scene, time: f32, sampler: &mut SphereSampler, path: &mut Vec<Vertex<'a>>) {
+        if scene.is_empty_lights() {
+            return;
+        }
+
+        let light_count = scene.lights.len();
+        let light_index = usize::min((sampler.sample_f32() * light_count as f32) as usize, light_count - 1);
+        let light = &scene.lights[light_index];
+        let pdf_choice = 1_f32 / light_count as f32;
+        let pdf_direction = 1_f32 / (4_f32 * std::f32::consts::PI);
+        let intensity = light.emission() / (4_f32 * std::f32::consts::PI);
+        let direction = sampler.sample_uniform_direction();
+        let origin = light.position();
+        path.push(Vertex::new_light(origin, time, intensity / pdf_choice, pdf_choice));
+
+        // A point light emits in every direction, so the cosine factor of the
+        // emitted ray is one.
+        let beta = intensity / (pdf_choice * pdf_direction);
+
+        self.random_walk(scene, Ray::new(origin, direction).with_time(time), beta, pdf_direction, sampler, path);
+    }
+
+    /// Evaluate the geometric term of the segment between two vertices, including
+    /// visibility.
+    fn geometric_term(&self, scene: &Scene, v0: &Vertex, v1: &Vertex) -> f32 {
+        let offset = v1.point - v0.point;
+        let distance_squared = offset.magnitude_squared();
+        if distance_squared == 0_f32 || !scene.line_of_sight(&v0.spawn_origin(&v1.point), &v1.spawn_origin(&v0.point), v0.time) {
+            return 0_f32;
+        }
+
+        let direction = offset / f32::sqrt(distance_squared);
+
+        v0.abs_cos_shading(&direction) * v1.abs_cos_shading(&direction) / distance_squared
+    }
+
+    /// Compute the multiple importance sampling weight of the strategy connecting
+    /// the first `s` vertices of the light subpath to the first `t` vertices of
+    /// the camera subpath, with the balance heuristic.
+    ///
+    /// When the strategy samples a fresh vertex at either end of the path,
+    /// `sampled` replaces the corresponding end vertex of its subpath.
+    fn mis_weight(
+        &self,
+        scene: &Scene,
+        light_path: &[Vertex],
+        camera_path: &[Vertex],
+        sampled: Option<Vertex>,
+        s: usize,
+        t: usize) -> f32
+    {
+        if s + t == 2 {
+            return 1_f32;
+        }
+
+        // Work on copies of the subpaths, updating the densities at the
+        // connection to those of the strategy being weighted.
+        let mut light_vertices = light_path[..s].to_vec();
+        let mut camera_vertices = camera_path[..t].to_vec();
+        if let Some(sampled) = sampled {
+            if s == 1 {
+                light_vertices[0] = sampled;
+            } else if t == 1 {
+                camera_vertices[0] = sampled;
+            }
+        }
+
+        // The end vertices of a connection scatter along the connecting segment,
+        // never along a delta direction.
+        camera_vertices[t - 1].is_delta = false;
+        if s > 0 {
+            light_vertices[s - 1].is_delta = false;
+        }
+
+        let pt = camera_vertices[t - 1];
+        let pt_minus = if t > 1 { Some(camera_vertices[t - 2]) } else { None };
+        if s > 0 {
+            let qs = light_vertices[s - 1];
+            let qs_minus = if s > 1 { Some(light_vertices[s - 2]) } else { None };
+            camera_vertices[t - 1].pdf_reverse = qs.pdf(scene, qs_minus.as_ref(), &pt);
+            if let Some(pt_minus) = pt_minus {
+                camera_vertices[t - 2].pdf_reverse = pt.pdf(scene, Some(&qs), &pt_minus);
+            }
+            light_vertices[s - 1].pdf_reverse = pt.pdf(scene, pt_minus.as_ref(), &qs);
+            if let Some(qs_minus) = qs_minus {
+                light_vertices[s - 2].pdf_reverse = qs.pdf(scene, Some(&pt), &qs_minus);
+            }
+        }
+
+        // Zero densities only occur at delta vertices, whose strategies are
+        // excluded from the sum anyway.
+        let remap = |pdf: f32| if pdf != 0_f32 { pdf } else { 1_f32 };
+        let mut sum_ratios = 0_f32;
+        let mut ratio = 1_f32;
+        for i in (1..t).rev() {
+            ratio *= remap(camera_vertices[i].pdf_reverse) / remap(camera_vertices[i].pdf_forward);
+            if !camera_vertices[i].is_delta && !camera_vertices[i - 1].is_delta {
+                sum_ratios += ratio;
+            }
+        }
+
+        // Point lights have positions described by a Dirac delta distribution, so
+        // no camera subpath can hit them.
+        let mut ratio = 1_f32;
+        for i in (1..s).rev() {
+            ratio *= remap(light_vertices[i].pdf_reverse) / remap(light_vertices[i].pdf_forward);
+            if !light_vertices[i].is_delta && !light_vertices[i - 1].is_delta {
+                sum_ratios += ratio;
+            }
+        }
+
+        1_f32 / (1_f32 + sum_ratios)
+    }
+
+    /// Estimate the contribution of the strategy connecting the first `s` vertices
+    /// of the light subpath to the first `t` vertices of the camera subpath.
+    ///
+    /// Strategies with `t == 1` connect to a fresh point on the camera lens, and
+    /// return the film coordinates the estimate lands on too.
+    fn connect(
+        &self,
+        scene: &Scene,
+        light_path: &[Vertex],
+        camera_path: &[Vertex],
+        s: usize,
+        t: usize,
+        sampler: &mut SphereSampler) -> Option<Connection>
+    {
+        let pt = &camera_path[t - 1];
+        let time = camera_path[0].time;
+        let mut sampled = None;
+        let mut film_position = None;
+        let radiance = if s == 0 {
+            // Only camera subpaths reach emitting surfaces, so their emission
+            // needs no weighting.
+            return match pt.kind {
+                VertexKind::Surface { object, hit, ray_incoming, } => {
+                    let radiance = pt.beta.component_mul(&object.emission(&hit, &ray_incoming));
+
+                    Some(Connection { radiance, film_position: None, })
+                }
+                _ => None,
+            };
+        } else if t == 1 {
+            let qs = &light_path[s - 1];
+            if !qs.is_connectible() {
+                return None;
+            }
+
+            let camera = scene.camera.at_time(time);
+            let lens_position = camera.sample_lens(sampler);
+            let (u, v) = camera.project(&lens_position, &qs.point)?;
+            let offset = lens_position - qs.point;
+            let distance_squared = offset.magnitude_squared();
+            let direction = offset / f32::sqrt(distance_squared);
+            let cos_lens = f32::abs(direction.dot(&camera.forward()));
+            let pdf = camera.pdf_lens(&lens_position) * distance_squared / cos_lens;
+            if pdf <= 0_f32 {
+                return None;
+            }
+
+            let importance = camera.importance(&lens_position, &(-direction));
+            let camera_vertex = Vertex::new_camera(lens_position, time, Vector3::from_fill(importance / pdf));
+            if !scene.line_of_sight(&qs.spawn_origin(&lens_position), &lens_position, time) {
+                return None;
+            }
+
+            let radiance = qs.beta
+                .component_mul(&qs.evaluate(&camera_vertex))
+                .component_mul(&camera_vertex.beta) * qs.abs_cos_shading(&direction);
+            sampled = Some(camera_vertex);
+            film_position = Some((u, v));
+
+            radiance
+        } else if s == 1 {
+            if !pt.is_connectible() || scene.is_empty_lights() {
+                return None;
+            }
+
+            let light_count = scene.lights.len();
+            let light_index = usize::min((sampler.sample_f32() * light_count as f32) as usize, light_count - 1);
+            let light = &scene.lights[light_index];
+            let pdf_choice = 1_f32 / light_count as f32;
+            let intensity = light.emission() / (4_f32 * std::f32::consts::PI);
+            let offset = light.position() - pt.point;
+            let distance_squared = offset.magnitude_squared();
+            let direction = offset / f32::sqrt(distance_squared);
+            let light_vertex = Vertex::new_light(
+                light.position(),
+                time,
+                intensity / (pdf_choice * distance_squared),
+                pdf_choice
+            );
+            if !scene.line_of_sight(&pt.spawn_origin(&light_vertex.point), &light_vertex.point, time) {
+                return None;
+            }
+
+            let radiance = pt.beta
+                .component_mul(&pt.evaluate(&light_vertex))
+                .component_mul(&light_vertex.beta) * pt.abs_cos_shading(&direction);
+            sampled = Some(light_vertex);
+
+            radiance
+        } else {
+            let qs = &light_path[s - 1];
+            if !qs.is_connectible() || !pt.is_connectible() {
+                return None;
+            }
+
+            let f = qs.evaluate(pt).component_mul(&pt.evaluate(qs));
+            if f == Vector3::zero() {
+                return None;
+            }
+
+            qs.beta.component_mul(&f).component_mul(&pt.beta) * self.geometric_term(scene, qs, pt)
+        };
+        if radiance == Vector3::zero() {
+            return None;
+        }
+
+        let weight = self.mis_weight(scene, light_path, camera_path, sampled, s, t);
+
+        Some(Connection { radiance: radiance * weight, film_position, })
+    }
+
+    /// Estimate the radiance arriving at the camera along the camera ray `ray`.
+    ///
+    /// Light tracing strategies that land on other pixels are splatted onto the
+    /// film.
+    pub fn sample(&self, scene: &Scene, ray: Ray, sampler: &mut SphereSampler, film: &mut Film) -> Vector3<f32> {
+        let mut camera_path = Vec::with_capacity(self.max_path_depth + 1);
+        let mut light_path = Vec::with_capacity(self.max_path_depth + 1);
+        let background = self.generate_camera_subpath(scene, ray, sampler, &mut camera_path);
+        self.generate_light_subpath(scene, ray.time, sampler, &mut light_path);
+
+        let mut radiance = background;
+        for t in 1..=camera_path.len() {
+            for s in 0..=light_path.len() {
+                if (s == 1 && t == 1) || s + t < 2 || s + t - 2 > self.max_path_depth {
+                    continue;
+                }
+
+                if let Some(connection) = self.connect(scene, &light_path, &camera_path, s, t, sampler) {
+                    match connection.film_position {
+                        Some((u, v)) => {
+                            if let Some((row, column)) = film.pixel_at(u, v) {
+                                film.add_splat(row, column, connection.radiance);
+                            }
+                        }
+                        None => radiance += connection.radiance,
+                    }
+                }
+            }
+        }
+
+        radiance
+    }
+}
+
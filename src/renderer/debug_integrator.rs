@@ -0,0 +1,239 @@
+use crate::core::*;
+use crate::renderer::*;
+use crate::scene::*;
+use cglinalg::{
+    Vector3,
+    Magnitude,
+};
+use std::fmt;
+use std::str;
+
+
+/// A quantity a debug integrator shows instead of the light in a scene.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DebugMode {
+    /// The unit shading normals of the surfaces seen by the camera, mapping each
+    /// component from `[-1, 1]` to the color range `[0, 1]`.
+    Normals,
+    /// The distance from the camera to the surfaces seen by the camera, from 
+    /// black at the camera to white at the maximum debug distance and beyond.
+    Depth,
+    /// The texture coordinates of the surfaces seen by the camera in the red and
+    /// green channels, wrapped into the unit square.
+    Uv,
+    /// The barycentric coordinates of the points seen by the camera inside the 
+    /// triangles they lie on, in the red, green, and blue channels. Surfaces 
+    /// other than triangles are black.
+    Barycentrics,
+    /// A distinct color per object, from the index of the object in the scene.
+    ObjectId,
+    /// A heatmap of the number of intersection tests a camera ray performs 
+    /// relative to testing every object of a scene, from blue to red.
+    TraversalCost,
+    /// A heatmap of the number of surfaces a path scatters from before it 
+    /// escapes the scene, is absorbed, or reaches the maximum path depth, from 
+    /// blue to red.
+    PathDepth,
+}
+
+impl DebugMode {
+    /// Returns every debug mode.
+    pub fn all() -> [DebugMode; 7] {
+        [
+            DebugMode::Normals,
+            DebugMode::Depth,
+            DebugMode::Uv,
+            DebugMode::Barycentrics,
+            DebugMode::ObjectId,
+            DebugMode::TraversalCost,
+            DebugMode::PathDepth,
+        ]
+    }
+
+    /// Returns the name of a debug mode on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DebugMode::Normals => "normals",
+            DebugMode::Depth => "depth",
+            DebugMode::Uv => "uv",
+            DebugMode::Barycentrics => "barycentrics",
+            DebugMode::ObjectId => "object-id",
+            DebugMode::TraversalCost => "traversal-cost",
+            DebugMode::PathDepth => "path-depth",
+        }
+    }
+}
+
+impl fmt::Display for DebugMode {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}", self.name())
+    }
+}
+
+/// An error from parsing the name of a debug mode.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseDebugModeError {
+    name: String,
+}
+
+impl fmt::Display for ParseDebugModeError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "unknown debug mode `{}`; expected one of", self.name)?;
+        for mode in DebugMode::all().iter() {
+            write!(formatter, " `{}`", mode)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseDebugModeError {}
+
+impl str::FromStr for DebugMode {
+    type Err = ParseDebugModeError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        DebugMode::all()
+            .into_iter()
+            .find(|mode| mode.name() == name)
+            .ok_or_else(|| ParseDebugModeError { name: name.to_string(), })
+    }
+}
+
+/// Returns the color of a heatmap at the value `value` in `[0, 1]`, running 
+/// from blue through cyan, green, and yellow to red.
+fn heat_color(value: f32) -> Vector3<f32> {
+    let value = value.clamp(0_f32, 1_f32) * 4_f32;
+    let segment = f32::min(value.floor(), 3_f32);
+    let t = value - segment;
+    match segment as usize {
+        0 => Vector3::new(0_f32, t, 1_f32),
+        1 => Vector3::new(0_f32, 1_f32, 1_f32 - t),
+        2 => Vector3::new(t, 1_f32, 0_f32),
+        _ => Vector3::new(1_f32, 1_f32 - t, 0_f32),
+    }
+}
+
+/// Returns a color for the object with index `index` that differs clearly from 
+/// the colors of the objects with nearby indices.
+fn false_color(index: usize) -> Vector3<f32> {
+    // Step around the color wheel by the golden ratio, so consecutive hues land 
+    // far apart.
+    let hue = ((index as f32) * 0.618_034).fract() * 6_f32;
+    let sector = hue.floor();
+    let t = hue - sector;
+    let (low, high) = (0.2, 0.9);
+    let rising = low + (high - low) * t;
+    let falling = high - (high - low) * t;
+    match sector as usize {
+        0 => Vector3::new(high, rising, low),
+        1 => Vector3::new(falling, high, low),
+        2 => Vector3::new(low, high, rising),
+        3 => Vector3::new(low, falling, high),
+        4 => Vector3::new(rising, low, high),
+        _ => Vector3::new(high, low, falling),
+    }
+}
+
+/// An integrator showing the geometric quantities of a scene and the work of 
+/// rendering it, for finding out whether the geometry, the normals, or the 
+/// materials of a scene are at fault when it looks wrong.
+///
+/// Rays escaping the scene are black, except in the heatmap modes.
+#[derive(Copy, Clone, Debug)]
+pub struct DebugIntegrator {
+    mode: DebugMode,
+    max_path_depth: usize,
+    max_distance: f32,
+    t_max: f32,
+}
+
+impl DebugIntegrator {
+    pub fn new(mode: DebugMode, max_path_depth: usize, t_max: f32) -> Self {
+        Self { mode, max_path_depth, max_distance: 100_f32, t_max, }
+    }
+
+    /// Set the distance from the camera shown as white in the depth mode.
+    pub fn with_max_distance(mut self, max_distance: f32) -> Self {
+        self.max_distance = max_distance;
+        self
+    }
+
+    /// Count the surfaces a path starting along the ray `ray` scatters from,
+    /// sampling the scattering functions of the surfaces the way the path 
+    /// tracer does. Participating media are ignored.
+    fn path_depth(&self, scene: &Scene, ray: &Ray, sampler: &mut SphereSampler) -> usize {
+        let mut ray = *ray;
+        let mut depth = 0;
+        while depth < self.max_path_depth {
+            let query = IntersectionQuery::new(ray, 0_f32, self.t_max);
+            let hit = match scene.intersect(&query) {
+                Some(hit) => hit,
+                None => break,
+            };
+            let intersection_result = hit.intersection_result.unwrap_hit_or_tangent();
+            let ray_incoming = ray.direction.normalize();
+            let (direction, bsdf_result) = match hit.object.sample_bsdf(&intersection_result, &ray_incoming, sampler) {
+                Some(sample) => sample,
+                None => break,
+            };
+            depth += 1;
+            if bsdf_result.scattering_fraction == Vector3::zero() {
+                break;
+            }
+
+            ray = intersection_result.spawn_ray(&direction);
+        }
+
+        depth
+    }
+}
+
+impl Integrator for DebugIntegrator {
+    fn radiance(&self, scene: &Scene, ray: &Ray, sampler: &mut SphereSampler) -> Vector3<f32> {
+        let query = IntersectionQuery::new(*ray, 0_f32, self.t_max);
+        match self.mode {
+            DebugMode::TraversalCost => {
+                let mut stats = TraversalStats::default();
+                scene.intersect_with_stats(&query, &mut stats);
+                let max_cost = usize::max(2 * scene.len_objects(), 1);
+
+                return heat_color((stats.total() as f32) / (max_cost as f32));
+            }
+            DebugMode::PathDepth => {
+                let depth = self.path_depth(scene, ray, sampler);
+                let max_depth = usize::max(self.max_path_depth, 1);
+
+                return heat_color((depth as f32) / (max_depth as f32));
+            }
+            _ => {}
+        }
+
+        let hit = match scene.intersect(&query) {
+            Some(hit) => hit,
+            None => return Vector3::zero(),
+        };
+        let intersection_result = hit.intersection_result.unwrap_hit_or_tangent();
+        match self.mode {
+            DebugMode::Normals => {
+                let normal = intersection_result.shading_normal.normalize();
+
+                (normal + Vector3::from_fill(1_f32)) * 0.5
+            }
+            DebugMode::Depth => {
+                let distance = intersection_result.t * ray.direction.magnitude();
+
+                Vector3::from_fill(f32::min(distance / self.max_distance, 1_f32))
+            }
+            DebugMode::Uv => {
+                let uv = intersection_result.uv;
+
+                Vector3::new(uv.x - uv.x.floor(), uv.y - uv.y.floor(), 0_f32)
+            }
+            DebugMode::Barycentrics => intersection_result.barycentrics.unwrap_or_else(Vector3::zero),
+            DebugMode::ObjectId => false_color(hit.index),
+            DebugMode::TraversalCost | DebugMode::PathDepth => unreachable!(),
+        }
+    }
+}
+
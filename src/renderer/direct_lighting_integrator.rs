@@ -0,0 +1,109 @@
+use crate::core::*;
+use crate::renderer::*;
+use crate::scene::*;
+use cglinalg::{
+    Vector3,
+    Magnitude,
+};
+
+
+/// An integrator estimating the light arriving at the surfaces seen by the 
+/// camera directly from the lights of a scene.
+///
+/// Paths only continue through perfect specular reflection and refraction, so 
+/// mirrors and glass show the direct lighting of the surfaces behind them. 
+/// Participating media attenuate the light on its way to a surface, but do not 
+/// scatter it. Shadow catchers show the light from behind them darkened by the
+/// shadows cast onto them.
+#[derive(Copy, Clone, Debug)]
+pub struct DirectLightingIntegrator {
+    max_path_depth: usize,
+    t_max: f32,
+}
+
+impl DirectLightingIntegrator {
+    pub fn new(max_path_depth: usize, t_max: f32) -> Self {
+        Self { max_path_depth, t_max, }
+    }
+
+    /// Estimate the light the surface at a world space intersection reflects 
+    /// towards the origin of the ray arriving in the direction `ray_incoming`
+    /// directly from the point lights of a scene.
+    fn estimate_direct(
+        &self, 
+        scene: &Scene, 
+        object: &SceneObject, 
+        hit: &IntersectionData, 
+        ray_incoming: &Vector3<f32>, 
+        medium: Option<&dyn Medium>, 
+        sampler: &mut SphereSampler) -> Vector3<f32>
+    {
+        let shading_normal = hit.shading_normal.normalize();
+        let mut radiance = Vector3::zero();
+        for light in scene.lights.iter() {
+            let origin = hit.spawn_origin(&(light.position() - hit.point));
+            let transmittance = scene.transmittance(&origin, &light.position(), hit.time, medium, sampler);
+            if transmittance == Vector3::zero() {
+                continue;
+            }
+
+            let direction = light.position() - hit.point;
+            let distance_squared = direction.magnitude_squared();
+            let direction = direction / f32::sqrt(distance_squared);
+            let irradiance = light.emission() / (4_f32 * std::f32::consts::PI * distance_squared);
+            let cos_theta = f32::abs(direction.dot(&shading_normal));
+            let bsdf = object.evaluate_bsdf(hit, ray_incoming, &direction);
+
+            radiance += bsdf.component_mul(&irradiance).component_mul(&transmittance) * cos_theta;
+        }
+
+        radiance
+    }
+
+    fn trace(&self, scene: &Scene, ray: &Ray, medium: Option<&dyn Medium>, sampler: &mut SphereSampler, depth: usize) -> Vector3<f32> {
+        let query = IntersectionQuery::new(*ray, 0_f32, self.t_max);
+        let hit = match scene.intersect(&query) {
+            Some(hit) => hit,
+            None => return background_radiance(&ray.direction),
+        };
+        let intersection_result = hit.intersection_result.unwrap_hit_or_tangent();
+        if hit.object.is_shadow_catcher() {
+            let next_ray = intersection_result.spawn_ray(&ray.direction);
+            let behind = self.trace(scene, &next_ray, medium, sampler, depth);
+
+            return behind * shadow_fraction(scene, &intersection_result);
+        }
+
+        let ray_incoming = ray.direction.normalize();
+        let flags = hit.object.bsdf().flags();
+        let emission = hit.object.emission(&intersection_result, &ray_incoming);
+        let direct = if flags.is_non_delta() {
+            self.estimate_direct(scene, hit.object, &intersection_result, &ray_incoming, medium, sampler)
+        } else {
+            Vector3::zero()
+        };
+        let specular = if (flags.is_delta() || flags.is_pass_through()) && depth < self.max_path_depth {
+            match hit.object.sample_bsdf(&intersection_result, &ray_incoming, sampler) {
+                Some((direction, bsdf_result)) if bsdf_result.flags.is_delta() || bsdf_result.flags.is_pass_through() => {
+                    let next_medium = scene.medium_after_crossing(hit.object, &intersection_result.normal, &direction, medium);
+                    let next_ray = intersection_result.spawn_ray(&direction);
+                    let next_estimate = self.trace(scene, &next_ray, next_medium, sampler, depth + 1);
+
+                    bsdf_result.scattering_fraction.component_mul(&next_estimate)
+                }
+                _ => Vector3::zero(),
+            }
+        } else {
+            Vector3::zero()
+        };
+
+        emission + direct + specular
+    }
+}
+
+impl Integrator for DirectLightingIntegrator {
+    fn radiance(&self, scene: &Scene, ray: &Ray, sampler: &mut SphereSampler) -> Vector3<f32> {
+        self.trace(scene, ray, scene.medium(), sampler, 0)
+    }
+}
+
@@ -0,0 +1,102 @@
+use crate::canvas::*;
+use cglinalg::{
+    Vector3,
+};
+
+
+/// The film of a camera accumulating radiance estimates for each pixel of an
+/// image.
+///
+/// A film keeps two separate sums per pixel. Samples are estimates made for a
+/// pixel from paths starting at that pixel. Splats are estimates that land on a
+/// pixel from paths starting elsewhere, e.g. paths traced from the lights of a
+/// scene towards the camera. Both sums are divided by the number of samples taken
+/// per pixel when the film is resolved into an image.
+#[derive(Clone, Debug)]
+pub struct Film {
+    width: usize,
+    height: usize,
+    samples: Vec<Vector3<f32>>,
+    splats: Vec<Vector3<f32>>,
+}
+
+impl Film {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            samples: vec![Vector3::zero(); width * height],
+            splats: vec![Vector3::zero(); width * height],
+        }
+    }
+
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Add a radiance estimate made for the pixel at `(row, column)`.
+    #[inline]
+    pub fn add_sample(&mut self, row: usize, column: usize, radiance: Vector3<f32>) {
+        self.samples[row * self.width + column] += radiance;
+    }
+
+    /// Add a radiance estimate that landed on the pixel at `(row, column)` from a
+    /// path that did not start there.
+    #[inline]
+    pub fn add_splat(&mut self, row: usize, column: usize, radiance: Vector3<f32>) {
+        self.splats[row * self.width + column] += radiance;
+    }
+
+    /// Find the pixel containing the film coordinates `(u, v)` accepted by
+    /// [`crate::Camera::cast_ray`], as a pair `(row, column)`.
+    ///
+    /// The rows of the film run from the top of the image to the bottom, and
+    /// the **v** coordinate runs from the bottom to the top.
+    pub fn pixel_at(&self, u: f32, v: f32) -> Option<(usize, usize)> {
+        if !(0_f32..=1_f32).contains(&u) || !(0_f32..=1_f32).contains(&v) {
+            return None;
+        }
+
+        let column = usize::min((u * self.width as f32) as usize, self.width - 1);
+        let row_from_bottom = usize::min((v * self.height as f32) as usize, self.height - 1);
+
+        Some((self.height - 1 - row_from_bottom, column))
+    }
+
+    /// Returns the radiance estimate of the pixel at `(row, column)` after
+    /// `samples_per_pixel` samples per pixel.
+    #[inline]
+    pub fn pixel(&self, row: usize, column: usize, samples_per_pixel: usize) -> Vector3<f32> {
+        let index = row * self.width + column;
+
+        (self.samples[index] + self.splats[index]) / samples_per_pixel as f32
+    }
+
+    /// Resolve the film after `samples_per_pixel` samples per pixel into a gamma
+    /// corrected image.
+    pub fn write_canvas(&self, samples_per_pixel: usize, canvas: &mut Canvas) {
+        for row in 0..self.height {
+            for column in 0..self.width {
+                let color = self.pixel(row, column, samples_per_pixel);
+                let color = Vector3::new(
+                    f32::sqrt(color[0]),
+                    f32::sqrt(color[1]),
+                    f32::sqrt(color[2])
+                );
+
+                let ir = (255.99 * color[0]) as u8;
+                let ig = (255.99 * color[1]) as u8;
+                let ib = (255.99 * color[2]) as u8;
+
+                canvas[row][column] = Rgba::new(ir, ig, ib);
+            }
+        }
+    }
+}
+
@@ -0,0 +1,15 @@
+use crate::core::*;
+use crate::scene::*;
+use cglinalg::{
+    Vector3,
+};
+
+
+/// An estimator of the light arriving at the camera along camera rays, 
+/// independent of the image the estimates contribute to.
+pub trait Integrator {
+    /// Estimate the radiance arriving at the origin of the ray `ray` along the 
+    /// ray.
+    fn radiance(&self, scene: &Scene, ray: &Ray, sampler: &mut SphereSampler) -> Vector3<f32>;
+}
+
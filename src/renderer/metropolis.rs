@@ -0,0 +1,198 @@
+use crate::core::*;
+use crate::renderer::*;
+use crate::scene::*;
+use cglinalg::{
+    Vector3,
+};
+use rand::prelude::*;
+
+
+/// The parameters of Metropolis light transport.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MetropolisSettings {
+    bootstrap_samples: usize,
+    chain_count: usize,
+    large_step_probability: f32,
+    sigma: f32,
+}
+
+impl MetropolisSettings {
+    /// Construct the settings for estimating the brightness of an image from
+    /// `bootstrap_samples` independent paths, and rendering the image with
+    /// `chain_count` Markov chains started from them.
+    pub fn new(bootstrap_samples: usize, chain_count: usize) -> Self {
+        Self {
+            bootstrap_samples,
+            chain_count,
+            large_step_probability: 0.3,
+            sigma: 0.01,
+        }
+    }
+
+    /// Set the probability that a mutation replaces a path with an independent
+    /// one instead of perturbing it.
+    pub fn with_large_step_probability(mut self, large_step_probability: f32) -> Self {
+        self.large_step_probability = large_step_probability;
+        self
+    }
+
+    /// Set the standard deviation of the perturbations of the random numbers of
+    /// a path.
+    pub fn with_sigma(mut self, sigma: f32) -> Self {
+        self.sigma = sigma;
+        self
+    }
+}
+
+impl Default for MetropolisSettings {
+    fn default() -> Self {
+        Self::new(100_000, 1000)
+    }
+}
+
+/// A path generated from a point in primary sample space.
+#[derive(Copy, Clone, Debug)]
+struct PathSample {
+    radiance: Vector3<f32>,
+    row: usize,
+    column: usize,
+    /// The luminance of the radiance, which the Markov chains sample paths
+    /// proportionally to.
+    luminance: f32,
+}
+
+/// A primary sample space Metropolis light transport integrator in the manner
+/// of Kelemen et al.
+///
+/// The integrator runs Markov chains over the random numbers a radiance estimator,
+/// such as the path tracer, consumes, including the film position of the camera
+/// ray. Every chain visits paths with frequency proportional to their luminance.
+/// The average luminance of the image, estimated from independent bootstrap
+/// paths, normalizes the contributions of the chains to the film.
+#[derive(Copy, Clone, Debug)]
+pub struct MetropolisLightTransport {
+    settings: MetropolisSettings,
+}
+
+impl MetropolisLightTransport {
+    pub fn new(settings: MetropolisSettings) -> Self {
+        Self { settings, }
+    }
+
+    /// Construct the sampler of a Markov chain. Samplers with the same seed draw
+    /// the same random numbers until their first mutation, so a chain can replay
+    /// the bootstrap path it starts from.
+    fn chain_sampler(&self, seed: u64) -> SphereSampler {
+        SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(seed))
+            .with_primary_sample_space(self.settings.sigma, self.settings.large_step_probability)
+    }
+
+    /// Generate a path from the random numbers of the sampler, starting with the
+    /// film position of the camera ray.
+    fn sample_path<F>(scene: &Scene, film: &Film, sampler: &mut SphereSampler, radiance: &mut F) -> PathSample
+    where
+        F: FnMut(&Ray, &mut SphereSampler) -> Vector3<f32>,
+    {
+        let width = film.width();
+        let height = film.height();
+        let u = sampler.sample_f32();
+        let y = sampler.sample_f32() * (height as f32);
+        let column = usize::min((u * width as f32) as usize, width - 1);
+        let row = usize::min(y as usize, height - 1);
+        let dv = y - (row as f32);
+        let v = (((height - row - 1) as f32) + dv) / (height as f32);
+        let ray = scene.camera.cast_ray(sampler, u, v);
+        let radiance = radiance(&ray, sampler);
+        let luminance = if radiance.x.is_finite() && radiance.y.is_finite() && radiance.z.is_finite() {
+            f32::max(luminance(&radiance), 0_f32)
+        } else {
+            0_f32
+        };
+
+        PathSample { radiance, row, column, luminance, }
+    }
+
+    /// Splat the contribution of a path to the film with weight `weight`.
+    #[inline]
+    fn splat(film: &mut Film, path: &PathSample, weight: f32) {
+        if path.luminance > 0_f32 && weight > 0_f32 {
+            film.add_splat(path.row, path.column, path.radiance * (weight / path.luminance));
+        }
+    }
+
+    /// Render a scene onto a film with as many mutations as taking
+    /// `samples_per_pixel` samples per pixel, estimating the radiance along
+    /// camera rays with `radiance`.
+    ///
+    /// The contributions of the chains land on the film as splats, scaled so that
+    /// resolving the film with `samples_per_pixel` samples per pixel yields the
+    /// image.
+    pub fn render<F>(&self, scene: &Scene, samples_per_pixel: usize, sampler: &mut SphereSampler, film: &mut Film, mut radiance: F)
+    where
+        F: FnMut(&Ray, &mut SphereSampler) -> Vector3<f32>,
+    {
+        let bootstrap_samples = self.settings.bootstrap_samples;
+        let chain_count = self.settings.chain_count;
+        if bootstrap_samples == 0 || chain_count == 0 {
+            return;
+        }
+
+        // Estimate the average luminance of the image from independent paths.
+        println!("Bootstrapping Metropolis light transport");
+        let seed = sampler.rng.gen::<u64>();
+        let mut cumulative_weights = Vec::with_capacity(bootstrap_samples);
+        let mut total_weight = 0_f32;
+        for i in 0..bootstrap_samples {
+            let mut chain_sampler = self.chain_sampler(seed.wrapping_add(i as u64));
+            let path = Self::sample_path(scene, film, &mut chain_sampler, &mut radiance);
+            total_weight += path.luminance;
+            cumulative_weights.push(total_weight);
+        }
+        if total_weight <= 0_f32 {
+            return;
+        }
+
+        let normalization = total_weight / (bootstrap_samples as f32);
+        let mutation_count = samples_per_pixel * film.width() * film.height();
+        for chain in 0..chain_count {
+            println!("Running Markov chain {} of {}", chain + 1, chain_count);
+            let chain_mutation_count = mutation_count / chain_count + usize::from(chain < mutation_count % chain_count);
+            if chain_mutation_count == 0 {
+                continue;
+            }
+
+            // Start the chain from a bootstrap path chosen proportionally to its
+            // luminance, so that the chain starts out in its stationary distribution.
+            let choice = sampler.sample_f32() * total_weight;
+            let bootstrap_index = usize::min(
+                cumulative_weights.partition_point(|&weight| weight <= choice),
+                bootstrap_samples - 1
+            );
+            let mut chain_sampler = self.chain_sampler(seed.wrapping_add(bootstrap_index as u64));
+            let mut current = Self::sample_path(scene, film, &mut chain_sampler, &mut radiance);
+            for _ in 0..chain_mutation_count {
+                chain_sampler.start_iteration();
+                let proposed = Self::sample_path(scene, film, &mut chain_sampler, &mut radiance);
+                let acceptance = if current.luminance > 0_f32 {
+                    f32::min(1_f32, proposed.luminance / current.luminance)
+                } else {
+                    1_f32
+                };
+
+                // Both paths contribute in proportion to their probability of
+                // being the next state of the chain, which lowers the variance of
+                // the image over splatting only the next state.
+                Self::splat(film, &proposed, normalization * acceptance);
+                Self::splat(film, &current, normalization * (1_f32 - acceptance));
+
+                if sampler.sample_f32() < acceptance {
+                    chain_sampler.accept();
+                    current = proposed;
+                } else {
+                    chain_sampler.reject();
+                }
+            }
+        }
+    }
+}
+
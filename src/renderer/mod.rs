@@ -0,0 +1,26 @@
+mod ambient_occlusion_integrator;
+mod bidirectional_path_tracer;
+mod debug_integrator;
+mod direct_lighting_integrator;
+mod film;
+mod integrator;
+mod metropolis;
+mod path_guiding;
+mod path_tracer;
+mod photon_map;
+mod photon_mapper;
+mod renderer;
+
+
+pub use ambient_occlusion_integrator::*;
+pub use bidirectional_path_tracer::*;
+pub use debug_integrator::*;
+pub use direct_lighting_integrator::*;
+pub use film::*;
+pub use integrator::*;
+pub use metropolis::*;
+pub use path_guiding::*;
+pub use path_tracer::*;
+pub use photon_map::*;
+pub use photon_mapper::*;
+pub use renderer::*;
@@ -0,0 +1,466 @@
+use crate::core::*;
+use cglinalg::{
+    Vector2,
+    Vector3,
+};
+use std::cell::Cell;
+
+
+/// The maximum depth of the quadtree of a directional distribution.
+const MAX_QUADTREE_DEPTH: usize = 20;
+
+/// The maximum depth of the spatial tree of an SD-tree.
+const MAX_SPATIAL_DEPTH: usize = 48;
+
+/// The parameters of path guiding.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PathGuidingSettings {
+    training_passes: usize,
+    bsdf_sampling_fraction: f32,
+    spatial_threshold: f32,
+    directional_threshold: f32,
+}
+
+impl PathGuidingSettings {
+    /// Construct the settings for learning the guiding distribution over
+    /// `training_passes` passes, with the number of samples per pixel doubling
+    /// from one pass to the next, starting from one.
+    pub fn new(training_passes: usize) -> Self {
+        Self {
+            training_passes,
+            bsdf_sampling_fraction: 0.5,
+            spatial_threshold: 12000_f32,
+            directional_threshold: 0.01,
+        }
+    }
+
+    /// Set the probability of sampling a direction from the scattering function
+    /// of a surface instead of from the guiding distribution.
+    pub fn with_bsdf_sampling_fraction(mut self, bsdf_sampling_fraction: f32) -> Self {
+        self.bsdf_sampling_fraction = bsdf_sampling_fraction;
+        self
+    }
+
+    /// Set the number of samples a region of space must receive in the first
+    /// training pass before it is split. The threshold grows with the square
+    /// root of the number of samples per pixel of later passes.
+    pub fn with_spatial_threshold(mut self, spatial_threshold: f32) -> Self {
+        self.spatial_threshold = spatial_threshold;
+        self
+    }
+
+    /// Set the fraction of the energy of a directional distribution above which
+    /// a quadrant of its quadtree is subdivided.
+    pub fn with_directional_threshold(mut self, directional_threshold: f32) -> Self {
+        self.directional_threshold = directional_threshold;
+        self
+    }
+
+    #[inline]
+    pub fn training_passes(&self) -> usize {
+        self.training_passes
+    }
+
+    #[inline]
+    pub fn bsdf_sampling_fraction(&self) -> f32 {
+        self.bsdf_sampling_fraction
+    }
+}
+
+/// Map a unit direction onto the unit square with an area preserving mapping.
+///
+/// The first coordinate encodes the **z-component** of the direction, and the
+/// second coordinate encodes its azimuth about the **z-axis**.
+fn direction_to_square(direction: &Vector3<f32>) -> Vector2<f32> {
+    let cos_theta = direction.z.clamp(-1_f32, 1_f32);
+    let mut phi = f32::atan2(direction.y, direction.x);
+    if phi < 0_f32 {
+        phi += 2_f32 * std::f32::consts::PI;
+    }
+
+    let u = f32::min((cos_theta + 1_f32) * 0.5, 1_f32 - f32::EPSILON);
+    let v = f32::min(phi / (2_f32 * std::f32::consts::PI), 1_f32 - f32::EPSILON);
+
+    Vector2::new(u, v)
+}
+
+/// Map a point on the unit square onto a unit direction, inverting
+/// [`direction_to_square`].
+fn square_to_direction(point: &Vector2<f32>) -> Vector3<f32> {
+    let cos_theta = 2_f32 * point.x - 1_f32;
+    let sin_theta = f32::sqrt(f32::max(0_f32, 1_f32 - cos_theta * cos_theta));
+    let phi = 2_f32 * std::f32::consts::PI * point.y;
+
+    Vector3::new(sin_theta * f32::cos(phi), sin_theta * f32::sin(phi), cos_theta)
+}
+
+/// Returns the quadrant of the unit square containing a point, and the point
+/// rescaled into the unit square of the quadrant.
+#[inline]
+fn quadrant_of(point: &Vector2<f32>) -> (usize, Vector2<f32>) {
+    let right = point.x >= 0.5;
+    let top = point.y >= 0.5;
+    let quadrant = (right as usize) + 2 * (top as usize);
+    let x = if right { 2_f32 * point.x - 1_f32 } else { 2_f32 * point.x };
+    let y = if top { 2_f32 * point.y - 1_f32 } else { 2_f32 * point.y };
+
+    (quadrant, Vector2::new(x, y))
+}
+
+/// A node of a directional quadtree. A child index of zero marks a quadrant
+/// without children, since the root is never a child.
+#[derive(Clone, Debug)]
+struct QuadTreeNode {
+    sums: [Cell<f32>; 4],
+    children: [usize; 4],
+}
+
+impl QuadTreeNode {
+    fn new(sums: [f32; 4]) -> Self {
+        Self { sums: sums.map(Cell::new), children: [0; 4], }
+    }
+
+    #[inline]
+    fn sum(&self, quadrant: usize) -> f32 {
+        self.sums[quadrant].get()
+    }
+
+    #[inline]
+    fn total(&self) -> f32 {
+        self.sums.iter().map(|sum| sum.get()).sum()
+    }
+}
+
+/// A piecewise constant distribution over the unit sphere whose pieces are the
+/// leaves of a quadtree over the unit square.
+#[derive(Clone, Debug)]
+struct QuadTree {
+    nodes: Vec<QuadTreeNode>,
+}
+
+impl QuadTree {
+    /// Construct a quadtree with a single level of quadrants carrying no energy.
+    fn new() -> Self {
+        Self { nodes: vec![QuadTreeNode::new([0_f32; 4])], }
+    }
+
+    /// Returns the energy the quadtree has recorded.
+    #[inline]
+    fn total(&self) -> f32 {
+        self.nodes[0].total()
+    }
+
+    /// Add energy to every node containing the point `point`.
+    fn record(&self, point: &Vector2<f32>, value: f32) {
+        let mut index = 0;
+        let mut point = *point;
+        loop {
+            let (quadrant, child_point) = quadrant_of(&point);
+            let node = &self.nodes[index];
+            node.sums[quadrant].set(node.sum(quadrant) + value);
+            if node.children[quadrant] == 0 {
+                return;
+            }
+
+            index = node.children[quadrant];
+            point = child_point;
+        }
+    }
+
+    /// Evaluate the probability density with respect to area on the unit square
+    /// of the distribution at the point `point`.
+    ///
+    /// A quadtree without energy describes the uniform distribution.
+    fn pdf(&self, point: &Vector2<f32>) -> f32 {
+        let mut index = 0;
+        let mut point = *point;
+        let mut pdf = 1_f32;
+        loop {
+            let node = &self.nodes[index];
+            let total = node.total();
+            if total <= 0_f32 {
+                return if index == 0 { 1_f32 } else { 0_f32 };
+            }
+
+            let (quadrant, child_point) = quadrant_of(&point);
+            pdf *= 4_f32 * node.sum(quadrant) / total;
+            if pdf <= 0_f32 || node.children[quadrant] == 0 {
+                return pdf;
+            }
+
+            index = node.children[quadrant];
+            point = child_point;
+        }
+    }
+
+    /// Sample a point on the unit square from the distribution.
+    fn sample(&self, sampler: &mut SphereSampler) -> Vector2<f32> {
+        if self.total() <= 0_f32 {
+            return Vector2::new(sampler.sample_f32(), sampler.sample_f32());
+        }
+
+        let mut index = 0;
+        let mut origin = Vector2::new(0_f32, 0_f32);
+        let mut size = 1_f32;
+        loop {
+            let node = &self.nodes[index];
+            let total = node.total();
+            let mut choice = sampler.sample_f32() * total;
+            let mut quadrant = 3;
+            for candidate in 0..4 {
+                let sum = node.sum(candidate);
+                if choice < sum && sum > 0_f32 {
+                    quadrant = candidate;
+                    break;
+                }
+
+                choice -= sum;
+            }
+            // Guard against rounding choosing an empty quadrant.
+            while node.sum(quadrant) <= 0_f32 {
+                quadrant -= 1;
+            }
+
+            size *= 0.5;
+            origin.x += size * ((quadrant % 2) as f32);
+            origin.y += size * ((quadrant / 2) as f32);
+            if node.children[quadrant] == 0 {
+                let x = origin.x + size * sampler.sample_f32();
+                let y = origin.y + size * sampler.sample_f32();
+
+                return Vector2::new(f32::min(x, 1_f32 - f32::EPSILON), f32::min(y, 1_f32 - f32::EPSILON));
+            }
+
+            index = node.children[quadrant];
+        }
+    }
+
+    /// Construct a quadtree whose structure adapts to the energy recorded in the
+    /// quadtree: quadrants holding more than the fraction `threshold` of the
+    /// total energy are subdivided, and the others become leaves.
+    ///
+    /// The new quadtree carries the recorded energy.
+    fn refine(&self, threshold: f32) -> QuadTree {
+        let total = self.total();
+        if total <= 0_f32 {
+            return self.clone();
+        }
+
+        let root_sums = [0, 1, 2, 3].map(|quadrant| self.nodes[0].sum(quadrant));
+        let mut nodes = vec![QuadTreeNode::new(root_sums)];
+        let mut stack = vec![(0, Some(0), 1)];
+        while let Some((index, old_index, depth)) = stack.pop() {
+            for quadrant in 0..4 {
+                let value = nodes[index].sum(quadrant);
+                if value / total <= threshold || depth >= MAX_QUADTREE_DEPTH {
+                    continue;
+                }
+
+                let old_child = old_index
+                    .map(|old_index: usize| self.nodes[old_index].children[quadrant])
+                    .filter(|&old_child| old_child != 0);
+                let child_sums = match old_child {
+                    Some(old_child) => [0, 1, 2, 3].map(|child_quadrant| self.nodes[old_child].sum(child_quadrant)),
+                    None => [value / 4_f32; 4],
+                };
+                let child_index = nodes.len();
+                nodes.push(QuadTreeNode::new(child_sums));
+                nodes[index].children[quadrant] = child_index;
+                stack.push((child_index, old_child, depth + 1));
+            }
+        }
+
+        QuadTree { nodes, }
+    }
+
+    /// Construct a quadtree with the same structure carrying no energy.
+    fn cleared(&self) -> QuadTree {
+        let nodes = self.nodes.iter()
+            .map(|node| QuadTreeNode { sums: [0_f32; 4].map(Cell::new), children: node.children, })
+            .collect();
+
+        QuadTree { nodes, }
+    }
+}
+
+/// The directional distribution of a region of space: one quadtree to sample
+/// directions from, learned in the previous training pass, and one recording the
+/// light arriving in the region during the current pass.
+#[derive(Clone, Debug)]
+struct DirectionalTree {
+    sampling: QuadTree,
+    recording: QuadTree,
+    sample_count: Cell<f32>,
+}
+
+impl DirectionalTree {
+    fn new() -> Self {
+        Self {
+            sampling: QuadTree::new(),
+            recording: QuadTree::new(),
+            sample_count: Cell::new(0_f32),
+        }
+    }
+}
+
+/// A node of the spatial binary tree of an SD-tree. Interior nodes split their
+/// region in half along the axis with index `depth % 3` for the depth of the
+/// node, and leaves own a directional tree.
+#[derive(Clone, Debug)]
+enum SpatialNode {
+    Interior { axis: usize, children: [usize; 2], },
+    Leaf { directional_tree: usize, },
+}
+
+/// A spatial-directional tree for guiding the directions paths are traced
+/// along towards where light arrives from.
+///
+/// An SD-tree partitions a cube of space with a binary tree that splits regions
+/// in half along the **x-axis**, **y-axis**, and **z-axis** in turn, so that the
+/// regions every third level down are cubes again. Each leaf stores a quadtree
+/// over the sphere of directions that approximates the light arriving in the
+/// region. The distributions are learned online: during a training pass paths
+/// record the light they find, and after the pass the spatial tree is subdivided
+/// where many paths were recorded and the quadtrees are refined to the recorded
+/// energy.
+/// See Müller et al., "Practical Path Guiding for Efficient Light-Transport
+/// Simulation", EGSR 2017.
+#[derive(Clone, Debug)]
+pub struct SdTree {
+    bounds: BoundingBox,
+    nodes: Vec<SpatialNode>,
+    directional_trees: Vec<DirectionalTree>,
+}
+
+impl SdTree {
+    /// Construct an SD-tree over the smallest cube centered on the region
+    /// `bounds` that contains it, guiding paths uniformly over the sphere until
+    /// it is trained.
+    pub fn new(bounds: BoundingBox) -> Self {
+        // Enlarge the cube slightly so that points on its boundary fall inside.
+        let extent = bounds.extent();
+        let side = f32::max(f32::max(extent.x, extent.y), extent.z);
+        let half_side = Vector3::from_fill(side * 0.5005 + 1e-4);
+        let center = bounds.center();
+        let bounds = BoundingBox::new(center - half_side, center + half_side);
+
+        Self {
+            bounds,
+            nodes: vec![SpatialNode::Leaf { directional_tree: 0, }],
+            directional_trees: vec![DirectionalTree::new()],
+        }
+    }
+
+    /// Returns the number of leaves of the spatial tree.
+    #[inline]
+    pub fn len_regions(&self) -> usize {
+        self.directional_trees.len()
+    }
+
+    fn directional_tree(&self, point: &Vector3<f32>) -> &DirectionalTree {
+        let mut point = *point;
+        for axis in 0..3 {
+            let extent = self.bounds.max[axis] - self.bounds.min[axis];
+            point[axis] = ((point[axis] - self.bounds.min[axis]) / extent).clamp(0_f32, 1_f32);
+        }
+
+        let mut index = 0;
+        loop {
+            match &self.nodes[index] {
+                SpatialNode::Leaf { directional_tree, } => return &self.directional_trees[*directional_tree],
+                SpatialNode::Interior { axis, children, } => {
+                    let axis = *axis;
+                    if point[axis] < 0.5 {
+                        point[axis] *= 2_f32;
+                        index = children[0];
+                    } else {
+                        point[axis] = 2_f32 * point[axis] - 1_f32;
+                        index = children[1];
+                    }
+                }
+            }
+        }
+    }
+
+    /// Record light with luminance `luminance` arriving at the point `point` from
+    /// the unit direction `direction`, which was sampled with probability
+    /// density `pdf` with respect to solid angle.
+    pub fn record(&self, point: &Vector3<f32>, direction: &Vector3<f32>, luminance: f32, pdf: f32) {
+        if pdf <= 0_f32 || !luminance.is_finite() || luminance < 0_f32 {
+            return;
+        }
+
+        let directional_tree = self.directional_tree(point);
+        directional_tree.sample_count.set(directional_tree.sample_count.get() + 1_f32);
+        directional_tree.recording.record(&direction_to_square(direction), luminance / pdf);
+    }
+
+    /// Sample a unit direction at the point `point` from the guiding
+    /// distribution learned in the previous training pass.
+    pub fn sample(&self, point: &Vector3<f32>, sampler: &mut SphereSampler) -> Vector3<f32> {
+        let directional_tree = self.directional_tree(point);
+
+        square_to_direction(&directional_tree.sampling.sample(sampler))
+    }
+
+    /// Evaluate the probability density with respect to solid angle that
+    /// [`SdTree::sample`] samples the unit direction `direction` at the point
+    /// `point`.
+    pub fn pdf(&self, point: &Vector3<f32>, direction: &Vector3<f32>) -> f32 {
+        let directional_tree = self.directional_tree(point);
+
+        directional_tree.sampling.pdf(&direction_to_square(direction)) / (4_f32 * std::f32::consts::PI)
+    }
+
+    /// Adapt the SD-tree to the light recorded during the training pass with
+    /// index `pass`, in which every pixel took `2^pass` samples.
+    ///
+    /// Regions receiving too many samples are split, and the directional
+    /// distribution of every region is rebuilt from the light it recorded.
+    pub fn refine(&mut self, pass: usize, settings: &PathGuidingSettings) {
+        let threshold = settings.spatial_threshold * f32::sqrt(2_f32.powi(pass as i32));
+        let mut stack = vec![(0, 0)];
+        while let Some((index, depth)) = stack.pop() {
+            match self.nodes[index].clone() {
+                SpatialNode::Interior { children, .. } => {
+                    stack.push((children[0], depth + 1));
+                    stack.push((children[1], depth + 1));
+                }
+                SpatialNode::Leaf { directional_tree, } => {
+                    let sample_count = self.directional_trees[directional_tree].sample_count.get();
+                    if sample_count <= threshold || depth >= MAX_SPATIAL_DEPTH {
+                        continue;
+                    }
+
+                    // Both halves start from the statistics of the region, since
+                    // each received about half of its samples.
+                    let half = self.directional_trees[directional_tree].clone();
+                    half.sample_count.set(sample_count * 0.5);
+                    self.directional_trees[directional_tree] = half.clone();
+                    let sibling = self.directional_trees.len();
+                    self.directional_trees.push(half);
+
+                    let first_child = self.nodes.len();
+                    self.nodes.push(SpatialNode::Leaf { directional_tree, });
+                    self.nodes.push(SpatialNode::Leaf { directional_tree: sibling, });
+                    self.nodes[index] = SpatialNode::Interior {
+                        axis: depth % 3,
+                        children: [first_child, first_child + 1],
+                    };
+                    stack.push((first_child, depth + 1));
+                    stack.push((first_child + 1, depth + 1));
+                }
+            }
+        }
+
+        for directional_tree in self.directional_trees.iter_mut() {
+            if directional_tree.recording.total() > 0_f32 {
+                directional_tree.sampling = directional_tree.recording.refine(settings.directional_threshold);
+            }
+
+            directional_tree.recording = directional_tree.sampling.cleared();
+            directional_tree.sample_count.set(0_f32);
+        }
+    }
+}
+
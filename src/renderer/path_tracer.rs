@@ -0,0 +1,317 @@
+use crate::core::*;
+use crate::renderer::*;
+use crate::scene::*;
+use cglinalg::{
+    Vector3,
+    Magnitude,
+};
+
+
+/// The guiding distribution the path tracer samples directions from, together 
+/// with whether the current pass trains it.
+struct PathGuide {
+    tree: SdTree,
+    bsdf_sampling_fraction: f32,
+    is_training: bool,
+}
+
+/// The number of times a path has scattered from surfaces and inside media.
+#[derive(Copy, Clone, Debug, Default)]
+struct PathDepth {
+    surface: usize,
+    medium: usize,
+}
+
+impl PathDepth {
+    #[inline]
+    fn next_surface(self) -> Self {
+        Self { surface: self.surface + 1, medium: self.medium, }
+    }
+
+    #[inline]
+    fn next_medium(self) -> Self {
+        Self { surface: self.surface, medium: self.medium + 1, }
+    }
+}
+
+/// A unidirectional path tracer with next event estimation, scattering from 
+/// surfaces and inside participating media.
+///
+/// The path tracer optionally samples directions at surfaces from a learned 
+/// guiding distribution in addition to the scattering functions of the surfaces.
+pub struct PathTracer {
+    max_path_depth: usize,
+    max_medium_depth: usize,
+    t_max: f32,
+    guide: Option<PathGuide>,
+}
+
+impl PathTracer {
+    pub fn new(max_path_depth: usize, max_medium_depth: usize, t_max: f32) -> Self {
+        Self { max_path_depth, max_medium_depth, t_max, guide: None, }
+    }
+
+    /// Mix sampling directions from the guiding distribution `sd_tree` with 
+    /// sampling the scattering functions of surfaces, choosing the scattering
+    /// function with probability `bsdf_sampling_fraction`. 
+    ///
+    /// The path tracer records the light its paths find into the guiding 
+    /// distribution until [`PathTracer::finish_path_guide_training`] is called.
+    pub fn with_path_guide(mut self, sd_tree: SdTree, bsdf_sampling_fraction: f32) -> Self {
+        self.guide = Some(PathGuide { tree: sd_tree, bsdf_sampling_fraction, is_training: true, });
+        self
+    }
+
+    /// Adapt the guiding distribution to the light recorded during the training 
+    /// pass with index `pass`.
+    pub fn refine_path_guide(&mut self, pass: usize, settings: &PathGuidingSettings) {
+        if let Some(guide) = self.guide.as_mut() {
+            guide.tree.refine(pass, settings);
+        }
+    }
+
+    /// Stop recording light into the guiding distribution.
+    pub fn finish_path_guide_training(&mut self) {
+        if let Some(guide) = self.guide.as_mut() {
+            guide.is_training = false;
+        }
+    }
+
+    /// Estimate the light scattered at a point directly from the point lights of a 
+    /// scene. The function `spawn_origin` returns the origin of the shadow ray 
+    /// from the point towards a light position, offset from the surface the 
+    /// point lies on, if any, and the function `scattering_weight` returns the 
+    /// fraction of the light arriving from a unit direction that scatters 
+    /// towards the origin of the path.
+    fn estimate_direct_from_point_lights<S, F>(
+        &self, 
+        scene: &Scene, 
+        spawn_origin: S, 
+        time: f32,
+        medium: Option<&dyn Medium>, 
+        sampler: &mut SphereSampler, 
+        scattering_weight: F) -> Vector3<f32>
+    where
+        S: Fn(&Vector3<f32>) -> Vector3<f32>,
+        F: Fn(&Vector3<f32>) -> Vector3<f32>,
+    {
+        let mut L_o = Vector3::zero();
+        for light in scene.lights.iter() {
+            let origin = spawn_origin(&light.position());
+            let transmittance = scene.transmittance(&origin, &light.position(), time, medium, sampler);
+            if transmittance != Vector3::zero() {
+                let w_i = light.position() - origin;
+                let distance_squared = w_i.magnitude_squared();
+                let w_i = w_i / f32::sqrt(distance_squared);
+
+                let E_i = light.emission() / (4_f32 * std::f32::consts::PI * distance_squared);
+
+                L_o += E_i.component_mul(&transmittance).component_mul(&scattering_weight(&w_i));
+                // debug_assert(radiance.is_finite());
+            }
+        }
+
+        L_o
+    }
+
+    fn path_trace(
+        &self, 
+        scene: &Scene, 
+        query: &IntersectionQuery, 
+        medium: Option<&dyn Medium>, 
+        sampler: &mut SphereSampler, 
+        depth: PathDepth) -> Vector3<f32>
+    {
+        let hit = scene.intersect(query);
+        if let Some(medium) = medium {
+            // Sample the distance the ray travels through the medium before it 
+            // scatters. When it reaches the next surface first, the weight of the
+            // sample accounts for the light the medium absorbs along the way.
+            let t_max = match &hit {
+                Some(hit) => hit.intersection_result.unwrap_hit_or_tangent().t,
+                None => query.t_max,
+            };
+            let medium_result = medium.sample(&query.ray, t_max, sampler);
+            if let Some(interaction) = medium_result.interaction {
+                if depth.medium < self.max_medium_depth {
+                    let estimate = self.path_trace_medium(scene, query, medium, &interaction, sampler, depth);
+
+                    return medium_result.weight.component_mul(&estimate);
+                } else {
+                    return Vector3::zero();
+                }
+            }
+
+            let estimate = self.path_trace_surface(scene, query, hit, Some(medium), sampler, depth);
+
+            medium_result.weight.component_mul(&estimate)
+        } else {
+            self.path_trace_surface(scene, query, hit, None, sampler, depth)
+        }
+    }
+
+    fn path_trace_medium(
+        &self, 
+        scene: &Scene, 
+        query: &IntersectionQuery, 
+        medium: &dyn Medium, 
+        interaction: &MediumInteraction, 
+        sampler: &mut SphereSampler, 
+        depth: PathDepth) -> Vector3<f32>
+    {
+        let phase_function = medium.phase_function();
+        let ray_incoming = query.ray.direction.normalize();
+        let estimated_from_direct_point_light = self.estimate_direct_from_point_lights(
+            scene, 
+            |_light_position| interaction.point, 
+            query.ray.time,
+            Some(medium), 
+            sampler, 
+            |w_i| Vector3::from_fill(phase_function.evaluate(&ray_incoming, w_i)),
+        );
+        let phase_result = phase_function.sample(&ray_incoming, sampler);
+        let estimated_from_indirect_light = if phase_result.pdf > 0_f32 {
+            let next_incoming_ray = Ray::new(interaction.point, phase_result.ray_outgoing).with_time(query.ray.time);
+            let next_intersection_query = IntersectionQuery::new(next_incoming_ray, query.t_min, query.t_max);
+            let next_estimate = self.path_trace(scene, &next_intersection_query, Some(medium), sampler, depth.next_medium());
+
+            next_estimate * (phase_result.value / phase_result.pdf)
+        } else {
+            Vector3::zero()
+        };
+
+        estimated_from_indirect_light + estimated_from_direct_point_light
+    }
+
+    fn path_trace_surface(
+        &self, 
+        scene: &Scene, 
+        query: &IntersectionQuery, 
+        hit: Option<ObjectIntersectionResult>, 
+        medium: Option<&dyn Medium>, 
+        sampler: &mut SphereSampler, 
+        depth: PathDepth) -> Vector3<f32>
+    {
+        if let Some(hit) = hit {
+            if depth.surface < self.max_path_depth {
+                let intersection_result = hit.intersection_result.unwrap_hit_or_tangent();
+                if hit.object.is_shadow_catcher() {
+                    // Shadow catchers show what lies behind them, darkened by the 
+                    // shadows cast onto them.
+                    let next_incoming_ray = intersection_result.spawn_ray(&query.ray.direction);
+                    let next_intersection_query = IntersectionQuery::new(next_incoming_ray, query.t_min, query.t_max);
+                    let behind = self.path_trace(scene, &next_intersection_query, medium, sampler, depth.next_surface());
+
+                    return behind * shadow_fraction(scene, &intersection_result);
+                }
+
+                let ray_incoming = query.ray.direction.normalize();
+                let guide = self.guide.as_ref();
+                let flags = hit.object.bsdf().flags();
+                let (emission, next_direction, scattering_fraction, guide_pdf) = match guide {
+                    Some(guide) if flags.is_non_delta() && !flags.is_delta() => {
+                        let emission = hit.object.emission(&intersection_result, &ray_incoming);
+                        match self.sample_guided(guide, hit.object, &intersection_result, &ray_incoming, sampler) {
+                            Some((direction, scattering_fraction, pdf)) => (emission, direction, scattering_fraction, pdf),
+                            None => (emission, Vector3::zero(), Vector3::zero(), 0_f32),
+                        }
+                    }
+                    _ => {
+                        let scattering_query = ScatteringQuery::from_hit(query.ray.direction, &intersection_result);
+                        let scattering_result = hit.object.scatter(&scattering_query, sampler);
+
+                        (scattering_result.emission, scattering_result.ray_outgoing, scattering_result.scattering_fraction, 0_f32)
+                    }
+                };
+                let estimated_from_indirect_light = if scattering_fraction == Vector3::zero() {
+                    // The surface absorbed the ray, so there is no path to continue.
+                    Vector3::zero()
+                } else {
+                    let next_medium = scene.medium_after_crossing(hit.object, &intersection_result.normal, &next_direction, medium);
+                    let next_incoming_ray = intersection_result.spawn_ray(&next_direction);
+                    let next_intersection_query = IntersectionQuery::new(next_incoming_ray, query.t_min, query.t_max);
+                    let next_estimate = self.path_trace(scene, &next_intersection_query, next_medium, sampler, depth.next_surface());
+                    if let Some(guide) = guide.filter(|guide| guide.is_training && guide_pdf > 0_f32) {
+                        guide.tree.record(&intersection_result.point, &next_direction, luminance(&next_estimate), guide_pdf);
+                    }
+                    
+                    scattering_fraction.component_mul(&next_estimate)
+                };
+                let estimated_from_direct_point_light = if flags.is_pass_through() {
+                    // Light arriving at an invisible boundary is accounted for where
+                    // the path scatters next.
+                    Vector3::zero()
+                } else if !flags.is_non_delta() {
+                    // A point light lies along a perfect specular direction with 
+                    // probability zero.
+                    Vector3::zero()
+                } else {
+                    let spawn_origin = |light_position: &Vector3<f32>| {
+                        intersection_result.spawn_origin(&(light_position - intersection_result.point))
+                    };
+                    let shading_normal = intersection_result.shading_normal.normalize();
+                    let scattering_weight = |w_i: &Vector3<f32>| {
+                        let cos_theta = f32::abs(w_i.dot(&shading_normal));
+
+                        hit.object.evaluate_bsdf(&intersection_result, &ray_incoming, w_i) * cos_theta
+                    };
+
+                    self.estimate_direct_from_point_lights(scene, spawn_origin, intersection_result.time, medium, sampler, scattering_weight)
+                };
+
+                emission + 
+                    estimated_from_indirect_light + 
+                    estimated_from_direct_point_light
+            } else {
+                Vector3::new(0_f32, 0_f32, 0_f32)
+            }
+        } else {
+            background_radiance(&query.ray.direction)
+        }
+    }
+
+    /// Sample a direction at a surface from the mixture of the scattering function
+    /// of the surface and the guiding distribution, selecting the scattering 
+    /// function with probability equal to the BSDF sampling fraction of the guide.
+    ///
+    /// Returns the sampled direction, the throughput weight of the sample, and 
+    /// the probability density of the mixture, or `None` when the sample carries
+    /// no light.
+    fn sample_guided(
+        &self, 
+        guide: &PathGuide, 
+        object: &SceneObject, 
+        hit: &IntersectionData, 
+        ray_incoming: &Vector3<f32>, 
+        sampler: &mut SphereSampler) -> Option<(Vector3<f32>, Vector3<f32>, f32)>
+    {
+        let bsdf_sampling_fraction = guide.bsdf_sampling_fraction;
+        let direction = if sampler.sample_f32() < bsdf_sampling_fraction {
+            let (direction, _) = object.sample_bsdf(hit, ray_incoming, sampler)?;
+            
+            direction
+        } else {
+            guide.tree.sample(&hit.point, sampler)
+        };
+        let pdf_bsdf = object.pdf_bsdf(hit, ray_incoming, &direction);
+        let pdf_guide = guide.tree.pdf(&hit.point, &direction);
+        let pdf = bsdf_sampling_fraction * pdf_bsdf + (1_f32 - bsdf_sampling_fraction) * pdf_guide;
+        if pdf <= 0_f32 {
+            return None;
+        }
+
+        let cos_theta = f32::abs(direction.dot(&hit.shading_normal.normalize()));
+        let scattering_fraction = object.evaluate_bsdf(hit, ray_incoming, &direction) * (cos_theta / pdf);
+
+        Some((direction, scattering_fraction, pdf))
+    }
+}
+
+impl Integrator for PathTracer {
+    fn radiance(&self, scene: &Scene, ray: &Ray, sampler: &mut SphereSampler) -> Vector3<f32> {
+        let query = IntersectionQuery::new(*ray, 0_f32, self.t_max);
+
+        self.path_trace(scene, &query, scene.medium(), sampler, PathDepth::default())
+    }
+}
+
@@ -0,0 +1,228 @@
+use crate::core::*;
+use cglinalg::{
+    Vector3,
+    Magnitude,
+};
+use std::cmp;
+use std::collections::BinaryHeap;
+
+
+/// A packet of light deposited on a surface while tracing light from the lights
+/// of a scene.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Photon {
+    /// The point the photon landed on.
+    pub position: Vector3<f32>,
+    /// The unit direction of travel of the photon when it landed.
+    pub direction: Vector3<f32>,
+    /// The power the photon carries.
+    pub power: Vector3<f32>,
+}
+
+impl Photon {
+    pub fn new(position: Vector3<f32>, direction: Vector3<f32>, power: Vector3<f32>) -> Self {
+        Self { position, direction, power, }
+    }
+}
+
+/// A photon found by a nearest neighbor query, ordered by its distance to the
+/// query point.
+#[derive(Copy, Clone, Debug)]
+struct Neighbor {
+    distance_squared: f32,
+    index: usize,
+}
+
+impl PartialEq for Neighbor {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == cmp::Ordering::Equal
+    }
+}
+
+impl Eq for Neighbor {}
+
+impl PartialOrd for Neighbor {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Neighbor {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.distance_squared.total_cmp(&other.distance_squared)
+    }
+}
+
+/// A collection of photons organized into a balanced kd-tree for finding the
+/// photons near a point.
+///
+/// The tree is stored implicitly: the photons of every subtree occupy a
+/// contiguous range of the photon array, with the splitting photon of the
+/// subtree at the middle of the range, the photons of the left subtree before it,
+/// and the photons of the right subtree after it.
+#[derive(Clone, Debug, Default)]
+pub struct PhotonMap {
+    photons: Vec<Photon>,
+    /// The axis along which the subtree whose splitting photon has the same
+    /// index is split.
+    split_axes: Vec<usize>,
+}
+
+impl PhotonMap {
+    /// Construct a photon map from a collection of photons.
+    pub fn new(photons: Vec<Photon>) -> Self {
+        let mut photons = photons;
+        let mut split_axes = vec![0; photons.len()];
+        Self::build(&mut photons, &mut split_axes);
+
+        Self { photons, split_axes, }
+    }
+
+    fn build(photons: &mut [Photon], split_axes: &mut [usize]) {
+        if photons.is_empty() {
+            return;
+        }
+
+        // Split along the axis in which the photons spread the most.
+        let bounds = photons.iter().fold(BoundingBox::empty(), |bounds, photon| bounds.union_point(&photon.position));
+        let extent = bounds.extent();
+        let axis = if extent.x >= extent.y && extent.x >= extent.z {
+            0
+        } else if extent.y >= extent.z {
+            1
+        } else {
+            2
+        };
+        let middle = photons.len() / 2;
+        photons.select_nth_unstable_by(middle, |a, b| a.position[axis].total_cmp(&b.position[axis]));
+        split_axes[middle] = axis;
+
+        let (left_photons, right_photons) = photons.split_at_mut(middle);
+        let (left_axes, right_axes) = split_axes.split_at_mut(middle);
+        Self::build(left_photons, left_axes);
+        Self::build(&mut right_photons[1..], &mut right_axes[1..]);
+    }
+
+    /// Returns the number of photons in a photon map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.photons.len()
+    }
+
+    /// Determine whether a photon map contains any photons.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.photons.is_empty()
+    }
+
+    /// Returns the photons in a photon map, in no particular order.
+    #[inline]
+    pub fn photons(&self) -> &[Photon] {
+        &self.photons
+    }
+
+    /// Visit every photon within the distance `radius` of the point `point`,
+    /// together with its squared distance to the point.
+    pub fn for_each_within<F>(&self, point: &Vector3<f32>, radius: f32, mut visit: F)
+    where
+        F: FnMut(&Photon, f32),
+    {
+        self.visit_within(0, self.photons.len(), point, radius * radius, &mut visit);
+    }
+
+    fn visit_within<F>(&self, start: usize, end: usize, point: &Vector3<f32>, radius_squared: f32, visit: &mut F)
+    where
+        F: FnMut(&Photon, f32),
+    {
+        if start >= end {
+            return;
+        }
+
+        let middle = start + (end - start) / 2;
+        let photon = &self.photons[middle];
+        let axis = self.split_axes[middle];
+        let offset = point[axis] - photon.position[axis];
+        let (near, far) = if offset <= 0_f32 {
+            ((start, middle), (middle + 1, end))
+        } else {
+            ((middle + 1, end), (start, middle))
+        };
+
+        self.visit_within(near.0, near.1, point, radius_squared, visit);
+        let distance_squared = (photon.position - point).magnitude_squared();
+        if distance_squared <= radius_squared {
+            visit(photon, distance_squared);
+        }
+        if offset * offset <= radius_squared {
+            self.visit_within(far.0, far.1, point, radius_squared, visit);
+        }
+    }
+
+    /// Find the at most `count` photons nearest to the point `point` within the
+    /// distance `max_radius` of it.
+    ///
+    /// Returns the photons together with their squared distances to the point,
+    /// ordered from the nearest to the farthest.
+    pub fn nearest(&self, point: &Vector3<f32>, count: usize, max_radius: f32) -> Vec<(&Photon, f32)> {
+        if count == 0 {
+            return Vec::new();
+        }
+
+        let mut neighbors = BinaryHeap::with_capacity(count + 1);
+        self.visit_nearest(0, self.photons.len(), point, count, max_radius * max_radius, &mut neighbors);
+
+        neighbors
+            .into_sorted_vec()
+            .into_iter()
+            .map(|neighbor| (&self.photons[neighbor.index], neighbor.distance_squared))
+            .collect()
+    }
+
+    fn visit_nearest(
+        &self,
+        start: usize,
+        end: usize,
+        point: &Vector3<f32>,
+        count: usize,
+        max_radius_squared: f32,
+        neighbors: &mut BinaryHeap<Neighbor>)
+    {
+        if start >= end {
+            return;
+        }
+
+        let middle = start + (end - start) / 2;
+        let photon = &self.photons[middle];
+        let axis = self.split_axes[middle];
+        let offset = point[axis] - photon.position[axis];
+        let (near, far) = if offset <= 0_f32 {
+            ((start, middle), (middle + 1, end))
+        } else {
+            ((middle + 1, end), (start, middle))
+        };
+
+        self.visit_nearest(near.0, near.1, point, count, max_radius_squared, neighbors);
+
+        // Once the heap is full, only photons closer than the farthest photon
+        // found so far can be among the nearest.
+        let search_radius_squared = |neighbors: &BinaryHeap<Neighbor>| {
+            if neighbors.len() < count {
+                max_radius_squared
+            } else {
+                neighbors.peek().map_or(max_radius_squared, |farthest| farthest.distance_squared)
+            }
+        };
+
+        let distance_squared = (photon.position - point).magnitude_squared();
+        if distance_squared <= search_radius_squared(neighbors) {
+            neighbors.push(Neighbor { distance_squared, index: middle, });
+            if neighbors.len() > count {
+                neighbors.pop();
+            }
+        }
+        if offset * offset <= search_radius_squared(neighbors) {
+            self.visit_nearest(far.0, far.1, point, count, max_radius_squared, neighbors);
+        }
+    }
+}
+
@@ -0,0 +1,517 @@
+use crate::core::*;
+use crate::renderer::*;
+use crate::scene::*;
+use cglinalg::{
+    Vector3,
+    Magnitude,
+};
+
+
+/// The parameters of the photon mapping integrators.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PhotonMappingSettings {
+    photon_count: usize,
+    gather_count: usize,
+    gather_radius: f32,
+    final_gather_samples: usize,
+    radius_reduction: f32,
+}
+
+impl PhotonMappingSettings {
+    /// Construct the settings for tracing `photon_count` photons per pass,
+    /// gathering photons within the distance `gather_radius` of a point.
+    pub fn new(photon_count: usize, gather_radius: f32) -> Self {
+        Self {
+            photon_count,
+            gather_count: 64,
+            gather_radius,
+            final_gather_samples: 16,
+            radius_reduction: 2_f32 / 3_f32,
+        }
+    }
+
+    /// Set the number of nearest photons a radiance estimate uses.
+    pub fn with_gather_count(mut self, gather_count: usize) -> Self {
+        self.gather_count = gather_count;
+        self
+    }
+
+    /// Set the number of rays traced to gather the indirect light arriving at a
+    /// point seen by the camera.
+    pub fn with_final_gather_samples(mut self, final_gather_samples: usize) -> Self {
+        self.final_gather_samples = final_gather_samples;
+        self
+    }
+
+    /// Set the fraction of the photons found in a pass that progressive photon
+    /// mapping keeps. Smaller values shrink the gather radii faster.
+    pub fn with_radius_reduction(mut self, radius_reduction: f32) -> Self {
+        self.radius_reduction = radius_reduction;
+        self
+    }
+}
+
+impl Default for PhotonMappingSettings {
+    fn default() -> Self {
+        Self::new(100_000, 0.1)
+    }
+}
+
+/// Trace `photon_count` photon paths from the point lights of a scene, visiting
+/// each surface the photons land on.
+///
+/// The visitor receives each photon, the number of surfaces the photon scattered
+/// from before landing, and whether all of those scattering events were along
+/// directions described by Dirac delta distributions. The power of a photon is
+/// the power of a single light path, i.e. it is not divided by the number of
+/// photons traced.
+fn trace_photons<F>(
+    scene: &Scene,
+    photon_count: usize,
+    max_path_depth: usize,
+    sampler: &mut SphereSampler,
+    mut visit: F)
+where
+    F: FnMut(&Photon, usize, bool),
+{
+    if scene.is_empty_lights() {
+        return;
+    }
+
+    let light_count = scene.lights.len();
+    let pdf_choice = 1_f32 / light_count as f32;
+    for _ in 0..photon_count {
+        let light_index = usize::min((sampler.sample_f32() * light_count as f32) as usize, light_count - 1);
+        let light = &scene.lights[light_index];
+        let mut power = light.emission() / pdf_choice;
+        // Photons are emitted at times spread over the shutter interval so that 
+        // the photon maps see moving objects blurred along their motion.
+        let time = scene.camera.sample_time(sampler);
+        let mut ray = Ray::new(light.position(), sampler.sample_uniform_direction()).with_time(time);
+        let mut is_specular_path = true;
+        for depth in 0..max_path_depth {
+            let query = IntersectionQuery::new(ray, 0_f32, f32::MAX);
+            let hit = match scene.intersect(&query) {
+                Some(hit) => hit,
+                None => break,
+            };
+            let intersection_result = hit.intersection_result.unwrap_hit_or_tangent();
+            let ray_incoming = ray.direction.normalize();
+            if hit.object.bsdf().flags().is_non_delta() {
+                let photon = Photon::new(intersection_result.point, ray_incoming, power);
+                visit(&photon, depth, is_specular_path);
+            }
+
+            let (ray_outgoing, bsdf_result) = match hit.object.sample_bsdf(&intersection_result, &ray_incoming, sampler) {
+                Some(sample) => sample,
+                None => break,
+            };
+            if !bsdf_result.flags.is_delta() {
+                is_specular_path = false;
+            }
+
+            // Terminate photons with Russian roulette so that the power of the
+            // surviving photons stays roughly constant.
+            let fraction = bsdf_result.scattering_fraction;
+            let survival_probability = f32::min(1_f32, f32::max(fraction.x, f32::max(fraction.y, fraction.z)));
+            if survival_probability <= 0_f32 || sampler.sample_f32() >= survival_probability {
+                break;
+            }
+
+            power = power.component_mul(&fraction) / survival_probability;
+            ray = intersection_result.spawn_ray(&ray_outgoing);
+        }
+    }
+}
+
+/// Estimate the radiance reflected from a point towards the origin of a ray
+/// arriving in the direction `ray_incoming` from the photons near the point.
+///
+/// The photons are given together with the squared radius of the disk they were
+/// gathered from.
+fn estimate_radiance<'a, I>(
+    object: &SceneObject,
+    hit: &IntersectionData,
+    ray_incoming: &Vector3<f32>,
+    photons: I,
+    radius_squared: f32) -> Vector3<f32>
+where
+    I: IntoIterator<Item = &'a Photon>,
+{
+    if radius_squared <= 0_f32 {
+        return Vector3::zero();
+    }
+
+    let ray_outgoing = -ray_incoming;
+    let mut flux = Vector3::zero();
+    for photon in photons {
+        let bsdf = object.evaluate_bsdf(hit, &photon.direction, &ray_outgoing);
+        flux += bsdf.component_mul(&photon.power);
+    }
+
+    flux / (std::f32::consts::PI * radius_squared)
+}
+
+/// Estimate the radiance reflected from a point towards the origin of a ray
+/// arriving in the direction `ray_incoming` directly from the point lights of
+/// a scene.
+fn estimate_direct_lighting(
+    scene: &Scene,
+    object: &SceneObject,
+    hit: &IntersectionData,
+    ray_incoming: &Vector3<f32>) -> Vector3<f32>
+{
+    let shading_normal = hit.shading_normal.normalize();
+    let mut radiance = Vector3::zero();
+    for light in scene.lights.iter() {
+        let offset = light.position() - hit.point;
+        let distance_squared = offset.magnitude_squared();
+        let ray_outgoing = offset / f32::sqrt(distance_squared);
+        let bsdf = object.evaluate_bsdf(hit, ray_incoming, &ray_outgoing);
+        if bsdf == Vector3::zero() || !scene.line_of_sight(&hit.spawn_origin(&offset), &light.position(), hit.time) {
+            continue;
+        }
+
+        let intensity = light.emission() / (4_f32 * std::f32::consts::PI);
+        let cos_theta = f32::abs(shading_normal.dot(&ray_outgoing));
+
+        radiance += bsdf.component_mul(&intensity) * (cos_theta / distance_squared);
+    }
+
+    radiance
+}
+
+/// The point where a ray first lands on a surface that does not scatter light
+/// along directions described by Dirac delta distributions.
+#[derive(Copy, Clone, Debug)]
+struct DiffuseHit<'a> {
+    object: &'a SceneObject,
+    hit: IntersectionData,
+    /// The unit direction of the ray arriving at the point.
+    ray_incoming: Vector3<f32>,
+    /// The throughput of the path from the origin of the ray to the point.
+    beta: Vector3<f32>,
+}
+
+/// Follow a ray through the specular surfaces of a scene to the first surface
+/// that is not specular.
+///
+/// Returns the radiance emitted towards the origin of the ray by the surfaces
+/// and the background the ray reaches along the way, weighted by the throughput
+/// of the path, together with the point the ray lands on, if any.
+fn trace_to_diffuse_hit<'a>(
+    scene: &'a Scene,
+    ray: Ray,
+    max_path_depth: usize,
+    t_max: f32,
+    sampler: &mut SphereSampler) -> (Vector3<f32>, Option<DiffuseHit<'a>>)
+{
+    let mut ray = ray;
+    let mut beta = Vector3::from_fill(1_f32);
+    let mut radiance = Vector3::zero();
+    for _ in 0..max_path_depth {
+        let query = IntersectionQuery::new(ray, 0_f32, t_max);
+        let hit = match scene.intersect(&query) {
+            Some(hit) => hit,
+            None => {
+                radiance += beta.component_mul(&background_radiance(&ray.direction));
+
+                return (radiance, None);
+            }
+        };
+        let intersection_result = hit.intersection_result.unwrap_hit_or_tangent();
+        let ray_incoming = ray.direction.normalize();
+        radiance += beta.component_mul(&hit.object.emission(&intersection_result, &ray_incoming));
+        if hit.object.bsdf().flags().is_non_delta() {
+            let diffuse_hit = DiffuseHit {
+                object: hit.object,
+                hit: intersection_result,
+                ray_incoming,
+                beta,
+            };
+
+            return (radiance, Some(diffuse_hit));
+        }
+
+        let (ray_outgoing, bsdf_result) = match hit.object.sample_bsdf(&intersection_result, &ray_incoming, sampler) {
+            Some(sample) => sample,
+            None => break,
+        };
+        beta = beta.component_mul(&bsdf_result.scattering_fraction);
+        if beta == Vector3::zero() {
+            break;
+        }
+
+        ray = intersection_result.spawn_ray(&ray_outgoing);
+    }
+
+    (radiance, None)
+}
+
+/// The photon maps a photon mapper gathers light from.
+#[derive(Clone, Debug, Default)]
+pub struct PhotonMaps {
+    /// The photons landing on every surface that is not specular.
+    global: PhotonMap,
+    /// The photons that reached a surface that is not specular only by
+    /// scattering from specular surfaces after leaving a light.
+    caustic: PhotonMap,
+}
+
+impl PhotonMaps {
+    #[inline]
+    pub fn global(&self) -> &PhotonMap {
+        &self.global
+    }
+
+    #[inline]
+    pub fn caustic(&self) -> &PhotonMap {
+        &self.caustic
+    }
+}
+
+/// A two pass photon mapper.
+///
+/// The first pass traces photons from the point lights of a scene into a global
+/// photon map and a caustic photon map. The second pass follows camera rays
+/// through specular surfaces to the first surface that is not specular, and
+/// estimates the light it reflects from the direct lighting of the point lights,
+/// the density of the caustic photons around it, and a final gather of the
+/// global photon map over rays sampled from its scattering function.
+///
+/// Photons leave point lights only, so emitting surfaces and the background
+/// contribute only along rays that reach them directly from the camera or from
+/// a final gather. The photon mapper treats the space between surfaces as a
+/// vacuum, so it ignores participating media.
+#[derive(Copy, Clone, Debug)]
+pub struct PhotonMapper {
+    settings: PhotonMappingSettings,
+    max_path_depth: usize,
+    t_max: f32,
+}
+
+impl PhotonMapper {
+    pub fn new(settings: PhotonMappingSettings, max_path_depth: usize, t_max: f32) -> Self {
+        Self { settings, max_path_depth, t_max, }
+    }
+
+    /// Trace the photons of the first pass of the photon mapper.
+    pub fn trace_photons(&self, scene: &Scene, sampler: &mut SphereSampler) -> PhotonMaps {
+        let photon_count = self.settings.photon_count;
+        let mut global_photons = Vec::with_capacity(photon_count);
+        let mut caustic_photons = Vec::new();
+        trace_photons(scene, photon_count, self.max_path_depth, sampler, |photon, depth, is_specular_path| {
+            let photon = Photon::new(photon.position, photon.direction, photon.power / photon_count as f32);
+            if depth > 0 && is_specular_path {
+                caustic_photons.push(photon);
+            }
+
+            global_photons.push(photon);
+        });
+
+        PhotonMaps {
+            global: PhotonMap::new(global_photons),
+            caustic: PhotonMap::new(caustic_photons),
+        }
+    }
+
+    /// Estimate the radiance reflected from a point from the photons nearest to
+    /// it in a photon map.
+    fn estimate_photon_radiance(&self, photon_map: &PhotonMap, diffuse_hit: &DiffuseHit) -> Vector3<f32> {
+        let neighbors = photon_map.nearest(&diffuse_hit.hit.point, self.settings.gather_count, self.settings.gather_radius);
+        if neighbors.is_empty() {
+            return Vector3::zero();
+        }
+
+        // With fewer photons than requested around the point, the density is
+        // estimated over the whole search disk.
+        let radius_squared = if neighbors.len() < self.settings.gather_count {
+            self.settings.gather_radius * self.settings.gather_radius
+        } else {
+            neighbors[neighbors.len() - 1].1
+        };
+        let photons = neighbors.iter().map(|(photon, _)| *photon);
+
+        estimate_radiance(diffuse_hit.object, &diffuse_hit.hit, &diffuse_hit.ray_incoming, photons, radius_squared)
+    }
+
+    /// Estimate the indirect light reflected from a point by tracing rays from
+    /// its scattering function and looking up the global photon map where they
+    /// land.
+    fn final_gather(&self, scene: &Scene, photon_maps: &PhotonMaps, diffuse_hit: &DiffuseHit, sampler: &mut SphereSampler) -> Vector3<f32> {
+        let sample_count = self.settings.final_gather_samples;
+        if sample_count == 0 {
+            return Vector3::zero();
+        }
+
+        let mut radiance = Vector3::zero();
+        for _ in 0..sample_count {
+            let sample = diffuse_hit.object.sample_bsdf(&diffuse_hit.hit, &diffuse_hit.ray_incoming, sampler);
+            let (ray_outgoing, bsdf_result) = match sample {
+                Some(sample) => sample,
+                None => continue,
+            };
+            let ray = diffuse_hit.hit.spawn_ray(&ray_outgoing);
+            let (emitted, gather_hit) = trace_to_diffuse_hit(scene, ray, self.max_path_depth, self.t_max, sampler);
+            let mut gathered = emitted;
+            if let Some(gather_hit) = gather_hit {
+                let reflected = self.estimate_photon_radiance(photon_maps.global(), &gather_hit);
+                gathered += gather_hit.beta.component_mul(&reflected);
+            }
+
+            radiance += bsdf_result.scattering_fraction.component_mul(&gathered);
+        }
+
+        radiance / sample_count as f32
+    }
+
+    /// Estimate the radiance arriving at the camera along the camera ray `ray`.
+    pub fn sample(&self, scene: &Scene, photon_maps: &PhotonMaps, ray: Ray, sampler: &mut SphereSampler) -> Vector3<f32> {
+        let (emitted, diffuse_hit) = trace_to_diffuse_hit(scene, ray, self.max_path_depth, self.t_max, sampler);
+        let diffuse_hit = match diffuse_hit {
+            Some(diffuse_hit) => diffuse_hit,
+            None => return emitted,
+        };
+        let direct = estimate_direct_lighting(scene, diffuse_hit.object, &diffuse_hit.hit, &diffuse_hit.ray_incoming);
+        let caustics = self.estimate_photon_radiance(photon_maps.caustic(), &diffuse_hit);
+        let indirect = self.final_gather(scene, photon_maps, &diffuse_hit, sampler);
+
+        emitted + diffuse_hit.beta.component_mul(&(direct + caustics + indirect))
+    }
+}
+
+/// The state of a pixel of stochastic progressive photon mapping.
+#[derive(Copy, Clone, Debug)]
+struct ProgressivePixel {
+    /// The radius photons are gathered within around the point the pixel sees.
+    radius: f32,
+    /// The sum over passes of the light reaching the pixel other than through
+    /// photons.
+    radiance: Vector3<f32>,
+    /// The number of photons the pixel has accumulated, reduced by the radius
+    /// shrinking.
+    photon_count: f32,
+    /// The flux of the photons the pixel has accumulated, rescaled to the
+    /// current radius.
+    flux: Vector3<f32>,
+}
+
+/// A stochastic progressive photon mapper.
+///
+/// Each pass traces a ray from every pixel to the first surface that is not
+/// specular, and then traces a fresh set of photons into a photon map. Every
+/// pixel gathers the photons landing within its radius of the point it sees,
+/// after which its radius shrinks so that the photon density estimate converges
+/// as the number of passes grows. Direct lighting from the point lights is
+/// estimated with shadow rays instead of photons.
+///
+/// Like [`PhotonMapper`], it traces photons from point lights only and ignores
+/// participating media.
+#[derive(Copy, Clone, Debug)]
+pub struct StochasticProgressivePhotonMapper {
+    settings: PhotonMappingSettings,
+    max_path_depth: usize,
+    t_max: f32,
+}
+
+impl StochasticProgressivePhotonMapper {
+    pub fn new(settings: PhotonMappingSettings, max_path_depth: usize, t_max: f32) -> Self {
+        Self { settings, max_path_depth, t_max, }
+    }
+
+    /// Render `pass_count` passes into a film.
+    ///
+    /// Each pixel of the film receives the sum of its radiance estimates over
+    /// the passes as a single sample, so the film resolves with `pass_count`
+    /// samples per pixel.
+    pub fn render(&self, scene: &Scene, pass_count: usize, sampler: &mut SphereSampler, film: &mut Film) {
+        let width = film.width();
+        let height = film.height();
+        let initial_pixel = ProgressivePixel {
+            radius: self.settings.gather_radius,
+            radiance: Vector3::zero(),
+            photon_count: 0_f32,
+            flux: Vector3::zero(),
+        };
+        let mut pixels = vec![initial_pixel; width * height];
+        for pass in 0..pass_count {
+            println!("Rendering pass {} of {}", pass + 1, pass_count);
+            let mut visible_points = Vec::with_capacity(width * height);
+            for row in 0..height {
+                for column in 0..width {
+                    let u = (column as f32 + sampler.sample_f32()) / (width as f32);
+                    let v = (((height - row - 1) as f32) + sampler.sample_f32()) / (height as f32);
+                    let ray = scene.camera.cast_ray(sampler, u, v);
+                    let (emitted, diffuse_hit) = trace_to_diffuse_hit(scene, ray, self.max_path_depth, self.t_max, sampler);
+                    let pixel = &mut pixels[row * width + column];
+                    pixel.radiance += emitted;
+                    if let Some(diffuse_hit) = &diffuse_hit {
+                        let direct = estimate_direct_lighting(
+                            scene,
+                            diffuse_hit.object,
+                            &diffuse_hit.hit,
+                            &diffuse_hit.ray_incoming
+                        );
+                        pixel.radiance += diffuse_hit.beta.component_mul(&direct);
+                    }
+
+                    visible_points.push(diffuse_hit);
+                }
+            }
+
+            // Photons landing straight from a light are accounted for by the
+            // direct lighting estimate.
+            let mut photons = Vec::with_capacity(self.settings.photon_count);
+            trace_photons(scene, self.settings.photon_count, self.max_path_depth, sampler, |photon, depth, _is_specular_path| {
+                if depth > 0 {
+                    photons.push(*photon);
+                }
+            });
+            let photon_map = PhotonMap::new(photons);
+
+            for (pixel, visible_point) in pixels.iter_mut().zip(visible_points.iter()) {
+                if let Some(visible_point) = visible_point {
+                    self.update_pixel(pixel, visible_point, &photon_map);
+                }
+            }
+        }
+
+        let photon_count = self.settings.photon_count as f32;
+        for row in 0..height {
+            for column in 0..width {
+                let pixel = &pixels[row * width + column];
+                let area = std::f32::consts::PI * pixel.radius * pixel.radius;
+                let photon_radiance = pixel.flux / (photon_count * area);
+
+                film.add_sample(row, column, pixel.radiance + photon_radiance);
+            }
+        }
+    }
+
+    /// Gather the photons of a pass around the point a pixel sees and shrink the
+    /// radius of the pixel.
+    fn update_pixel(&self, pixel: &mut ProgressivePixel, visible_point: &DiffuseHit, photon_map: &PhotonMap) {
+        let mut found_count = 0;
+        let mut found_flux = Vector3::zero();
+        let ray_outgoing = -visible_point.ray_incoming;
+        photon_map.for_each_within(&visible_point.hit.point, pixel.radius, |photon, _distance_squared| {
+            let bsdf = visible_point.object.evaluate_bsdf(&visible_point.hit, &photon.direction, &ray_outgoing);
+            found_flux += bsdf.component_mul(&photon.power);
+            found_count += 1;
+        });
+        if found_count == 0 {
+            return;
+        }
+
+        let found_count = found_count as f32;
+        let photon_count = pixel.photon_count + self.settings.radius_reduction * found_count;
+        let radius = pixel.radius * f32::sqrt(photon_count / (pixel.photon_count + found_count));
+        let flux = pixel.flux + visible_point.beta.component_mul(&found_flux);
+        let area_ratio = (radius * radius) / (pixel.radius * pixel.radius);
+
+        pixel.flux = flux * area_ratio;
+        pixel.photon_count = photon_count;
+        pixel.radius = radius;
+    }
+}
+
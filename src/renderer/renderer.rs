@@ -0,0 +1,336 @@
+use crate::core::*;
+use crate::canvas::*;
+use crate::renderer::*;
+use crate::scene::*;
+use cglinalg::{ 
+    Vector3,
+    Magnitude,
+};
+
+
+/// The algorithm a renderer estimates the light arriving at the camera with.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RenderingAlgorithm {
+    /// Unidirectional path tracing from the camera with next event estimation.
+    PathTracing,
+    /// Bidirectional path tracing, connecting subpaths traced from the camera 
+    /// and from the lights of a scene.
+    BidirectionalPathTracing,
+    /// Two pass photon mapping with a final gather.
+    PhotonMapping,
+    /// Stochastic progressive photon mapping, with one photon pass per sample
+    /// per pixel.
+    StochasticProgressivePhotonMapping,
+    /// Primary sample space Metropolis light transport over the paths of the path
+    /// tracer.
+    MetropolisLightTransport,
+    /// Light arriving directly from the lights of a scene, following only perfect
+    /// specular reflection and refraction.
+    DirectLighting,
+    /// The fraction of the hemisphere above the surface seen by the camera that
+    /// is unoccluded within the ambient occlusion distance.
+    AmbientOcclusion,
+    /// A visualization of the geometry of a scene or of the work of rendering it.
+    Debug(DebugMode),
+}
+
+#[derive(Copy, Clone)]
+pub struct RendererSettings {
+    algorithm: RenderingAlgorithm,
+    photon_mapping: PhotonMappingSettings,
+    metropolis: MetropolisSettings,
+    path_guiding: Option<PathGuidingSettings>,
+    ambient_occlusion_distance: f32,
+    debug_max_distance: f32,
+    samples_per_pixel: usize,
+    max_path_depth: usize,
+    max_medium_depth: usize,
+    t_max: f32,
+}
+
+impl RendererSettings {
+    pub fn new(samples_per_pixel: usize, max_path_depth: usize) -> Self {
+        Self { 
+            algorithm: RenderingAlgorithm::PathTracing,
+            photon_mapping: PhotonMappingSettings::default(),
+            metropolis: MetropolisSettings::default(),
+            path_guiding: None,
+            ambient_occlusion_distance: f32::MAX,
+            debug_max_distance: 100_f32,
+            samples_per_pixel, 
+            max_path_depth,
+            max_medium_depth: 256,
+            t_max: f32::MAX
+        }
+    }
+
+    /// Set the maximum number of times a path may scatter inside participating 
+    /// media. Scattering events inside media do not count towards the maximum 
+    /// path depth, because dense media such as those of subsurface scattering 
+    /// materials need long random walks.
+    pub fn with_max_medium_depth(mut self, max_medium_depth: usize) -> Self {
+        self.max_medium_depth = max_medium_depth;
+        self
+    }
+
+    /// Set the algorithm the renderer uses. The default is path tracing.
+    pub fn with_algorithm(mut self, algorithm: RenderingAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Set the parameters of the photon mapping algorithms.
+    pub fn with_photon_mapping(mut self, photon_mapping: PhotonMappingSettings) -> Self {
+        self.photon_mapping = photon_mapping;
+        self
+    }
+
+    /// Set the parameters of Metropolis light transport.
+    pub fn with_metropolis(mut self, metropolis: MetropolisSettings) -> Self {
+        self.metropolis = metropolis;
+        self
+    }
+
+    /// Set the distance within which surfaces occlude each other for ambient 
+    /// occlusion. The default is unbounded.
+    pub fn with_ambient_occlusion_distance(mut self, ambient_occlusion_distance: f32) -> Self {
+        self.ambient_occlusion_distance = ambient_occlusion_distance;
+        self
+    }
+
+    /// Set the distance from the camera shown as white when rendering depth for 
+    /// debugging. The default is one hundred.
+    pub fn with_debug_max_distance(mut self, debug_max_distance: f32) -> Self {
+        self.debug_max_distance = debug_max_distance;
+        self
+    }
+
+    /// Guide the paths of the path tracer with a distribution of incident light 
+    /// learned over a number of training passes before the final pass. Path 
+    /// guiding only applies to path tracing.
+    pub fn with_path_guiding(mut self, path_guiding: PathGuidingSettings) -> Self {
+        self.path_guiding = Some(path_guiding);
+        self
+    }
+}
+
+/// Estimate the fraction of the light arriving at the point of a hit directly 
+/// from the point lights of a scene that no object casting shadows blocks.
+///
+/// Shadow catchers scale the light arriving from behind them by this fraction,
+/// so that they show only the shadows cast onto them. A point lit by no light 
+/// is not in shadow.
+pub(crate) fn shadow_fraction(scene: &Scene, hit: &IntersectionData) -> f32 {
+    let mut unoccluded_light = 0_f32;
+    let mut total_light = 0_f32;
+    for light in scene.lights.iter() {
+        let offset = light.position() - hit.point;
+        let distance_squared = offset.magnitude_squared();
+        if distance_squared == 0_f32 {
+            continue;
+        }
+
+        let cos_theta = f32::abs(hit.normal.dot(&offset)) / f32::sqrt(distance_squared);
+        let irradiance = luminance(&light.emission()) * cos_theta / distance_squared;
+        total_light += irradiance;
+        if scene.line_of_sight(&hit.spawn_origin(&offset), &light.position(), hit.time) {
+            unoccluded_light += irradiance;
+        }
+    }
+
+    if total_light > 0_f32 {
+        unoccluded_light / total_light
+    } else {
+        1_f32
+    }
+}
+
+/// Returns the radiance arriving from the background of a scene along a ray 
+/// leaving the scene in the direction `direction`.
+pub(crate) fn background_radiance(direction: &Vector3<f32>) -> Vector3<f32> {
+    let unit_direction = direction.normalize();
+    let t = (unit_direction.y + 1_f32) * 0.5;
+
+    // TODO: Convert default value to some kind of ambient light instead of baking into path tracer.
+    // When we reach the end of a ray, there is always the ambient lighting to return.
+    Vector3::new(1_f32, 1_f32, 1_f32) * (1_f32 - t) + Vector3::new(0.5, 0.7, 1.0) * t
+}
+
+/// Returns the luminance of a linear RGB color.
+#[inline]
+pub(crate) fn luminance(color: &Vector3<f32>) -> f32 {
+    0.2126 * color.x + 0.7152 * color.y + 0.0722 * color.z
+}
+
+pub struct Renderer {
+    algorithm: RenderingAlgorithm,
+    photon_mapping: PhotonMappingSettings,
+    metropolis: MetropolisSettings,
+    path_guiding: Option<PathGuidingSettings>,
+    ambient_occlusion_distance: f32,
+    debug_max_distance: f32,
+    samples_per_pixel: usize,
+    max_path_depth: usize,
+    max_medium_depth: usize,
+    t_max: f32,
+}
+
+impl Renderer {
+    pub fn new(settings: RendererSettings) -> Self {
+        Self { 
+            algorithm: settings.algorithm,
+            photon_mapping: settings.photon_mapping,
+            metropolis: settings.metropolis,
+            path_guiding: settings.path_guiding,
+            ambient_occlusion_distance: settings.ambient_occlusion_distance,
+            debug_max_distance: settings.debug_max_distance,
+            samples_per_pixel: settings.samples_per_pixel, 
+            max_path_depth: settings.max_path_depth,
+            max_medium_depth: settings.max_medium_depth,
+            t_max: settings.t_max,
+        }
+    }
+
+    /// Cast a ray from the camera through a random point inside the pixel 
+    /// `(row, column)` of the film.
+    #[inline]
+    fn sample_camera_ray(scene: &Scene, film: &Film, row: usize, column: usize, sampler: &mut SphereSampler) -> Ray {
+        let du = sampler.sample_f32();
+        let u = (column as f32 + du) / (film.width() as f32);
+        let dv = sampler.sample_f32();
+        let v = (((film.height() - row - 1) as f32) + dv) / (film.height() as f32);
+
+        scene.camera.cast_ray(sampler, u, v)
+    }
+
+    /// Take `samples_per_pixel` samples in every pixel of the film, estimating 
+    /// the radiance arriving along each camera ray with `estimate`. 
+    ///
+    /// The estimator receives the film for the estimates that land on other 
+    /// pixels than the one the camera ray passes through.
+    fn render_pixels<F>(scene: &Scene, samples_per_pixel: usize, sampler: &mut SphereSampler, film: &mut Film, mut estimate: F)
+    where
+        F: FnMut(Ray, &mut SphereSampler, &mut Film) -> Vector3<f32>,
+    {
+        let height = film.height();
+        for row in 0..height {
+            println!("Rendering line {} of {}", row+1, height);
+            for column in 0..film.width() {
+                for _ in 0..samples_per_pixel {
+                    let ray = Self::sample_camera_ray(scene, film, row, column, sampler);
+                    let radiance = estimate(ray, sampler, film);
+                    film.add_sample(row, column, radiance);
+                }
+            }
+        }
+    }
+
+    /// Construct the path tracer of the renderer. With path guiding enabled, the 
+    /// guiding distribution of the path tracer is learned over the training passes
+    /// of the path guiding settings first, doubling the number of samples per 
+    /// pixel from one pass to the next. The images of the training passes are 
+    /// discarded.
+    fn path_tracer(&self, scene: &Scene, sampler: &mut SphereSampler, film: &Film) -> PathTracer {
+        let path_tracer = PathTracer::new(self.max_path_depth, self.max_medium_depth, self.t_max);
+        let settings = match self.path_guiding {
+            Some(settings) => settings,
+            None => return path_tracer,
+        };
+        // Infinite objects such as planes have no region worth guiding.
+        let bounds = scene.objects()
+            .iter()
+            .map(|object| object.bounds())
+            .filter(|bounds| !bounds.is_infinite())
+            .fold(BoundingBox::empty(), |bounds, object_bounds| bounds.union(&object_bounds));
+        if bounds.is_empty() {
+            return path_tracer;
+        }
+
+        let mut path_tracer = path_tracer.with_path_guide(SdTree::new(bounds), settings.bsdf_sampling_fraction());
+        for pass in 0..settings.training_passes() {
+            println!("Training path guiding pass {} of {}", pass + 1, settings.training_passes());
+            let mut training_film = Film::new(film.width(), film.height());
+            Self::render_pixels(scene, 1 << pass, sampler, &mut training_film, |ray, sampler, _film| {
+                path_tracer.radiance(scene, &ray, sampler)
+            });
+            path_tracer.refine_path_guide(pass, &settings);
+        }
+        path_tracer.finish_path_guide_training();
+
+        path_tracer
+    }
+
+    /// Construct the integrator of the rendering algorithm, for the algorithms
+    /// estimating the radiance along each camera ray independently.
+    fn integrator(&self, scene: &Scene, sampler: &mut SphereSampler, film: &Film) -> Option<Box<dyn Integrator>> {
+        match self.algorithm {
+            RenderingAlgorithm::PathTracing => {
+                Some(Box::new(self.path_tracer(scene, sampler, film)))
+            }
+            RenderingAlgorithm::BidirectionalPathTracing if scene.has_media() => {
+                // The bidirectional path tracer treats the space between surfaces
+                // as a vacuum, so it would render the scene without its media.
+                println!("Bidirectional path tracing does not support participating media; falling back to path tracing");
+                Some(Box::new(self.path_tracer(scene, sampler, film)))
+            }
+            RenderingAlgorithm::DirectLighting => {
+                Some(Box::new(DirectLightingIntegrator::new(self.max_path_depth, self.t_max)))
+            }
+            RenderingAlgorithm::AmbientOcclusion => {
+                Some(Box::new(AmbientOcclusionIntegrator::new(self.ambient_occlusion_distance, self.t_max)))
+            }
+            RenderingAlgorithm::Debug(mode) => {
+                let integrator = DebugIntegrator::new(mode, self.max_path_depth, self.t_max)
+                    .with_max_distance(self.debug_max_distance);
+
+                Some(Box::new(integrator))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn render(&self, scene: &Scene, sampler: &mut SphereSampler, canvas: &mut Canvas) {
+        let height = canvas.height;
+        let width = canvas.width;
+        let mut film = Film::new(width, height);
+        if let Some(integrator) = self.integrator(scene, sampler, &film) {
+            Self::render_pixels(scene, self.samples_per_pixel, sampler, &mut film, |ray, sampler, _film| {
+                integrator.radiance(scene, &ray, sampler)
+            });
+            film.write_canvas(self.samples_per_pixel, canvas);
+
+            return;
+        }
+
+        match self.algorithm {
+            RenderingAlgorithm::BidirectionalPathTracing => {
+                let bidirectional_path_tracer = BidirectionalPathTracer::new(self.max_path_depth, self.t_max);
+                Self::render_pixels(scene, self.samples_per_pixel, sampler, &mut film, |ray, sampler, film| {
+                    bidirectional_path_tracer.sample(scene, ray, sampler, film)
+                });
+            }
+            RenderingAlgorithm::PhotonMapping => {
+                println!("Tracing photons");
+                let photon_mapper = PhotonMapper::new(self.photon_mapping, self.max_path_depth, self.t_max);
+                let photon_maps = photon_mapper.trace_photons(scene, sampler);
+                Self::render_pixels(scene, self.samples_per_pixel, sampler, &mut film, |ray, sampler, _film| {
+                    photon_mapper.sample(scene, &photon_maps, ray, sampler)
+                });
+            }
+            RenderingAlgorithm::StochasticProgressivePhotonMapping => {
+                let progressive_photon_mapper = StochasticProgressivePhotonMapper::new(self.photon_mapping, self.max_path_depth, self.t_max);
+                progressive_photon_mapper.render(scene, self.samples_per_pixel, sampler, &mut film);
+            }
+            RenderingAlgorithm::MetropolisLightTransport => {
+                let path_tracer = PathTracer::new(self.max_path_depth, self.max_medium_depth, self.t_max);
+                let metropolis_light_transport = MetropolisLightTransport::new(self.metropolis);
+                metropolis_light_transport.render(scene, self.samples_per_pixel, sampler, &mut film, |ray, sampler| {
+                    path_tracer.radiance(scene, ray, sampler)
+                });
+            }
+            _ => unreachable!("the integrators of the remaining algorithms render pixels independently"),
+        }
+
+        film.write_canvas(self.samples_per_pixel, canvas);
+    }
+}
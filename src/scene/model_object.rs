@@ -12,43 +12,109 @@ pub trait ModelSpaceObject: std::fmt::Debug {
 
     fn contains(&self, point: &Vector3<f32>) -> bool;
 
-    fn normal(&self, point: &Vector3<f32>) -> Option<Vector3<f32>>;
+    /// Returns the bounding box of the object in model space.
+    fn bounds(&self) -> BoundingBox;
 
+    /// Scatter a ray off the object at the intersection recorded in `query`. 
+    ///
+    /// The normal of the scattering result is the geometric normal of the 
+    /// intersection.
     fn scatter(&self, query: &ScatteringQuery, sampler: &mut SphereSampler) -> ScatteringResult;
+
+    fn bsdf(&self) -> &dyn Bsdf;
+
+    fn emitter(&self) -> &dyn LightMapping;
+}
+
+/// Scatter a ray off a surface with the scattering function `bsdf` and the 
+/// emitter `emitter` at the intersection recorded in `query`.
+///
+/// The scattering function is sampled in the shading frame of the query and
+/// evaluates its textures at `query.point` and `query.world_point`. The emission
+/// and the point of the scattering result are taken at `query.world_point`. 
+/// This is the one implementation of scattering that model space objects and 
+/// scene objects share.
+pub(crate) fn scatter_from_surface(
+    bsdf: &dyn Bsdf, 
+    emitter: &dyn LightMapping, 
+    query: &ScatteringQuery, 
+    sampler: &mut SphereSampler) -> ScatteringResult
+{
+    let frame = query.shading_frame;
+    let ray_incoming_local = frame.to_local(&query.ray_incoming.normalize());
+    let mut bsdf_query = BsdfSampleQuery::new(ray_incoming_local, query.point, query.uv);
+    bsdf_query.world_point = query.world_point;
+    let lighting_query = LightingQuery::new(query.ray_incoming, query.world_point);
+    let lighting_result = emitter.emit(&lighting_query);
+    let (ray_outgoing, scattering_fraction) = match bsdf.sample(&bsdf_query, sampler) {
+        Some(bsdf_result) => (frame.to_world(&bsdf_result.ray_outgoing), bsdf_result.scattering_fraction),
+        // The surface absorbed the incoming ray.
+        None => (Vector3::zero(), Vector3::zero()),
+    };
+
+    ScatteringResult::new(
+        query.ray_incoming, 
+        ray_outgoing, 
+        query.world_point, 
+        query.normal, 
+        scattering_fraction, 
+        lighting_result.radiance,
+    )
 }
 
 #[derive(Debug)]
-pub struct ModelSpaceGeometryObject<Geom, Bsdf, Emitter> 
+pub struct ModelSpaceGeometryObject<Geom> 
 where 
     Geom: Geometry,
-    Bsdf: BsdfMapping,
-    Emitter: LightMapping,
 {
     geometry: Geom,
-    bsdf: Box<Bsdf>,
-    sampler: Box<dyn BsdfQuerySampler<Bsdf = Bsdf>>,
-    emitter: Box<Emitter>
+    bsdf: Box<dyn Bsdf>,
+    emitter: Box<dyn LightMapping>,
+    normal_mapping: Option<Box<dyn NormalMapping>>,
 }
 
-impl<Geom, Bsdf, Emitter> ModelSpaceGeometryObject<Geom, Bsdf, Emitter> 
+impl<Geom> ModelSpaceGeometryObject<Geom> 
 where 
     Geom: Geometry,
-    Bsdf: BsdfMapping, 
-    Emitter: LightMapping,
 {
-    pub fn new(geometry: Geom, bsdf: Box<Bsdf>, sampler: Box<dyn BsdfQuerySampler<Bsdf = Bsdf>>, emitter: Box<Emitter>) -> Self {
-        Self { geometry, bsdf, sampler, emitter, }
+    pub fn new(geometry: Geom, bsdf: Box<dyn Bsdf>, emitter: Box<dyn LightMapping>) -> Self {
+        Self { geometry, bsdf, emitter, normal_mapping: None, }
+    }
+
+    /// Perturb the shading normals of the object with a normal map or a bump map.
+    pub fn with_normal_mapping(mut self, normal_mapping: Box<dyn NormalMapping>) -> Self {
+        self.normal_mapping = Some(normal_mapping);
+        self
+    }
+
+    fn apply_normal_mapping(&self, hit: &IntersectionData) -> IntersectionData {
+        if let Some(normal_mapping) = &self.normal_mapping {
+            let shading_normal = normal_mapping.shading_normal(hit);
+            let mut mapped_hit = *hit;
+            mapped_hit.shading_normal = safeguard_shading_normal(&hit.normal, &shading_normal);
+
+            mapped_hit
+        } else {
+            *hit
+        }
     }
 }
 
-impl<Geom, Bsdf, Emitter> ModelSpaceObject for ModelSpaceGeometryObject<Geom, Bsdf, Emitter>
+impl<Geom> ModelSpaceObject for ModelSpaceGeometryObject<Geom>
 where 
     Geom: Geometry,
-    Bsdf: BsdfMapping,
-    Emitter: LightMapping,
 {
     fn intersect(&self, query: &IntersectionQuery) -> IntersectionResult {
-        self.geometry.intersect(query)
+        let direction = &query.ray.direction;
+        match self.geometry.intersect(query) {
+            IntersectionResult::Hit(hit) => {
+                IntersectionResult::Hit(self.apply_normal_mapping(&hit.with_face_orientation(direction)))
+            }
+            IntersectionResult::Tangent(hit) => {
+                IntersectionResult::Tangent(self.apply_normal_mapping(&hit.with_face_orientation(direction)))
+            }
+            result @ IntersectionResult::Miss(_) => result,
+        }
     }
 
     fn center(&self) -> Vector3<f32> {
@@ -60,30 +126,22 @@ where
         self.geometry.contains(point)
     }
 
-    fn normal(&self, point: &Vector3<f32>) -> Option<Vector3<f32>> {
-        if self.contains(point) {
-            Some((point - self.geometry.center()).normalize())
-        } else {
-            None
-        }
+    #[inline]
+    fn bounds(&self) -> BoundingBox {
+        self.geometry.bounds()
     }
 
     fn scatter(&self, query: &ScatteringQuery, sampler: &mut SphereSampler) -> ScatteringResult {
-        let normal = (query.point - self.geometry.center()).normalize();
-        let ray_incoming = query.ray_incoming;
-        let bsdf_query = self.sampler.sample(&self.bsdf, &ray_incoming, &normal, &query.point, sampler);
-        let bsdf_result = self.bsdf.sample(&bsdf_query);
-        let lighting_query = LightingQuery::new(query.ray_incoming, query.point);
-        let lighting_result = self.emitter.emit(&lighting_query);
-             
-        ScatteringResult::new(
-            bsdf_result.ray_incoming,
-            bsdf_result.ray_outgoing,
-            bsdf_result.point,
-            bsdf_result.normal,
-            bsdf_result.scattering_fraction,
-            lighting_result.radiance,
-        )
+        scatter_from_surface(self.bsdf.as_ref(), self.emitter.as_ref(), query, sampler)
     }
-}
 
+    #[inline]
+    fn bsdf(&self) -> &dyn Bsdf {
+        self.bsdf.as_ref()
+    }
+
+    #[inline]
+    fn emitter(&self) -> &dyn LightMapping {
+        self.emitter.as_ref()
+    }
+}
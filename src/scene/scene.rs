@@ -5,24 +5,53 @@ use cglinalg::{
     Vector3,
     Magnitude,
 };
+use std::cell::OnceCell;
+use std::sync::Arc;
 
 
 #[derive(Copy, Clone, Debug)]
 pub struct ObjectIntersectionResult<'a> {
     pub intersection_result: IntersectionResult,
     pub object: &'a SceneObject,
+    /// The index of the object in the objects of the scene.
+    pub index: usize,
 }
 
 impl<'a> ObjectIntersectionResult<'a> {
-    pub fn new(intersection_result: IntersectionResult, object: &'a SceneObject) -> Self {
-        Self { intersection_result, object, }
+    pub fn new(intersection_result: IntersectionResult, object: &'a SceneObject, index: usize) -> Self {
+        Self { intersection_result, object, index, }
     }
 }
 
+/// The work a ray query does to find the closest intersection in a scene.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TraversalStats {
+    /// The number of bounding boxes tested against the ray.
+    pub bounds_tests: usize,
+    /// The number of objects tested against the ray.
+    pub object_tests: usize,
+}
+
+impl TraversalStats {
+    /// Returns the total number of intersection tests.
+    #[inline]
+    pub fn total(&self) -> usize {
+        self.bounds_tests + self.object_tests
+    }
+}
+
+/// A scene of objects and lights viewed by a camera.
+///
+/// A scene finds the objects a ray hits with a bounding volume hierarchy over 
+/// the world space bounding boxes of its objects, built on the first ray query
+/// after the objects change. The objects of a scene only change through its 
+/// methods, so the hierarchy is never stale.
 pub struct Scene {
-    pub objects: Vec<SceneObject>,
+    objects: Vec<SceneObject>,
     pub lights: Vec<ScenePointLightObject>,
     pub camera: Camera,
+    medium: Option<Arc<dyn Medium>>,
+    bvh: OnceCell<Bvh>,
 }
 
 impl Scene {
@@ -31,6 +60,52 @@ impl Scene {
             objects: Vec::new(),
             lights: Vec::new(),
             camera,
+            medium: None,
+            bvh: OnceCell::new(),
+        }
+    }
+
+    /// Fill the space between the objects of a scene, where the camera sits, with
+    /// a participating medium.
+    pub fn set_medium(&mut self, medium: Arc<dyn Medium>) {
+        self.medium = Some(medium);
+    }
+
+    /// Returns the participating medium filling the space between the objects of
+    /// a scene, if there is one.
+    #[inline]
+    pub fn medium(&self) -> Option<&dyn Medium> {
+        self.medium.as_deref()
+    }
+
+    /// Determine whether a scene contains a participating medium, either between
+    /// its objects or inside one of them.
+    pub fn has_media(&self) -> bool {
+        self.medium.is_some() || self.objects.iter().any(|object| object.interior_medium().is_some())
+    }
+
+    /// Determine the participating medium a ray travels through after leaving the
+    /// surface of an object in the direction `direction`.
+    ///
+    /// Objects without an interior medium leave the current medium unchanged.
+    /// Media do not nest: leaving the interior of an object returns the ray to 
+    /// the medium of the scene.
+    pub fn medium_after_crossing<'a>(
+        &'a self, 
+        object: &'a SceneObject, 
+        normal: &Vector3<f32>, 
+        direction: &Vector3<f32>, 
+        medium: Option<&'a dyn Medium>) -> Option<&'a dyn Medium>
+    {
+        match object.interior_medium() {
+            Some(interior_medium) => {
+                if direction.dot(normal) < 0_f32 {
+                    Some(interior_medium)
+                } else {
+                    self.medium()
+                }
+            }
+            None => medium,
         }
     }
 
@@ -49,9 +124,49 @@ impl Scene {
         self.lights.is_empty()
     }
 
+    /// Returns the objects of a scene.
+    #[inline]
+    pub fn objects(&self) -> &[SceneObject] {
+        &self.objects
+    }
+
+    /// Returns the objects of a scene for editing in place. The bounding volume 
+    /// hierarchy is rebuilt on the next ray query.
+    pub fn objects_mut(&mut self) -> &mut [SceneObject] {
+        self.bvh = OnceCell::new();
+
+        &mut self.objects
+    }
+
     /// Insert an object into a scene.
     pub fn push_object(&mut self, object: SceneObject) {
         self.objects.push(object);
+        self.bvh = OnceCell::new();
+    }
+
+    /// Remove the object with index `index` from a scene, shifting the objects 
+    /// after it down by one.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `index` is out of bounds.
+    pub fn remove_object(&mut self, index: usize) -> SceneObject {
+        self.bvh = OnceCell::new();
+
+        self.objects.remove(index)
+    }
+
+    /// Returns the bounding volume hierarchy over the objects of a scene, 
+    /// building it if the objects changed since the last ray query.
+    pub fn bvh(&self) -> &Bvh {
+        self.bvh.get_or_init(|| {
+            let bounds = self.objects
+                .iter()
+                .map(|object| object.bounds())
+                .collect::<Vec<BoundingBox>>();
+
+            Bvh::new(&bounds)
+        })
     }
 
     pub fn push_light(&mut self, light: ScenePointLightObject) {
@@ -61,34 +176,168 @@ impl Scene {
     /// Cast a ray into a scene and determine whether the ray intersects and 
     /// scattering object inside the scene.
     pub fn intersect(&self, query: &IntersectionQuery) -> Option<ObjectIntersectionResult> {
+        self.intersect_with_stats(query, &mut TraversalStats::default())
+    }
+
+    /// Cast a ray into a scene like [`Scene::intersect`], counting the 
+    /// intersection tests the query performs into `stats`.
+    pub fn intersect_with_stats(&self, query: &IntersectionQuery, stats: &mut TraversalStats) -> Option<ObjectIntersectionResult<'_>> {
         let mut closest_result = None;
-        let mut t_closest_so_far = query.t_max;
-        let mut closest_ray = *query;
-        for object in self.objects.iter() {
-            let new_ray = Ray::new(closest_ray.ray.origin, closest_ray.ray.direction);
-            let new_query = IntersectionQuery::new(new_ray, closest_ray.t_min, t_closest_so_far);
+        let bounds_tests = self.bvh().traverse(&query.ray, query.t_min, query.t_max, |index, t_closest_so_far| {
+            let object = &self.objects[index];
+            // Skip objects whose bounding boxes the ray misses before the closest 
+            // intersection so far.
+            stats.bounds_tests += 1;
+            if object.bounds().intersect(&query.ray, query.t_min, t_closest_so_far).is_none() {
+                return t_closest_so_far;
+            }
+
+            let new_query = IntersectionQuery::new(query.ray, query.t_min, t_closest_so_far);
+            stats.object_tests += 1;
             let new_intersection_result = object.intersect(&new_query);
             if let 
                 IntersectionResult::Hit(new_intersection_desc) | 
                 IntersectionResult::Tangent(new_intersection_desc) = new_intersection_result 
             {
                 if new_intersection_desc.t < t_closest_so_far {
-                    closest_ray = new_query;
-                    t_closest_so_far = new_intersection_desc.t;
-                    closest_result = Some(ObjectIntersectionResult::new(new_intersection_result, object));
+                    closest_result = Some(ObjectIntersectionResult::new(new_intersection_result, object, index));
+
+                    return new_intersection_desc.t;
                 }
             }
-        }
+
+            t_closest_so_far
+        });
+        stats.bounds_tests += bounds_tests;
 
         closest_result
     }
 
-    pub fn line_of_sight(&self, from_location: &Vector3<f32>, to_location: &Vector3<f32>) -> bool {
-        let direction = (to_location - from_location).normalize();
-        let ray = Ray::new(*from_location, direction);
-        let query = IntersectionQuery::new(ray, 0.0001, f32::MAX);
-        
-        self.intersect(&query).is_none()
+    /// Focus the camera on the first surface seen through the center of the pixel 
+    /// in the row `row` and the column `column` of an image of `width` by 
+    /// `height` pixels, with the rows counted from the top of the image.
+    ///
+    /// A moving camera focuses on the scene as it is seen at the time its shutter
+    /// opens, and keeps that focus distance for the rest of its motion.
+    ///
+    /// Returns the new focus distance, or `None`, leaving the camera as it is, 
+    /// when the pixel lies outside the image or the ray through the pixel leaves
+    /// the scene.
+    pub fn autofocus(&mut self, row: usize, column: usize, width: usize, height: usize) -> Option<f32> {
+        if row >= height || column >= width {
+            return None;
+        }
+
+        let u = (column as f32 + 0.5) / (width as f32);
+        let v = ((height - row - 1) as f32 + 0.5) / (height as f32);
+        let (shutter_open, _) = self.camera.shutter();
+        let camera = self.camera.at_time(shutter_open);
+        let ray = camera.cast_pinhole_ray(u, v);
+        let query = IntersectionQuery::new(ray, 0_f32, f32::MAX);
+        let hit = self.intersect(&query)?;
+        let point = hit.intersection_result.unwrap_hit_or_tangent().point;
+        let focus_dist = camera.depth(&point);
+        if focus_dist <= 0_f32 {
+            return None;
+        }
+
+        self.camera = self.camera.clone().with_focus_dist(focus_dist);
+
+        Some(focus_dist)
+    }
+
+    /// Estimate the fraction of light that travels along the line segment between
+    /// two points at the time `time`, starting in the participating medium 
+    /// `medium`.
+    ///
+    /// Surfaces that only bound participating media and objects that cast no 
+    /// shadows let light through, and every other surface blocks it. A starting
+    /// point on a surface is offset from the surface first, e.g. with 
+    /// [`IntersectionData::spawn_origin`].
+    pub fn transmittance(
+        &self, 
+        from_location: &Vector3<f32>, 
+        to_location: &Vector3<f32>, 
+        time: f32,
+        medium: Option<&dyn Medium>, 
+        sampler: &mut SphereSampler) -> Vector3<f32>
+    {
+        let mut transmittance = Vector3::from_fill(1_f32);
+        let mut origin = *from_location;
+        let mut current_medium = medium;
+        loop {
+            let offset = to_location - origin;
+            let distance = offset.magnitude();
+            if distance == 0_f32 {
+                return transmittance;
+            }
+
+            let direction = offset / distance;
+            let ray = Ray::new(origin, direction).with_time(time);
+            let query = IntersectionQuery::new(ray, 0_f32, distance);
+            let hit = self.intersect(&query);
+            let t_segment = match &hit {
+                Some(hit) => hit.intersection_result.unwrap_hit_or_tangent().t,
+                None => distance,
+            };
+            if let Some(current_medium) = current_medium {
+                transmittance = transmittance.component_mul(&current_medium.transmittance(&ray, t_segment, sampler));
+            }
+
+            match hit {
+                Some(hit) => {
+                    let intersection_result = hit.intersection_result.unwrap_hit_or_tangent();
+                    if hit.object.bsdf().flags().is_pass_through() {
+                        current_medium = self.medium_after_crossing(hit.object, &intersection_result.normal, &direction, current_medium);
+                    } else if hit.object.casts_shadows() {
+                        return Vector3::zero();
+                    }
+                    
+                    origin = intersection_result.spawn_origin(&direction);
+                }
+                None => return transmittance,
+            }
+        }
+    }
+
+    /// Determine whether a surface of an object casting shadows blocks the ray of 
+    /// a shadow query before it travels the distance of the query. 
+    ///
+    /// The query ends at the first blocking surface it finds rather than looking
+    /// for the closest one.
+    pub fn occluded(&self, query: &ShadowQuery) -> bool {
+        let t_max = query.t_max();
+        if t_max <= 0_f32 {
+            return false;
+        }
+
+        let mut is_occluded = false;
+        self.bvh().traverse(&query.ray, 0_f32, t_max, |index, t_closest_so_far| {
+            let object = &self.objects[index];
+            if !object.casts_shadows() || object.bounds().intersect(&query.ray, 0_f32, t_closest_so_far).is_none() {
+                return t_closest_so_far;
+            }
+
+            let object_query = IntersectionQuery::new(query.ray, 0_f32, t_closest_so_far);
+            if object.intersect(&object_query).is_hit_or_tangent() {
+                is_occluded = true;
+
+                return f32::NEG_INFINITY;
+            }
+
+            t_closest_so_far
+        });
+
+        is_occluded
+    }
+
+    /// Determine whether the line segment between two points is unobstructed by 
+    /// the surfaces of the scene at the time `time`. 
+    ///
+    /// Points on surfaces are offset from their surfaces first, e.g. with 
+    /// [`IntersectionData::spawn_origin`].
+    pub fn line_of_sight(&self, from_location: &Vector3<f32>, to_location: &Vector3<f32>, time: f32) -> bool {
+        !self.occluded(&ShadowQuery::between(from_location, to_location, time))
     }
 }
 
@@ -3,101 +3,363 @@ use crate::scene::*;
 use cglinalg::{
     Vector3,
     Matrix4x4,
+    Magnitude,
 };
+use std::sync::Arc;
 
 
+/// An object placed in a scene by a model matrix.
+///
+/// The model space object of a scene object is shared, so many scene objects 
+/// can be instances of the same prototype, each with its own transformation,
+/// without copying the prototype. An instance can override the scattering 
+/// function and the emitter of its prototype to give it a different material.
+///
+/// The model matrix of a moving object is animated by keyframes, and every ray
+/// query sees the object where it is at the time of the ray.
+///
+/// A transparent object lets shadow rays through, so it casts no shadows, but
+/// other rays see it as usual. A shadow catcher casts no shadows either, and 
+/// the integrators that support it show only the shadows other objects cast 
+/// onto it, on top of whatever lies behind it.
 #[derive(Debug)]
 pub struct SceneObject {
-    object: Box<dyn ModelSpaceObject>,
+    object: Arc<dyn ModelSpaceObject>,
+    /// The model matrix of the object, or its model matrix at the first 
+    /// keyframe when the object moves.
     pub model_matrix: Matrix4x4<f32>,
     model_matrix_inv: Matrix4x4<f32>,
+    transform: AnimatedTransform,
+    bounds: BoundingBox,
+    interior_medium: Option<Arc<dyn Medium>>,
+    bsdf: Option<Arc<dyn Bsdf>>,
+    emitter: Option<Arc<dyn LightMapping>>,
+    is_transparent: bool,
+    is_shadow_catcher: bool,
 }
 
 impl SceneObject {
     pub fn new(object: Box<dyn ModelSpaceObject>, model_matrix: Matrix4x4<f32>) -> Self {
+        Self::new_instance(Arc::from(object), model_matrix)
+    }
+
+    /// Construct an instance of the shared model space object `prototype`
+    /// placed in the scene by the model matrix `model_matrix`.
+    pub fn new_instance(prototype: Arc<dyn ModelSpaceObject>, model_matrix: Matrix4x4<f32>) -> Self {
+        Self::new_animated(prototype, AnimatedTransform::new(model_matrix))
+    }
+
+    /// Construct an instance of the shared model space object `prototype` moving
+    /// through the scene with the animated model matrix `transform`.
+    ///
+    /// The bounding box of a moving object contains the object at all times.
+    pub fn new_animated(prototype: Arc<dyn ModelSpaceObject>, transform: AnimatedTransform) -> Self {
+        let model_matrix = transform.start_matrix();
         let model_matrix_inv = model_matrix.inverse().unwrap();
+        let bounds = transform.bounds(&prototype.bounds());
         
-        Self { object, model_matrix, model_matrix_inv, }
+        Self { 
+            object: prototype, 
+            model_matrix, 
+            model_matrix_inv, 
+            transform,
+            bounds, 
+            interior_medium: None, 
+            bsdf: None, 
+            emitter: None, 
+            is_transparent: false,
+            is_shadow_catcher: false,
+        }
     }
 
+    /// Returns the model space object the scene object is an instance of.
     #[inline]
-    fn ray_world_space_to_model_space(&self, ray: &Ray) -> Ray {
-        let ray_origin_model_space = (self.model_matrix_inv * ray.origin.extend(1_f32)).contract();
-        let ray_direction_model_space = (self.model_matrix_inv * ray.direction.extend(0_f32)).contract();
-        
-        Ray::new(ray_origin_model_space, ray_direction_model_space)
+    pub fn prototype(&self) -> &Arc<dyn ModelSpaceObject> {
+        &self.object
+    }
+
+    /// Override the scattering function of the prototype of the object.
+    pub fn with_bsdf(mut self, bsdf: Arc<dyn Bsdf>) -> Self {
+        self.bsdf = Some(bsdf);
+        self
+    }
+
+    /// Override the emitter of the prototype of the object.
+    pub fn with_emitter(mut self, emitter: Arc<dyn LightMapping>) -> Self {
+        self.emitter = Some(emitter);
+        self
+    }
+
+    /// Let shadow rays through the object, so that it casts no shadows.
+    pub fn with_transparent(mut self, is_transparent: bool) -> Self {
+        self.is_transparent = is_transparent;
+        self
+    }
+
+    /// Make the object a shadow catcher, which casts no shadows and shows only
+    /// the shadows cast onto it.
+    pub fn with_shadow_catcher(mut self, is_shadow_catcher: bool) -> Self {
+        self.is_shadow_catcher = is_shadow_catcher;
+        self
     }
 
     #[inline]
-    fn intersection_query_world_space_to_model_space(&self, query: &IntersectionQuery) -> IntersectionQuery {
-        let ray_model_space = self.ray_world_space_to_model_space(&query.ray);
+    pub fn is_transparent(&self) -> bool {
+        self.is_transparent
+    }
 
-        IntersectionQuery::new(ray_model_space, query.t_min, query.t_max)
+    #[inline]
+    pub fn is_shadow_catcher(&self) -> bool {
+        self.is_shadow_catcher
     }
 
+    /// Determine whether the object blocks shadow rays.
     #[inline]
-    fn intersection_result_model_space_to_world_space(&self, result: &IntersectionResult) -> IntersectionResult {
-        if let IntersectionResult::Hit(result_model_space) = result {
-            let result_t_world_space = result_model_space.t;
-            let result_p_world_space = (self.model_matrix * result_model_space.point.extend(1_f32)).contract();
-            let result_normal_world_space = (self.model_matrix * result_model_space.normal.extend(0_f32)).contract();
-
-            IntersectionResult::new_hit(
-                result_t_world_space,
-                result_p_world_space,
-                result_normal_world_space,
-            )
-        } else if let IntersectionResult::Tangent(result_model_space) = result {
-            let result_t_world_space = result_model_space.t;
-            let result_p_world_space = (self.model_matrix * result_model_space.point.extend(1_f32)).contract();
-            let result_normal_world_space = (self.model_matrix * result_model_space.normal.extend(0_f32)).contract();
-
-            IntersectionResult::new_tangent(
-                result_t_world_space,
-                result_p_world_space,
-                result_normal_world_space,
-            )
-        } else {
-            // The ray missed the object.
-            *result
+    pub fn casts_shadows(&self) -> bool {
+        !self.is_transparent && !self.is_shadow_catcher
+    }
+
+    /// Returns the animated model matrix of the object.
+    #[inline]
+    pub fn transform(&self) -> &AnimatedTransform {
+        &self.transform
+    }
+
+    /// Compute the model matrix of the object and its inverse at a time.
+    fn model_matrices_at(&self, time: f32) -> (Matrix4x4<f32>, Matrix4x4<f32>) {
+        if !self.transform.is_animated() {
+            return (self.model_matrix, self.model_matrix_inv);
+        }
+
+        let model_matrix = self.transform.matrix_at(time);
+        match model_matrix.inverse() {
+            Some(model_matrix_inv) => (model_matrix, model_matrix_inv),
+            None => (self.model_matrix, self.model_matrix_inv),
         }
     }
 
-    pub fn intersect(&self, query: &IntersectionQuery) -> IntersectionResult {
-        let query_model_space = self.intersection_query_world_space_to_model_space(query);
-        let result_model_space = self.object.intersect(&query_model_space);
+    /// Returns the bounding box of the object in world space.
+    #[inline]
+    pub fn bounds(&self) -> BoundingBox {
+        self.bounds
+    }
 
-        self.intersection_result_model_space_to_world_space(&result_model_space)
+    /// Fill the interior of the object with a participating medium. 
+    ///
+    /// The medium is measured in world space.
+    pub fn with_interior_medium(mut self, medium: Arc<dyn Medium>) -> Self {
+        self.interior_medium = Some(medium);
+        self
     }
 
-    fn scattering_query_world_space_to_model_space(&self, query: &ScatteringQuery) -> ScatteringQuery {
-        let ray_incoming_model_space = (self.model_matrix_inv * query.ray_incoming.extend(0_f32)).contract();
-        let point_model_space = (self.model_matrix_inv * query.point.extend(1_f32)).contract();
+    #[inline]
+    pub fn interior_medium(&self) -> Option<&dyn Medium> {
+        self.interior_medium.as_deref()
+    }
 
-        ScatteringQuery::new(ray_incoming_model_space, point_model_space)
+    #[inline]
+    pub fn bsdf(&self) -> &dyn Bsdf {
+        match &self.bsdf {
+            Some(bsdf) => bsdf.as_ref(),
+            None => self.object.bsdf(),
+        }
     }
 
-    fn scattering_result_model_space_to_world_space(&self, result: &ScatteringResult) -> ScatteringResult {
-        let ray_incoming_model_space = (self.model_matrix * result.ray_incoming.extend(0_f32)).contract();
-        let ray_outgoing_model_space = (self.model_matrix * result.ray_outgoing.extend(0_f32)).contract();
-        let point_model_space = (self.model_matrix * result.point.extend(1_f32)).contract();
-        let normal_model_space = (self.model_matrix * result.normal.extend(0_f32)).contract();
+    #[inline]
+    pub fn emitter(&self) -> &dyn LightMapping {
+        match &self.emitter {
+            Some(emitter) => emitter.as_ref(),
+            None => self.object.emitter(),
+        }
+    }
 
-        ScatteringResult::new(
-            ray_incoming_model_space,
-            ray_outgoing_model_space,
-            point_model_space,
-            normal_model_space,
-            result.scattering_fraction,
-            result.emission,
-        )
+    /// Construct the shading frame of a world space intersection with the object.
+    #[inline]
+    fn shading_frame_world_space(&self, hit: &IntersectionData) -> Frame {
+        Frame::from_normal_tangent(&hit.shading_normal.normalize(), &hit.dpdu)
     }
 
-    pub fn scatter(&self, query: &ScatteringQuery, sampler: &mut SphereSampler) -> ScatteringResult {
-        let query_model_space = self.scattering_query_world_space_to_model_space(query);
-        let result_model_space = self.object.scatter(&query_model_space, sampler);
+    fn bsdf_query_world_space(
+        &self, 
+        hit: &IntersectionData, 
+        ray_incoming: &Vector3<f32>, 
+        ray_outgoing: &Vector3<f32>) -> BsdfQuery
+    {
+        let frame = self.shading_frame_world_space(hit);
+        let (_, model_matrix_inv) = self.model_matrices_at(hit.time);
+        let point_model_space = (model_matrix_inv * hit.point.extend(1_f32)).contract();
+        let mut query = BsdfQuery::new(
+            frame.to_local(ray_incoming), 
+            frame.to_local(ray_outgoing), 
+            point_model_space, 
+            hit.uv
+        );
+        query.world_point = hit.point;
+
+        query
+    }
+
+    /// Evaluate the scattering function of the object at a world space 
+    /// intersection for a pair of unit world space directions.
+    pub fn evaluate_bsdf(
+        &self, 
+        hit: &IntersectionData, 
+        ray_incoming: &Vector3<f32>, 
+        ray_outgoing: &Vector3<f32>) -> Vector3<f32>
+    {
+        let query = self.bsdf_query_world_space(hit, ray_incoming, ray_outgoing);
+
+        self.bsdf().evaluate(&query)
+    }
+
+    /// Evaluate the probability density, with respect to solid angle, that 
+    /// [`SceneObject::sample_bsdf`] samples the world space direction 
+    /// `ray_outgoing` given the world space direction `ray_incoming`.
+    pub fn pdf_bsdf(
+        &self, 
+        hit: &IntersectionData, 
+        ray_incoming: &Vector3<f32>, 
+        ray_outgoing: &Vector3<f32>) -> f32
+    {
+        let query = self.bsdf_query_world_space(hit, ray_incoming, ray_outgoing);
+
+        self.bsdf().pdf(&query)
+    }
+
+    /// Sample an outgoing direction from the scattering function of the object at
+    /// a world space intersection. 
+    ///
+    /// Returns the sampled direction in world space together with the sample in 
+    /// the local shading frame, or `None` when the incident light is absorbed.
+    pub fn sample_bsdf(
+        &self, 
+        hit: &IntersectionData, 
+        ray_incoming: &Vector3<f32>, 
+        sampler: &mut SphereSampler) -> Option<(Vector3<f32>, BsdfResult)>
+    {
+        let frame = self.shading_frame_world_space(hit);
+        let (_, model_matrix_inv) = self.model_matrices_at(hit.time);
+        let point_model_space = (model_matrix_inv * hit.point.extend(1_f32)).contract();
+        let mut query = BsdfSampleQuery::new(frame.to_local(ray_incoming), point_model_space, hit.uv);
+        query.world_point = hit.point;
+
+        self.bsdf()
+            .sample(&query, sampler)
+            .map(|result| (frame.to_world(&result.ray_outgoing), result))
+    }
+
+    /// Evaluate the radiance the object emits at a world space intersection 
+    /// towards the origin of the ray arriving in the direction `ray_incoming`.
+    pub fn emission(&self, hit: &IntersectionData, ray_incoming: &Vector3<f32>) -> Vector3<f32> {
+        let query = LightingQuery::new(*ray_incoming, hit.point);
+
+        self.emitter().emit(&query).radiance
+    }
+
+    /// Transform a surface normal from model space to world space.
+    ///
+    /// Normals transform by the inverse transpose of the model matrix so that they
+    /// stay perpendicular to the surface under nonuniform scaling and shearing.
+    #[inline]
+    fn normal_model_space_to_world_space(normal: &Vector3<f32>, model_matrix_inv: &Matrix4x4<f32>) -> Vector3<f32> {
+        (model_matrix_inv.transpose() * normal.extend(0_f32)).contract().normalize()
+    }
+
+    /// Transform a ray from world space to model space. 
+    ///
+    /// The direction of the model space ray is not renormalized, so the ray 
+    /// parameter of a point is the same along both rays, and the ray parameters 
+    /// of the intersections of an object in model space are their ray parameters 
+    /// in world space.
+    #[inline]
+    fn ray_world_space_to_model_space(ray: &Ray, model_matrix_inv: &Matrix4x4<f32>) -> Ray {
+        let ray_origin_model_space = (model_matrix_inv * ray.origin.extend(1_f32)).contract();
+        let ray_direction_model_space = (model_matrix_inv * ray.direction.extend(0_f32)).contract();
         
-        self.scattering_result_model_space_to_world_space(&result_model_space)
+        Ray::new(ray_origin_model_space, ray_direction_model_space).with_time(ray.time)
+    }
+
+    #[inline]
+    fn intersection_query_world_space_to_model_space(query: &IntersectionQuery, model_matrix_inv: &Matrix4x4<f32>) -> IntersectionQuery {
+        let ray_model_space = Self::ray_world_space_to_model_space(&query.ray, model_matrix_inv);
+
+        IntersectionQuery::new(ray_model_space, query.t_min, query.t_max)
+    }
+
+    #[inline]
+    fn intersection_data_model_space_to_world_space(
+        data: &IntersectionData, 
+        model_matrix: &Matrix4x4<f32>, 
+        model_matrix_inv: &Matrix4x4<f32>, 
+        time: f32) -> IntersectionData
+    {
+        let t_world_space = data.t;
+        let point_world_space = (model_matrix * data.point.extend(1_f32)).contract();
+        let normal_world_space = Self::normal_model_space_to_world_space(&data.normal, model_matrix_inv);
+        let shading_normal_world_space = Self::normal_model_space_to_world_space(&data.shading_normal, model_matrix_inv);
+        let dpdu_world_space = (model_matrix * data.dpdu.extend(0_f32)).contract();
+        let dpdv_world_space = (model_matrix * data.dpdv.extend(0_f32)).contract();
+        let mut data_world_space = IntersectionData::from_surface(
+            t_world_space,
+            point_world_space,
+            normal_world_space,
+            shading_normal_world_space,
+            dpdu_world_space,
+            dpdv_world_space,
+            data.uv,
+        );
+        data_world_space.point_error = transform_point_error(model_matrix, &data.point, &data.point_error);
+        data_world_space.barycentrics = data.barycentrics;
+        data_world_space.time = time;
+        // The inverse transpose keeps the sign of the dot product of a normal 
+        // with a direction, so the ray hits the same side in world space.
+        data_world_space.is_front_face = data.is_front_face;
+
+        data_world_space
+    }
+
+    #[inline]
+    fn intersection_result_model_space_to_world_space(
+        result: &IntersectionResult, 
+        model_matrix: &Matrix4x4<f32>, 
+        model_matrix_inv: &Matrix4x4<f32>, 
+        time: f32) -> IntersectionResult
+    {
+        match result {
+            IntersectionResult::Hit(result_model_space) => {
+                let data = Self::intersection_data_model_space_to_world_space(result_model_space, model_matrix, model_matrix_inv, time);
+                IntersectionResult::Hit(data)
+            }
+            IntersectionResult::Tangent(result_model_space) => {
+                let data = Self::intersection_data_model_space_to_world_space(result_model_space, model_matrix, model_matrix_inv, time);
+                IntersectionResult::Tangent(data)
+            }
+            // The ray missed the object.
+            IntersectionResult::Miss(_) => *result,
+        }
+    }
+
+    pub fn intersect(&self, query: &IntersectionQuery) -> IntersectionResult {
+        let (model_matrix, model_matrix_inv) = self.model_matrices_at(query.ray.time);
+        let query_model_space = Self::intersection_query_world_space_to_model_space(query, &model_matrix_inv);
+        let result_model_space = self.object.intersect(&query_model_space);
+
+        Self::intersection_result_model_space_to_world_space(&result_model_space, &model_matrix, &model_matrix_inv, query.ray.time)
+    }
+
+    /// Scatter a ray off the object at a world space intersection.
+    ///
+    /// The scattering function is sampled in the world space shading frame of 
+    /// the intersection, so the sampled directions follow the same distribution 
+    /// that [`SceneObject::evaluate_bsdf`] and [`SceneObject::pdf_bsdf`] describe,
+    /// whatever the model matrix of the object.
+    pub fn scatter(&self, query: &ScatteringQuery, sampler: &mut SphereSampler) -> ScatteringResult {
+        let (_, model_matrix_inv) = self.model_matrices_at(query.time);
+        let mut query_model_space = *query;
+        query_model_space.point = (model_matrix_inv * query.point.extend(1_f32)).contract();
+        query_model_space.world_point = query.point;
+
+        scatter_from_surface(self.bsdf(), self.emitter(), &query_model_space, sampler)
     }
 
     #[inline]
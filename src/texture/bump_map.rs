@@ -0,0 +1,71 @@
+use crate::core::*;
+use cglinalg::{
+    Vector2,
+    Vector3,
+    Magnitude,
+};
+use std::sync::Arc;
+
+
+/// A bump map that perturbs the shading normal as though the surface were 
+/// displaced along its normal by a height field.
+///
+/// The slope of the height field is estimated by finite differences of the 
+/// height texture along the surface parameters.
+#[derive(Clone, Debug)]
+pub struct BumpMap {
+    height: Arc<dyn Texture<f32>>,
+    /// The displacement of the surface per unit of height.
+    scale: f32,
+    /// The step in the surface parameters used for the finite differences.
+    delta: f32,
+}
+
+impl BumpMap {
+    pub fn new(height: Arc<dyn Texture<f32>>, scale: f32) -> Self {
+        Self { height, scale, delta: 1_f32 / 1024_f32, }
+    }
+
+    /// Construct a bump map with a given finite difference step in the surface
+    /// parameters. The step should be about the size of a texel of the height
+    /// texture.
+    pub fn with_delta(height: Arc<dyn Texture<f32>>, scale: f32, delta: f32) -> Self {
+        Self { height, scale, delta, }
+    }
+}
+
+impl NormalMapping for BumpMap {
+    fn shading_normal(&self, hit: &IntersectionData) -> Vector3<f32> {
+        let normal = hit.shading_normal;
+        let height = self.height.evaluate(&TextureQuery::new(hit.point, hit.uv));
+        let height_u = self.height.evaluate(&TextureQuery::new(
+            hit.point + hit.dpdu * self.delta,
+            hit.uv + Vector2::new(self.delta, 0_f32),
+        ));
+        let height_v = self.height.evaluate(&TextureQuery::new(
+            hit.point + hit.dpdv * self.delta,
+            hit.uv + Vector2::new(0_f32, self.delta),
+        ));
+        let dhdu = self.scale * (height_u - height) / self.delta;
+        let dhdv = self.scale * (height_v - height) / self.delta;
+
+        // Differentiate the displaced surface `p + h * n`, neglecting the change in
+        // the normal itself, which is small for surfaces of low curvature.
+        let displaced_dpdu = hit.dpdu + normal * dhdu;
+        let displaced_dpdv = hit.dpdv + normal * dhdv;
+        let shading_normal = displaced_dpdu.cross(&displaced_dpdv);
+        let magnitude = shading_normal.magnitude();
+        if magnitude > 0_f32 && magnitude.is_finite() {
+            // The cross product follows the handedness of the parameterization, which
+            // need not agree with the orientation of the surface.
+            let shading_normal = shading_normal / magnitude;
+            if shading_normal.dot(&normal) < 0_f32 {
+                -shading_normal
+            } else {
+                shading_normal
+            }
+        } else {
+            normal
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use crate::core::*;
+use cglinalg::{
+    Vector2,
+};
+use std::sync::Arc;
+
+
+/// A texture that alternates between two textures in a checkerboard pattern over
+/// the surface parameters.
+#[derive(Clone, Debug)]
+pub struct CheckerboardTexture<T> {
+    even: Arc<dyn Texture<T>>,
+    odd: Arc<dyn Texture<T>>,
+    /// The number of checks along each surface parameter per unit length.
+    frequency: Vector2<f32>,
+}
+
+impl<T> CheckerboardTexture<T> {
+    pub fn new(even: Arc<dyn Texture<T>>, odd: Arc<dyn Texture<T>>, frequency: Vector2<f32>) -> Self {
+        Self { even, odd, frequency, }
+    }
+}
+
+impl<T> Texture<T> for CheckerboardTexture<T> 
+where
+    T: std::fmt::Debug,
+{
+    fn evaluate(&self, query: &TextureQuery) -> T {
+        let check_u = f32::floor(query.uv.x * self.frequency.x) as i64;
+        let check_v = f32::floor(query.uv.y * self.frequency.y) as i64;
+        if (check_u + check_v).rem_euclid(2) == 0 {
+            self.even.evaluate(query)
+        } else {
+            self.odd.evaluate(query)
+        }
+    }
+}
+
+/// A solid texture that alternates between two textures in a three dimensional
+/// checkerboard pattern, so that the pattern does not depend on the surface
+/// parameterization.
+#[derive(Clone, Debug)]
+pub struct SolidCheckerboardTexture<T> {
+    even: Arc<dyn Texture<T>>,
+    odd: Arc<dyn Texture<T>>,
+    /// The number of checks along each axis per unit length.
+    frequency: f32,
+    space: TextureSpace,
+}
+
+impl<T> SolidCheckerboardTexture<T> {
+    pub fn new(even: Arc<dyn Texture<T>>, odd: Arc<dyn Texture<T>>, frequency: f32, space: TextureSpace) -> Self {
+        Self { even, odd, frequency, space, }
+    }
+}
+
+impl<T> Texture<T> for SolidCheckerboardTexture<T> 
+where
+    T: std::fmt::Debug,
+{
+    fn evaluate(&self, query: &TextureQuery) -> T {
+        let point = query.point_in(self.space) * self.frequency;
+        let check_x = f32::floor(point.x) as i64;
+        let check_y = f32::floor(point.y) as i64;
+        let check_z = f32::floor(point.z) as i64;
+        if (check_x + check_y + check_z).rem_euclid(2) == 0 {
+            self.even.evaluate(query)
+        } else {
+            self.odd.evaluate(query)
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use crate::core::*;
+
+
+/// A texture that takes the same value everywhere.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ConstantTexture<T> {
+    pub value: T,
+}
+
+impl<T> ConstantTexture<T> {
+    pub fn new(value: T) -> Self {
+        Self { value, }
+    }
+}
+
+impl<T> Texture<T> for ConstantTexture<T> 
+where
+    T: Copy + std::fmt::Debug,
+{
+    #[inline]
+    fn evaluate(&self, _query: &TextureQuery) -> T {
+        self.value
+    }
+}
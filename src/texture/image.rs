@@ -0,0 +1,253 @@
+use cglinalg::{
+    Vector3,
+};
+use std::fs::File;
+use std::io;
+use std::io::{
+    BufReader,
+    Read,
+};
+use std::path::Path;
+
+
+/// The encoding of the color values stored in an image file.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ColorEncoding {
+    /// The values are encoded with the sRGB transfer function, as is typical for
+    /// color images.
+    Srgb,
+    /// The values are stored as is, as is typical for non-color data such as
+    /// normal maps or roughness maps.
+    Linear,
+}
+
+impl ColorEncoding {
+    /// Decode an 8-bit encoded value into a linear value in `[0, 1]`.
+    #[inline]
+    pub fn decode(&self, value: u8) -> f32 {
+        let value = (value as f32) / 255_f32;
+        match *self {
+            ColorEncoding::Srgb => {
+                if value <= 0.04045 {
+                    value / 12.92
+                } else {
+                    f32::powf((value + 0.055) / 1.055, 2.4)
+                }
+            }
+            ColorEncoding::Linear => value,
+        }
+    }
+}
+
+/// A two dimensional array of linear RGB values.
+///
+/// Pixels are stored in row-major order, starting from the top row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    data: Vec<Vector3<f32>>,
+}
+
+impl Image {
+    /// Construct an image from its pixels in row-major order, starting from the
+    /// top row.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the number of pixels does not match the dimensions
+    /// of the image.
+    pub fn new(width: usize, height: usize, data: Vec<Vector3<f32>>) -> Self {
+        assert_eq!(data.len(), width * height, "An image of dimensions {}x{} must have {} pixels", width, height, width * height);
+
+        Self { width, height, data, }
+    }
+
+    /// Construct an image whose pixels all have the same value.
+    pub fn from_fill(width: usize, height: usize, value: Vector3<f32>) -> Self {
+        Self::new(width, height, vec![value; width * height])
+    }
+
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the pixel in column `x` and row `y`, counting rows from the top.
+    #[inline]
+    pub fn pixel(&self, x: usize, y: usize) -> Vector3<f32> {
+        self.data[y * self.width + x]
+    }
+
+    #[inline]
+    pub fn set_pixel(&mut self, x: usize, y: usize, value: Vector3<f32>) {
+        self.data[y * self.width + x] = value;
+    }
+
+    /// Load an image from a file, choosing the file format from the file
+    /// extension. Supported formats are PNG (`.png`) and PFM (`.pfm`).
+    ///
+    /// The color encoding only applies to formats that store encoded values.
+    pub fn from_file<P: AsRef<Path>>(path: P, encoding: ColorEncoding) -> io::Result<Image> {
+        let extension = path.as_ref()
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("png") => Self::from_png_file(path, encoding),
+            Some("pfm") => Self::from_pfm_file(path),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unsupported image file format: {}", path.as_ref().display())
+            )),
+        }
+    }
+
+    /// Load an image from a PNG file.
+    pub fn from_png_file<P: AsRef<Path>>(path: P, encoding: ColorEncoding) -> io::Result<Image> {
+        let file = File::open(path)?;
+
+        Self::from_png(BufReader::new(file), encoding)
+    }
+
+    /// Decode an image in PNG format.
+    ///
+    /// Grayscale images are expanded to RGB, the alpha channel is discarded, and
+    /// 16-bit channels are reduced to 8 bits.
+    pub fn from_png<R: Read>(reader: R, encoding: ColorEncoding) -> io::Result<Image> {
+        fn to_io_error(error: png::DecodingError) -> io::Error {
+            io::Error::new(io::ErrorKind::InvalidData, error)
+        }
+
+        let mut decoder = png::Decoder::new(reader);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut png_reader = decoder.read_info().map_err(to_io_error)?;
+        let mut buffer = vec![0; png_reader.output_buffer_size()];
+        let info = png_reader.next_frame(&mut buffer).map_err(to_io_error)?;
+        let channels = match info.color_type {
+            png::ColorType::Grayscale => 1,
+            png::ColorType::GrayscaleAlpha => 2,
+            png::ColorType::Rgb => 3,
+            png::ColorType::Rgba => 4,
+            png::ColorType::Indexed => {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "Unexpanded indexed PNG image"));
+            }
+        };
+
+        let width = info.width as usize;
+        let height = info.height as usize;
+        let mut data = Vec::with_capacity(width * height);
+        for row in buffer.chunks(info.line_size).take(height) {
+            for pixel in row.chunks(channels).take(width) {
+                let value = if channels < 3 {
+                    Vector3::from_fill(encoding.decode(pixel[0]))
+                } else {
+                    Vector3::new(encoding.decode(pixel[0]), encoding.decode(pixel[1]), encoding.decode(pixel[2]))
+                };
+                data.push(value);
+            }
+        }
+
+        Ok(Self::new(width, height, data))
+    }
+
+    /// Load an image from a PFM file.
+    pub fn from_pfm_file<P: AsRef<Path>>(path: P) -> io::Result<Image> {
+        let file = File::open(path)?;
+
+        Self::from_pfm(BufReader::new(file))
+    }
+
+    /// Decode an image in portable float map (PFM) format.
+    ///
+    /// Grayscale images are expanded to RGB.
+    pub fn from_pfm<R: Read>(mut reader: R) -> io::Result<Image> {
+        fn invalid_data(message: &str) -> io::Error {
+            io::Error::new(io::ErrorKind::InvalidData, format!("Invalid PFM image: {}", message))
+        }
+
+        fn next_token<'a>(bytes: &'a [u8], cursor: &mut usize) -> io::Result<&'a str> {
+            while *cursor < bytes.len() && bytes[*cursor].is_ascii_whitespace() {
+                *cursor += 1;
+            }
+            let start = *cursor;
+            while *cursor < bytes.len() && !bytes[*cursor].is_ascii_whitespace() {
+                *cursor += 1;
+            }
+            if start == *cursor {
+                return Err(invalid_data("unexpected end of header"));
+            }
+
+            std::str::from_utf8(&bytes[start..*cursor]).map_err(|_| invalid_data("header is not ASCII"))
+        }
+
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        let mut cursor = 0;
+        let channels = match next_token(&bytes, &mut cursor)? {
+            "PF" => 3,
+            "Pf" => 1,
+            _ => return Err(invalid_data("unknown magic number")),
+        };
+        let width = next_token(&bytes, &mut cursor)?
+            .parse::<usize>()
+            .map_err(|_| invalid_data("invalid width"))?;
+        let height = next_token(&bytes, &mut cursor)?
+            .parse::<usize>()
+            .map_err(|_| invalid_data("invalid height"))?;
+        let scale = next_token(&bytes, &mut cursor)?
+            .parse::<f32>()
+            .map_err(|_| invalid_data("invalid scale"))?;
+        // Exactly one whitespace character separates the header from the raster.
+        cursor += 1;
+
+        let is_little_endian = scale < 0_f32;
+        let raster = bytes.get(cursor..).unwrap_or(&[]);
+        if width == 0 || height == 0 {
+            return Err(invalid_data("a dimension is zero"));
+        }
+
+        let expected_len = width
+            .checked_mul(height)
+            .and_then(|len| len.checked_mul(channels * 4))
+            .ok_or_else(|| invalid_data("dimensions are too large"))?;
+        // The raster must hold every pixel before the image is allocated, so that 
+        // the dimensions of a malformed header cannot ask for a huge image.
+        if raster.len() < expected_len {
+            return Err(invalid_data("raster is truncated"));
+        }
+
+        let mut values = raster[..expected_len].chunks_exact(4).map(|chunk| {
+            let bytes = [chunk[0], chunk[1], chunk[2], chunk[3]];
+            if is_little_endian {
+                f32::from_le_bytes(bytes)
+            } else {
+                f32::from_be_bytes(bytes)
+            }
+        });
+
+        // The raster of a PFM image starts from the bottom row.
+        let mut image = Self::from_fill(width, height, Vector3::zero());
+        for y in (0..height).rev() {
+            for x in 0..width {
+                let value = if channels == 1 {
+                    Vector3::from_fill(values.next().unwrap())
+                } else {
+                    let r = values.next().unwrap();
+                    let g = values.next().unwrap();
+                    let b = values.next().unwrap();
+                    Vector3::new(r, g, b)
+                };
+                image.set_pixel(x, y, value);
+            }
+        }
+
+        Ok(image)
+    }
+}
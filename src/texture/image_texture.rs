@@ -0,0 +1,127 @@
+use crate::core::*;
+use crate::texture::*;
+use cglinalg::{
+    Vector2,
+    Vector3,
+};
+use std::sync::Arc;
+
+
+/// The way texture lookups outside the unit square of surface parameters map
+/// back onto the image.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WrapMode {
+    /// Tile the image.
+    Repeat,
+    /// Tile the image, mirroring every other tile.
+    Mirror,
+    /// Extend the edges of the image.
+    Clamp,
+}
+
+impl WrapMode {
+    /// Map a texel coordinate onto the range `[0, size)`.
+    #[inline]
+    fn wrap(&self, coordinate: i64, size: usize) -> usize {
+        let size = size as i64;
+        let wrapped = match *self {
+            WrapMode::Repeat => coordinate.rem_euclid(size),
+            WrapMode::Mirror => {
+                let period = coordinate.rem_euclid(2 * size);
+                if period >= size {
+                    2 * size - 1 - period
+                } else {
+                    period
+                }
+            }
+            WrapMode::Clamp => coordinate.clamp(0, size - 1),
+        };
+
+        wrapped as usize
+    }
+}
+
+/// The way texture lookups between texel centers are reconstructed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FilterMode {
+    /// Use the value of the nearest texel.
+    Nearest,
+    /// Linearly interpolate between the four nearest texels.
+    Bilinear,
+}
+
+/// A texture that looks up its values in an image by the surface parameters.
+///
+/// The surface parameters `(0, 0)` map to the bottom left corner of the image,
+/// and `(1, 1)` map to the top right corner of the image.
+#[derive(Clone, Debug)]
+pub struct ImageTexture {
+    image: Arc<Image>,
+    wrap_mode: WrapMode,
+    filter_mode: FilterMode,
+}
+
+impl ImageTexture {
+    pub fn new(image: Arc<Image>, wrap_mode: WrapMode, filter_mode: FilterMode) -> Self {
+        Self { image, wrap_mode, filter_mode, }
+    }
+
+    #[inline]
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+
+    #[inline]
+    fn texel(&self, x: i64, y: i64) -> Vector3<f32> {
+        let x_wrapped = self.wrap_mode.wrap(x, self.image.width());
+        let y_wrapped = self.wrap_mode.wrap(y, self.image.height());
+
+        self.image.pixel(x_wrapped, y_wrapped)
+    }
+
+    /// Look up the filtered value of the image at the surface parameters `uv`.
+    pub fn lookup(&self, uv: &Vector2<f32>) -> Vector3<f32> {
+        let width = self.image.width() as f32;
+        let height = self.image.height() as f32;
+        // Image rows start from the top, while the `v` surface parameter starts from
+        // the bottom. Texel centers lie at half-integer coordinates.
+        let x = uv.x * width - 0.5;
+        let y = (1_f32 - uv.y) * height - 0.5;
+        match self.filter_mode {
+            FilterMode::Nearest => {
+                self.texel(f32::round(x) as i64, f32::round(y) as i64)
+            }
+            FilterMode::Bilinear => {
+                let x0 = f32::floor(x);
+                let y0 = f32::floor(y);
+                let dx = x - x0;
+                let dy = y - y0;
+                let x0 = x0 as i64;
+                let y0 = y0 as i64;
+
+                self.texel(x0, y0) * ((1_f32 - dx) * (1_f32 - dy)) +
+                    self.texel(x0 + 1, y0) * (dx * (1_f32 - dy)) +
+                    self.texel(x0, y0 + 1) * ((1_f32 - dx) * dy) +
+                    self.texel(x0 + 1, y0 + 1) * (dx * dy)
+            }
+        }
+    }
+}
+
+impl Texture<Vector3<f32>> for ImageTexture {
+    #[inline]
+    fn evaluate(&self, query: &TextureQuery) -> Vector3<f32> {
+        self.lookup(&query.uv)
+    }
+}
+
+impl Texture<f32> for ImageTexture {
+    /// Evaluate the image as scalar data. The scalar value of a pixel is its
+    /// luminance, which is the common value of the channels of a grayscale image.
+    #[inline]
+    fn evaluate(&self, query: &TextureQuery) -> f32 {
+        let value = self.lookup(&query.uv);
+
+        0.2126 * value.x + 0.7152 * value.y + 0.0722 * value.z
+    }
+}
@@ -0,0 +1,67 @@
+use crate::core::*;
+use crate::texture::*;
+use cglinalg::{
+    Vector3,
+};
+
+
+/// A solid texture of marble veins.
+///
+/// The veins are bands of a sine wave along the **x-axis** whose phase is 
+/// disturbed by turbulence. As a scalar texture it takes values in `[0, 1]`, 
+/// with zero in the base color and one in the veins.
+#[derive(Clone, Debug)]
+pub struct MarbleTexture {
+    noise: PerlinNoise,
+    /// The spatial frequency of the pattern.
+    scale: f32,
+    /// The number of octaves of turbulence.
+    octaves: usize,
+    /// The amount by which turbulence distorts the veins.
+    variation: f32,
+    base_color: Vector3<f32>,
+    vein_color: Vector3<f32>,
+    space: TextureSpace,
+}
+
+impl MarbleTexture {
+    pub fn new(seed: u64, scale: f32, base_color: Vector3<f32>, vein_color: Vector3<f32>) -> Self {
+        Self { 
+            noise: PerlinNoise::new(seed), 
+            scale, 
+            octaves: 6, 
+            variation: 4_f32, 
+            base_color, 
+            vein_color, 
+            space: TextureSpace::Object,
+        }
+    }
+
+    pub fn with_turbulence(mut self, octaves: usize, variation: f32) -> Self {
+        self.octaves = octaves;
+        self.variation = variation;
+        self
+    }
+
+    pub fn with_space(mut self, space: TextureSpace) -> Self {
+        self.space = space;
+        self
+    }
+}
+
+impl Texture<f32> for MarbleTexture {
+    fn evaluate(&self, query: &TextureQuery) -> f32 {
+        let point = query.point_in(self.space) * self.scale;
+        let phase = point.x + self.variation * turbulence(&self.noise, &point, self.octaves, 2_f32, 0.5);
+
+        0.5 * (1_f32 - f32::cos(std::f32::consts::PI * phase))
+    }
+}
+
+impl Texture<Vector3<f32>> for MarbleTexture {
+    fn evaluate(&self, query: &TextureQuery) -> Vector3<f32> {
+        let t = <Self as Texture<f32>>::evaluate(self, query);
+
+        self.base_color * (1_f32 - t) + self.vein_color * t
+    }
+}
@@ -0,0 +1,24 @@
+mod bump_map;
+mod checkerboard_texture;
+mod constant_texture;
+mod image;
+mod image_texture;
+mod marble_texture;
+mod noise;
+mod noise_texture;
+mod normal_map;
+mod wood_texture;
+mod worley_texture;
+
+
+pub use bump_map::*;
+pub use checkerboard_texture::*;
+pub use constant_texture::*;
+pub use image::*;
+pub use image_texture::*;
+pub use marble_texture::*;
+pub use noise::*;
+pub use noise_texture::*;
+pub use normal_map::*;
+pub use wood_texture::*;
+pub use worley_texture::*;
@@ -0,0 +1,317 @@
+use rand::prelude::*;
+use cglinalg::{
+    Vector3,
+    Magnitude,
+};
+
+
+/// A seeded three dimensional noise function taking values in `[-1, 1]`.
+pub trait Noise: std::fmt::Debug {
+    /// Evaluate the noise function at a point.
+    fn noise(&self, point: &Vector3<f32>) -> f32;
+}
+
+/// Generate a doubled permutation table of the integers `0..256` from a seed.
+fn permutation_table(seed: u64) -> Vec<u8> {
+    let mut rng = rand_isaac::Isaac64Rng::seed_from_u64(seed);
+    let mut table: Vec<u8> = (0..=255).collect();
+    table.shuffle(&mut rng);
+
+    table.iter().chain(table.iter()).copied().collect()
+}
+
+/// Seeded three dimensional gradient noise.
+///
+/// This is Ken Perlin's improved noise function, with a permutation table
+/// generated from a seed so that distinct seeds give uncorrelated patterns.
+#[derive(Clone, Debug)]
+pub struct PerlinNoise {
+    permutation: Vec<u8>,
+}
+
+impl PerlinNoise {
+    pub fn new(seed: u64) -> Self {
+        Self { permutation: permutation_table(seed), }
+    }
+
+    #[inline]
+    fn hash(&self, x: usize, y: usize, z: usize) -> u8 {
+        let p = &self.permutation;
+
+        p[p[p[x] as usize + y] as usize + z]
+    }
+
+    /// Evaluate the sum of `octaves` octaves of the noise function, each at twice
+    /// the frequency and half the amplitude of the previous one. The result is
+    /// normalized to lie in `[-1, 1]`.
+    pub fn fractal_sum(&self, point: &Vector3<f32>, octaves: usize) -> f32 {
+        fbm(self, point, octaves, 2_f32, 0.5)
+    }
+}
+
+impl Noise for PerlinNoise {
+    fn noise(&self, point: &Vector3<f32>) -> f32 {
+        #[inline]
+        fn fade(t: f32) -> f32 {
+            t * t * t * (t * (t * 6_f32 - 15_f32) + 10_f32)
+        }
+
+        #[inline]
+        fn lerp(t: f32, a: f32, b: f32) -> f32 {
+            a + t * (b - a)
+        }
+
+        #[inline]
+        fn gradient(hash: u8, x: f32, y: f32, z: f32) -> f32 {
+            let h = hash & 15;
+            let u = if h < 8 { x } else { y };
+            let v = if h < 4 { y } else if h == 12 || h == 14 { x } else { z };
+            let u_signed = if h & 1 == 0 { u } else { -u };
+            let v_signed = if h & 2 == 0 { v } else { -v };
+
+            u_signed + v_signed
+        }
+
+        let x_floor = f32::floor(point.x);
+        let y_floor = f32::floor(point.y);
+        let z_floor = f32::floor(point.z);
+        let xi = (x_floor as i64).rem_euclid(256) as usize;
+        let yi = (y_floor as i64).rem_euclid(256) as usize;
+        let zi = (z_floor as i64).rem_euclid(256) as usize;
+        let x = point.x - x_floor;
+        let y = point.y - y_floor;
+        let z = point.z - z_floor;
+        let u = fade(x);
+        let v = fade(y);
+        let w = fade(z);
+
+        let value = lerp(w,
+            lerp(v,
+                lerp(u, gradient(self.hash(xi, yi, zi), x, y, z), gradient(self.hash(xi + 1, yi, zi), x - 1_f32, y, z)),
+                lerp(u, gradient(self.hash(xi, yi + 1, zi), x, y - 1_f32, z), gradient(self.hash(xi + 1, yi + 1, zi), x - 1_f32, y - 1_f32, z)),
+            ),
+            lerp(v,
+                lerp(u, gradient(self.hash(xi, yi, zi + 1), x, y, z - 1_f32), gradient(self.hash(xi + 1, yi, zi + 1), x - 1_f32, y, z - 1_f32)),
+                lerp(u, gradient(self.hash(xi, yi + 1, zi + 1), x, y - 1_f32, z - 1_f32), gradient(self.hash(xi + 1, yi + 1, zi + 1), x - 1_f32, y - 1_f32, z - 1_f32)),
+            ),
+        );
+
+        f32::clamp(value, -1_f32, 1_f32)
+    }
+}
+
+/// Seeded three dimensional simplex noise.
+///
+/// Simplex noise interpolates gradients over the corners of a tetrahedral
+/// lattice rather than a cubic one, which makes it cheaper to evaluate than
+/// gradient noise and free of axis-aligned artifacts.
+#[derive(Clone, Debug)]
+pub struct SimplexNoise {
+    permutation: Vec<u8>,
+}
+
+impl SimplexNoise {
+    pub fn new(seed: u64) -> Self {
+        Self { permutation: permutation_table(seed), }
+    }
+
+    #[inline]
+    fn hash(&self, x: usize, y: usize, z: usize) -> usize {
+        let p = &self.permutation;
+
+        p[p[p[x] as usize + y] as usize + z] as usize
+    }
+}
+
+impl Noise for SimplexNoise {
+    fn noise(&self, point: &Vector3<f32>) -> f32 {
+        const GRADIENTS: [[f32; 3]; 12] = [
+            [1_f32, 1_f32, 0_f32], [-1_f32, 1_f32, 0_f32], [1_f32, -1_f32, 0_f32], [-1_f32, -1_f32, 0_f32],
+            [1_f32, 0_f32, 1_f32], [-1_f32, 0_f32, 1_f32], [1_f32, 0_f32, -1_f32], [-1_f32, 0_f32, -1_f32],
+            [0_f32, 1_f32, 1_f32], [0_f32, -1_f32, 1_f32], [0_f32, 1_f32, -1_f32], [0_f32, -1_f32, -1_f32],
+        ];
+        // The skewing and unskewing factors for three dimensions.
+        const F3: f32 = 1_f32 / 3_f32;
+        const G3: f32 = 1_f32 / 6_f32;
+
+        #[inline]
+        fn corner(gradient_index: usize, x: f32, y: f32, z: f32) -> f32 {
+            let t = 0.6 - x * x - y * y - z * z;
+            if t < 0_f32 {
+                0_f32
+            } else {
+                let gradient = GRADIENTS[gradient_index % 12];
+                let t_squared = t * t;
+
+                t_squared * t_squared * (gradient[0] * x + gradient[1] * y + gradient[2] * z)
+            }
+        }
+
+        // Find the simplex cell containing the point by skewing the input space.
+        let skew = (point.x + point.y + point.z) * F3;
+        let i = f32::floor(point.x + skew);
+        let j = f32::floor(point.y + skew);
+        let k = f32::floor(point.z + skew);
+        let unskew = (i + j + k) * G3;
+        let x0 = point.x - (i - unskew);
+        let y0 = point.y - (j - unskew);
+        let z0 = point.z - (k - unskew);
+
+        // Determine which of the six tetrahedra of the cell contains the point.
+        let (i1, j1, k1, i2, j2, k2) = if x0 >= y0 {
+            if y0 >= z0 {
+                (1, 0, 0, 1, 1, 0)
+            } else if x0 >= z0 {
+                (1, 0, 0, 1, 0, 1)
+            } else {
+                (0, 0, 1, 1, 0, 1)
+            }
+        } else if y0 < z0 {
+            (0, 0, 1, 0, 1, 1)
+        } else if x0 < z0 {
+            (0, 1, 0, 0, 1, 1)
+        } else {
+            (0, 1, 0, 1, 1, 0)
+        };
+
+        let x1 = x0 - i1 as f32 + G3;
+        let y1 = y0 - j1 as f32 + G3;
+        let z1 = z0 - k1 as f32 + G3;
+        let x2 = x0 - i2 as f32 + 2_f32 * G3;
+        let y2 = y0 - j2 as f32 + 2_f32 * G3;
+        let z2 = z0 - k2 as f32 + 2_f32 * G3;
+        let x3 = x0 - 1_f32 + 3_f32 * G3;
+        let y3 = y0 - 1_f32 + 3_f32 * G3;
+        let z3 = z0 - 1_f32 + 3_f32 * G3;
+
+        let ii = (i as i64).rem_euclid(256) as usize;
+        let jj = (j as i64).rem_euclid(256) as usize;
+        let kk = (k as i64).rem_euclid(256) as usize;
+        let value = corner(self.hash(ii, jj, kk), x0, y0, z0) +
+            corner(self.hash(ii + i1, jj + j1, kk + k1), x1, y1, z1) +
+            corner(self.hash(ii + i2, jj + j2, kk + k2), x2, y2, z2) +
+            corner(self.hash(ii + 1, jj + 1, kk + 1), x3, y3, z3);
+
+        // Scale the sum of the corner contributions to cover `[-1, 1]`.
+        f32::clamp(32_f32 * value, -1_f32, 1_f32)
+    }
+}
+
+/// The distances from a point to the nearest feature points of cellular noise.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WorleyDistances {
+    /// The distance to the nearest feature point.
+    pub f1: f32,
+    /// The distance to the second nearest feature point.
+    pub f2: f32,
+}
+
+/// Seeded three dimensional cellular noise.
+///
+/// Steven Worley's cellular noise scatters one feature point at random in each
+/// cell of the integer lattice, and measures the distances from a point to the
+/// nearest feature points.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WorleyNoise {
+    seed: u64,
+}
+
+impl WorleyNoise {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, }
+    }
+
+    /// Hash a lattice cell into a stream of random bits with the `SplitMix64`
+    /// finalizer.
+    #[inline]
+    fn hash(&self, x: i64, y: i64, z: i64) -> u64 {
+        let mut state = self.seed
+            ^ (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+            ^ (z as u64).wrapping_mul(0x1656_67B1_9E37_79F9);
+        state = (state ^ (state >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        state = (state ^ (state >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+        state ^ (state >> 31)
+    }
+
+    /// Returns the position of the feature point of a lattice cell.
+    #[inline]
+    fn feature_point(&self, x: i64, y: i64, z: i64) -> Vector3<f32> {
+        let bits = self.hash(x, y, z);
+        let offset_x = ((bits & 0x1F_FFFF) as f32) / (0x20_0000 as f32);
+        let offset_y = (((bits >> 21) & 0x1F_FFFF) as f32) / (0x20_0000 as f32);
+        let offset_z = (((bits >> 42) & 0x1F_FFFF) as f32) / (0x20_0000 as f32);
+
+        Vector3::new(x as f32 + offset_x, y as f32 + offset_y, z as f32 + offset_z)
+    }
+
+    /// Compute the distances from a point to its two nearest feature points.
+    pub fn distances(&self, point: &Vector3<f32>) -> WorleyDistances {
+        let cell_x = f32::floor(point.x) as i64;
+        let cell_y = f32::floor(point.y) as i64;
+        let cell_z = f32::floor(point.z) as i64;
+        let mut f1 = f32::MAX;
+        let mut f2 = f32::MAX;
+        // Every feature point lies in its own cell, so the two nearest feature
+        // points lie within the neighboring cells.
+        for dz in -1..=1 {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let feature_point = self.feature_point(cell_x + dx, cell_y + dy, cell_z + dz);
+                    let distance = (feature_point - point).magnitude();
+                    if distance < f1 {
+                        f2 = f1;
+                        f1 = distance;
+                    } else if distance < f2 {
+                        f2 = distance;
+                    }
+                }
+            }
+        }
+
+        WorleyDistances { f1, f2, }
+    }
+}
+
+/// Evaluate fractional Brownian motion: the sum of `octaves` octaves of a noise
+/// function, each at `lacunarity` times the frequency and `gain` times the
+/// amplitude of the previous one. The result is normalized to lie in `[-1, 1]`.
+pub fn fbm<N>(noise: &N, point: &Vector3<f32>, octaves: usize, lacunarity: f32, gain: f32) -> f32
+where
+    N: Noise + ?Sized,
+{
+    let mut sum = 0_f32;
+    let mut amplitude = 1_f32;
+    let mut total_amplitude = 0_f32;
+    let mut sample_point = *point;
+    for _ in 0..usize::max(octaves, 1) {
+        sum += amplitude * noise.noise(&sample_point);
+        total_amplitude += amplitude;
+        amplitude *= gain;
+        sample_point *= lacunarity;
+    }
+
+    sum / total_amplitude
+}
+
+/// Evaluate turbulence: fractional Brownian motion of the absolute value of a
+/// noise function. The folds at the zeros of the noise give turbulence its
+/// sharp creases. The result is normalized to lie in `[0, 1]`.
+pub fn turbulence<N>(noise: &N, point: &Vector3<f32>, octaves: usize, lacunarity: f32, gain: f32) -> f32
+where
+    N: Noise + ?Sized,
+{
+    let mut sum = 0_f32;
+    let mut amplitude = 1_f32;
+    let mut total_amplitude = 0_f32;
+    let mut sample_point = *point;
+    for _ in 0..usize::max(octaves, 1) {
+        sum += amplitude * f32::abs(noise.noise(&sample_point));
+        total_amplitude += amplitude;
+        amplitude *= gain;
+        sample_point *= lacunarity;
+    }
+
+    sum / total_amplitude
+}
@@ -0,0 +1,47 @@
+use crate::core::*;
+use crate::texture::*;
+use cglinalg::{
+    Vector3,
+};
+use std::sync::Arc;
+
+
+/// A texture whose value varies with fractional Brownian motion of a noise
+/// function over the surface.
+///
+/// The texture takes values in `[0, 1]`.
+#[derive(Clone, Debug)]
+pub struct NoiseTexture {
+    noise: Arc<dyn Noise>,
+    /// The spatial frequency of the noise.
+    scale: f32,
+    /// The number of octaves of noise summed together.
+    octaves: usize,
+    space: TextureSpace,
+}
+
+impl NoiseTexture {
+    /// Construct a texture from gradient noise in model space.
+    pub fn new(seed: u64, scale: f32, octaves: usize) -> Self {
+        Self::from_noise(Arc::new(PerlinNoise::new(seed)), scale, octaves, TextureSpace::Object)
+    }
+
+    pub fn from_noise(noise: Arc<dyn Noise>, scale: f32, octaves: usize, space: TextureSpace) -> Self {
+        Self { noise, scale, octaves, space, }
+    }
+}
+
+impl Texture<f32> for NoiseTexture {
+    fn evaluate(&self, query: &TextureQuery) -> f32 {
+        let point = query.point_in(self.space) * self.scale;
+        let value = fbm(self.noise.as_ref(), &point, self.octaves, 2_f32, 0.5);
+
+        0.5 * (value + 1_f32)
+    }
+}
+
+impl Texture<Vector3<f32>> for NoiseTexture {
+    fn evaluate(&self, query: &TextureQuery) -> Vector3<f32> {
+        Vector3::from_fill(<Self as Texture<f32>>::evaluate(self, query))
+    }
+}
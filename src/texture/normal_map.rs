@@ -0,0 +1,56 @@
+use crate::core::*;
+use cglinalg::{
+    Vector3,
+    Magnitude,
+};
+use std::sync::Arc;
+
+
+/// A tangent space normal map.
+///
+/// Each value of the texture encodes a unit normal vector in the shading frame 
+/// of the surface, with each component mapped from `[-1, 1]` to `[0, 1]`. The 
+/// **x-axis** of the frame points along the **u** tangent, the **y-axis** points 
+/// along the **v** tangent, and the **z-axis** is the unperturbed shading normal. 
+/// Normal map images store non-color data, so they should be loaded with linear 
+/// color encoding.
+#[derive(Clone, Debug)]
+pub struct NormalMap {
+    texture: Arc<dyn Texture<Vector3<f32>>>,
+    /// The amount by which the normal map tilts the shading normal. A strength of 
+    /// zero leaves the shading normal unperturbed.
+    strength: f32,
+}
+
+impl NormalMap {
+    pub fn new(texture: Arc<dyn Texture<Vector3<f32>>>, strength: f32) -> Self {
+        Self { texture, strength, }
+    }
+}
+
+impl NormalMapping for NormalMap {
+    fn shading_normal(&self, hit: &IntersectionData) -> Vector3<f32> {
+        let frame = hit.shading_frame();
+        // The bitangent of the frame must follow the **v** tangent of the surface, 
+        // whichever handedness the surface parameterization has.
+        let bitangent = if frame.bitangent.dot(&hit.dpdv) < 0_f32 {
+            -frame.bitangent
+        } else {
+            frame.bitangent
+        };
+        let texture_query = TextureQuery::new(hit.point, hit.uv);
+        let value = self.texture.evaluate(&texture_query);
+        let normal_local = Vector3::new(
+            (2_f32 * value.x - 1_f32) * self.strength,
+            (2_f32 * value.y - 1_f32) * self.strength,
+            2_f32 * value.z - 1_f32,
+        );
+        let shading_normal = frame.tangent * normal_local.x + bitangent * normal_local.y + frame.normal * normal_local.z;
+        let magnitude = shading_normal.magnitude();
+        if magnitude > 0_f32 {
+            shading_normal / magnitude
+        } else {
+            hit.shading_normal
+        }
+    }
+}
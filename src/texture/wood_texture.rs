@@ -0,0 +1,71 @@
+use crate::core::*;
+use crate::texture::*;
+use cglinalg::{
+    Vector3,
+};
+
+
+/// A solid texture of the growth rings of wood.
+///
+/// The rings are concentric cylinders about the **y-axis** whose radii are
+/// disturbed by gradient noise. Within each ring the color darkens gradually from
+/// the early wood to the late wood, and changes abruptly at the start of the 
+/// next ring. As a scalar texture it takes values in `[0, 1]`, with zero in the 
+/// early wood and one in the late wood.
+#[derive(Clone, Debug)]
+pub struct WoodTexture {
+    noise: PerlinNoise,
+    /// The number of rings per unit length.
+    ring_frequency: f32,
+    /// The amount by which noise distorts the rings, as a fraction of a ring.
+    distortion: f32,
+    /// The spatial frequency of the distorting noise.
+    noise_scale: f32,
+    early_color: Vector3<f32>,
+    late_color: Vector3<f32>,
+    space: TextureSpace,
+}
+
+impl WoodTexture {
+    pub fn new(seed: u64, ring_frequency: f32, early_color: Vector3<f32>, late_color: Vector3<f32>) -> Self {
+        Self {
+            noise: PerlinNoise::new(seed),
+            ring_frequency,
+            distortion: 0.5,
+            noise_scale: 2_f32,
+            early_color,
+            late_color,
+            space: TextureSpace::Object,
+        }
+    }
+
+    pub fn with_distortion(mut self, distortion: f32, noise_scale: f32) -> Self {
+        self.distortion = distortion;
+        self.noise_scale = noise_scale;
+        self
+    }
+
+    pub fn with_space(mut self, space: TextureSpace) -> Self {
+        self.space = space;
+        self
+    }
+}
+
+impl Texture<f32> for WoodTexture {
+    fn evaluate(&self, query: &TextureQuery) -> f32 {
+        let point = query.point_in(self.space);
+        let radius = f32::sqrt(point.x * point.x + point.z * point.z);
+        let distortion = self.distortion * self.noise.noise(&(point * self.noise_scale));
+        let ring = radius * self.ring_frequency + distortion;
+
+        ring - f32::floor(ring)
+    }
+}
+
+impl Texture<Vector3<f32>> for WoodTexture {
+    fn evaluate(&self, query: &TextureQuery) -> Vector3<f32> {
+        let t = <Self as Texture<f32>>::evaluate(self, query);
+
+        self.early_color * (1_f32 - t) + self.late_color * t
+    }
+}
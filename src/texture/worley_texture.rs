@@ -0,0 +1,55 @@
+use crate::core::*;
+use crate::texture::*;
+use cglinalg::{
+    Vector3,
+};
+
+
+/// The feature of cellular noise a [`WorleyTexture`] displays.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WorleyFeature {
+    /// The distance to the nearest feature point, giving rounded cells.
+    F1,
+    /// The distance to the second nearest feature point.
+    F2,
+    /// The difference between the distances to the two nearest feature points, 
+    /// which vanishes on the borders between cells and gives a web of cracks.
+    F2MinusF1,
+}
+
+/// A texture whose value varies with cellular noise over the surface.
+///
+/// The texture takes values in `[0, 1]`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WorleyTexture {
+    noise: WorleyNoise,
+    /// The number of cells per unit length.
+    scale: f32,
+    feature: WorleyFeature,
+    space: TextureSpace,
+}
+
+impl WorleyTexture {
+    pub fn new(seed: u64, scale: f32, feature: WorleyFeature, space: TextureSpace) -> Self {
+        Self { noise: WorleyNoise::new(seed), scale, feature, space, }
+    }
+}
+
+impl Texture<f32> for WorleyTexture {
+    fn evaluate(&self, query: &TextureQuery) -> f32 {
+        let distances = self.noise.distances(&(query.point_in(self.space) * self.scale));
+        let value = match self.feature {
+            WorleyFeature::F1 => distances.f1,
+            WorleyFeature::F2 => distances.f2,
+            WorleyFeature::F2MinusF1 => distances.f2 - distances.f1,
+        };
+
+        f32::clamp(value, 0_f32, 1_f32)
+    }
+}
+
+impl Texture<Vector3<f32>> for WorleyTexture {
+    fn evaluate(&self, query: &TextureQuery) -> Vector3<f32> {
+        Vector3::from_fill(<Self as Texture<f32>>::evaluate(self, query))
+    }
+}
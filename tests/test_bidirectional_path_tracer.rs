@@ -0,0 +1,114 @@
+extern crate tracer;
+extern crate approx;
+extern crate rand;
+extern crate rand_isaac;
+
+
+#[cfg(test)]
+mod bidirectional_path_tracer_tests {
+    use tracer::*;
+    use tracer::core::*;
+    use tracer::bsdf::*;
+    use tracer::geometry::*;
+    use tracer::light::*;
+    use tracer::medium::*;
+    use tracer::renderer::*;
+    use tracer::scene::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector3,
+        Matrix4x4,
+    };
+    use rand::prelude::*;
+    use std::sync::Arc;
+
+
+    fn sphere(center: Vector3<f32>, radius: f32, albedo: f32) -> SceneObject {
+        let object = ModelSpaceGeometryObject::new(
+            Sphere::new(Vector3::zero(), radius),
+            Box::new(SimpleLambertianBsdf::new(Vector3::from_fill(albedo))),
+            Box::new(NoLight::new()),
+        );
+
+        SceneObject::new(Box::new(object), Matrix4x4::from_affine_translation(&center))
+    }
+
+    /// A camera looking at a diffuse floor lit by a point light straight above
+    /// the point the camera looks at, inside a black enclosure.
+    fn scene() -> Scene {
+        let look_from = Vector3::new(0_f32, 1_f32, 1_f32);
+        let look_at = Vector3::zero();
+        let v_fov = 1_f32;
+        let aperture = 0_f32;
+        let distance_to_focus = f32::sqrt(2_f32);
+        let camera = Camera::new(look_from, look_at, Vector3::unit_y(), v_fov, 1_f32, aperture, distance_to_focus);
+        let mut scene = Scene::new(1, 1, camera);
+        scene.push_object(sphere(Vector3::new(0_f32, -1000_f32, 0_f32), 1000_f32, 0.5));
+        scene.push_object(sphere(Vector3::zero(), 5000_f32, 0_f32));
+        scene.push_light(ScenePointLightObject::new(
+            PointLight::new(Vector3::from_fill(4_f32 * std::f32::consts::PI * 16_f32)),
+            Matrix4x4::from_affine_translation(&Vector3::new(0_f32, 2_f32, 0_f32)),
+        ));
+
+        scene
+    }
+
+    #[test]
+    fn test_bidirectional_path_tracer_direct_lighting() {
+        let scene = scene();
+        let mut sampler = SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0));
+        let bidirectional_path_tracer = BidirectionalPathTracer::new(4, f32::MAX);
+        let mut film = Film::new(1, 1);
+        let samples_per_pixel = 64;
+        for _ in 0..samples_per_pixel {
+            let ray = scene.camera.cast_ray(&mut sampler, 0.5, 0.5);
+            let radiance = bidirectional_path_tracer.sample(&scene, ray, &mut sampler, &mut film);
+            film.add_sample(0, 0, radiance);
+        }
+        // The radiance the floor reflects is `albedo / pi * intensity / distance^2`
+        // for a light at distance two with intensity sixteen.
+        let expected = Vector3::from_fill(0.5 / std::f32::consts::PI * 16_f32 / 4_f32);
+        let result = film.pixel(0, 0, samples_per_pixel);
+
+        assert_relative_eq!(result, expected, epsilon = 1e-2);
+    }
+
+    #[test]
+    fn test_scene_with_media_is_detected() {
+        let mut scene = scene();
+
+        assert!(!scene.has_media());
+
+        let medium = Arc::new(HomogeneousMedium::new(Vector3::from_fill(0.1), Vector3::from_fill(0.1), 0_f32));
+        let object = sphere(Vector3::new(0_f32, 0.5, 0_f32), 0.5, 0.5).with_interior_medium(medium.clone());
+        scene.push_object(object);
+
+        assert!(scene.has_media());
+
+        let mut scene = self::scene();
+        scene.set_medium(medium);
+
+        assert!(scene.has_media());
+    }
+
+    #[test]
+    fn test_bidirectional_path_tracing_scene_with_media_falls_back_to_path_tracing() {
+        let mut scene = scene();
+        scene.set_medium(Arc::new(HomogeneousMedium::new(Vector3::from_fill(0.1), Vector3::from_fill(0.1), 0_f32)));
+        let render = |algorithm| {
+            let settings = RendererSettings::new(16, 4).with_algorithm(algorithm);
+            let renderer = Renderer::new(settings);
+            let mut sampler = SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0));
+            let mut canvas = Canvas::new(1, 1);
+            renderer.render(&scene, &mut sampler, &mut canvas);
+
+            canvas
+        };
+        let result = render(RenderingAlgorithm::BidirectionalPathTracing);
+        let expected = render(RenderingAlgorithm::PathTracing);
+
+        assert_eq!(result.data, expected.data);
+    }
+}
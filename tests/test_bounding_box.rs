@@ -0,0 +1,160 @@
+extern crate tracer;
+extern crate approx;
+
+
+#[cfg(test)]
+mod bounding_box_tests {
+    use tracer::core::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector3,
+        Matrix4x4,
+    };
+
+
+    fn unit_box() -> BoundingBox {
+        BoundingBox::new(Vector3::from_fill(-1_f32), Vector3::from_fill(1_f32))
+    }
+
+    #[test]
+    fn test_bounding_box_orders_corners() {
+        let bounds = BoundingBox::new(Vector3::new(1_f32, -2_f32, 3_f32), Vector3::new(-1_f32, 2_f32, -3_f32));
+
+        assert_eq!(bounds.min, Vector3::new(-1_f32, -2_f32, -3_f32));
+        assert_eq!(bounds.max, Vector3::new(1_f32, 2_f32, 3_f32));
+    }
+
+    #[test]
+    fn test_empty_bounding_box_is_union_identity() {
+        let bounds = unit_box();
+
+        assert!(BoundingBox::empty().is_empty());
+        assert_eq!(BoundingBox::empty().union(&bounds), bounds);
+    }
+
+    #[test]
+    fn test_ray_through_bounding_box() {
+        let bounds = unit_box();
+        let ray = Ray::new(Vector3::new(-5_f32, 0.5, 0_f32), Vector3::unit_x());
+        let (t_enter, t_exit) = bounds.intersect(&ray, 0_f32, f32::MAX).unwrap();
+
+        assert_relative_eq!(t_enter, 4_f32);
+        assert_relative_eq!(t_exit, 6_f32);
+    }
+
+    #[test]
+    fn test_ray_starting_inside_bounding_box() {
+        let bounds = unit_box();
+        let ray = Ray::new(Vector3::zero(), Vector3::new(0_f32, 0_f32, 2_f32));
+        let (t_enter, t_exit) = bounds.intersect(&ray, 0_f32, f32::MAX).unwrap();
+
+        assert_eq!(t_enter, 0_f32);
+        assert_relative_eq!(t_exit, 0.5);
+    }
+
+    #[test]
+    fn test_ray_missing_bounding_box() {
+        let bounds = unit_box();
+        let ray = Ray::new(Vector3::new(-5_f32, 2_f32, 0_f32), Vector3::unit_x());
+
+        assert!(bounds.intersect(&ray, 0_f32, f32::MAX).is_none());
+    }
+
+    #[test]
+    fn test_ray_ending_before_bounding_box() {
+        let bounds = unit_box();
+        let ray = Ray::new(Vector3::new(-5_f32, 0_f32, 0_f32), Vector3::unit_x());
+
+        assert!(bounds.intersect(&ray, 0_f32, 3_f32).is_none());
+    }
+
+    #[test]
+    fn test_transformed_bounding_box() {
+        let bounds = unit_box();
+        let matrix = Matrix4x4::from_affine_translation(&Vector3::new(1_f32, 2_f32, 3_f32)) *
+            Matrix4x4::from_affine_nonuniform_scale(&Vector3::new(2_f32, 1_f32, 0.5));
+        let result = bounds.transform(&matrix);
+
+        assert_relative_eq!(result.min, Vector3::new(-1_f32, 1_f32, 2.5));
+        assert_relative_eq!(result.max, Vector3::new(3_f32, 3_f32, 3.5));
+    }
+}
+
+#[cfg(test)]
+mod axis_aligned_box_tests {
+    use tracer::core::*;
+    use tracer::geometry::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector2,
+        Vector3,
+    };
+
+
+    fn cube() -> AxisAlignedBox {
+        AxisAlignedBox::new(Vector3::from_fill(-1_f32), Vector3::from_fill(1_f32))
+    }
+
+    #[test]
+    fn test_box_intersection_from_outside() {
+        let cube = cube();
+        let ray = Ray::new(Vector3::new(0.5, 0.25, 5_f32), -Vector3::unit_z());
+        let query = IntersectionQuery::new(ray, 0.0001, f32::MAX);
+        let result = cube.intersect(&query).unwrap_hit();
+
+        assert_relative_eq!(result.t, 4_f32);
+        assert_relative_eq!(result.point, Vector3::new(0.5, 0.25, 1_f32));
+        assert_eq!(result.normal, Vector3::unit_z());
+        assert_relative_eq!(result.uv, Vector2::new(0.75, 0.625));
+    }
+
+    #[test]
+    fn test_box_intersection_from_inside() {
+        let cube = cube();
+        let ray = Ray::new(Vector3::zero(), Vector3::unit_x());
+        let query = IntersectionQuery::new(ray, 0.0001, f32::MAX);
+        let result = cube.intersect(&query).unwrap_hit();
+
+        assert_relative_eq!(result.t, 1_f32);
+        assert_eq!(result.normal, Vector3::unit_x());
+    }
+
+    #[test]
+    fn test_box_miss() {
+        let cube = cube();
+        let ray = Ray::new(Vector3::new(0_f32, 3_f32, 5_f32), -Vector3::unit_z());
+        let query = IntersectionQuery::new(ray, 0.0001, f32::MAX);
+
+        assert!(cube.intersect(&query).is_miss());
+    }
+
+    #[test]
+    fn test_box_tangents_lie_in_face() {
+        let cube = cube();
+        let origins = [
+            Vector3::new(5_f32, 0.1, 0.2),
+            Vector3::new(0.1, -5_f32, 0.2),
+            Vector3::new(0.1, 0.2, -5_f32),
+        ];
+        for origin in origins {
+            let ray = Ray::new(origin, -origin);
+            let query = IntersectionQuery::new(ray, 0.0001, f32::MAX);
+            let result = cube.intersect(&query).unwrap_hit();
+
+            assert_eq!(result.normal.dot(&result.dpdu), 0_f32);
+            assert_eq!(result.normal.dot(&result.dpdv), 0_f32);
+            assert!(result.normal.dot(&origin) > 0_f32);
+        }
+    }
+
+    #[test]
+    fn test_box_bounds() {
+        let cube = cube();
+
+        assert_eq!(cube.bounds(), BoundingBox::new(Vector3::from_fill(-1_f32), Vector3::from_fill(1_f32)));
+    }
+}
@@ -0,0 +1,364 @@
+extern crate tracer;
+extern crate approx;
+extern crate rand;
+extern crate rand_isaac;
+
+
+#[cfg(test)]
+mod lambertian_bsdf_tests {
+    use tracer::core::*;
+    use tracer::bsdf::{
+        OrenNayarBsdf,
+        SimpleLambertianBsdf,
+    };
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector2,
+        Vector3,
+        Magnitude,
+    };
+    use rand::prelude::*;
+
+
+    fn sampler() -> SphereSampler {
+        let rng = rand_isaac::Isaac64Rng::seed_from_u64(0);
+
+        SphereSampler::new(rng)
+    }
+
+    fn ray_incoming() -> Vector3<f32> {
+        Vector3::new(1_f32, 0_f32, -1_f32).normalize()
+    }
+
+    fn query(ray_outgoing: Vector3<f32>) -> BsdfQuery {
+        BsdfQuery::new(ray_incoming(), ray_outgoing, Vector3::zero(), Vector2::zero())
+    }
+
+    #[test]
+    fn test_lambertian_evaluate_reflection() {
+        let bsdf = SimpleLambertianBsdf::new(Vector3::new(0.5, 0.5, 0.5));
+        let query = query(Vector3::new(0_f32, 1_f32, 1_f32).normalize());
+        let expected = Vector3::new(0.5, 0.5, 0.5) / std::f32::consts::PI;
+        let result = bsdf.evaluate(&query);
+
+        assert_relative_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_lambertian_evaluate_transmission_is_zero() {
+        let bsdf = SimpleLambertianBsdf::new(Vector3::new(0.5, 0.5, 0.5));
+        let query = query(Vector3::new(0_f32, 1_f32, -1_f32).normalize());
+
+        assert_eq!(bsdf.evaluate(&query), Vector3::zero());
+        assert_eq!(bsdf.pdf(&query), 0_f32);
+    }
+
+    #[test]
+    fn test_lambertian_pdf_is_cosine_weighted() {
+        let bsdf = SimpleLambertianBsdf::new(Vector3::new(0.5, 0.5, 0.5));
+        let ray_outgoing = Vector3::new(0_f32, 1_f32, 1_f32).normalize();
+        let query = query(ray_outgoing);
+        let expected = ray_outgoing.z / std::f32::consts::PI;
+        let result = bsdf.pdf(&query);
+
+        assert_relative_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_lambertian_samples_lie_in_reflection_hemisphere() {
+        let bsdf = SimpleLambertianBsdf::new(Vector3::new(0.5, 0.5, 0.5));
+        let mut sampler = sampler();
+        let query = BsdfSampleQuery::new(ray_incoming(), Vector3::zero(), Vector2::zero());
+        for _ in 0..1024 {
+            let result = bsdf.sample(&query, &mut sampler).unwrap();
+
+            assert!(result.ray_outgoing.z >= 0_f32);
+            assert!(result.flags.is_diffuse());
+        }
+    }
+
+    #[test]
+    fn test_lambertian_samples_from_below_lie_in_lower_hemisphere() {
+        let bsdf = SimpleLambertianBsdf::new(Vector3::new(0.5, 0.5, 0.5));
+        let mut sampler = sampler();
+        let ray_incoming = Vector3::new(1_f32, 0_f32, 1_f32).normalize();
+        let query = BsdfSampleQuery::new(ray_incoming, Vector3::zero(), Vector2::zero());
+        for _ in 0..1024 {
+            let result = bsdf.sample(&query, &mut sampler).unwrap();
+
+            assert!(result.ray_outgoing.z <= 0_f32);
+        }
+    }
+
+    #[test]
+    fn test_lambertian_sample_is_consistent_with_evaluate_and_pdf() {
+        let bsdf = SimpleLambertianBsdf::new(Vector3::new(0.2, 0.4, 0.6));
+        let mut sampler = sampler();
+        let sample_query = BsdfSampleQuery::new(ray_incoming(), Vector3::zero(), Vector2::zero());
+        for _ in 0..64 {
+            let result = bsdf.sample(&sample_query, &mut sampler).unwrap();
+            let query = query(result.ray_outgoing);
+            let expected = bsdf.evaluate(&query) * result.ray_outgoing.z / bsdf.pdf(&query);
+
+            assert_relative_eq!(result.scattering_fraction, expected, epsilon = 1e-4);
+            assert_relative_eq!(result.pdf, bsdf.pdf(&query), epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_oren_nayar_with_zero_roughness_is_lambertian() {
+        let albedo = Vector3::new(0.3, 0.6, 0.9);
+        let lambertian = SimpleLambertianBsdf::new(albedo);
+        let oren_nayar = OrenNayarBsdf::new(albedo, 0_f32);
+        let query = query(Vector3::new(0_f32, 1_f32, 2_f32).normalize());
+
+        assert_relative_eq!(oren_nayar.evaluate(&query), lambertian.evaluate(&query));
+        assert_relative_eq!(oren_nayar.pdf(&query), lambertian.pdf(&query));
+    }
+
+    #[test]
+    fn test_oren_nayar_is_reciprocal() {
+        let bsdf = OrenNayarBsdf::new(Vector3::new(0.5, 0.5, 0.5), 0.5);
+        let w_in = Vector3::new(0.3, -0.2, 1_f32).normalize();
+        let w_out = Vector3::new(-0.6, 0.1, 0.4).normalize();
+        let forward = BsdfQuery::new(-w_in, w_out, Vector3::zero(), Vector2::zero());
+        let backward = BsdfQuery::new(-w_out, w_in, Vector3::zero(), Vector2::zero());
+
+        assert_relative_eq!(bsdf.evaluate(&forward), bsdf.evaluate(&backward), epsilon = 1e-6);
+    }
+}
+
+
+#[cfg(test)]
+mod specular_bsdf_tests {
+    use tracer::core::*;
+    use tracer::bsdf::{
+        BlackBodyBsdf,
+        SimpleDielectricBsdf,
+        SimpleMetalBsdf,
+        SubsurfaceBsdf,
+    };
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector2,
+        Vector3,
+        Magnitude,
+    };
+    use rand::prelude::*;
+
+
+    fn sampler() -> SphereSampler {
+        let rng = rand_isaac::Isaac64Rng::seed_from_u64(0);
+
+        SphereSampler::new(rng)
+    }
+
+    #[test]
+    fn test_metal_without_fuzz_reflects_about_normal() {
+        let bsdf = SimpleMetalBsdf::new(Vector3::new(0.7, 0.6, 0.5), 0_f32);
+        let mut sampler = sampler();
+        let ray_incoming = Vector3::new(1_f32, 2_f32, -1_f32).normalize();
+        let query = BsdfSampleQuery::new(ray_incoming, Vector3::zero(), Vector2::zero());
+        let result = bsdf.sample(&query, &mut sampler).unwrap();
+        let expected = Vector3::new(1_f32, 2_f32, 1_f32).normalize();
+
+        assert_relative_eq!(result.ray_outgoing, expected, epsilon = 1e-6);
+        assert_eq!(result.scattering_fraction, Vector3::new(0.7, 0.6, 0.5));
+        assert!(result.flags.is_delta());
+    }
+
+    #[test]
+    fn test_dielectric_samples_are_delta_with_unit_weight() {
+        let bsdf = SimpleDielectricBsdf::new(1.5);
+        let mut sampler = sampler();
+        let ray_incoming = Vector3::new(1_f32, 0_f32, -1_f32).normalize();
+        let query = BsdfSampleQuery::new(ray_incoming, Vector3::zero(), Vector2::zero());
+        for _ in 0..256 {
+            let result = bsdf.sample(&query, &mut sampler).unwrap();
+
+            assert!(result.flags.is_delta());
+            assert_eq!(result.scattering_fraction, Vector3::from_fill(1_f32));
+            if result.flags.is_transmissive() {
+                assert!(result.ray_outgoing.z < 0_f32);
+            } else {
+                assert!(result.ray_outgoing.z > 0_f32);
+            }
+        }
+    }
+
+    #[test]
+    fn test_dielectric_total_internal_reflection() {
+        let bsdf = SimpleDielectricBsdf::new(1.5);
+        let mut sampler = sampler();
+        // A grazing ray travelling from inside the dielectric out into vacuum.
+        let ray_incoming = Vector3::new(1_f32, 0_f32, 0.1).normalize();
+        let query = BsdfSampleQuery::new(ray_incoming, Vector3::zero(), Vector2::zero());
+        let result = bsdf.sample(&query, &mut sampler).unwrap();
+
+        assert!(result.flags.is_reflective());
+        assert!(result.ray_outgoing.z < 0_f32);
+    }
+
+    #[test]
+    fn test_black_body_absorbs_everything() {
+        let bsdf = BlackBodyBsdf::new();
+        let mut sampler = sampler();
+        let query = BsdfSampleQuery::new(-Vector3::unit_z(), Vector3::zero(), Vector2::zero());
+
+        assert!(bsdf.sample(&query, &mut sampler).is_none());
+        assert!(bsdf.flags().is_empty());
+    }
+
+    #[test]
+    fn test_bsdfs_are_object_safe() {
+        let bsdfs: Vec<Box<dyn Bsdf>> = vec![
+            Box::new(SimpleMetalBsdf::new(Vector3::from_fill(0.5), 0_f32)),
+            Box::new(SimpleDielectricBsdf::new(1.5)),
+            Box::new(BlackBodyBsdf::new()),
+        ];
+
+        for bsdf in bsdfs.iter() {
+            assert!(!bsdf.flags().is_non_delta());
+        }
+    }
+
+    #[test]
+    fn test_subsurface_boundary_transmits_without_refraction() {
+        let bsdf = SubsurfaceBsdf::new(1.4);
+        let mut sampler = sampler();
+        let ray_incoming = Vector3::new(1_f32, 0_f32, -1_f32).normalize();
+        let query = BsdfSampleQuery::new(ray_incoming, Vector3::zero(), Vector2::zero());
+        let mut reflected = 0;
+        let count = 10000;
+        for _ in 0..count {
+            let result = bsdf.sample(&query, &mut sampler).unwrap();
+
+            assert_eq!(result.scattering_fraction, Vector3::from_fill(1_f32));
+            if result.flags.is_reflective() {
+                assert_relative_eq!(result.ray_outgoing, reflect_local(&ray_incoming));
+                reflected += 1;
+            } else {
+                assert_eq!(result.ray_outgoing, ray_incoming);
+            }
+        }
+        // The Schlick approximation of the Fresnel reflectance at 45 degrees.
+        let r0 = (0.4_f32 / 2.4) * (0.4_f32 / 2.4);
+        let expected = r0 + (1_f32 - r0) * f32::powf(1_f32 - f32::sqrt(0.5), 5_f32);
+
+        assert_relative_eq!(reflected as f32 / count as f32, expected, epsilon = 1e-2);
+        assert!(bsdf.flags().is_pass_through());
+    }
+}
+
+
+#[cfg(test)]
+mod glossy_bsdf_tests {
+    use tracer::core::*;
+    use tracer::bsdf::{
+        SimpleMetalBsdf,
+    };
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector2,
+        Vector3,
+        Magnitude,
+    };
+    use rand::prelude::*;
+
+
+    fn sampler() -> SphereSampler {
+        let rng = rand_isaac::Isaac64Rng::seed_from_u64(0);
+
+        SphereSampler::new(rng)
+    }
+
+    /// Integrate the sampling density of a BSDF over the hemisphere above the
+    /// surface with the midpoint rule in the cosine of the polar angle and the
+    /// azimuth.
+    fn integrate_pdf(bsdf: &SimpleMetalBsdf, ray_incoming: Vector3<f32>) -> f32 {
+        let steps = 512;
+        let d_cos_theta = 1_f32 / (steps as f32);
+        let d_phi = 2_f32 * std::f32::consts::PI / (steps as f32);
+        let mut integral = 0_f32;
+        for i in 0..steps {
+            let cos_theta = (i as f32 + 0.5) * d_cos_theta;
+            let sin_theta = f32::sqrt(1_f32 - cos_theta * cos_theta);
+            for j in 0..steps {
+                let phi = (j as f32 + 0.5) * d_phi;
+                let ray_outgoing = Vector3::new(sin_theta * f32::cos(phi), sin_theta * f32::sin(phi), cos_theta);
+                let query = BsdfQuery::new(ray_incoming, ray_outgoing, Vector3::zero(), Vector2::zero());
+                integral += bsdf.pdf(&query) * d_cos_theta * d_phi;
+            }
+        }
+
+        integral
+    }
+
+    #[test]
+    fn test_fuzzy_metal_is_glossy() {
+        let fuzzy = SimpleMetalBsdf::new(Vector3::from_fill(0.5), 0.2);
+        let mirror = SimpleMetalBsdf::new(Vector3::from_fill(0.5), 0_f32);
+
+        assert!(fuzzy.flags().is_glossy());
+        assert!(!fuzzy.flags().is_delta());
+        assert!(mirror.flags().is_delta());
+        assert!(!mirror.flags().is_non_delta());
+    }
+
+    #[test]
+    fn test_fuzzy_metal_sample_is_consistent_with_evaluate_and_pdf() {
+        let ray_incoming = Vector3::new(1_f32, 2_f32, -1_f32).normalize();
+        let sample_query = BsdfSampleQuery::new(ray_incoming, Vector3::zero(), Vector2::zero());
+        let mut sampler = sampler();
+        for fuzz in [0.05, 0.3, 1_f32, 1.5] {
+            let bsdf = SimpleMetalBsdf::new(Vector3::new(0.7, 0.6, 0.5), fuzz);
+            for _ in 0..64 {
+                let result = match bsdf.sample(&sample_query, &mut sampler) {
+                    Some(result) => result,
+                    None => continue,
+                };
+                let query = BsdfQuery::new(ray_incoming, result.ray_outgoing, Vector3::zero(), Vector2::zero());
+                let expected = bsdf.evaluate(&query) * abs_cos_theta(&result.ray_outgoing) / bsdf.pdf(&query);
+
+                assert!(result.flags.is_glossy());
+                assert_relative_eq!(result.scattering_fraction, expected, epsilon = 1e-4);
+                assert_relative_eq!(result.pdf, bsdf.pdf(&query), max_relative = 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_metal_density_integrates_to_one() {
+        // At normal incidence, fuzz of at most one never pushes the reflected
+        // ray beneath the surface.
+        let ray_incoming = -Vector3::unit_z();
+        for fuzz in [0.5, 1_f32] {
+            let bsdf = SimpleMetalBsdf::new(Vector3::from_fill(0.5), fuzz);
+            let result = integrate_pdf(&bsdf, ray_incoming);
+
+            assert_relative_eq!(result, 1_f32, epsilon = 1e-2);
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_metal_density_integrates_to_the_fraction_of_samples_kept() {
+        let ray_incoming = Vector3::new(1_f32, 0_f32, -1_f32).normalize();
+        let bsdf = SimpleMetalBsdf::new(Vector3::from_fill(0.5), 1.25);
+        let sample_query = BsdfSampleQuery::new(ray_incoming, Vector3::zero(), Vector2::zero());
+        let mut sampler = sampler();
+        let sample_count = 100_000;
+        let kept = (0..sample_count)
+            .filter(|_| bsdf.sample(&sample_query, &mut sampler).is_some())
+            .count();
+        let expected = (kept as f32) / (sample_count as f32);
+        let result = integrate_pdf(&bsdf, ray_incoming);
+
+        assert_relative_eq!(result, expected, epsilon = 1e-2);
+    }
+}
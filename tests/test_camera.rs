@@ -0,0 +1,437 @@
+extern crate tracer;
+extern crate approx;
+extern crate rand;
+extern crate rand_isaac;
+
+
+#[cfg(test)]
+mod camera_tests {
+    use tracer::*;
+    use tracer::core::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector3,
+        Magnitude,
+    };
+    use rand::prelude::*;
+
+
+    fn camera(aperture: f32) -> Camera {
+        let look_from = Vector3::new(1_f32, 2_f32, 3_f32);
+        let look_at = Vector3::new(-1_f32, 0_f32, -2_f32);
+        let v_up = Vector3::unit_y();
+        let v_fov = 40_f32;
+        let aspect_ratio = 1.5_f32;
+        let distance_to_focus = 4_f32;
+
+        Camera::new(look_from, look_at, v_up, v_fov, aspect_ratio, aperture, distance_to_focus)
+    }
+
+    fn sampler() -> SphereSampler {
+        SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0))
+    }
+
+    #[test]
+    fn test_project_inverts_cast_ray() {
+        let camera = camera(0.2);
+        let mut sampler = sampler();
+        for (u, v) in [(0.5, 0.5), (0.1, 0.9), (0.75, 0.25), (0.01, 0.02)] {
+            let ray = camera.cast_ray(&mut sampler, u, v);
+            let point = ray.interpolate(2.5);
+            let (result_u, result_v) = camera.project(&ray.origin, &point).unwrap();
+
+            assert_relative_eq!(result_u, u, epsilon = 1e-4);
+            assert_relative_eq!(result_v, v, epsilon = 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_project_outside_field_of_view() {
+        let camera = camera(0_f32);
+        let behind = camera.position() - camera.forward();
+        let beside = camera.position() + camera.forward() + Vector3::new(0_f32, 10_f32, 0_f32);
+
+        assert!(camera.project(&camera.position(), &behind).is_none());
+        assert!(camera.project(&camera.position(), &beside).is_none());
+    }
+
+    #[test]
+    fn test_pinhole_importance_matches_direction_density() {
+        // For a pinhole camera, the importance weighted by the cosine of the 
+        // direction with the viewing direction is the density of the directions
+        // the camera casts rays along.
+        let camera = camera(0_f32);
+        let mut sampler = sampler();
+        for (u, v) in [(0.5, 0.5), (0.2, 0.7), (0.9, 0.1)] {
+            let ray = camera.cast_ray(&mut sampler, u, v);
+            let direction = ray.direction;
+            let cos_theta = direction.normalize().dot(&camera.forward());
+            let expected = camera.pdf_direction(&direction);
+            let result = camera.importance(&ray.origin, &direction) * cos_theta;
+
+            assert_relative_eq!(result, expected, epsilon = 1e-6, max_relative = 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_importance_integrates_to_one() {
+        // Integrate the importance over the film by sampling the directions the
+        // camera casts rays along uniformly over the film.
+        let camera = camera(0.2);
+        let mut sampler = sampler();
+        let sample_count = 10000;
+        let mut sum = 0_f32;
+        for _ in 0..sample_count {
+            let u = sampler.sample_f32();
+            let v = sampler.sample_f32();
+            let ray = camera.cast_ray(&mut sampler, u, v);
+            let direction = ray.direction;
+            let cos_theta = direction.normalize().dot(&camera.forward());
+
+            sum += camera.importance(&ray.origin, &direction) * cos_theta / (camera.pdf_lens(&ray.origin) * camera.pdf_direction(&direction));
+        }
+        let result = sum / sample_count as f32;
+
+        assert_relative_eq!(result, 1_f32, epsilon = 1e-4);
+    }
+}
+
+
+#[cfg(test)]
+mod aperture_tests {
+    use tracer::*;
+    use tracer::core::*;
+    use tracer::texture::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector3,
+        Magnitude,
+    };
+    use rand::prelude::*;
+    use std::sync::Arc;
+
+
+    fn sampler() -> SphereSampler {
+        SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0))
+    }
+
+    fn camera(aperture_shape: ApertureShape) -> Camera {
+        Camera::new(Vector3::zero(), -Vector3::unit_z(), Vector3::unit_y(), 40_f32, 1.5, 0.5, 4_f32)
+            .with_aperture_shape(aperture_shape)
+    }
+
+    #[test]
+    fn test_polygon_area() {
+        let hexagon = ApertureShape::polygon(6);
+        let square = ApertureShape::Polygon { blade_count: 4, rotation: 0.3 };
+
+        assert_relative_eq!(hexagon.area(), 1.5 * f32::sqrt(3_f32), epsilon = 1e-6);
+        assert_relative_eq!(square.area(), 2_f32, epsilon = 1e-6);
+        assert_relative_eq!(ApertureShape::Circle.area(), std::f32::consts::PI, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_polygon_samples_lie_inside_the_polygon() {
+        let blade_count = 5;
+        let rotation = 0.2;
+        let shape = ApertureShape::Polygon { blade_count, rotation };
+        let angle = 2_f32 * std::f32::consts::PI / (blade_count as f32);
+        let apothem = f32::cos(0.5 * angle);
+        let mut sampler = sampler();
+        for _ in 0..1000 {
+            let point = shape.sample(&mut sampler);
+
+            assert_eq!(point.z, 0_f32);
+            for edge in 0..blade_count {
+                let theta = rotation + angle * (edge as f32 + 0.5);
+                let edge_normal = Vector3::new(f32::cos(theta), f32::sin(theta), 0_f32);
+
+                assert!(point.dot(&edge_normal) <= apothem + 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_polygon_samples_are_centered() {
+        let shape = ApertureShape::polygon(6);
+        let mut sampler = sampler();
+        let count = 10000;
+        let mut sum = Vector3::zero();
+        for _ in 0..count {
+            sum += shape.sample(&mut sampler);
+        }
+        let result = sum / (count as f32);
+
+        assert_relative_eq!(result, Vector3::zero(), epsilon = 0.02);
+    }
+
+    #[test]
+    fn test_lens_samples_lie_on_the_opening() {
+        let camera = camera(ApertureShape::polygon(6));
+        let mut sampler = sampler();
+        for _ in 0..1000 {
+            let offset = camera.sample_lens(&mut sampler) - camera.position();
+
+            assert_relative_eq!(offset.z, 0_f32, epsilon = 1e-6);
+            assert!(offset.magnitude() <= 0.25 + 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_lens_area_follows_the_aperture_shape() {
+        let circle = camera(ApertureShape::Circle);
+        let hexagon = camera(ApertureShape::polygon(6));
+
+        assert_relative_eq!(circle.lens_area(), std::f32::consts::PI * 0.0625, epsilon = 1e-6);
+        assert_relative_eq!(hexagon.lens_area(), 1.5 * f32::sqrt(3_f32) * 0.0625, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_bokeh_texture_samples_the_bright_pixels() {
+        let image = Image::new(2, 2, vec![
+            Vector3::from_fill(1_f32), Vector3::zero(),
+            Vector3::zero(),           Vector3::zero(),
+        ]);
+        let texture = BokehTexture::new(&image).unwrap();
+        let mut sampler = sampler();
+
+        assert_relative_eq!(texture.coverage(), 0.25, epsilon = 1e-6);
+        assert_eq!(texture.evaluate(-0.5, 0.5), 1_f32);
+        assert_eq!(texture.evaluate(0.5, 0.5), 0_f32);
+        for _ in 0..1000 {
+            let point = texture.sample(&mut sampler);
+
+            assert!((-1_f32..=0_f32).contains(&point.x));
+            assert!((0_f32..=1_f32).contains(&point.y));
+        }
+    }
+
+    #[test]
+    fn test_bokeh_texture_samples_in_proportion_to_luminance() {
+        let image = Image::new(2, 1, vec![Vector3::from_fill(1_f32), Vector3::from_fill(3_f32)]);
+        let texture = BokehTexture::new(&image).unwrap();
+        let mut sampler = sampler();
+        let count = 10000;
+        let mut right_count = 0;
+        for _ in 0..count {
+            right_count += usize::from(texture.sample(&mut sampler).x > 0_f32);
+        }
+        let result = right_count as f32 / count as f32;
+
+        assert_relative_eq!(texture.coverage(), 4_f32 / 6_f32, epsilon = 1e-6);
+        assert_relative_eq!(result, 0.75, epsilon = 0.02);
+    }
+
+    #[test]
+    fn test_black_bokeh_texture_is_an_error() {
+        let image = Image::from_fill(4, 4, Vector3::zero());
+
+        assert_eq!(BokehTexture::new(&image), Err(BokehTextureError));
+    }
+
+    #[test]
+    fn test_textured_lens_samples_follow_the_texture() {
+        let image = Image::new(2, 1, vec![Vector3::zero(), Vector3::from_fill(1_f32)]);
+        let texture = Arc::new(BokehTexture::new(&image).unwrap());
+        let camera = camera(ApertureShape::Texture(texture));
+        let mut sampler = sampler();
+
+        assert_relative_eq!(camera.lens_area(), 2_f32 * 0.0625, epsilon = 1e-6);
+        for _ in 0..1000 {
+            let offset = camera.sample_lens(&mut sampler) - camera.position();
+
+            assert!(offset.x >= 0_f32);
+        }
+    }
+
+    #[test]
+    fn test_lens_density_integrates_to_one() {
+        let image = Image::new(2, 1, vec![Vector3::from_fill(1_f32), Vector3::from_fill(3_f32)]);
+        let shapes = [
+            ApertureShape::Circle,
+            ApertureShape::polygon(5),
+            ApertureShape::Texture(Arc::new(BokehTexture::new(&image).unwrap())),
+        ];
+        for shape in shapes {
+            // Integrate the density over the square circumscribing the lens of 
+            // radius one quarter.
+            let camera = camera(shape);
+            let mut sampler = sampler();
+            let sample_count = 20000;
+            let mut sum = 0_f32;
+            for _ in 0..sample_count {
+                let x = 0.5 * sampler.sample_f32() - 0.25;
+                let y = 0.5 * sampler.sample_f32() - 0.25;
+                sum += camera.pdf_lens(&Vector3::new(x, y, 0_f32)) * 0.25;
+            }
+            let result = sum / sample_count as f32;
+
+            assert_relative_eq!(result, 1_f32, epsilon = 0.03);
+        }
+    }
+
+    #[test]
+    fn test_lens_samples_lie_where_the_lens_density_is_positive() {
+        let image = Image::new(2, 2, vec![
+            Vector3::from_fill(1_f32), Vector3::zero(),
+            Vector3::zero(),           Vector3::from_fill(0.5),
+        ]);
+        let shapes = [
+            ApertureShape::Circle,
+            ApertureShape::polygon(6),
+            ApertureShape::Texture(Arc::new(BokehTexture::new(&image).unwrap())),
+        ];
+        for shape in shapes {
+            let camera = camera(shape);
+            let mut sampler = sampler();
+            for _ in 0..1000 {
+                let lens_position = camera.sample_lens(&mut sampler);
+
+                assert!(camera.pdf_lens(&lens_position) > 0_f32);
+            }
+        }
+    }
+
+    #[test]
+    fn test_textured_lens_importance_follows_the_texture() {
+        let image = Image::new(2, 1, vec![Vector3::from_fill(1_f32), Vector3::from_fill(3_f32)]);
+        let texture = Arc::new(BokehTexture::new(&image).unwrap());
+        let camera = camera(ApertureShape::Texture(texture));
+        let direction = -Vector3::unit_z();
+        let dim = Vector3::new(-0.1, 0_f32, 0_f32);
+        let bright = Vector3::new(0.1, 0_f32, 0_f32);
+
+        assert_relative_eq!(camera.importance(&bright, &direction), 3_f32 * camera.importance(&dim, &direction), epsilon = 1e-6);
+        assert_relative_eq!(camera.pdf_lens(&bright), 3_f32 * camera.pdf_lens(&dim), epsilon = 1e-6);
+    }
+}
+
+#[cfg(test)]
+mod physical_camera_tests {
+    use tracer::*;
+    use tracer::core::*;
+    use tracer::bsdf::*;
+    use tracer::geometry::*;
+    use tracer::light::*;
+    use tracer::scene::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector3,
+        Matrix4x4,
+    };
+    use rand::prelude::*;
+
+
+    fn sampler() -> SphereSampler {
+        SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0))
+    }
+
+    #[test]
+    fn test_physical_camera_settings() {
+        let settings = PhysicalCameraSettings::new(50_f32, 2_f32);
+        let expected_v_fov = 2_f32 * f32::atan(12_f32 / 50_f32) * 180_f32 / std::f32::consts::PI;
+
+        assert_relative_eq!(settings.v_fov(), expected_v_fov, epsilon = 1e-5);
+        assert_relative_eq!(settings.aspect(), 1.5, epsilon = 1e-6);
+        assert_relative_eq!(settings.aperture(), 0.025, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_longer_focal_length_narrows_the_field_of_view() {
+        let wide = PhysicalCameraSettings::new(24_f32, 8_f32);
+        let long = PhysicalCameraSettings::new(200_f32, 8_f32);
+        let small_sensor = PhysicalCameraSettings::new(24_f32, 8_f32).with_sensor_size(6.4, 4.8);
+
+        assert!(long.v_fov() < wide.v_fov());
+        assert!(small_sensor.v_fov() < wide.v_fov());
+        assert_relative_eq!(small_sensor.aspect(), 4_f32 / 3_f32, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_camera_from_physical_settings() {
+        let settings = PhysicalCameraSettings::new(50_f32, 2_f32);
+        let camera = Camera::from_physical(Vector3::zero(), -Vector3::unit_z(), Vector3::unit_y(), &settings, 3_f32);
+
+        assert_relative_eq!(camera.lens_area(), std::f32::consts::PI * 0.0125 * 0.0125, epsilon = 1e-8);
+        assert_eq!(camera.focus_dist(), 3_f32);
+    }
+
+    /// A camera with a wide aperture looking down the negative **z-axis** at a 
+    /// unit sphere centered five units away.
+    fn scene() -> Scene {
+        let camera = Camera::new(Vector3::zero(), -Vector3::unit_z(), Vector3::unit_y(), 40_f32, 1_f32, 0.5, 1_f32)
+            .with_aperture_shape(ApertureShape::polygon(6))
+            .with_shutter(0_f32, 1_f32);
+        let object = ModelSpaceGeometryObject::new(
+            Sphere::new(Vector3::zero(), 1_f32),
+            Box::new(SimpleLambertianBsdf::new(Vector3::from_fill(0.5))),
+            Box::new(NoLight::new()),
+        );
+        let mut scene = Scene::new(9, 9, camera);
+        scene.push_object(SceneObject::new(Box::new(object), Matrix4x4::from_affine_translation(&Vector3::new(0_f32, 0_f32, -5_f32))));
+
+        scene
+    }
+
+    #[test]
+    fn test_autofocus_on_the_first_hit_through_a_pixel() {
+        let mut scene = scene();
+        let result = scene.autofocus(4, 4, 9, 9).unwrap();
+
+        assert_relative_eq!(result, 4_f32, epsilon = 0.1);
+        assert_eq!(scene.camera.focus_dist(), result);
+        assert_eq!(scene.camera.shutter(), (0_f32, 1_f32));
+        assert_eq!(scene.camera.aperture_shape(), &ApertureShape::polygon(6));
+    }
+
+    #[test]
+    fn test_autofocus_brings_the_hit_into_focus() {
+        let mut scene = scene();
+        scene.autofocus(4, 4, 9, 9).unwrap();
+        let u = 4.5 / 9_f32;
+        let v = 4.5 / 9_f32;
+        let pinhole_ray = scene.camera.cast_pinhole_ray(u, v);
+        let hit = scene.intersect(&IntersectionQuery::new(pinhole_ray, 0_f32, f32::MAX)).unwrap();
+        let point = hit.intersection_result.unwrap_hit().point;
+        let mut sampler = sampler();
+        for _ in 0..100 {
+            let ray = scene.camera.cast_ray(&mut sampler, u, v);
+
+            assert_relative_eq!(ray.interpolate(1_f32), point, epsilon = 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_autofocus_on_a_moving_camera_focuses_when_the_shutter_opens() {
+        let mut scene = scene();
+        scene.camera = scene.camera.clone().with_motion(Vector3::new(0_f32, 0_f32, -2_f32), Vector3::new(0_f32, 0_f32, -3_f32));
+        let result = scene.autofocus(4, 4, 9, 9).unwrap();
+
+        assert_relative_eq!(result, 4_f32, epsilon = 0.1);
+        assert!(scene.camera.is_moving());
+    }
+
+    #[test]
+    fn test_autofocus_outside_the_image_keeps_the_focus() {
+        let mut scene = scene();
+
+        assert!(scene.autofocus(9, 4, 9, 9).is_none());
+        assert!(scene.autofocus(4, 9, 9, 9).is_none());
+        assert!(scene.autofocus(0, 0, 0, 0).is_none());
+        assert_eq!(scene.camera.focus_dist(), 1_f32);
+    }
+
+    #[test]
+    fn test_autofocus_on_the_background_keeps_the_focus() {
+        let mut scene = scene();
+
+        assert!(scene.autofocus(0, 0, 9, 9).is_none());
+        assert_eq!(scene.camera.focus_dist(), 1_f32);
+    }
+}
@@ -0,0 +1,214 @@
+extern crate tracer;
+extern crate approx;
+extern crate rand;
+extern crate rand_isaac;
+
+
+#[cfg(test)]
+mod interval_tests {
+    use tracer::core::*;
+    use tracer::geometry::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector3,
+    };
+
+
+    fn query(origin: Vector3<f32>, direction: Vector3<f32>) -> IntersectionQuery {
+        IntersectionQuery::new(Ray::new(origin, direction), 0.0001_f32, f32::MAX)
+    }
+
+    #[test]
+    fn test_ray_through_sphere_has_one_interval() {
+        let sphere = Sphere::new(Vector3::zero(), 1_f32);
+        let intervals = sphere.intersect_intervals(&query(Vector3::new(-3_f32, 0_f32, 0_f32), Vector3::unit_x()));
+
+        assert_eq!(intervals.len(), 1);
+        assert_relative_eq!(intervals[0].t_entry, 2_f32, epsilon = 1e-5);
+        assert_relative_eq!(intervals[0].t_exit, 4_f32, epsilon = 1e-5);
+        assert_relative_eq!(intervals[0].entry.unwrap().normal, -Vector3::unit_x(), epsilon = 1e-5);
+        assert_relative_eq!(intervals[0].exit.unwrap().normal, Vector3::unit_x(), epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_ray_starting_inside_sphere_has_no_entry() {
+        let sphere = Sphere::new(Vector3::zero(), 1_f32);
+        let intervals = sphere.intersect_intervals(&query(Vector3::zero(), Vector3::unit_x()));
+
+        assert_eq!(intervals.len(), 1);
+        assert!(intervals[0].entry.is_none());
+        assert_relative_eq!(intervals[0].t_exit, 1_f32, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_ray_missing_sphere_has_no_intervals() {
+        let sphere = Sphere::new(Vector3::zero(), 1_f32);
+        let intervals = sphere.intersect_intervals(&query(Vector3::new(-3_f32, 2_f32, 0_f32), Vector3::unit_x()));
+
+        assert!(intervals.is_empty());
+    }
+
+    #[test]
+    fn test_ray_through_torus_has_two_intervals() {
+        let torus = Torus::new(Vector3::zero(), 2_f32, 0.5_f32);
+        let intervals = torus.intersect_intervals(&query(Vector3::new(-5_f32, 0_f32, 0_f32), Vector3::unit_x()));
+
+        assert_eq!(intervals.len(), 2);
+        assert_relative_eq!(intervals[0].t_entry, 2.5_f32, epsilon = 1e-4);
+        assert_relative_eq!(intervals[0].t_exit, 3.5_f32, epsilon = 1e-4);
+        assert_relative_eq!(intervals[1].t_entry, 6.5_f32, epsilon = 1e-4);
+        assert_relative_eq!(intervals[1].t_exit, 7.5_f32, epsilon = 1e-4);
+    }
+}
+
+#[cfg(test)]
+mod csg_tests {
+    use tracer::core::*;
+    use tracer::geometry::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector3,
+    };
+    use rand::prelude::*;
+
+
+    fn query(origin: Vector3<f32>, direction: Vector3<f32>) -> IntersectionQuery {
+        IntersectionQuery::new(Ray::new(origin, direction), 0.0001_f32, f32::MAX)
+    }
+
+    fn sphere(x: f32, radius: f32) -> Box<dyn Geometry> {
+        Box::new(Sphere::new(Vector3::new(x, 0_f32, 0_f32), radius))
+    }
+
+    #[test]
+    fn test_union_merges_overlapping_intervals() {
+        let csg = Csg::union(sphere(-0.5, 1_f32), sphere(0.5, 1_f32));
+        let intervals = csg.intersect_intervals(&query(Vector3::new(-3_f32, 0_f32, 0_f32), Vector3::unit_x()));
+
+        assert_eq!(intervals.len(), 1);
+        assert_relative_eq!(intervals[0].t_entry, 1.5_f32, epsilon = 1e-5);
+        assert_relative_eq!(intervals[0].t_exit, 4.5_f32, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_union_hides_inner_surfaces() {
+        let csg = Csg::union(sphere(-0.5, 1_f32), sphere(0.5, 1_f32));
+        let data = csg.intersect(&query(Vector3::new(0.2_f32, 0_f32, 0_f32), Vector3::unit_x())).unwrap_hit();
+
+        assert_relative_eq!(data.t, 1.3_f32, epsilon = 1e-5);
+        assert_relative_eq!(data.normal, Vector3::unit_x(), epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_intersection_keeps_overlap() {
+        let csg = Csg::intersection(sphere(-0.5, 1_f32), sphere(0.5, 1_f32));
+        let intervals = csg.intersect_intervals(&query(Vector3::new(-3_f32, 0_f32, 0_f32), Vector3::unit_x()));
+
+        assert_eq!(intervals.len(), 1);
+        assert_relative_eq!(intervals[0].t_entry, 2.5_f32, epsilon = 1e-5);
+        assert_relative_eq!(intervals[0].t_exit, 3.5_f32, epsilon = 1e-5);
+        assert!(csg.contains(&Vector3::zero()));
+        assert!(!csg.contains(&Vector3::new(-1_f32, 0_f32, 0_f32)));
+    }
+
+    #[test]
+    fn test_difference_carves_with_flipped_normals() {
+        let csg = Csg::difference(sphere(0_f32, 1_f32), sphere(1_f32, 1_f32));
+        let data = csg.intersect(&query(Vector3::new(3_f32, 0_f32, 0_f32), -Vector3::unit_x())).unwrap_hit();
+
+        // The ray passes through the removed solid and hits the carved surface,
+        // whose normal faces out of the difference.
+        assert_relative_eq!(data.t, 3_f32, epsilon = 1e-5);
+        assert_relative_eq!(data.normal, Vector3::unit_x(), epsilon = 1e-5);
+        assert!(!csg.contains(&Vector3::new(0.5_f32, 0_f32, 0_f32)));
+        assert!(csg.contains(&Vector3::new(-0.5_f32, 0_f32, 0_f32)));
+    }
+
+    #[test]
+    fn test_difference_splits_intervals() {
+        let slab = Box::new(AxisAlignedBox::new(Vector3::new(-0.25_f32, -2_f32, -2_f32), Vector3::new(0.25_f32, 2_f32, 2_f32)));
+        let csg = Csg::difference(sphere(0_f32, 1_f32), slab);
+        let intervals = csg.intersect_intervals(&query(Vector3::new(-3_f32, 0_f32, 0_f32), Vector3::unit_x()));
+
+        assert_eq!(intervals.len(), 2);
+        assert_relative_eq!(intervals[0].t_exit, 2.75_f32, epsilon = 1e-5);
+        assert_relative_eq!(intervals[0].exit.unwrap().normal, Vector3::unit_x(), epsilon = 1e-5);
+        assert_relative_eq!(intervals[1].t_entry, 3.25_f32, epsilon = 1e-5);
+        assert_relative_eq!(intervals[1].entry.unwrap().normal, -Vector3::unit_x(), epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_nested_csg() {
+        // A hollow shell with a hole drilled along the x-axis.
+        let shell = Csg::difference(sphere(0_f32, 1_f32), sphere(0_f32, 0.5_f32));
+        let drill = Box::new(Csg::intersection(
+            Box::new(AxisAlignedBox::new(Vector3::new(-2_f32, -0.1_f32, -0.1_f32), Vector3::new(2_f32, 0.1_f32, 0.1_f32))),
+            sphere(0_f32, 2_f32),
+        ));
+        let csg = Csg::difference(Box::new(shell), drill);
+
+        assert!(csg.intersect(&query(Vector3::new(-3_f32, 0_f32, 0_f32), Vector3::unit_x())).is_miss());
+
+        let data = csg.intersect(&query(Vector3::new(-3_f32, 0.3_f32, 0_f32), Vector3::unit_x())).unwrap_hit();
+
+        assert_relative_eq!(data.point.x, -f32::sqrt(1_f32 - 0.09_f32), epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_csg_bounds() {
+        let union = Csg::union(sphere(-1_f32, 1_f32), sphere(1_f32, 1_f32));
+        let intersection = Csg::intersection(sphere(-0.5, 1_f32), sphere(0.5, 1_f32));
+
+        assert_relative_eq!(union.bounds().min, Vector3::new(-2_f32, -1_f32, -1_f32), epsilon = 1e-6);
+        assert_relative_eq!(union.bounds().max, Vector3::new(2_f32, 1_f32, 1_f32), epsilon = 1e-6);
+        assert_relative_eq!(intersection.bounds().min, Vector3::new(-0.5_f32, -1_f32, -1_f32), epsilon = 1e-6);
+        assert_relative_eq!(intersection.bounds().max, Vector3::new(0.5_f32, 1_f32, 1_f32), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_csg_area_estimate() {
+        let disjoint = Csg::union(sphere(-2_f32, 1_f32), sphere(2_f32, 1_f32));
+        // Two unit spheres a unit apart overlap in caps of height one half, 
+        // each with area `pi`.
+        let overlapping = Csg::union(sphere(-0.5, 1_f32), sphere(0.5, 1_f32));
+        let expected = 8_f32 * std::f32::consts::PI - 2_f32 * std::f32::consts::PI;
+
+        assert_relative_eq!(disjoint.area(), 8_f32 * std::f32::consts::PI, epsilon = 1e-4);
+        assert_relative_eq!(overlapping.area(), expected, max_relative = 0.03);
+    }
+
+    #[test]
+    fn test_csg_surface_samples_lie_on_surface() {
+        let csg = Csg::difference(sphere(0_f32, 1_f32), sphere(1_f32, 1_f32));
+        let mut sampler = SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0));
+        let offset = 0.01_f32;
+        for _ in 0..1000 {
+            let sample = csg.sample_surface(&mut sampler).unwrap();
+            let data = csg.intersect(&query(sample.point + sample.normal * offset, -sample.normal)).unwrap_hit();
+
+            assert_relative_eq!(sample.pdf, 1_f32 / csg.area(), epsilon = 1e-6);
+            assert_relative_eq!(data.t, offset, epsilon = 1e-3);
+            assert_relative_eq!(data.normal, sample.normal, epsilon = 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_csg_with_unbounded_child_cannot_be_sampled() {
+        let half_space = Box::new(Plane::new(Vector3::zero(), Vector3::unit_y()));
+        let csg = Csg::intersection(sphere(0_f32, 1_f32), half_space);
+        let mut sampler = SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0));
+
+        assert!(csg.area().is_infinite());
+        assert!(csg.sample_surface(&mut sampler).is_none());
+
+        let data = csg.intersect(&query(Vector3::new(0_f32, 3_f32, 0_f32), -Vector3::unit_y())).unwrap_hit();
+
+        assert_relative_eq!(data.t, 3_f32, epsilon = 1e-5);
+        assert_relative_eq!(data.normal, Vector3::unit_y(), epsilon = 1e-5);
+    }
+}
+
@@ -0,0 +1,65 @@
+extern crate tracer;
+extern crate approx;
+
+
+#[cfg(test)]
+mod film_tests {
+    use tracer::renderer::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector3,
+    };
+
+
+    #[test]
+    fn test_film_resolves_samples_and_splats() {
+        let mut film = Film::new(4, 3);
+        film.add_sample(1, 2, Vector3::new(1_f32, 2_f32, 3_f32));
+        film.add_sample(1, 2, Vector3::new(3_f32, 2_f32, 1_f32));
+        film.add_splat(1, 2, Vector3::new(4_f32, 4_f32, 4_f32));
+        film.add_splat(0, 0, Vector3::new(2_f32, 0_f32, 0_f32));
+        let samples_per_pixel = 2;
+
+        assert_relative_eq!(film.pixel(1, 2, samples_per_pixel), Vector3::new(4_f32, 4_f32, 4_f32));
+        assert_relative_eq!(film.pixel(0, 0, samples_per_pixel), Vector3::new(1_f32, 0_f32, 0_f32));
+        assert_relative_eq!(film.pixel(2, 3, samples_per_pixel), Vector3::zero());
+    }
+
+    #[test]
+    fn test_film_pixel_at_matches_pixel_sampling() {
+        // The renderer samples the pixel at `(row, column)` with film coordinates
+        // `u = (column + du) / width` and `v = (height - row - 1 + dv) / height`.
+        let width = 4;
+        let height = 3;
+        let film = Film::new(width, height);
+        for row in 0..height {
+            for column in 0..width {
+                let u = (column as f32 + 0.5) / width as f32;
+                let v = ((height - row - 1) as f32 + 0.5) / height as f32;
+
+                assert_eq!(film.pixel_at(u, v), Some((row, column)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_film_pixel_at_outside_film() {
+        let film = Film::new(4, 3);
+
+        assert_eq!(film.pixel_at(-0.1, 0.5), None);
+        assert_eq!(film.pixel_at(0.5, 1.1), None);
+    }
+
+    #[test]
+    fn test_film_pixel_at_covers_every_row() {
+        let film = Film::new(4, 3);
+
+        assert_eq!(film.pixel_at(0.5, 1_f32), Some((0, 2)));
+        assert_eq!(film.pixel_at(0.5, 0.9), Some((0, 2)));
+        assert_eq!(film.pixel_at(0.5, 0.1), Some((2, 2)));
+        assert_eq!(film.pixel_at(0.5, 0_f32), Some((2, 2)));
+    }
+}
+
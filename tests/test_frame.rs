@@ -0,0 +1,80 @@
+extern crate tracer;
+extern crate approx;
+
+
+#[cfg(test)]
+mod frame_tests {
+    use tracer::core::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector3,
+        Magnitude,
+    };
+
+
+    fn normals() -> Vec<Vector3<f32>> {
+        vec![
+            Vector3::unit_x(),
+            Vector3::unit_y(),
+            Vector3::unit_z(),
+            -Vector3::unit_z(),
+            Vector3::new(1_f32, 2_f32, 3_f32).normalize(),
+            Vector3::new(-4_f32, 1_f32, -0.5).normalize(),
+        ]
+    }
+
+    #[test]
+    fn test_frame_from_normal_is_orthonormal() {
+        for normal in normals() {
+            let frame = Frame::from_normal(&normal);
+
+            assert_relative_eq!(frame.tangent.magnitude(), 1_f32, epsilon = 1e-6);
+            assert_relative_eq!(frame.bitangent.magnitude(), 1_f32, epsilon = 1e-6);
+            assert_relative_eq!(frame.tangent.dot(&frame.bitangent), 0_f32, epsilon = 1e-6);
+            assert_relative_eq!(frame.tangent.dot(&frame.normal), 0_f32, epsilon = 1e-6);
+            assert_relative_eq!(frame.bitangent.dot(&frame.normal), 0_f32, epsilon = 1e-6);
+            assert_relative_eq!(frame.tangent.cross(&frame.bitangent), frame.normal, epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_frame_to_local_of_normal_is_z_axis() {
+        for normal in normals() {
+            let frame = Frame::from_normal(&normal);
+
+            assert_relative_eq!(frame.to_local(&normal), Vector3::unit_z(), epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_frame_round_trip() {
+        let vector = Vector3::new(0.3, -2_f32, 5_f32);
+        for normal in normals() {
+            let frame = Frame::from_normal(&normal);
+            let result = frame.to_world(&frame.to_local(&vector));
+
+            assert_relative_eq!(result, vector, epsilon = 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_frame_from_normal_tangent_aligns_tangent() {
+        let normal = Vector3::unit_y();
+        let tangent = Vector3::new(1_f32, 1_f32, 0_f32);
+        let frame = Frame::from_normal_tangent(&normal, &tangent);
+
+        assert_relative_eq!(frame.tangent, Vector3::unit_x(), epsilon = 1e-6);
+        assert_relative_eq!(frame.bitangent, -Vector3::unit_z(), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_frame_from_degenerate_tangent_is_orthonormal() {
+        let normal = Vector3::unit_y();
+        let frame = Frame::from_normal_tangent(&normal, &Vector3::unit_y());
+
+        assert_relative_eq!(frame.tangent.dot(&frame.normal), 0_f32, epsilon = 1e-6);
+        assert_relative_eq!(frame.tangent.magnitude(), 1_f32, epsilon = 1e-6);
+    }
+}
@@ -0,0 +1,308 @@
+extern crate tracer;
+extern crate approx;
+extern crate rand;
+extern crate rand_isaac;
+
+
+#[cfg(test)]
+mod bvh_tests {
+    use tracer::core::*;
+    use tracer::geometry::*;
+    use cglinalg::{
+        Vector3,
+    };
+    use rand::prelude::*;
+
+
+    fn spheres(count: usize, sampler: &mut SphereSampler) -> Vec<Sphere> {
+        (0..count)
+            .map(|_| {
+                let center = Vector3::new(sampler.sample_f32(), sampler.sample_f32(), sampler.sample_f32()) * 100_f32;
+
+                Sphere::new(center, 0.5 + sampler.sample_f32())
+            })
+            .collect()
+    }
+
+    fn closest_hit_brute_force(spheres: &[Sphere], query: &IntersectionQuery) -> Option<f32> {
+        spheres
+            .iter()
+            .filter_map(|sphere| match sphere.intersect(query) {
+                IntersectionResult::Hit(data) | IntersectionResult::Tangent(data) => Some(data.t),
+                IntersectionResult::Miss(_) => None,
+            })
+            .min_by(|t1, t2| t1.total_cmp(t2))
+    }
+
+    fn closest_hit_bvh(bvh: &Bvh, spheres: &[Sphere], query: &IntersectionQuery) -> (Option<f32>, usize) {
+        let mut closest = None;
+        let bounds_tests = bvh.traverse(&query.ray, query.t_min, query.t_max, |index, t_closest| {
+            let sphere_query = IntersectionQuery::new(query.ray, query.t_min, t_closest);
+            match spheres[index].intersect(&sphere_query) {
+                IntersectionResult::Hit(data) | IntersectionResult::Tangent(data) if data.t < t_closest => {
+                    closest = Some(data.t);
+
+                    data.t
+                }
+                _ => t_closest,
+            }
+        });
+
+        (closest, bounds_tests)
+    }
+
+    #[test]
+    fn test_bvh_finds_the_closest_hit() {
+        let mut sampler = SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0));
+        let spheres = spheres(1000, &mut sampler);
+        let bounds = spheres.iter().map(|sphere| sphere.bounds()).collect::<Vec<BoundingBox>>();
+        let bvh = Bvh::new(&bounds);
+        let mut hit_count = 0;
+        for _ in 0..1000 {
+            let origin = Vector3::from_fill(50_f32) + sampler.sample_uniform_direction() * 100_f32;
+            let target = Vector3::new(sampler.sample_f32(), sampler.sample_f32(), sampler.sample_f32()) * 100_f32;
+            let query = IntersectionQuery::new(Ray::new(origin, target - origin), 0.0001, f32::MAX);
+            let expected = closest_hit_brute_force(&spheres, &query);
+            let (result, _) = closest_hit_bvh(&bvh, &spheres, &query);
+
+            assert_eq!(result, expected);
+            hit_count += usize::from(result.is_some());
+        }
+
+        assert!(hit_count > 100);
+    }
+
+    #[test]
+    fn test_bvh_tests_few_bounding_boxes() {
+        let mut sampler = SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0));
+        let spheres = spheres(10000, &mut sampler);
+        let bounds = spheres.iter().map(|sphere| sphere.bounds()).collect::<Vec<BoundingBox>>();
+        let bvh = Bvh::new(&bounds);
+        let query = IntersectionQuery::new(Ray::new(Vector3::new(-10_f32, 50_f32, 50_f32), Vector3::unit_x()), 0.0001, f32::MAX);
+        let (_, bounds_tests) = closest_hit_bvh(&bvh, &spheres, &query);
+
+        assert!(bvh.len_nodes() < spheres.len());
+        assert!(bounds_tests < 500);
+    }
+
+    #[test]
+    fn test_bvh_visits_unbounded_items() {
+        let bounds = [
+            BoundingBox::new(Vector3::from_fill(-1_f32), Vector3::from_fill(1_f32)),
+            BoundingBox::infinite(),
+            BoundingBox::empty(),
+        ];
+        let bvh = Bvh::new(&bounds);
+        let ray = Ray::new(Vector3::new(0_f32, 10_f32, 0_f32), Vector3::unit_x());
+        let mut visited = Vec::new();
+        bvh.traverse(&ray, 0.0001, f32::MAX, |index, t_closest| {
+            visited.push(index);
+
+            t_closest
+        });
+
+        assert_eq!(visited, vec![1]);
+    }
+}
+
+#[cfg(test)]
+mod geometry_group_tests {
+    use tracer::core::*;
+    use tracer::geometry::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector3,
+    };
+    use rand::prelude::*;
+
+
+    fn group() -> GeometryGroup {
+        let children = (0..100)
+            .map(|i| {
+                let center = Vector3::new((i % 10) as f32, (i / 10) as f32, 0_f32) * 3_f32;
+
+                Box::new(Sphere::new(center, 1_f32)) as Box<dyn Geometry>
+            })
+            .collect::<Vec<Box<dyn Geometry>>>();
+
+        GeometryGroup::new(children)
+    }
+
+    #[test]
+    fn test_geometry_group_intersection() {
+        let group = group();
+        let query = IntersectionQuery::new(Ray::new(Vector3::new(9_f32, 6_f32, 10_f32), -Vector3::unit_z()), 0.0001, f32::MAX);
+        let data = group.intersect(&query).unwrap_hit();
+
+        assert_relative_eq!(data.t, 9_f32, epsilon = 1e-5);
+        assert_relative_eq!(data.normal, Vector3::unit_z(), epsilon = 1e-5);
+
+        let query = IntersectionQuery::new(Ray::new(Vector3::new(-5_f32, 1.5_f32, 0_f32), Vector3::unit_x()), 0.0001, f32::MAX);
+
+        assert!(group.intersect(&query).is_miss());
+        assert!(group.contains(&Vector3::new(27_f32, 27_f32, 0.5_f32)));
+        assert!(!group.contains(&Vector3::new(1.5_f32, 1.5_f32, 0_f32)));
+    }
+
+    #[test]
+    fn test_geometry_group_surface_samples() {
+        let group = group();
+        let mut sampler = SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0));
+
+        assert_relative_eq!(group.area(), 400_f32 * std::f32::consts::PI, max_relative = 1e-5);
+
+        for _ in 0..100 {
+            let sample = group.sample_surface(&mut sampler).unwrap();
+            let query = IntersectionQuery::new(Ray::new(sample.point + sample.normal * 0.01, -sample.normal), 0.0001, f32::MAX);
+            let data = group.intersect(&query).unwrap_hit();
+
+            assert_relative_eq!(sample.pdf, 1_f32 / group.area(), epsilon = 1e-6);
+            assert_relative_eq!(data.t, 0.01_f32, epsilon = 1e-4);
+        }
+    }
+}
+
+#[cfg(test)]
+mod instancing_tests {
+    use tracer::Camera;
+    use tracer::bsdf::*;
+    use tracer::core::*;
+    use tracer::geometry::*;
+    use tracer::light::*;
+    use tracer::scene::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector3,
+        Matrix4x4,
+    };
+    use std::sync::Arc;
+
+
+    fn prototype() -> Arc<dyn ModelSpaceObject> {
+        let prototype: Box<dyn ModelSpaceObject> = Box::new(ModelSpaceGeometryObject::new(
+            Sphere::new(Vector3::zero(), 0.25),
+            Box::new(SimpleLambertianBsdf::new(Vector3::from_fill(0.5))),
+            Box::new(NoLight::new())
+        ));
+
+        Arc::from(prototype)
+    }
+
+    fn scene() -> Scene {
+        let camera = Camera::new(Vector3::zero(), -Vector3::unit_z(), Vector3::unit_y(), 90_f32, 1_f32, 0_f32, 1_f32);
+
+        Scene::new(4, 4, camera)
+    }
+
+    /// A scene with a 100 by 100 grid of instances of a sphere in the plane 
+    /// `z == 0`.
+    fn instanced_scene(prototype: &Arc<dyn ModelSpaceObject>) -> Scene {
+        let mut scene = scene();
+        for i in 0..100 {
+            for j in 0..100 {
+                let position = Vector3::new(i as f32, j as f32, 0_f32);
+                let model_matrix = Matrix4x4::from_affine_translation(&position);
+                scene.push_object(SceneObject::new_instance(prototype.clone(), model_matrix));
+            }
+        }
+
+        scene
+    }
+
+    #[test]
+    fn test_instances_share_their_prototype() {
+        let prototype = prototype();
+        let scene = instanced_scene(&prototype);
+
+        assert_eq!(Arc::strong_count(&prototype), 10001);
+        assert!(Arc::ptr_eq(scene.objects()[0].prototype(), &prototype));
+        assert!(Arc::ptr_eq(scene.objects()[9999].prototype(), &prototype));
+    }
+
+    #[test]
+    fn test_instances_have_their_own_transforms() {
+        let prototype = prototype();
+        let scene = instanced_scene(&prototype);
+        let query = IntersectionQuery::new(Ray::new(Vector3::new(42_f32, 17_f32, 10_f32), -Vector3::unit_z()), 0.0001, f32::MAX);
+        let result = scene.intersect(&query).unwrap();
+        let data = result.intersection_result.unwrap_hit();
+
+        assert_relative_eq!(data.t, 9.75_f32, epsilon = 1e-5);
+        assert_relative_eq!(result.object.center(), Vector3::new(42_f32, 17_f32, 0_f32), epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_scene_tests_few_of_many_instances() {
+        let prototype = prototype();
+        let scene = instanced_scene(&prototype);
+        let query = IntersectionQuery::new(Ray::new(Vector3::new(-10_f32, 50_f32, 0_f32), Vector3::unit_x()), 0.0001, f32::MAX);
+        let mut stats = TraversalStats::default();
+        let result = scene.intersect_with_stats(&query, &mut stats).unwrap();
+
+        assert_relative_eq!(result.intersection_result.unwrap_hit().t, 9.75_f32, epsilon = 1e-5);
+        assert!(stats.object_tests < 10);
+        assert!(stats.total() < 200);
+    }
+
+    #[test]
+    fn test_scene_finds_objects_pushed_after_a_query() {
+        let mut scene = scene();
+        let query = IntersectionQuery::new(Ray::new(Vector3::new(0_f32, 0_f32, 10_f32), -Vector3::unit_z()), 0.0001, f32::MAX);
+
+        assert!(scene.intersect(&query).is_none());
+
+        scene.push_object(SceneObject::new_instance(prototype(), Matrix4x4::identity()));
+
+        assert!(scene.intersect(&query).is_some());
+    }
+
+    #[test]
+    fn test_instances_override_material_of_prototype() {
+        let prototype = prototype();
+        let reflectance = Vector3::new(0.9, 0.1, 0.1);
+        let emission = Vector3::new(1_f32, 2_f32, 3_f32);
+        let bsdf: Box<dyn Bsdf> = Box::new(SimpleLambertianBsdf::new(reflectance));
+        let plain = SceneObject::new_instance(prototype.clone(), Matrix4x4::identity());
+        let overridden = SceneObject::new_instance(prototype, Matrix4x4::identity())
+            .with_bsdf(Arc::from(bsdf))
+            .with_emitter(Arc::new(PointLight::new(emission)));
+        let query = IntersectionQuery::new(Ray::new(Vector3::new(0_f32, 0_f32, 10_f32), -Vector3::unit_z()), 0.0001, f32::MAX);
+        let hit = overridden.intersect(&query).unwrap_hit();
+        let ray_incoming = -Vector3::unit_z();
+        let ray_outgoing = Vector3::unit_z();
+
+        assert_relative_eq!(
+            plain.evaluate_bsdf(&hit, &ray_incoming, &ray_outgoing), 
+            Vector3::from_fill(0.5) / std::f32::consts::PI, 
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            overridden.evaluate_bsdf(&hit, &ray_incoming, &ray_outgoing), 
+            reflectance / std::f32::consts::PI, 
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(plain.emission(&hit, &ray_incoming), Vector3::zero(), epsilon = 1e-6);
+        assert_relative_eq!(overridden.emission(&hit, &ray_incoming), emission, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_scatter_uses_material_override() {
+        let emission = Vector3::new(1_f32, 2_f32, 3_f32);
+        let object = SceneObject::new_instance(prototype(), Matrix4x4::from_affine_translation(&Vector3::new(1_f32, 2_f32, 3_f32)))
+            .with_emitter(Arc::new(PointLight::new(emission)));
+        let query = IntersectionQuery::new(Ray::new(Vector3::new(1_f32, 2_f32, 10_f32), -Vector3::unit_z()), 0.0001, f32::MAX);
+        let hit = object.intersect(&query).unwrap_hit();
+        let scattering_query = ScatteringQuery::new(-Vector3::unit_z(), hit.point, hit.uv, hit.shading_frame());
+        let mut sampler = SphereSampler::new(<rand_isaac::Isaac64Rng as rand::SeedableRng>::seed_from_u64(0));
+        let result = object.scatter(&scattering_query, &mut sampler);
+
+        assert_relative_eq!(result.emission, emission, epsilon = 1e-6);
+        assert_relative_eq!(result.scattering_fraction, Vector3::from_fill(0.5), epsilon = 1e-6);
+        assert!(result.ray_outgoing.z > 0_f32);
+    }
+}
+
@@ -0,0 +1,294 @@
+extern crate tracer;
+extern crate approx;
+extern crate rand;
+extern crate rand_isaac;
+
+
+#[cfg(test)]
+mod integrator_tests {
+    use tracer::*;
+    use tracer::core::*;
+    use tracer::bsdf::*;
+    use tracer::geometry::*;
+    use tracer::light::*;
+    use tracer::renderer::*;
+    use tracer::scene::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector3,
+        Matrix4x4,
+    };
+    use rand::prelude::*;
+
+
+    fn sampler() -> SphereSampler {
+        SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0))
+    }
+
+    fn sphere(center: Vector3<f32>, radius: f32, albedo: f32) -> SceneObject {
+        let object = ModelSpaceGeometryObject::new(
+            Sphere::new(Vector3::zero(), radius),
+            Box::new(SimpleLambertianBsdf::new(Vector3::from_fill(albedo))),
+            Box::new(NoLight::new()),
+        );
+
+        SceneObject::new(Box::new(object), Matrix4x4::from_affine_translation(&center))
+    }
+
+    /// A camera looking down at a diffuse floor from the height one.
+    fn scene() -> Scene {
+        let look_from = Vector3::new(0_f32, 1_f32, 1_f32);
+        let look_at = Vector3::zero();
+        let camera = Camera::new(look_from, look_at, Vector3::unit_y(), 1_f32, 1_f32, 0_f32, f32::sqrt(2_f32));
+        let mut scene = Scene::new(1, 1, camera);
+        scene.push_object(sphere(Vector3::new(0_f32, -1000_f32, 0_f32), 1000_f32, 0.5));
+
+        scene
+    }
+
+    fn camera_ray(scene: &Scene, sampler: &mut SphereSampler) -> Ray {
+        scene.camera.cast_ray(sampler, 0.5, 0.5)
+    }
+
+    #[test]
+    fn test_debug_integrator_normals() {
+        let scene = scene();
+        let mut sampler = sampler();
+        let integrator = DebugIntegrator::new(DebugMode::Normals, 4, f32::MAX);
+        let ray = camera_ray(&scene, &mut sampler);
+        let expected = Vector3::new(0.5, 1_f32, 0.5);
+        let result = integrator.radiance(&scene, &ray, &mut sampler);
+
+        assert_relative_eq!(result, expected, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn test_debug_integrator_normals_miss_is_black() {
+        let scene = scene();
+        let mut sampler = sampler();
+        let integrator = DebugIntegrator::new(DebugMode::Normals, 4, f32::MAX);
+        let ray = Ray::new(Vector3::new(0_f32, 1_f32, 0_f32), Vector3::unit_y());
+        let result = integrator.radiance(&scene, &ray, &mut sampler);
+
+        assert_eq!(result, Vector3::zero());
+    }
+
+    #[test]
+    fn test_debug_integrator_depth() {
+        let scene = scene();
+        let mut sampler = sampler();
+        let integrator = DebugIntegrator::new(DebugMode::Depth, 4, f32::MAX).with_max_distance(10_f32);
+        let ray = camera_ray(&scene, &mut sampler);
+        let expected = Vector3::from_fill(f32::sqrt(2_f32) / 10_f32);
+        let result = integrator.radiance(&scene, &ray, &mut sampler);
+
+        assert_relative_eq!(result, expected, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn test_debug_integrator_uv_is_a_color() {
+        let scene = scene();
+        let mut sampler = sampler();
+        let integrator = DebugIntegrator::new(DebugMode::Uv, 4, f32::MAX);
+        let ray = camera_ray(&scene, &mut sampler);
+        let result = integrator.radiance(&scene, &ray, &mut sampler);
+        for i in 0..3 {
+            assert!((0_f32..=1_f32).contains(&result[i]));
+        }
+    }
+
+    #[test]
+    fn test_debug_integrator_barycentrics() {
+        let mut scene = scene();
+        let triangle = ModelSpaceGeometryObject::new(
+            Triangle::new(Vector3::zero(), Vector3::unit_x(), Vector3::unit_z()),
+            Box::new(SimpleLambertianBsdf::new(Vector3::from_fill(0.5))),
+            Box::new(NoLight::new()),
+        );
+        scene.push_object(SceneObject::new(Box::new(triangle), Matrix4x4::from_affine_translation(&Vector3::new(2_f32, 0.5, 0_f32))));
+        let mut sampler = sampler();
+        let integrator = DebugIntegrator::new(DebugMode::Barycentrics, 4, f32::MAX);
+        let triangle_ray = Ray::new(Vector3::new(2.25, 1_f32, 0.5), -Vector3::unit_y());
+        let floor_ray = camera_ray(&scene, &mut sampler);
+
+        assert_relative_eq!(integrator.radiance(&scene, &triangle_ray, &mut sampler), Vector3::new(0.25, 0.25, 0.5), epsilon = 1e-5);
+        assert_eq!(integrator.radiance(&scene, &floor_ray, &mut sampler), Vector3::zero());
+    }
+
+    #[test]
+    fn test_debug_integrator_object_ids_differ() {
+        let mut scene = scene();
+        scene.push_object(sphere(Vector3::new(0_f32, 0.5, 0.5), 0.1, 0.5));
+        let mut sampler = sampler();
+        let integrator = DebugIntegrator::new(DebugMode::ObjectId, 4, f32::MAX);
+        let floor_ray = Ray::new(Vector3::new(2_f32, 1_f32, 0_f32), -Vector3::unit_y());
+        let sphere_ray = camera_ray(&scene, &mut sampler);
+        let floor_color = integrator.radiance(&scene, &floor_ray, &mut sampler);
+        let sphere_color = integrator.radiance(&scene, &sphere_ray, &mut sampler);
+
+        assert_ne!(floor_color, Vector3::zero());
+        assert_ne!(sphere_color, Vector3::zero());
+        assert_ne!(floor_color, sphere_color);
+    }
+
+    #[test]
+    fn test_scene_hit_records_the_object_index() {
+        let mut scene = scene();
+        for i in 1..5 {
+            scene.push_object(sphere(Vector3::new(i as f32, 0.5, 0_f32), 0.1, 0.5));
+        }
+        let mut sampler = sampler();
+        let integrator = DebugIntegrator::new(DebugMode::ObjectId, 4, f32::MAX);
+        let mut colors = Vec::new();
+        for i in 1..5 {
+            let ray = Ray::new(Vector3::new(i as f32, 2_f32, 0_f32), -Vector3::unit_y());
+            let hit = scene.intersect(&IntersectionQuery::new(ray, 0_f32, f32::MAX)).unwrap();
+            let color = integrator.radiance(&scene, &ray, &mut sampler);
+
+            assert_eq!(hit.index, i);
+            assert!(!colors.contains(&color));
+            colors.push(color);
+        }
+    }
+
+    #[test]
+    fn test_debug_integrator_traversal_cost() {
+        let scene = scene();
+        let mut sampler = sampler();
+        let integrator = DebugIntegrator::new(DebugMode::TraversalCost, 4, f32::MAX);
+        // A ray testing both the bounding box and the object costs the most, and 
+        // a ray missing the bounding box costs half as much.
+        let hit_ray = camera_ray(&scene, &mut sampler);
+        let miss_ray = Ray::new(Vector3::new(0_f32, 1_f32, 0_f32), Vector3::unit_y());
+
+        assert_eq!(integrator.radiance(&scene, &hit_ray, &mut sampler), Vector3::new(1_f32, 0_f32, 0_f32));
+        assert_eq!(integrator.radiance(&scene, &miss_ray, &mut sampler), Vector3::new(0_f32, 1_f32, 0_f32));
+    }
+
+    #[test]
+    fn test_debug_integrator_path_depth() {
+        let scene = scene();
+        let mut sampler = sampler();
+        let integrator = DebugIntegrator::new(DebugMode::PathDepth, 4, f32::MAX);
+        // Paths scatter from the convex floor once before escaping.
+        let hit_ray = camera_ray(&scene, &mut sampler);
+        let miss_ray = Ray::new(Vector3::new(0_f32, 1_f32, 0_f32), Vector3::unit_y());
+
+        assert_eq!(integrator.radiance(&scene, &hit_ray, &mut sampler), Vector3::new(0_f32, 1_f32, 1_f32));
+        assert_eq!(integrator.radiance(&scene, &miss_ray, &mut sampler), Vector3::new(0_f32, 0_f32, 1_f32));
+    }
+
+    #[test]
+    fn test_debug_mode_names() {
+        for mode in DebugMode::all() {
+            assert_eq!(mode.name().parse::<DebugMode>(), Ok(mode));
+        }
+
+        assert!("wireframe".parse::<DebugMode>().is_err());
+    }
+
+    #[test]
+    fn test_ambient_occlusion_integrator_unoccluded_floor() {
+        let scene = scene();
+        let mut sampler = sampler();
+        let integrator = AmbientOcclusionIntegrator::new(f32::MAX, f32::MAX);
+        for _ in 0..100 {
+            let ray = camera_ray(&scene, &mut sampler);
+            let result = integrator.radiance(&scene, &ray, &mut sampler);
+
+            assert_eq!(result, Vector3::from_fill(1_f32));
+        }
+    }
+
+    #[test]
+    fn test_ambient_occlusion_integrator_occlusion_distance() {
+        let mut scene = scene();
+        scene.push_object(sphere(Vector3::zero(), 10_f32, 0.5));
+        let mut sampler = sampler();
+        let enclosed = AmbientOcclusionIntegrator::new(f32::MAX, f32::MAX);
+        let nearby = AmbientOcclusionIntegrator::new(1_f32, f32::MAX);
+        for _ in 0..100 {
+            let ray = camera_ray(&scene, &mut sampler);
+
+            assert_eq!(enclosed.radiance(&scene, &ray, &mut sampler), Vector3::zero());
+            assert_eq!(nearby.radiance(&scene, &ray, &mut sampler), Vector3::from_fill(1_f32));
+        }
+    }
+
+    #[test]
+    fn test_direct_lighting_integrator() {
+        let mut scene = scene();
+        scene.push_object(sphere(Vector3::zero(), 5000_f32, 0_f32));
+        scene.push_light(ScenePointLightObject::new(
+            PointLight::new(Vector3::from_fill(4_f32 * std::f32::consts::PI * 16_f32)),
+            Matrix4x4::from_affine_translation(&Vector3::new(0_f32, 2_f32, 0_f32)),
+        ));
+        let mut sampler = sampler();
+        let integrator = DirectLightingIntegrator::new(4, f32::MAX);
+        let ray = camera_ray(&scene, &mut sampler);
+        // The radiance the floor reflects is `albedo / pi * intensity / distance^2`
+        // for a light at distance two with intensity sixteen.
+        let expected = Vector3::from_fill(0.5 / std::f32::consts::PI * 16_f32 / 4_f32);
+        let result = integrator.radiance(&scene, &ray, &mut sampler);
+
+        assert_relative_eq!(result, expected, epsilon = 1e-2);
+    }
+
+    /// A floor with the scattering function `bsdf` inside a black sphere, lit by 
+    /// a point light two units above the floor.
+    fn enclosed_lit_scene(bsdf: Box<dyn Bsdf>) -> Scene {
+        let floor = ModelSpaceGeometryObject::new(Sphere::new(Vector3::zero(), 1000_f32), bsdf, Box::new(NoLight::new()));
+        let mut scene = Scene::new(1, 1, scene().camera);
+        scene.push_object(SceneObject::new(Box::new(floor), Matrix4x4::from_affine_translation(&Vector3::new(0_f32, -1000_f32, 0_f32))));
+        scene.push_object(sphere(Vector3::zero(), 5000_f32, 0_f32));
+        scene.push_light(ScenePointLightObject::new(
+            PointLight::new(Vector3::from_fill(4_f32 * std::f32::consts::PI * 16_f32)),
+            Matrix4x4::from_affine_translation(&Vector3::new(0_f32, 2_f32, 0_f32)),
+        ));
+
+        scene
+    }
+
+    #[test]
+    fn test_path_tracer_direct_lighting_is_weighted_by_the_bsdf() {
+        let scene = enclosed_lit_scene(Box::new(SimpleLambertianBsdf::new(Vector3::from_fill(0.5))));
+        let mut sampler = sampler();
+        let integrator = PathTracer::new(4, 16, f32::MAX);
+        let ray = camera_ray(&scene, &mut sampler);
+        let expected = Vector3::from_fill(0.5 / std::f32::consts::PI * 16_f32 / 4_f32);
+        let result = integrator.radiance(&scene, &ray, &mut sampler);
+
+        assert_relative_eq!(result, expected, epsilon = 1e-2);
+    }
+
+    #[test]
+    fn test_path_tracer_does_not_sample_point_lights_from_mirrors() {
+        let scene = enclosed_lit_scene(Box::new(SimpleMetalBsdf::new(Vector3::from_fill(0.9), 0_f32)));
+        let mut sampler = sampler();
+        let integrator = PathTracer::new(4, 16, f32::MAX);
+        let ray = camera_ray(&scene, &mut sampler);
+
+        assert_eq!(integrator.radiance(&scene, &ray, &mut sampler), Vector3::zero());
+    }
+
+    #[test]
+    fn test_integrators_are_interchangeable() {
+        let scene = scene();
+        let mut sampler = sampler();
+        let integrators: Vec<Box<dyn Integrator>> = vec![
+            Box::new(PathTracer::new(4, 16, f32::MAX)),
+            Box::new(DirectLightingIntegrator::new(4, f32::MAX)),
+            Box::new(AmbientOcclusionIntegrator::new(1_f32, f32::MAX)),
+            Box::new(DebugIntegrator::new(DebugMode::Normals, 4, f32::MAX)),
+        ];
+        for integrator in integrators.iter() {
+            let ray = camera_ray(&scene, &mut sampler);
+            let result = integrator.radiance(&scene, &ray, &mut sampler);
+
+            assert!(result.x.is_finite() && result.y.is_finite() && result.z.is_finite());
+        }
+    }
+}
+
@@ -0,0 +1,410 @@
+extern crate tracer;
+extern crate approx;
+extern crate rand;
+extern crate rand_isaac;
+
+
+#[cfg(test)]
+mod phase_function_tests {
+    use tracer::core::*;
+    use tracer::medium::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector3,
+        Magnitude,
+    };
+    use rand::prelude::*;
+
+
+    fn sampler() -> SphereSampler {
+        SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0))
+    }
+
+    #[test]
+    fn test_isotropic_phase_function_is_uniform() {
+        let phase_function = HenyeyGreensteinPhaseFunction::new(0_f32);
+        let ray_incoming = Vector3::unit_z();
+        let expected = 1_f32 / (4_f32 * std::f32::consts::PI);
+
+        assert_relative_eq!(phase_function.evaluate(&ray_incoming, &Vector3::unit_z()), expected, epsilon = 1e-6);
+        assert_relative_eq!(phase_function.evaluate(&ray_incoming, &Vector3::unit_x()), expected, epsilon = 1e-6);
+        assert_relative_eq!(phase_function.evaluate(&ray_incoming, &(-Vector3::unit_z())), expected, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_phase_function_is_normalized() {
+        let phase_function = HenyeyGreensteinPhaseFunction::new(0.7);
+        let ray_incoming = Vector3::unit_z();
+        let count = 2048;
+        let mut integral = 0_f32;
+        for i in 0..count {
+            // Integrate over the cosine of the scattering angle with the midpoint rule.
+            let cos_theta = -1_f32 + (i as f32 + 0.5) * 2_f32 / count as f32;
+            let sin_theta = f32::sqrt(1_f32 - cos_theta * cos_theta);
+            let ray_outgoing = Vector3::new(sin_theta, 0_f32, cos_theta);
+            integral += phase_function.evaluate(&ray_incoming, &ray_outgoing) * 2_f32 * std::f32::consts::PI * (2_f32 / count as f32);
+        }
+
+        assert_relative_eq!(integral, 1_f32, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn test_phase_function_samples_are_consistent() {
+        let phase_function = HenyeyGreensteinPhaseFunction::new(-0.4);
+        let ray_incoming = Vector3::new(1_f32, 2_f32, -1_f32).normalize();
+        let mut sampler = sampler();
+        let mut mean_cos_theta = 0_f32;
+        let count = 20000;
+        for _ in 0..count {
+            let result = phase_function.sample(&ray_incoming, &mut sampler);
+
+            assert_relative_eq!(result.ray_outgoing.magnitude(), 1_f32, epsilon = 1e-5);
+            assert_relative_eq!(result.pdf, phase_function.evaluate(&ray_incoming, &result.ray_outgoing), epsilon = 1e-3, max_relative = 1e-3);
+            mean_cos_theta += ray_incoming.dot(&result.ray_outgoing) / count as f32;
+        }
+
+        // The asymmetry parameter is the mean cosine of the scattering angle.
+        assert_relative_eq!(mean_cos_theta, -0.4, epsilon = 2e-2);
+    }
+}
+
+#[cfg(test)]
+mod homogeneous_medium_tests {
+    use tracer::core::*;
+    use tracer::medium::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector3,
+    };
+    use rand::prelude::*;
+
+
+    fn sampler() -> SphereSampler {
+        SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0))
+    }
+
+    #[test]
+    fn test_transmittance_follows_beer_lambert_law() {
+        let medium = HomogeneousMedium::new(Vector3::new(0.1, 0.2, 0.3), Vector3::new(0.4, 0.3, 0.2), 0_f32);
+        let ray = Ray::new(Vector3::zero(), Vector3::new(2_f32, 0_f32, 0_f32));
+        let mut sampler = sampler();
+        let transmittance = medium.transmittance(&ray, 1.5, &mut sampler);
+        // The ray travels a distance of three units.
+        let expected = Vector3::new(f32::exp(-1.5), f32::exp(-1.5), f32::exp(-1.5));
+
+        assert_relative_eq!(transmittance, expected, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_transmittance_of_vacuum_over_unbounded_ray() {
+        let medium = HomogeneousMedium::new(Vector3::zero(), Vector3::zero(), 0_f32);
+        let ray = Ray::new(Vector3::zero(), Vector3::unit_x());
+        let mut sampler = sampler();
+        let transmittance = medium.transmittance(&ray, f32::MAX, &mut sampler);
+
+        assert_eq!(transmittance, Vector3::from_fill(1_f32));
+    }
+
+    #[test]
+    fn test_medium_from_albedo() {
+        let mean_free_path = Vector3::new(1_f32, 0.5, 0.25);
+        let white = HomogeneousMedium::from_albedo(Vector3::from_fill(1_f32), mean_free_path, 0_f32);
+        let black = HomogeneousMedium::from_albedo(Vector3::zero(), mean_free_path, 0_f32);
+        let gray = HomogeneousMedium::from_albedo(Vector3::from_fill(0.5), mean_free_path, 0_f32);
+
+        assert_relative_eq!(white.sigma_t(), Vector3::new(1_f32, 2_f32, 4_f32), epsilon = 1e-6);
+        assert_relative_eq!(white.sigma_a(), Vector3::zero(), epsilon = 1e-4);
+        assert_relative_eq!(black.sigma_s(), Vector3::zero(), epsilon = 1e-4);
+        // Light scatters many times inside a medium before leaving it, so the 
+        // medium must scatter much more than it absorbs to look light gray.
+        assert!(gray.sigma_s().x > 0.8 * gray.sigma_t().x);
+        assert!(gray.sigma_s().x < gray.sigma_t().x);
+    }
+
+    #[test]
+    fn test_free_flight_sampling_is_unbiased() {
+        // The expected weight of a sample that passes through the medium is the 
+        // transmittance of the segment, and the expected weight of a scattering 
+        // event is the probability of scattering within the segment.
+        let sigma_a = Vector3::new(0.2, 0.5, 0.1);
+        let sigma_s = Vector3::new(0.6, 0.3, 0.9);
+        let medium = HomogeneousMedium::new(sigma_a, sigma_s, 0.5);
+        let ray = Ray::new(Vector3::zero(), Vector3::unit_y());
+        let t_max = 1.25;
+        let mut sampler = sampler();
+        let count = 50000;
+        let mut passed = Vector3::zero();
+        let mut scattered = Vector3::zero();
+        for _ in 0..count {
+            let result = medium.sample(&ray, t_max, &mut sampler);
+            match result.interaction {
+                Some(interaction) => {
+                    assert!(interaction.t >= 0_f32 && interaction.t < t_max);
+                    assert_relative_eq!(interaction.point, ray.interpolate(interaction.t));
+                    scattered += result.weight / count as f32;
+                }
+                None => passed += result.weight / count as f32,
+            }
+        }
+        let sigma_t = sigma_a + sigma_s;
+        let expected_passed = Vector3::new(
+            f32::exp(-sigma_t.x * t_max),
+            f32::exp(-sigma_t.y * t_max),
+            f32::exp(-sigma_t.z * t_max),
+        );
+        let expected_scattered = Vector3::new(
+            sigma_s.x / sigma_t.x * (1_f32 - expected_passed.x),
+            sigma_s.y / sigma_t.y * (1_f32 - expected_passed.y),
+            sigma_s.z / sigma_t.z * (1_f32 - expected_passed.z),
+        );
+
+        assert_relative_eq!(passed, expected_passed, epsilon = 2e-2);
+        assert_relative_eq!(scattered, expected_scattered, epsilon = 2e-2);
+    }
+}
+
+#[cfg(test)]
+mod scene_medium_tests {
+    use tracer::{
+        Camera,
+    };
+    use tracer::core::*;
+    use tracer::bsdf::*;
+    use tracer::geometry::*;
+    use tracer::light::*;
+    use tracer::medium::*;
+    use tracer::scene::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector3,
+        Matrix4x4,
+    };
+    use rand::prelude::*;
+    use std::sync::Arc;
+
+
+    fn sampler() -> SphereSampler {
+        SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0))
+    }
+
+    fn camera() -> Camera {
+        let look_from = Vector3::new(0_f32, 0_f32, 10_f32);
+        let look_at = Vector3::zero();
+        let v_up = Vector3::unit_y();
+
+        Camera::new(look_from, look_at, v_up, 20_f32, 1_f32, 0_f32, 10_f32)
+    }
+
+    fn scene(bsdf: Box<dyn Bsdf>) -> Scene {
+        let sphere = Sphere::new(Vector3::zero(), 1_f32);
+        let object = Box::new(ModelSpaceGeometryObject::new(sphere, bsdf, Box::new(NoLight::new())));
+        let medium = Arc::new(HomogeneousMedium::new(Vector3::from_fill(0.5), Vector3::from_fill(0.5), 0_f32));
+        let scene_object = SceneObject::new(object, Matrix4x4::identity()).with_interior_medium(medium);
+        let mut scene = Scene::new(1, 1, camera());
+        scene.push_object(scene_object);
+
+        scene
+    }
+
+    #[test]
+    fn test_transmittance_through_medium_boundary() {
+        let scene = scene(Box::new(PassThroughBsdf::new()));
+        let from = Vector3::new(-3_f32, 0_f32, 0_f32);
+        let to = Vector3::new(3_f32, 0_f32, 0_f32);
+        let mut sampler = sampler();
+        let transmittance = scene.transmittance(&from, &to, 0_f32, None, &mut sampler);
+        // The segment crosses two units of the interior medium.
+        let expected = Vector3::from_fill(f32::exp(-2_f32));
+
+        assert_relative_eq!(transmittance, expected, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_transmittance_through_opaque_surface() {
+        let scene = scene(Box::new(SimpleLambertianBsdf::new(Vector3::from_fill(0.5))));
+        let from = Vector3::new(-3_f32, 0_f32, 0_f32);
+        let to = Vector3::new(3_f32, 0_f32, 0_f32);
+        let mut sampler = sampler();
+        let transmittance = scene.transmittance(&from, &to, 0_f32, None, &mut sampler);
+
+        assert_eq!(transmittance, Vector3::zero());
+    }
+
+    #[test]
+    fn test_transmittance_of_segment_before_surface() {
+        let scene = scene(Box::new(SimpleLambertianBsdf::new(Vector3::from_fill(0.5))));
+        let from = Vector3::new(-3_f32, 0_f32, 0_f32);
+        let to = Vector3::new(-2_f32, 0_f32, 0_f32);
+        let mut sampler = sampler();
+        let transmittance = scene.transmittance(&from, &to, 0_f32, None, &mut sampler);
+
+        assert_eq!(transmittance, Vector3::from_fill(1_f32));
+    }
+
+    #[test]
+    fn test_scene_medium_attenuates_shadow_rays() {
+        let mut scene = scene(Box::new(PassThroughBsdf::new()));
+        scene.set_medium(Arc::new(HomogeneousMedium::new(Vector3::from_fill(0.25), Vector3::zero(), 0_f32)));
+        let from = Vector3::new(-3_f32, 0_f32, 0_f32);
+        let to = Vector3::new(3_f32, 0_f32, 0_f32);
+        let mut sampler = sampler();
+        let transmittance = scene.transmittance(&from, &to, 0_f32, scene.medium(), &mut sampler);
+        // Four units of the scene medium and two units of the interior medium.
+        let expected = Vector3::from_fill(f32::exp(-1_f32 - 2_f32));
+
+        assert_relative_eq!(transmittance, expected, epsilon = 1e-4);
+    }
+}
+
+#[cfg(test)]
+mod grid_medium_tests {
+    use tracer::core::*;
+    use tracer::medium::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector3,
+    };
+    use rand::prelude::*;
+
+
+    fn sampler() -> SphereSampler {
+        SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0))
+    }
+
+    fn unit_box() -> BoundingBox {
+        BoundingBox::new(Vector3::zero(), Vector3::from_fill(1_f32))
+    }
+
+    #[test]
+    fn test_density_grid_interpolates_between_cell_centers() {
+        let grid = DensityGrid::new(2, 1, 1, vec![0_f32, 1_f32]);
+
+        assert_relative_eq!(grid.lookup(&Vector3::new(0.25, 0.5, 0.5)), 0_f32);
+        assert_relative_eq!(grid.lookup(&Vector3::new(0.5, 0.5, 0.5)), 0.5);
+        assert_relative_eq!(grid.lookup(&Vector3::new(0.75, 0.5, 0.5)), 1_f32);
+        // The grid clamps to the values at the outermost cell centers.
+        assert_relative_eq!(grid.lookup(&Vector3::new(1_f32, 0.5, 0.5)), 1_f32);
+        assert_eq!(grid.lookup(&Vector3::new(1.5, 0.5, 0.5)), 0_f32);
+    }
+
+    #[test]
+    fn test_density_grid_file_format() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"GRID");
+        for dimension in [2_u32, 1, 2] {
+            bytes.extend_from_slice(&dimension.to_le_bytes());
+        }
+        for value in [0.5_f32, 1_f32, 2_f32, 4_f32] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        let grid = DensityGrid::from_grid(bytes.as_slice()).unwrap();
+
+        assert_eq!(grid.dimensions(), [2, 1, 2]);
+        assert_eq!(grid.value(1, 0, 0), 1_f32);
+        assert_eq!(grid.value(0, 0, 1), 2_f32);
+        assert_eq!(grid.max_value(), 4_f32);
+    }
+
+    #[test]
+    fn test_density_grid_truncated_file() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"GRID");
+        for dimension in [2_u32, 2, 2] {
+            bytes.extend_from_slice(&dimension.to_le_bytes());
+        }
+        bytes.extend_from_slice(&1_f32.to_le_bytes());
+
+        assert!(DensityGrid::from_grid(bytes.as_slice()).is_err());
+        assert!(DensityGrid::from_grid(&b"VOXL"[..]).is_err());
+    }
+
+    #[test]
+    fn test_density_grid_file_with_huge_dimensions() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"GRID");
+        for dimension in [u32::MAX, u32::MAX, u32::MAX] {
+            bytes.extend_from_slice(&dimension.to_le_bytes());
+        }
+        bytes.extend_from_slice(&1_f32.to_le_bytes());
+        let error = DensityGrid::from_grid(bytes.as_slice()).unwrap_err();
+
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert!(DensityGrid::from_raw(&[0_u8; 16][..], usize::MAX, 2, 1).is_err());
+    }
+
+    #[test]
+    fn test_density_grid_file_with_zero_dimension() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"GRID");
+        for dimension in [2_u32, 0, 2] {
+            bytes.extend_from_slice(&dimension.to_le_bytes());
+        }
+        let error = DensityGrid::from_grid(bytes.as_slice()).unwrap_err();
+
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert!(DensityGrid::from_raw(&[0_u8; 0][..], 0, 0, 0).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_density_grid_with_zero_dimension_panics() {
+        let _ = DensityGrid::new(0, 1, 1, Vec::new());
+    }
+
+    #[test]
+    fn test_ratio_tracking_matches_homogeneous_medium() {
+        let medium = GridMedium::new(DensityGrid::from_fill(4, 4, 4, 0.5), unit_box(), 2_f32, Vector3::from_fill(1_f32), 0_f32);
+        let ray = Ray::new(Vector3::new(-1_f32, 0.5, 0.5), Vector3::unit_x());
+        let mut sampler = sampler();
+        let count = 20000;
+        let mut transmittance = Vector3::zero();
+        for _ in 0..count {
+            transmittance += medium.transmittance(&ray, f32::MAX, &mut sampler) / count as f32;
+        }
+        // The ray crosses one unit of medium with an extinction coefficient of one.
+        let expected = Vector3::from_fill(f32::exp(-1_f32));
+
+        assert_relative_eq!(transmittance, expected, epsilon = 1e-2);
+    }
+
+    #[test]
+    fn test_delta_tracking_scatters_inside_bounds() {
+        let albedo = Vector3::new(0.9, 0.8, 0.7);
+        let medium = GridMedium::new(DensityGrid::from_fill(4, 4, 4, 1_f32), unit_box(), 1_f32, albedo, 0_f32);
+        let ray = Ray::new(Vector3::new(-1_f32, 0.5, 0.5), Vector3::unit_x());
+        let mut sampler = sampler();
+        let count = 20000;
+        let mut scattered = 0;
+        for _ in 0..count {
+            let result = medium.sample(&ray, f32::MAX, &mut sampler);
+            if let Some(interaction) = result.interaction {
+                assert!(interaction.t >= 1_f32 && interaction.t <= 2_f32);
+                assert_eq!(result.weight, albedo);
+                scattered += 1;
+            } else {
+                assert_eq!(result.weight, Vector3::from_fill(1_f32));
+            }
+        }
+        let expected = 1_f32 - f32::exp(-1_f32);
+
+        assert_relative_eq!(scattered as f32 / count as f32, expected, epsilon = 1e-2);
+    }
+
+    #[test]
+    fn test_ray_missing_grid_medium_is_unattenuated() {
+        let medium = GridMedium::new(DensityGrid::from_fill(2, 2, 2, 1_f32), unit_box(), 5_f32, Vector3::from_fill(1_f32), 0_f32);
+        let ray = Ray::new(Vector3::new(-1_f32, 2_f32, 0.5), Vector3::unit_x());
+        let mut sampler = sampler();
+
+        assert_eq!(medium.transmittance(&ray, f32::MAX, &mut sampler), Vector3::from_fill(1_f32));
+        assert!(!medium.sample(&ray, f32::MAX, &mut sampler).is_scattered());
+    }
+}
@@ -0,0 +1,136 @@
+extern crate tracer;
+extern crate approx;
+extern crate rand;
+extern crate rand_isaac;
+
+
+#[cfg(test)]
+mod primary_sample_space_tests {
+    use tracer::core::*;
+    use rand::prelude::*;
+
+
+    fn rng() -> rand_isaac::Isaac64Rng {
+        rand_isaac::Isaac64Rng::seed_from_u64(0)
+    }
+
+    #[test]
+    fn test_primary_sample_space_draws_the_numbers_of_the_generator_before_mutating() {
+        let mut sampler = SphereSampler::new(rng());
+        let mut primary_sampler = SphereSampler::new(rng()).with_primary_sample_space(0.01, 0.3);
+        for _ in 0..100 {
+            assert_eq!(primary_sampler.sample_f32(), sampler.sample_f32());
+        }
+    }
+
+    #[test]
+    fn test_primary_sample_space_small_steps_stay_close() {
+        let mut sampler = SphereSampler::new(rng()).with_primary_sample_space(0.01, 0_f32);
+        let before = (0..100).map(|_| sampler.sample_f32()).collect::<Vec<f32>>();
+        sampler.start_iteration();
+        let after = (0..100).map(|_| sampler.sample_f32()).collect::<Vec<f32>>();
+        for (value_before, value_after) in before.iter().zip(after.iter()) {
+            let distance = f32::abs(value_after - value_before);
+            let wrapped_distance = f32::min(distance, 1_f32 - distance);
+
+            assert!((0_f32..1_f32).contains(value_after));
+            assert!(wrapped_distance < 0.1);
+        }
+
+        assert_ne!(before, after);
+    }
+
+    #[test]
+    fn test_primary_sample_space_reject_restores_the_point() {
+        let mut sampler = SphereSampler::new(rng()).with_primary_sample_space(0.01, 0_f32);
+        let before = (0..100).map(|_| sampler.sample_f32()).collect::<Vec<f32>>();
+        // Without restoring the point, the rejected small steps would add up to a
+        // random walk far away from the starting point.
+        for _ in 0..100 {
+            sampler.start_iteration();
+            for _ in 0..100 {
+                sampler.sample_f32();
+            }
+            sampler.reject();
+        }
+        sampler.start_iteration();
+        let after = (0..100).map(|_| sampler.sample_f32()).collect::<Vec<f32>>();
+        for (value_before, value_after) in before.iter().zip(after.iter()) {
+            let distance = f32::abs(value_after - value_before);
+            let wrapped_distance = f32::min(distance, 1_f32 - distance);
+
+            assert!(wrapped_distance < 0.05);
+        }
+    }
+}
+
+#[cfg(test)]
+mod metropolis_tests {
+    use tracer::*;
+    use tracer::core::*;
+    use tracer::renderer::*;
+    use tracer::scene::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector3,
+    };
+    use rand::prelude::*;
+
+
+    fn scene() -> Scene {
+        let look_from = Vector3::zero();
+        let look_at = -Vector3::unit_z();
+        let camera = Camera::new(look_from, look_at, Vector3::unit_y(), 90_f32, 1_f32, 0_f32, 1_f32);
+
+        Scene::new(4, 4, camera)
+    }
+
+    /// A radiance estimator with a noisy estimate that is brighter towards the
+    /// right of the image.
+    fn radiance(ray: &Ray, sampler: &mut SphereSampler) -> Vector3<f32> {
+        if sampler.sample_f32() < 0.5 {
+            Vector3::from_fill(2_f32 * f32::max(0_f32, ray.direction.x + 0.5))
+        } else {
+            Vector3::zero()
+        }
+    }
+
+    #[test]
+    fn test_metropolis_light_transport_matches_independent_sampling() {
+        let scene = scene();
+        let mut sampler = SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0));
+        let samples_per_pixel = 16384;
+        let width = 4;
+        let height = 4;
+
+        let mut expected = Film::new(width, height);
+        for row in 0..height {
+            for column in 0..width {
+                for _ in 0..samples_per_pixel {
+                    let u = (column as f32 + sampler.sample_f32()) / (width as f32);
+                    let v = (((height - row - 1) as f32) + sampler.sample_f32()) / (height as f32);
+                    let ray = scene.camera.cast_ray(&mut sampler, u, v);
+                    expected.add_sample(row, column, radiance(&ray, &mut sampler));
+                }
+            }
+        }
+
+        let settings = MetropolisSettings::new(10_000, 64);
+        let metropolis_light_transport = MetropolisLightTransport::new(settings);
+        let mut result = Film::new(width, height);
+        metropolis_light_transport.render(&scene, samples_per_pixel, &mut sampler, &mut result, radiance);
+        for row in 0..height {
+            for column in 0..width {
+                assert_relative_eq!(
+                    result.pixel(row, column, samples_per_pixel),
+                    expected.pixel(row, column, samples_per_pixel),
+                    epsilon = 0.05,
+                    max_relative = 0.1,
+                );
+            }
+        }
+    }
+}
+
@@ -0,0 +1,277 @@
+extern crate tracer;
+extern crate approx;
+extern crate rand;
+extern crate rand_isaac;
+
+
+#[cfg(test)]
+mod animated_transform_tests {
+    use tracer::core::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Magnitude,
+        Matrix4x4,
+        Vector3,
+    };
+    use rand::prelude::*;
+
+
+    fn transform_point(matrix: &Matrix4x4<f32>, point: &Vector3<f32>) -> Vector3<f32> {
+        (matrix * point.extend(1_f32)).contract()
+    }
+
+    fn points() -> Vec<Vector3<f32>> {
+        vec![
+            Vector3::zero(),
+            Vector3::unit_x(),
+            Vector3::unit_y(),
+            Vector3::unit_z(),
+            Vector3::new(1_f32, -2_f32, 3_f32),
+        ]
+    }
+
+    #[test]
+    fn test_decomposition_round_trip() {
+        let translation = Vector3::new(1_f32, 2_f32, 3_f32);
+        let axis = Vector3::new(1_f32, 1_f32, 0_f32).normalize();
+        let scale = Vector3::new(2_f32, 0.5, 3_f32);
+        let matrix = DecomposedTransform::new(translation, axis, 0.7, scale).to_matrix();
+        let decomposed = DecomposedTransform::from_matrix(&matrix);
+        let result = decomposed.to_matrix();
+
+        assert_relative_eq!(decomposed.translation, translation, epsilon = 1e-5);
+        for point in points() {
+            assert_relative_eq!(transform_point(&result, &point), transform_point(&matrix, &point), epsilon = 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_decomposition_round_trip_with_reflection() {
+        let matrix = Matrix4x4::from_affine_nonuniform_scale(&Vector3::new(-1_f32, 2_f32, 1_f32));
+        let result = DecomposedTransform::from_matrix(&matrix).to_matrix();
+
+        for point in points() {
+            assert_relative_eq!(transform_point(&result, &point), transform_point(&matrix, &point), epsilon = 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_interpolated_rotation_stays_rigid() {
+        let start = DecomposedTransform::new(Vector3::zero(), Vector3::unit_z(), 0_f32, Vector3::from_fill(1_f32));
+        let end = DecomposedTransform::new(Vector3::zero(), Vector3::unit_z(), std::f32::consts::FRAC_PI_2, Vector3::from_fill(1_f32));
+        let matrix = start.interpolate(&end, 0.5).to_matrix();
+        let expected = Vector3::new(f32::sqrt(0.5), f32::sqrt(0.5), 0_f32);
+        let result = transform_point(&matrix, &Vector3::unit_x());
+
+        assert_relative_eq!(result, expected, epsilon = 1e-5);
+        for point in points() {
+            assert_relative_eq!(transform_point(&matrix, &point).magnitude(), point.magnitude(), epsilon = 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_matrix_at_keyframes() {
+        let end = Vector3::new(2_f32, 0_f32, 0_f32);
+        let transform = AnimatedTransform::from_keyframes(vec![
+            (0_f32, Matrix4x4::identity()),
+            (1_f32, Matrix4x4::from_affine_translation(&end)),
+        ]);
+
+        assert!(transform.is_animated());
+        assert_relative_eq!(transform_point(&transform.matrix_at(0_f32), &Vector3::zero()), Vector3::zero(), epsilon = 1e-6);
+        assert_relative_eq!(transform_point(&transform.matrix_at(1_f32), &Vector3::zero()), end, epsilon = 1e-6);
+        assert_relative_eq!(transform_point(&transform.matrix_at(0.25), &Vector3::zero()), end * 0.25, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_matrix_holds_still_outside_keyframes() {
+        let end = Vector3::new(2_f32, 0_f32, 0_f32);
+        let transform = AnimatedTransform::from_keyframes(vec![
+            (1_f32, Matrix4x4::from_affine_translation(&end)),
+            (0_f32, Matrix4x4::identity()),
+        ]);
+
+        assert_relative_eq!(transform_point(&transform.matrix_at(-1_f32), &Vector3::zero()), Vector3::zero(), epsilon = 1e-6);
+        assert_relative_eq!(transform_point(&transform.matrix_at(5_f32), &Vector3::zero()), end, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_static_transform_is_not_animated() {
+        let matrix = Matrix4x4::from_affine_translation(&Vector3::new(1_f32, 2_f32, 3_f32));
+        let transform = AnimatedTransform::new(matrix);
+
+        assert!(!transform.is_animated());
+        assert_eq!(transform.matrix_at(0.5), matrix);
+    }
+
+    #[test]
+    fn test_motion_bounds_contain_the_moving_box() {
+        let start = DecomposedTransform::new(Vector3::zero(), Vector3::unit_y(), 0_f32, Vector3::from_fill(1_f32));
+        let end = DecomposedTransform::new(Vector3::new(3_f32, 1_f32, 0_f32), Vector3::unit_y(), 2_f32, Vector3::new(1_f32, 2_f32, 1_f32));
+        let transform = AnimatedTransform::from_keyframes(vec![
+            (0_f32, start.to_matrix()),
+            (1_f32, end.to_matrix()),
+        ]);
+        let bounds = BoundingBox::new(-Vector3::from_fill(1_f32), Vector3::from_fill(1_f32));
+        let motion_bounds = transform.bounds(&bounds);
+        let mut sampler = SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0));
+        for _ in 0..1000 {
+            let time = sampler.sample_f32();
+            let point = Vector3::new(
+                2_f32 * sampler.sample_f32() - 1_f32,
+                2_f32 * sampler.sample_f32() - 1_f32,
+                2_f32 * sampler.sample_f32() - 1_f32,
+            );
+            let result = transform_point(&transform.matrix_at(time), &point);
+
+            assert!(motion_bounds.contains(&result), "{:?} at time {} is outside {:?}", result, time, motion_bounds);
+        }
+    }
+}
+
+#[cfg(test)]
+mod motion_blur_tests {
+    use tracer::Camera;
+    use tracer::bsdf::*;
+    use tracer::core::*;
+    use tracer::geometry::*;
+    use tracer::light::*;
+    use tracer::scene::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Matrix4x4,
+        Vector3,
+    };
+    use rand::prelude::*;
+    use std::sync::Arc;
+
+
+    fn sampler() -> SphereSampler {
+        SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0))
+    }
+
+    /// A scene with a sphere of radius one half moving from the origin to the
+    /// point `(4, 0, 0)` between the times zero and one.
+    fn scene() -> Scene {
+        let camera = Camera::new(Vector3::new(0_f32, 0_f32, 5_f32), Vector3::zero(), Vector3::unit_y(), 90_f32, 1_f32, 0_f32, 5_f32)
+            .with_shutter(0_f32, 1_f32);
+        let prototype: Box<dyn ModelSpaceObject> = Box::new(ModelSpaceGeometryObject::new(
+            Sphere::new(Vector3::zero(), 0.5),
+            Box::new(SimpleLambertianBsdf::new(Vector3::from_fill(0.5))),
+            Box::new(NoLight::new())
+        ));
+        let transform = AnimatedTransform::from_keyframes(vec![
+            (0_f32, Matrix4x4::identity()),
+            (1_f32, Matrix4x4::from_affine_translation(&Vector3::new(4_f32, 0_f32, 0_f32))),
+        ]);
+        let mut scene = Scene::new(4, 4, camera);
+        scene.push_object(SceneObject::new_animated(Arc::from(prototype), transform));
+
+        scene
+    }
+
+    fn query(x: f32, time: f32) -> IntersectionQuery {
+        let ray = Ray::new(Vector3::new(x, 0_f32, 5_f32), -Vector3::unit_z()).with_time(time);
+
+        IntersectionQuery::new(ray, 0.0001, f32::MAX)
+    }
+
+    #[test]
+    fn test_moving_object_is_hit_where_it_is_at_the_ray_time() {
+        let scene = scene();
+
+        assert!(scene.intersect(&query(0_f32, 0_f32)).is_some());
+        assert!(scene.intersect(&query(0_f32, 1_f32)).is_none());
+        assert!(scene.intersect(&query(4_f32, 0_f32)).is_none());
+        assert!(scene.intersect(&query(4_f32, 1_f32)).is_some());
+    }
+
+    #[test]
+    fn test_moving_object_hit_records_the_ray_time() {
+        let scene = scene();
+        let result = scene.intersect(&query(2_f32, 0.5)).unwrap();
+        let data = result.intersection_result.unwrap_hit();
+
+        assert_eq!(data.time, 0.5);
+        assert_relative_eq!(data.point, Vector3::new(2_f32, 0_f32, 0.5), epsilon = 1e-5);
+        assert_relative_eq!(data.normal, Vector3::unit_z(), epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_moving_object_bounds_cover_its_motion() {
+        let scene = scene();
+        let bounds = scene.objects()[0].bounds();
+
+        assert!(bounds.contains(&Vector3::new(-0.5, 0_f32, 0_f32)));
+        assert!(bounds.contains(&Vector3::new(4.5, 0_f32, 0_f32)));
+    }
+
+    /// A ray through the path of the sphere hits it during a quarter of the
+    /// shutter interval, so a quarter of the rays with times spread over the
+    /// shutter interval hit it.
+    #[test]
+    fn test_blurred_object_covers_a_fraction_of_the_exposure() {
+        let scene = scene();
+        let mut sampler = sampler();
+        let count = 10000;
+        let mut hit_count = 0;
+        for _ in 0..count {
+            let time = scene.camera.sample_time(&mut sampler);
+            hit_count += usize::from(scene.intersect(&query(2_f32, time)).is_some());
+        }
+        let result = hit_count as f32 / count as f32;
+
+        assert_relative_eq!(result, 0.25, epsilon = 0.02);
+    }
+
+    #[test]
+    fn test_camera_ray_times_lie_in_the_shutter_interval() {
+        let camera = Camera::new(Vector3::zero(), -Vector3::unit_z(), Vector3::unit_y(), 90_f32, 1_f32, 0_f32, 1_f32)
+            .with_shutter(0.25, 0.75);
+        let mut sampler = sampler();
+        let mut min_time = f32::MAX;
+        let mut max_time = f32::MIN;
+        for _ in 0..1000 {
+            let time = camera.cast_ray(&mut sampler, 0.5, 0.5).time;
+            min_time = f32::min(min_time, time);
+            max_time = f32::max(max_time, time);
+        }
+
+        assert_eq!(camera.shutter(), (0.25, 0.75));
+        assert!((0.25..0.3).contains(&min_time));
+        assert!(max_time > 0.7 && max_time <= 0.75);
+    }
+
+    #[test]
+    fn test_camera_without_shutter_casts_rays_at_time_zero() {
+        let camera = Camera::new(Vector3::zero(), -Vector3::unit_z(), Vector3::unit_y(), 90_f32, 1_f32, 0_f32, 1_f32);
+        let mut sampler = sampler();
+
+        assert_eq!(camera.cast_ray(&mut sampler, 0.5, 0.5).time, 0_f32);
+    }
+
+    #[test]
+    fn test_moving_camera_casts_rays_from_its_position_at_the_ray_time() {
+        let look_from_end = Vector3::new(2_f32, 0_f32, 0_f32);
+        let look_at_end = Vector3::new(2_f32, 0_f32, -1_f32);
+        let camera = Camera::new(Vector3::zero(), -Vector3::unit_z(), Vector3::unit_y(), 90_f32, 1_f32, 0_f32, 1_f32)
+            .with_shutter(0_f32, 1_f32)
+            .with_motion(look_from_end, look_at_end);
+        let mut sampler = sampler();
+
+        assert!(camera.is_moving());
+        assert_relative_eq!(camera.at_time(0_f32).position(), Vector3::zero(), epsilon = 1e-6);
+        assert_relative_eq!(camera.at_time(0.5).position(), look_from_end * 0.5, epsilon = 1e-6);
+        assert_relative_eq!(camera.at_time(1_f32).position(), look_from_end, epsilon = 1e-6);
+        for _ in 0..100 {
+            let ray = camera.cast_ray(&mut sampler, 0.5, 0.5);
+
+            assert_relative_eq!(ray.origin, look_from_end * ray.time, epsilon = 1e-5);
+            assert_relative_eq!(ray.direction, -Vector3::unit_z(), epsilon = 1e-5);
+        }
+    }
+}
@@ -0,0 +1,115 @@
+extern crate tracer;
+extern crate approx;
+
+
+#[cfg(test)]
+mod normal_mapping_tests {
+    use tracer::core::*;
+    use tracer::texture::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector2,
+        Vector3,
+        Magnitude,
+    };
+    use std::sync::Arc;
+
+
+    /// A point on the plane `z = 0` parameterized by `(u, v) = (x, y)`.
+    fn plane_hit() -> IntersectionData {
+        IntersectionData::from_surface(
+            1_f32,
+            Vector3::new(0.25, 0.5, 0_f32),
+            Vector3::unit_z(),
+            Vector3::unit_z(),
+            Vector3::unit_x(),
+            Vector3::unit_y(),
+            Vector2::new(0.25, 0.5),
+        )
+    }
+
+    #[derive(Debug)]
+    struct RampTexture {
+        slope: f32,
+    }
+
+    impl Texture<f32> for RampTexture {
+        fn evaluate(&self, query: &TextureQuery) -> f32 {
+            self.slope * query.uv.x
+        }
+    }
+
+    #[test]
+    fn test_flat_normal_map_leaves_normal_unchanged() {
+        let texture = Arc::new(ConstantTexture::new(Vector3::new(0.5, 0.5, 1_f32)));
+        let normal_map = NormalMap::new(texture, 1_f32);
+        let hit = plane_hit();
+        let result = normal_map.shading_normal(&hit);
+
+        assert_relative_eq!(result, Vector3::unit_z(), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_tilted_normal_map_tilts_towards_tangent() {
+        let texture = Arc::new(ConstantTexture::new(Vector3::new(1_f32, 0.5, 1_f32)));
+        let normal_map = NormalMap::new(texture, 1_f32);
+        let hit = plane_hit();
+        let result = normal_map.shading_normal(&hit);
+        let expected = Vector3::new(1_f32, 0_f32, 1_f32).normalize();
+
+        assert_relative_eq!(result, expected, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_normal_map_strength_zero_leaves_normal_unchanged() {
+        let texture = Arc::new(ConstantTexture::new(Vector3::new(1_f32, 0_f32, 0.5)));
+        let normal_map = NormalMap::new(texture, 0_f32);
+        let hit = plane_hit();
+        let result = normal_map.shading_normal(&hit);
+
+        assert_relative_eq!(result, Vector3::unit_z(), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_safeguard_keeps_normal_above_surface() {
+        let normal = Vector3::unit_z();
+        let shading_normal = Vector3::new(1_f32, 0_f32, -1_f32).normalize();
+        let result = safeguard_shading_normal(&normal, &shading_normal);
+
+        assert!(result.dot(&normal) > 0_f32);
+        assert_relative_eq!(result.magnitude(), 1_f32, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_safeguard_leaves_valid_normal_unchanged() {
+        let normal = Vector3::unit_z();
+        let shading_normal = Vector3::new(1_f32, 0_f32, 1_f32).normalize();
+        let result = safeguard_shading_normal(&normal, &shading_normal);
+
+        assert_eq!(result, shading_normal);
+    }
+
+    #[test]
+    fn test_constant_bump_map_leaves_normal_unchanged() {
+        let height = Arc::new(ConstantTexture::new(0.75_f32));
+        let bump_map = BumpMap::new(height, 1_f32);
+        let hit = plane_hit();
+        let result = bump_map.shading_normal(&hit);
+
+        assert_relative_eq!(result, Vector3::unit_z(), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_ramp_bump_map_tilts_against_slope() {
+        let height = Arc::new(RampTexture { slope: 1_f32 });
+        let bump_map = BumpMap::new(height, 1_f32);
+        let hit = plane_hit();
+        let result = bump_map.shading_normal(&hit);
+        // The surface `z = u` has the normal `(-1, 0, 1) / sqrt(2)`.
+        let expected = Vector3::new(-1_f32, 0_f32, 1_f32).normalize();
+
+        assert_relative_eq!(result, expected, epsilon = 1e-3);
+    }
+}
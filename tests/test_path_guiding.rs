@@ -0,0 +1,106 @@
+extern crate tracer;
+extern crate approx;
+extern crate rand;
+extern crate rand_isaac;
+
+
+#[cfg(test)]
+mod sd_tree_tests {
+    use tracer::core::*;
+    use tracer::renderer::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector3,
+    };
+    use rand::prelude::*;
+
+
+    fn sampler() -> SphereSampler {
+        SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0))
+    }
+
+    fn bounds() -> BoundingBox {
+        BoundingBox::new(Vector3::from_fill(-1_f32), Vector3::from_fill(1_f32))
+    }
+
+    /// Train an SD-tree on light arriving at the origin only from a narrow cone
+    /// about the **y-axis**.
+    fn trained_sd_tree(sampler: &mut SphereSampler) -> SdTree {
+        let settings = PathGuidingSettings::new(4).with_spatial_threshold(f32::MAX);
+        let mut sd_tree = SdTree::new(bounds());
+        let pdf = 1_f32 / (4_f32 * std::f32::consts::PI);
+        for pass in 0..settings.training_passes() {
+            for _ in 0..20000 {
+                let direction = sampler.sample_uniform_direction();
+                let luminance = if direction.y > 0.9 { 1_f32 } else { 0_f32 };
+                sd_tree.record(&Vector3::zero(), &direction, luminance, pdf);
+            }
+            sd_tree.refine(pass, &settings);
+        }
+
+        sd_tree
+    }
+
+    #[test]
+    fn test_untrained_sd_tree_is_uniform() {
+        let mut sampler = sampler();
+        let sd_tree = SdTree::new(bounds());
+        let expected = 1_f32 / (4_f32 * std::f32::consts::PI);
+        for _ in 0..100 {
+            let point = Vector3::new(sampler.sample_f32(), sampler.sample_f32(), sampler.sample_f32());
+            let direction = sampler.sample_uniform_direction();
+            let result = sd_tree.pdf(&point, &direction);
+
+            assert_relative_eq!(result, expected, epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_trained_sd_tree_favors_recorded_light() {
+        let mut sampler = sampler();
+        let sd_tree = trained_sd_tree(&mut sampler);
+        let uniform_pdf = 1_f32 / (4_f32 * std::f32::consts::PI);
+
+        assert!(sd_tree.pdf(&Vector3::zero(), &Vector3::unit_y()) > 4_f32 * uniform_pdf);
+        assert!(sd_tree.pdf(&Vector3::zero(), &(-Vector3::unit_y())) < uniform_pdf);
+
+        let sample_count = 10000;
+        let samples_towards_light = (0..sample_count)
+            .filter(|_| sd_tree.sample(&Vector3::zero(), &mut sampler).y > 0.8)
+            .count();
+
+        assert!(samples_towards_light > sample_count / 2);
+    }
+
+    #[test]
+    fn test_trained_sd_tree_pdf_integrates_to_one() {
+        let mut sampler = sampler();
+        let sd_tree = trained_sd_tree(&mut sampler);
+        let sample_count = 200000;
+        let integral = (0..sample_count)
+            .map(|_| {
+                let direction = sampler.sample_uniform_direction();
+
+                sd_tree.pdf(&Vector3::zero(), &direction) * 4_f32 * std::f32::consts::PI
+            })
+            .sum::<f32>() / (sample_count as f32);
+
+        assert_relative_eq!(integral, 1_f32, epsilon = 2e-2);
+    }
+
+    #[test]
+    fn test_sd_tree_splits_regions_receiving_many_samples() {
+        let settings = PathGuidingSettings::new(1).with_spatial_threshold(100_f32);
+        let mut sd_tree = SdTree::new(bounds());
+        for i in 0..1000 {
+            let point = Vector3::new((i as f32) / 1000_f32, 0_f32, 0_f32);
+            sd_tree.record(&point, &Vector3::unit_y(), 1_f32, 1_f32);
+        }
+        sd_tree.refine(0, &settings);
+
+        assert!(sd_tree.len_regions() > 1);
+    }
+}
+
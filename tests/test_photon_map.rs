@@ -0,0 +1,196 @@
+extern crate tracer;
+extern crate approx;
+extern crate rand;
+extern crate rand_isaac;
+
+
+#[cfg(test)]
+mod photon_map_tests {
+    use tracer::renderer::*;
+    use cglinalg::{
+        Vector3,
+        Magnitude,
+    };
+    use rand::prelude::*;
+
+
+    fn photons() -> Vec<Photon> {
+        let mut rng = rand_isaac::Isaac64Rng::seed_from_u64(0);
+        (0..500).map(|_| {
+            let position = Vector3::new(rng.gen::<f32>(), rng.gen::<f32>(), rng.gen::<f32>()) * 2_f32;
+            
+            Photon::new(position, -Vector3::unit_y(), Vector3::from_fill(1_f32))
+        })
+        .collect()
+    }
+
+    fn points() -> Vec<Vector3<f32>> {
+        vec![
+            Vector3::new(1_f32, 1_f32, 1_f32),
+            Vector3::new(0.1, 1.9, 0.4),
+            Vector3::new(-0.5, 0.5, 2.5),
+            Vector3::zero(),
+        ]
+    }
+
+    #[test]
+    fn test_photon_map_contains_every_photon() {
+        let photons = photons();
+        let photon_map = PhotonMap::new(photons.clone());
+
+        assert_eq!(photon_map.len(), photons.len());
+        for photon in photons.iter() {
+            assert!(photon_map.photons().contains(photon));
+        }
+    }
+
+    #[test]
+    fn test_photon_map_for_each_within() {
+        let photons = photons();
+        let photon_map = PhotonMap::new(photons.clone());
+        let radius = 0.3_f32;
+        for point in points() {
+            let mut result = Vec::new();
+            photon_map.for_each_within(&point, radius, |photon, _distance_squared| result.push(photon.position));
+            let expected: Vec<Vector3<f32>> = photons.iter()
+                .filter(|photon| (photon.position - point).magnitude() <= radius)
+                .map(|photon| photon.position)
+                .collect();
+
+            assert_eq!(result.len(), expected.len());
+            for position in expected.iter() {
+                assert!(result.contains(position));
+            }
+        }
+    }
+
+    #[test]
+    fn test_photon_map_nearest() {
+        let photons = photons();
+        let photon_map = PhotonMap::new(photons.clone());
+        let count = 10;
+        for point in points() {
+            let mut expected: Vec<f32> = photons.iter()
+                .map(|photon| (photon.position - point).magnitude_squared())
+                .collect();
+            expected.sort_by(|a, b| a.total_cmp(b));
+            expected.truncate(count);
+            let result: Vec<f32> = photon_map.nearest(&point, count, f32::MAX)
+                .iter()
+                .map(|(_photon, distance_squared)| *distance_squared)
+                .collect();
+
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn test_photon_map_nearest_within_max_radius() {
+        let photon_map = PhotonMap::new(photons());
+        let point = Vector3::new(1_f32, 1_f32, 1_f32);
+        let max_radius = 0.2_f32;
+        let result = photon_map.nearest(&point, 1000, max_radius);
+
+        assert!(!result.is_empty());
+        assert!(result.iter().all(|(_photon, distance_squared)| *distance_squared <= max_radius * max_radius));
+    }
+
+    #[test]
+    fn test_empty_photon_map() {
+        let photon_map = PhotonMap::new(Vec::new());
+        let mut visited = 0;
+        photon_map.for_each_within(&Vector3::zero(), 1_f32, |_photon, _distance_squared| visited += 1);
+
+        assert!(photon_map.is_empty());
+        assert_eq!(visited, 0);
+        assert!(photon_map.nearest(&Vector3::zero(), 4, 1_f32).is_empty());
+    }
+}
+
+
+#[cfg(test)]
+mod photon_mapper_tests {
+    use tracer::*;
+    use tracer::core::*;
+    use tracer::bsdf::*;
+    use tracer::geometry::*;
+    use tracer::light::*;
+    use tracer::renderer::*;
+    use tracer::scene::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector3,
+        Matrix4x4,
+    };
+    use rand::prelude::*;
+
+
+    fn sphere(center: Vector3<f32>, radius: f32, bsdf: Box<dyn Bsdf>) -> SceneObject {
+        let object = ModelSpaceGeometryObject::new(Sphere::new(Vector3::zero(), radius), bsdf, Box::new(NoLight::new()));
+
+        SceneObject::new(Box::new(object), Matrix4x4::from_affine_translation(&center))
+    }
+
+    /// A camera looking at a diffuse floor lit by a point light straight above
+    /// the point the camera looks at, inside a black enclosure. An invisible 
+    /// sphere around the light blocks shadow rays but lets light through, so the
+    /// floor receives all of its light from caustic photons.
+    fn scene() -> Scene {
+        let look_from = Vector3::new(0_f32, 1_f32, 1_f32);
+        let look_at = Vector3::zero();
+        let v_fov = 1_f32;
+        let aperture = 0_f32;
+        let distance_to_focus = f32::sqrt(2_f32);
+        let camera = Camera::new(look_from, look_at, Vector3::unit_y(), v_fov, 1_f32, aperture, distance_to_focus);
+        let light_position = Vector3::new(0_f32, 2_f32, 0_f32);
+        let mut scene = Scene::new(1, 1, camera);
+        scene.push_object(sphere(Vector3::new(0_f32, -1000_f32, 0_f32), 1000_f32, Box::new(SimpleLambertianBsdf::new(Vector3::from_fill(0.5)))));
+        scene.push_object(sphere(Vector3::zero(), 5000_f32, Box::new(SimpleLambertianBsdf::new(Vector3::zero()))));
+        scene.push_object(sphere(light_position, 0.5, Box::new(PassThroughBsdf::new())));
+        scene.push_light(ScenePointLightObject::new(
+            PointLight::new(Vector3::from_fill(4_f32 * std::f32::consts::PI * 16_f32)),
+            Matrix4x4::from_affine_translation(&light_position),
+        ));
+
+        scene
+    }
+
+    /// The radiance the floor reflects is `albedo / pi * intensity / distance^2`
+    /// for a light at distance two with intensity sixteen.
+    fn expected_radiance() -> Vector3<f32> {
+        Vector3::from_fill(0.5 / std::f32::consts::PI * 16_f32 / 4_f32)
+    }
+
+    #[test]
+    fn test_photon_mapper_caustics() {
+        let scene = scene();
+        let mut sampler = SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0));
+        let settings = PhotonMappingSettings::new(50_000, 0.3)
+            .with_gather_count(100_000)
+            .with_final_gather_samples(4);
+        let photon_mapper = PhotonMapper::new(settings, 4, f32::MAX);
+        let photon_maps = photon_mapper.trace_photons(&scene, &mut sampler);
+        let ray = scene.camera.cast_ray(&mut sampler, 0.5, 0.5);
+        let result = photon_mapper.sample(&scene, &photon_maps, ray, &mut sampler);
+
+        assert!(!photon_maps.caustic().is_empty());
+        assert_relative_eq!(result, expected_radiance(), max_relative = 0.1);
+    }
+
+    #[test]
+    fn test_progressive_photon_mapper_caustics() {
+        let scene = scene();
+        let mut sampler = SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0));
+        let settings = PhotonMappingSettings::new(10_000, 0.3);
+        let progressive_photon_mapper = StochasticProgressivePhotonMapper::new(settings, 4, f32::MAX);
+        let mut film = Film::new(1, 1);
+        let pass_count = 8;
+        progressive_photon_mapper.render(&scene, pass_count, &mut sampler, &mut film);
+        let result = film.pixel(0, 0, pass_count);
+
+        assert_relative_eq!(result, expected_radiance(), max_relative = 0.1);
+    }
+}
+
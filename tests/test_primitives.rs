@@ -0,0 +1,224 @@
+extern crate tracer;
+extern crate approx;
+extern crate rand;
+extern crate rand_isaac;
+
+
+#[cfg(test)]
+mod primitive_tests {
+    use tracer::core::*;
+    use tracer::geometry::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector3,
+    };
+    use rand::prelude::*;
+
+
+    fn sampler() -> SphereSampler {
+        SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0))
+    }
+
+    fn cast(geometry: &dyn Geometry, origin: Vector3<f32>, direction: Vector3<f32>) -> IntersectionResult {
+        let ray = Ray::new(origin, direction);
+        let query = IntersectionQuery::new(ray, 0.0001_f32, f32::MAX);
+
+        geometry.intersect(&query)
+    }
+
+    /// Check that the surface samples of a primitive lie on its surface inside
+    /// its bounds, that their normals agree with the normals of the ray hits at 
+    /// the same points, and that the surface parameters lie in the unit square.
+    fn assert_surface_samples_are_consistent(geometry: &dyn Geometry) {
+        let mut sampler = sampler();
+        let offset = 0.05_f32;
+        for _ in 0..1000 {
+            let sample = geometry.sample_surface(&mut sampler).unwrap();
+            let bounds = geometry.bounds().union(&BoundingBox::new(sample.point, sample.point));
+
+            assert_relative_eq!(sample.pdf, 1_f32 / geometry.area(), epsilon = 1e-6);
+            assert_relative_eq!(bounds.min, geometry.bounds().min, epsilon = 1e-4);
+            assert_relative_eq!(bounds.max, geometry.bounds().max, epsilon = 1e-4);
+
+            let result = cast(geometry, sample.point + sample.normal * offset, -sample.normal);
+            let data = result.unwrap_hit();
+
+            assert_relative_eq!(data.t, offset, epsilon = 1e-3);
+            assert_relative_eq!(data.point, sample.point, epsilon = 1e-3);
+            assert_relative_eq!(data.normal, sample.normal, epsilon = 1e-3);
+            assert!((0_f32..=1_f32).contains(&data.uv.x));
+            assert!((0_f32..=1_f32).contains(&data.uv.y));
+        }
+    }
+
+    #[test]
+    fn test_plane_intersection() {
+        let plane = Plane::new(Vector3::new(0_f32, -1_f32, 0_f32), Vector3::new(0_f32, 2_f32, 0_f32));
+        let result = cast(&plane, Vector3::new(3_f32, 1_f32, -7_f32), Vector3::new(0_f32, -1_f32, 0_f32));
+        let data = result.unwrap_hit();
+
+        assert_relative_eq!(data.t, 2_f32, epsilon = 1e-6);
+        assert_relative_eq!(data.normal, Vector3::unit_y(), epsilon = 1e-6);
+        assert!(cast(&plane, Vector3::zero(), Vector3::unit_y()).is_miss());
+    }
+
+    #[test]
+    fn test_plane_is_unbounded() {
+        let plane = Plane::new(Vector3::zero(), Vector3::unit_y());
+
+        assert!(plane.bounds().is_infinite());
+        assert!(plane.area().is_infinite());
+        assert!(plane.sample_surface(&mut sampler()).is_none());
+        assert!(plane.contains(&Vector3::new(0_f32, -1_f32, 0_f32)));
+        assert!(!plane.contains(&Vector3::new(0_f32, 1_f32, 0_f32)));
+    }
+
+    #[test]
+    fn test_disk_misses_its_hole() {
+        let disk = Disk::new(Vector3::zero(), 2_f32).with_inner_radius(1_f32);
+
+        assert!(cast(&disk, Vector3::new(0_f32, 1_f32, 0_f32), -Vector3::unit_y()).is_miss());
+        assert!(cast(&disk, Vector3::new(1.5_f32, 1_f32, 0_f32), -Vector3::unit_y()).is_hit());
+        assert!(cast(&disk, Vector3::new(2.5_f32, 1_f32, 0_f32), -Vector3::unit_y()).is_miss());
+    }
+
+    #[test]
+    fn test_disk_surface_samples() {
+        let disk = Disk::new(Vector3::new(1_f32, 2_f32, 3_f32), 2_f32).with_inner_radius(0.5);
+
+        assert_relative_eq!(disk.area(), std::f32::consts::PI * 3.75, epsilon = 1e-5);
+        assert_surface_samples_are_consistent(&disk);
+    }
+
+    #[test]
+    fn test_rectangle_surface_samples() {
+        let rectangle = Rectangle::new(Vector3::new(1_f32, 2_f32, 3_f32), 2_f32, 3_f32);
+
+        assert_relative_eq!(rectangle.area(), 6_f32, epsilon = 1e-6);
+        assert_surface_samples_are_consistent(&rectangle);
+    }
+
+    #[test]
+    fn test_axis_aligned_box_surface_samples() {
+        let axis_aligned_box = AxisAlignedBox::new(Vector3::new(-1_f32, -2_f32, -3_f32), Vector3::new(1_f32, 2_f32, 3_f32));
+
+        assert_relative_eq!(axis_aligned_box.area(), 2_f32 * (8_f32 + 12_f32 + 24_f32), epsilon = 1e-5);
+        assert_surface_samples_are_consistent(&axis_aligned_box);
+    }
+
+    #[test]
+    fn test_sphere_surface_samples() {
+        let sphere = Sphere::new(Vector3::new(1_f32, 2_f32, 3_f32), 2_f32);
+
+        assert_relative_eq!(sphere.area(), 16_f32 * std::f32::consts::PI, epsilon = 1e-5);
+        assert_surface_samples_are_consistent(&sphere);
+    }
+
+    #[test]
+    fn test_triangle_intersection_records_barycentrics() {
+        let triangle = Triangle::new(Vector3::zero(), Vector3::unit_x(), Vector3::unit_z());
+        let data = cast(&triangle, Vector3::new(0.25, 1_f32, 0.5), -Vector3::unit_y()).unwrap_hit();
+
+        assert_relative_eq!(data.t, 1_f32, epsilon = 1e-6);
+        assert_relative_eq!(data.barycentrics.unwrap(), Vector3::new(0.25, 0.25, 0.5), epsilon = 1e-6);
+        assert!(cast(&triangle, Vector3::new(0.75, 1_f32, 0.5), -Vector3::unit_y()).is_miss());
+        assert!(cast(&Sphere::new(Vector3::zero(), 1_f32), Vector3::new(0_f32, 2_f32, 0_f32), -Vector3::unit_y()).unwrap_hit().barycentrics.is_none());
+    }
+
+    #[test]
+    fn test_triangle_surface_samples() {
+        let triangle = Triangle::new(Vector3::new(1_f32, 2_f32, 3_f32), Vector3::new(3_f32, 2_f32, 4_f32), Vector3::new(1_f32, 5_f32, 2_f32));
+
+        assert_relative_eq!(triangle.area(), 3.5, epsilon = 1e-5);
+        assert_surface_samples_are_consistent(&triangle);
+    }
+
+    #[test]
+    fn test_cylinder_intersection() {
+        let cylinder = Cylinder::new(Vector3::zero(), 1_f32, 2_f32);
+        let data = cast(&cylinder, Vector3::new(-3_f32, 0.5_f32, 0_f32), Vector3::unit_x()).unwrap_hit();
+
+        assert_relative_eq!(data.t, 2_f32, epsilon = 1e-6);
+        assert_relative_eq!(data.normal, -Vector3::unit_x(), epsilon = 1e-6);
+        assert!(cast(&cylinder, Vector3::new(-3_f32, 1.5_f32, 0_f32), Vector3::unit_x()).is_miss());
+        // An open cylinder can be seen from inside through its ends.
+        let data = cast(&cylinder, Vector3::new(0_f32, 3_f32, 0_f32), Vector3::new(0.25_f32, -1_f32, 0_f32)).unwrap_hit();
+
+        assert_relative_eq!(data.point.x, 1_f32, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_cylinder_surface_samples() {
+        let cylinder = Cylinder::new(Vector3::new(1_f32, 2_f32, 3_f32), 0.5_f32, 2_f32);
+
+        assert_relative_eq!(cylinder.area(), 2_f32 * std::f32::consts::PI, epsilon = 1e-5);
+        assert_surface_samples_are_consistent(&cylinder);
+    }
+
+    #[test]
+    fn test_cone_intersection() {
+        let cone = Cone::new(Vector3::zero(), 1_f32, 1_f32);
+        let data = cast(&cone, Vector3::new(-3_f32, 0.5_f32, 0_f32), Vector3::unit_x()).unwrap_hit();
+        let normal = Vector3::new(-1_f32, 1_f32, 0_f32) / f32::sqrt(2_f32);
+
+        assert_relative_eq!(data.t, 2.5_f32, epsilon = 1e-5);
+        assert_relative_eq!(data.normal, normal, epsilon = 1e-5);
+        // The ray passes above the apex and misses the other nappe of the cone.
+        assert!(cast(&cone, Vector3::new(-3_f32, 1.5_f32, 0_f32), Vector3::unit_x()).is_miss());
+    }
+
+    #[test]
+    fn test_cone_surface_samples() {
+        let cone = Cone::new(Vector3::new(1_f32, 2_f32, 3_f32), 1_f32, 2_f32);
+
+        assert_relative_eq!(cone.area(), std::f32::consts::PI * f32::sqrt(5_f32), epsilon = 1e-5);
+        assert_surface_samples_are_consistent(&cone);
+    }
+
+    #[test]
+    fn test_torus_intersection() {
+        let torus = Torus::new(Vector3::zero(), 2_f32, 0.5_f32);
+        let data = cast(&torus, Vector3::new(-10_f32, 0_f32, 0_f32), Vector3::unit_x()).unwrap_hit();
+
+        assert_relative_eq!(data.t, 7.5_f32, epsilon = 1e-4);
+        assert_relative_eq!(data.normal, -Vector3::unit_x(), epsilon = 1e-4);
+        // The ray passes through the hole in the middle of the torus.
+        assert!(cast(&torus, Vector3::new(0_f32, 10_f32, 0_f32), -Vector3::unit_y()).is_miss());
+
+        let data = cast(&torus, Vector3::new(2_f32, 10_f32, 0_f32), -Vector3::unit_y()).unwrap_hit();
+
+        assert_relative_eq!(data.t, 9.5_f32, epsilon = 1e-4);
+        assert_relative_eq!(data.normal, Vector3::unit_y(), epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_torus_surface_samples() {
+        let torus = Torus::new(Vector3::new(1_f32, 2_f32, 3_f32), 1_f32, 0.25_f32);
+
+        assert_relative_eq!(torus.area(), std::f32::consts::PI * std::f32::consts::PI, epsilon = 1e-5);
+        assert_surface_samples_are_consistent(&torus);
+    }
+
+    #[test]
+    fn test_torus_surface_samples_are_uniform_in_area() {
+        // The outer half of the tube has more area than the inner half.
+        let torus = Torus::new(Vector3::zero(), 1_f32, 0.5_f32);
+        let mut sampler = sampler();
+        let sample_count = 100000;
+        let outer_count = (0..sample_count)
+            .filter(|_| {
+                let sample = torus.sample_surface(&mut sampler).unwrap();
+
+                f32::sqrt(sample.point.x * sample.point.x + sample.point.z * sample.point.z) > 1_f32
+            })
+            .count();
+        // The outer half has the area `2 * pi * r * (pi * R + 2 * r)` out of 
+        // `4 * pi^2 * R * r`.
+        let expected = (std::f32::consts::PI + 1_f32) / (2_f32 * std::f32::consts::PI);
+
+        assert_relative_eq!((outer_count as f32) / (sample_count as f32), expected, epsilon = 1e-2);
+    }
+}
+
@@ -0,0 +1,285 @@
+extern crate tracer;
+extern crate approx;
+extern crate rand;
+extern crate rand_isaac;
+
+
+#[cfg(test)]
+mod error_bounds_tests {
+    use tracer::core::*;
+    use cglinalg::{
+        Matrix4x4,
+        Vector3,
+    };
+
+
+    #[test]
+    fn test_next_float_up() {
+        assert!(next_float_up(1_f32) > 1_f32);
+        assert_eq!(next_float_up(1_f32), 1_f32 + f32::EPSILON);
+        assert!(next_float_up(0_f32) > 0_f32);
+        assert!(next_float_up(-0_f32) > 0_f32);
+        assert!(next_float_up(-1_f32) > -1_f32);
+        assert_eq!(next_float_up(f32::INFINITY), f32::INFINITY);
+    }
+
+    #[test]
+    fn test_next_float_down() {
+        assert!(next_float_down(1_f32) < 1_f32);
+        assert!(next_float_down(0_f32) < 0_f32);
+        assert!(next_float_down(-0_f32) < 0_f32);
+        assert!(next_float_down(-1_f32) < -1_f32);
+        assert_eq!(next_float_down(f32::NEG_INFINITY), f32::NEG_INFINITY);
+    }
+
+    #[test]
+    fn test_next_float_round_trip() {
+        for value in [-1000_f32, -1_f32, -1e-30, 1e-30, 0.5, 3_f32, 1e20] {
+            assert_eq!(next_float_down(next_float_up(value)), value);
+            assert_eq!(next_float_up(next_float_down(value)), value);
+        }
+    }
+
+    #[test]
+    fn test_gamma_grows_with_the_operation_count() {
+        assert_eq!(gamma(0), 0_f32);
+        assert!(gamma(1) >= MACHINE_EPSILON);
+        assert!(gamma(3) < gamma(5));
+        assert!(gamma(5) < gamma(7));
+    }
+
+    #[test]
+    fn test_offset_ray_origin_moves_to_the_side_of_the_direction() {
+        let point = Vector3::new(1_f32, 2_f32, 3_f32);
+        let point_error = Vector3::from_fill(1e-3);
+        let normal = Vector3::unit_z();
+        let above = offset_ray_origin(&point, &point_error, &normal, &Vector3::new(1_f32, 0_f32, 1_f32));
+        let below = offset_ray_origin(&point, &point_error, &normal, &Vector3::new(1_f32, 0_f32, -1_f32));
+
+        assert!(above.z > point.z + point_error.z);
+        assert!(below.z < point.z - point_error.z);
+        assert_eq!(above.x, point.x);
+        assert_eq!(above.y, point.y);
+    }
+
+    #[test]
+    fn test_offset_ray_origin_without_error_still_leaves_the_point() {
+        let point = Vector3::new(1_f32, 2_f32, 3_f32);
+        let normal = Vector3::unit_y();
+        let result = offset_ray_origin(&point, &Vector3::zero(), &normal, &normal);
+
+        assert_eq!(result, point);
+        let result = offset_ray_origin(&point, &Vector3::from_fill(f32::MIN_POSITIVE), &normal, &normal);
+
+        assert!(result.y > point.y);
+    }
+
+    #[test]
+    fn test_transform_point_error_covers_the_translation() {
+        let translation = Vector3::new(1000_f32, -2000_f32, 500_f32);
+        let matrix = Matrix4x4::from_affine_translation(&translation);
+        let point = Vector3::new(1_f32, 1_f32, 1_f32);
+        let result = transform_point_error(&matrix, &point, &Vector3::zero());
+
+        assert!(result.x >= 1000_f32 * MACHINE_EPSILON);
+        assert!(result.y >= 2000_f32 * MACHINE_EPSILON);
+        assert!(result.z >= 500_f32 * MACHINE_EPSILON);
+    }
+
+    #[test]
+    fn test_transform_point_error_scales_the_error() {
+        let matrix = Matrix4x4::from_affine_nonuniform_scale(&Vector3::new(4_f32, 1_f32, 1_f32));
+        let point_error = Vector3::from_fill(1e-3);
+        let result = transform_point_error(&matrix, &Vector3::zero(), &point_error);
+
+        assert!(result.x >= 4e-3);
+        assert!(result.y >= 1e-3);
+        assert!(result.y < 4e-3);
+    }
+}
+
+#[cfg(test)]
+mod ray_offset_tests {
+    use tracer::Camera;
+    use tracer::bsdf::*;
+    use tracer::core::*;
+    use tracer::geometry::*;
+    use tracer::light::*;
+    use tracer::scene::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Magnitude,
+        Matrix4x4,
+        Vector3,
+    };
+    use rand::prelude::*;
+
+
+    fn sampler() -> SphereSampler {
+        SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0))
+    }
+
+    fn scene_object<G: Geometry + 'static>(geometry: G, model_matrix: Matrix4x4<f32>) -> SceneObject {
+        let object = Box::new(ModelSpaceGeometryObject::new(
+            geometry,
+            Box::new(SimpleLambertianBsdf::new(Vector3::from_fill(0.5))),
+            Box::new(NoLight::new())
+        ));
+
+        SceneObject::new(object, model_matrix)
+    }
+
+    /// Cast rays at a sphere with the center `center` and the radius `radius`
+    /// from points around it, returning the points they hit.
+    fn sphere_hits(object: &SceneObject, center: Vector3<f32>, radius: f32, sampler: &mut SphereSampler) -> Vec<IntersectionData> {
+        (0..1000)
+            .filter_map(|_| {
+                let origin = center + sampler.sample_uniform_direction() * (4_f32 * radius);
+                let target = center + sampler.sample_unit_sphere() * (0.5 * radius);
+                let ray = Ray::new(origin, target - origin);
+                let query = IntersectionQuery::new(ray, 0_f32, f32::MAX);
+                match object.intersect(&query) {
+                    IntersectionResult::Hit(data) => Some(data),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Sample a unit direction on the side of the surface with the normal
+    /// `normal` given by `sign`.
+    fn sample_direction(normal: &Vector3<f32>, sign: f32, sampler: &mut SphereSampler) -> Vector3<f32> {
+        let direction = sampler.sample_uniform_direction();
+        if direction.dot(normal) * sign < 0_f32 {
+            -direction
+        } else {
+            direction
+        }
+    }
+
+    /// Rays spawned away from a sphere must not hit it again, and rays spawned
+    /// into it must reach its far side.
+    fn assert_no_self_intersection(object: &SceneObject, center: Vector3<f32>, radius: f32) {
+        let mut sampler = sampler();
+        let hits = sphere_hits(object, center, radius, &mut sampler);
+        assert!(hits.len() > 500);
+        for hit in hits {
+            let outward = sample_direction(&hit.normal, 1_f32, &mut sampler);
+            let query = IntersectionQuery::new(hit.spawn_ray(&outward), 0_f32, f32::MAX);
+
+            assert!(!object.intersect(&query).is_hit_or_tangent(), "{:?} re-hit the sphere", hit.point);
+
+            let inward = sample_direction(&hit.normal, -1_f32, &mut sampler);
+            let query = IntersectionQuery::new(hit.spawn_ray(&inward), 0_f32, f32::MAX);
+            if let IntersectionResult::Hit(data) = object.intersect(&query) {
+                assert!((data.point - hit.point).magnitude() > 1e-4 * radius, "{:?} re-hit itself", hit.point);
+            }
+        }
+    }
+
+    #[test]
+    fn test_large_sphere_does_not_intersect_itself() {
+        let center = Vector3::new(0_f32, 0_f32, -1000_f32);
+        let object = scene_object(Sphere::new(center, 1000_f32), Matrix4x4::identity());
+
+        assert_no_self_intersection(&object, center, 1000_f32);
+    }
+
+    #[test]
+    fn test_small_sphere_does_not_intersect_itself() {
+        let center = Vector3::new(1_f32, 2_f32, 3_f32);
+        let object = scene_object(Sphere::new(center, 0.001), Matrix4x4::identity());
+
+        assert_no_self_intersection(&object, center, 0.001);
+    }
+
+    #[test]
+    fn test_distant_transformed_sphere_does_not_intersect_itself() {
+        let translation = Vector3::new(5000_f32, -3000_f32, 2000_f32);
+        let model_matrix = Matrix4x4::from_affine_translation(&translation) * Matrix4x4::from_affine_scale(3_f32);
+        let object = scene_object(Sphere::new(Vector3::zero(), 1_f32), model_matrix);
+
+        assert_no_self_intersection(&object, translation, 3_f32);
+    }
+
+    #[test]
+    fn test_point_error_bounds_the_distance_to_the_surface() {
+        let center = Vector3::new(100_f32, -50_f32, 20_f32);
+        let radius = 10_f32;
+        let object = scene_object(Sphere::new(center, radius), Matrix4x4::identity());
+        let mut sampler = sampler();
+        for hit in sphere_hits(&object, center, radius, &mut sampler) {
+            let offset_x = hit.point.x as f64 - center.x as f64;
+            let offset_y = hit.point.y as f64 - center.y as f64;
+            let offset_z = hit.point.z as f64 - center.z as f64;
+            let distance = f64::sqrt(offset_x * offset_x + offset_y * offset_y + offset_z * offset_z);
+            let error = hit.point_error.magnitude() as f64;
+
+            assert!(f64::abs(distance - radius as f64) <= error, "{} > {}", f64::abs(distance - radius as f64), error);
+        }
+    }
+
+    #[test]
+    fn test_spawned_ray_carries_the_time_of_the_hit() {
+        let object = scene_object(Sphere::new(Vector3::zero(), 1_f32), Matrix4x4::identity());
+        let ray = Ray::new(Vector3::new(0_f32, 0_f32, 5_f32), -Vector3::unit_z()).with_time(0.25);
+        let hit = object.intersect(&IntersectionQuery::new(ray, 0_f32, f32::MAX)).unwrap_hit();
+        let result = hit.spawn_ray(&Vector3::unit_z());
+
+        assert_eq!(result.time, 0.25);
+        assert!(result.origin.z > hit.point.z);
+    }
+
+    #[test]
+    fn test_spawned_ray_to_a_target_ends_at_the_target() {
+        let object = scene_object(Sphere::new(Vector3::zero(), 1_f32), Matrix4x4::identity());
+        let ray = Ray::new(Vector3::new(0_f32, 0_f32, 5_f32), -Vector3::unit_z());
+        let hit = object.intersect(&IntersectionQuery::new(ray, 0_f32, f32::MAX)).unwrap_hit();
+        let target = Vector3::new(3_f32, 0_f32, 4_f32);
+        let result = hit.spawn_ray_to(&target);
+
+        assert_relative_eq!(result.interpolate(1_f32), target, epsilon = 1e-6);
+        assert!(result.origin.z > hit.point.z);
+    }
+
+    fn scene() -> Scene {
+        let camera = Camera::new(Vector3::new(0_f32, 0_f32, 5_f32), Vector3::zero(), Vector3::unit_y(), 90_f32, 1_f32, 0_f32, 5_f32);
+        let mut scene = Scene::new(4, 4, camera);
+        scene.push_object(scene_object(Sphere::new(Vector3::zero(), 1_f32), Matrix4x4::identity()));
+
+        scene
+    }
+
+    #[test]
+    fn test_line_of_sight_through_a_sphere_is_blocked() {
+        let scene = scene();
+
+        assert!(!scene.line_of_sight(&Vector3::new(0_f32, 0_f32, 5_f32), &Vector3::new(0_f32, 0_f32, -5_f32), 0_f32));
+        assert!(scene.line_of_sight(&Vector3::new(0_f32, 2_f32, 5_f32), &Vector3::new(0_f32, 2_f32, -5_f32), 0_f32));
+    }
+
+    #[test]
+    fn test_line_of_sight_stops_short_of_the_far_end() {
+        let scene = scene();
+
+        assert!(scene.line_of_sight(&Vector3::new(0_f32, 0_f32, 5_f32), &Vector3::new(0_f32, 0_f32, 1_f32), 0_f32));
+        assert!(!scene.line_of_sight(&Vector3::new(0_f32, 0_f32, 5_f32), &Vector3::new(0_f32, 0_f32, 0.9), 0_f32));
+    }
+
+    #[test]
+    fn test_line_of_sight_from_a_spawned_origin_is_unobstructed() {
+        let scene = scene();
+        let mut sampler = sampler();
+        for _ in 0..1000 {
+            let direction = sampler.sample_uniform_direction();
+            let ray = Ray::new(direction * 5_f32, -direction);
+            let hit = scene.intersect(&IntersectionQuery::new(ray, 0_f32, f32::MAX)).unwrap();
+            let data = hit.intersection_result.unwrap_hit();
+            let light_position = data.normal * 3_f32 + sampler.sample_unit_sphere();
+
+            assert!(scene.line_of_sight(&data.spawn_origin(&(light_position - data.point)), &light_position, 0_f32));
+        }
+    }
+}
@@ -12,7 +12,6 @@ mod scene_tests {
     use tracer::core::*;
     use tracer::bsdf::{
         SimpleLambertianBsdf,
-        SimpleLambertianBsdfQuerySampler,
     };
     use tracer::core::{
         IntersectionQuery, 
@@ -42,9 +41,8 @@ mod scene_tests {
         let model_matrix = Matrix4x4::from_affine_translation(&sphere_center_world_space);
         let sphere = Sphere::new(sphere_center_model_space, sphere_radius);
         let bsdf = Box::new(SimpleLambertianBsdf::new(Vector3::new(0.5, 0.5, 0.5)));
-        let bsdf_sampler = Box::new(SimpleLambertianBsdfQuerySampler::new());
         let emitter = Box::new(NoLight::new());
-        let object = Box::new(ModelSpaceGeometryObject::new(sphere, bsdf, bsdf_sampler, emitter));
+        let object = Box::new(ModelSpaceGeometryObject::new(sphere, bsdf, emitter));
         let scene_object = SceneObject::new(object, model_matrix);
         let camera = (|width: usize, height: usize| {
             let look_from = Vector3::new(-4_f32, -5_f32, 0_f32);
@@ -102,7 +100,7 @@ mod scene_tests {
     #[test]
     fn test_scene_sphere_center() {
         let scene = scene();
-        let sphere = &scene.objects[0];
+        let sphere = &scene.objects()[0];
         let expected = Vector3::zero().extend(1_f32);
         let sphere_center = sphere.center().extend(1_f32);
         let result = sphere.model_matrix.inverse().unwrap() * sphere_center;
@@ -122,6 +120,8 @@ mod scene_tests {
         let scattering_query = ScatteringQuery::new(
             intersection_query.ray.direction,
             intersection_result.point,
+            intersection_result.uv,
+            intersection_result.shading_frame(),
         );
         let expected = Vector3::new(0.5, 0.5, 0.5);
         let scattering_result = sphere.object.scatter(&scattering_query, &mut sampler);
@@ -0,0 +1,307 @@
+extern crate tracer;
+extern crate approx;
+extern crate rand;
+extern crate rand_isaac;
+
+
+#[cfg(test)]
+mod scene_object_transform_tests {
+    use tracer::bsdf::*;
+    use tracer::core::*;
+    use tracer::geometry::*;
+    use tracer::light::*;
+    use tracer::scene::*;
+    use tracer::texture::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Magnitude,
+        Matrix4x4,
+        Vector3,
+    };
+    use rand::prelude::*;
+    use std::sync::Arc;
+
+
+    fn sampler() -> SphereSampler {
+        SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0))
+    }
+
+    /// A unit sphere at the origin placed in the scene by the model matrix
+    /// `model_matrix`.
+    fn scene_object(model_matrix: Matrix4x4<f32>) -> SceneObject {
+        let object = Box::new(ModelSpaceGeometryObject::new(
+            Sphere::new(Vector3::zero(), 1_f32),
+            Box::new(SimpleLambertianBsdf::new(Vector3::from_fill(0.5))),
+            Box::new(NoLight::new())
+        ));
+
+        SceneObject::new(object, model_matrix)
+    }
+
+    /// The ellipsoid `x^2 / 4 + y^2 + z^2 == 1`.
+    fn scaled_object() -> SceneObject {
+        scene_object(Matrix4x4::from_affine_nonuniform_scale(&Vector3::new(2_f32, 1_f32, 1_f32)))
+    }
+
+    /// A unit sphere sheared along the x-axis in proportion to the y-coordinate,
+    /// then translated away from the origin.
+    fn sheared_object() -> SceneObject {
+        scene_object(Matrix4x4::new(
+            1_f32,   0_f32,   0_f32,   0_f32,
+            0.75,    1_f32,   0_f32,   0_f32,
+            0_f32,   0_f32,   1_f32,   0_f32,
+            1_f32,   2_f32,   -3_f32,  1_f32,
+        ))
+    }
+
+    fn hit(object: &SceneObject, ray: Ray) -> IntersectionData {
+        let query = IntersectionQuery::new(ray, 0.0001, f32::MAX);
+
+        object.intersect(&query).unwrap_hit()
+    }
+
+    /// Cast rays from points on a large sphere surrounding an object towards
+    /// points near its center.
+    fn hits(object: &SceneObject, sampler: &mut SphereSampler) -> Vec<(Ray, IntersectionData)> {
+        let center = object.center();
+        (0..1000)
+            .filter_map(|_| {
+                let origin = center + sampler.sample_uniform_direction() * 10_f32;
+                let target = center + sampler.sample_unit_sphere() * 0.5;
+                let ray = Ray::new(origin, (target - origin) * 0.25);
+                let query = IntersectionQuery::new(ray, 0.0001, f32::MAX);
+                match object.intersect(&query) {
+                    IntersectionResult::Hit(data) => Some((ray, data)),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_scaled_sphere_normal() {
+        let object = scaled_object();
+        let ray = Ray::new(Vector3::new(1_f32, 0.5, 5_f32), -Vector3::unit_z());
+        let data = hit(&object, ray);
+        let z = f32::sqrt(0.5);
+        let expected = Vector3::new(0.25, 0.5, z).normalize();
+
+        assert_relative_eq!(data.point, Vector3::new(1_f32, 0.5, z), epsilon = 1e-5);
+        assert_relative_eq!(data.normal, expected, epsilon = 1e-5);
+        assert_relative_eq!(data.shading_normal, expected, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_scaled_sphere_normals_are_unit_length() {
+        let object = scaled_object();
+        let mut sampler = sampler();
+        for (_, data) in hits(&object, &mut sampler) {
+            assert_relative_eq!(data.normal.magnitude(), 1_f32, epsilon = 1e-5);
+            assert_relative_eq!(data.shading_normal.magnitude(), 1_f32, epsilon = 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_scaled_sphere_normals_are_perpendicular_to_the_surface() {
+        let object = scaled_object();
+        let mut sampler = sampler();
+        for (_, data) in hits(&object, &mut sampler) {
+            // The gradient of the implicit function of the ellipsoid.
+            let gradient = Vector3::new(data.point.x / 4_f32, data.point.y, data.point.z).normalize();
+
+            assert_relative_eq!(data.normal, gradient, epsilon = 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_sheared_sphere_normals_are_perpendicular_to_the_surface() {
+        let object = sheared_object();
+        let mut sampler = sampler();
+        let hits = hits(&object, &mut sampler);
+        assert!(hits.len() > 500);
+        for (ray, data) in hits {
+            assert_relative_eq!(data.normal.magnitude(), 1_f32, epsilon = 1e-5);
+            assert_relative_eq!(data.normal.dot(&data.dpdu), 0_f32, epsilon = 1e-3);
+            assert_relative_eq!(data.normal.dot(&data.dpdv), 0_f32, epsilon = 1e-3);
+            assert!(data.normal.dot(&ray.direction) < 0_f32);
+        }
+    }
+
+    #[test]
+    fn test_transformed_hit_points_lie_on_the_ray() {
+        let mut sampler = sampler();
+        for object in [scaled_object(), sheared_object()] {
+            for (ray, data) in hits(&object, &mut sampler) {
+                assert_relative_eq!(ray.interpolate(data.t), data.point, epsilon = 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ray_parameter_scales_with_the_ray_direction() {
+        let object = sheared_object();
+        let origin = object.center() + Vector3::new(0.1, 0.2, 10_f32);
+        let direction = -Vector3::unit_z();
+        let result = hit(&object, Ray::new(origin, direction));
+        let result_long = hit(&object, Ray::new(origin, direction * 4_f32));
+
+        assert_relative_eq!(result_long.t * 4_f32, result.t, epsilon = 1e-4);
+        assert_relative_eq!(result_long.point, result.point, epsilon = 1e-4);
+        assert_relative_eq!(result_long.normal, result.normal, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_scattering_from_sheared_sphere_returns_unit_directions() {
+        let object = sheared_object();
+        let mut sampler = sampler();
+        for (ray, data) in hits(&object, &mut sampler) {
+            let query = ScatteringQuery::new(ray.direction, data.point, data.uv, data.shading_frame());
+            let result = object.scatter(&query, &mut sampler);
+
+            assert_relative_eq!(result.normal.magnitude(), 1_f32, epsilon = 1e-5);
+            assert_relative_eq!(result.normal, data.normal, epsilon = 1e-4);
+            assert_relative_eq!(result.ray_outgoing.magnitude(), 1_f32, epsilon = 1e-5);
+            assert!(result.ray_outgoing.dot(&result.normal) >= -1e-4);
+        }
+    }
+
+    /// Scattering samples in the world space shading frame whether or not the 
+    /// object overrides the material of its prototype.
+    #[test]
+    fn test_scattering_from_sheared_sphere_does_not_depend_on_material_override() {
+        let object = sheared_object();
+        let bsdf: Box<dyn Bsdf> = Box::new(SimpleLambertianBsdf::new(Vector3::from_fill(0.5)));
+        let overridden = sheared_object().with_bsdf(Arc::from(bsdf));
+        let mut sampler = sampler();
+        for (ray, data) in hits(&object, &mut sampler) {
+            let query = ScatteringQuery::from_hit(ray.direction, &data);
+            let mut sampler1 = SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(7));
+            let mut sampler2 = SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(7));
+            let result = object.scatter(&query, &mut sampler1);
+            let expected = overridden.scatter(&query, &mut sampler2);
+
+            assert_relative_eq!(result.ray_outgoing, expected.ray_outgoing, epsilon = 1e-6);
+            assert_relative_eq!(result.scattering_fraction, expected.scattering_fraction, epsilon = 1e-6);
+            assert!(result.ray_outgoing.dot(&data.shading_normal) >= 0_f32);
+        }
+    }
+
+    /// Sampling a rough diffuse surface looks up world space textures at the 
+    /// same point as evaluating it does.
+    #[test]
+    fn test_sampling_world_space_texture_on_translated_object_matches_evaluation() {
+        let even = Arc::new(ConstantTexture::new(Vector3::from_fill(0.2)));
+        let odd = Arc::new(ConstantTexture::new(Vector3::from_fill(0.8)));
+        let albedo: Box<dyn Texture<Vector3<f32>>> = Box::new(SolidCheckerboardTexture::new(even, odd, 1_f32, TextureSpace::World));
+        let bsdf = OrenNayarBsdf::from_textures(Arc::from(albedo), Arc::new(ConstantTexture::new(0.3)));
+        let object = Box::new(ModelSpaceGeometryObject::new(
+            Sphere::new(Vector3::zero(), 0.25),
+            Box::new(bsdf),
+            Box::new(NoLight::new())
+        ));
+        // The top of the sphere lies in an even check in model space and in an 
+        // odd check in world space.
+        let object = SceneObject::new(object, Matrix4x4::from_affine_translation(&Vector3::new(1.5, 0.5, 0.5)));
+        let ray = Ray::new(Vector3::new(1.5, 5_f32, 0.5), -Vector3::unit_y());
+        let data = hit(&object, ray);
+        let mut sampler = sampler();
+        for _ in 0..100 {
+            let (ray_outgoing, result) = object.sample_bsdf(&data, &ray.direction, &mut sampler).unwrap();
+            let cos_theta = ray_outgoing.dot(&data.shading_normal.normalize()).abs();
+            let expected = object.evaluate_bsdf(&data, &ray.direction, &ray_outgoing) * (cos_theta / result.pdf);
+
+            assert_relative_eq!(result.scattering_fraction, expected, epsilon = 1e-4);
+        }
+    }
+}
+
+#[cfg(test)]
+mod hit_record_tests {
+    use tracer::bsdf::*;
+    use tracer::core::*;
+    use tracer::geometry::*;
+    use tracer::light::*;
+    use tracer::scene::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Matrix4x4,
+        Vector3,
+    };
+    use rand::prelude::*;
+
+
+    fn sampler() -> SphereSampler {
+        SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0))
+    }
+
+    fn scene_object<G: Geometry + 'static>(geometry: G, model_matrix: Matrix4x4<f32>) -> SceneObject {
+        let object = Box::new(ModelSpaceGeometryObject::new(
+            geometry,
+            Box::new(SimpleLambertianBsdf::new(Vector3::from_fill(0.5))),
+            Box::new(NoLight::new())
+        ));
+
+        SceneObject::new(object, model_matrix)
+    }
+
+    fn hit(object: &SceneObject, ray: Ray) -> IntersectionData {
+        let query = IntersectionQuery::new(ray, 0.0001, f32::MAX);
+
+        object.intersect(&query).unwrap_hit()
+    }
+
+    #[test]
+    fn test_hit_from_outside_is_front_face() {
+        let scale = Matrix4x4::from_affine_nonuniform_scale(&Vector3::new(2_f32, 1_f32, 3_f32));
+        let object = scene_object(Sphere::new(Vector3::zero(), 1_f32), scale);
+        let data = hit(&object, Ray::new(Vector3::new(0.5, 0.25, 10_f32), -Vector3::unit_z()));
+
+        assert!(data.is_front_face);
+    }
+
+    #[test]
+    fn test_hit_from_inside_is_back_face() {
+        let scale = Matrix4x4::from_affine_nonuniform_scale(&Vector3::new(2_f32, 1_f32, 3_f32));
+        let object = scene_object(Sphere::new(Vector3::zero(), 1_f32), scale);
+        let data = hit(&object, Ray::new(Vector3::zero(), Vector3::new(1_f32, 2_f32, 3_f32)));
+
+        assert!(!data.is_front_face);
+        assert!(data.normal.dot(&Vector3::new(1_f32, 2_f32, 3_f32)) > 0_f32);
+    }
+
+    #[test]
+    fn test_scattering_query_from_hit_carries_the_hit_record() {
+        let object = scene_object(Sphere::new(Vector3::zero(), 1_f32), Matrix4x4::identity());
+        let ray = Ray::new(Vector3::new(0_f32, 0_f32, 10_f32), -Vector3::unit_z()).with_time(0.5);
+        let data = hit(&object, ray);
+        let query = ScatteringQuery::from_hit(ray.direction, &data);
+
+        assert_eq!(query.point, data.point);
+        assert_eq!(query.uv, data.uv);
+        assert_eq!(query.normal, data.normal);
+        assert_eq!(query.shading_frame, data.shading_frame());
+        assert_eq!(query.is_front_face, data.is_front_face);
+        assert_eq!(query.time, 0.5);
+    }
+
+    /// The normal of a cylinder points away from its axis, not from its center.
+    #[test]
+    fn test_scattering_uses_the_geometric_normal_of_the_hit() {
+        let translation = Matrix4x4::from_affine_translation(&Vector3::new(1_f32, 2_f32, 3_f32));
+        let object = scene_object(Cylinder::new(Vector3::zero(), 1_f32, 4_f32), translation);
+        let mut sampler = sampler();
+        let ray = Ray::new(Vector3::new(11_f32, 3.5, 3_f32), -Vector3::unit_x());
+        let data = hit(&object, ray);
+        let query = ScatteringQuery::from_hit(ray.direction, &data);
+        let result = object.scatter(&query, &mut sampler);
+
+        assert_relative_eq!(data.point, Vector3::new(2_f32, 3.5, 3_f32), epsilon = 1e-5);
+        assert_relative_eq!(data.normal, Vector3::unit_x(), epsilon = 1e-5);
+        assert_relative_eq!(result.normal, Vector3::unit_x(), epsilon = 1e-5);
+        assert!(result.ray_outgoing.x > 0_f32);
+    }
+}
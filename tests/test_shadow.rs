@@ -0,0 +1,266 @@
+extern crate tracer;
+extern crate approx;
+extern crate rand;
+extern crate rand_isaac;
+
+
+#[cfg(test)]
+mod shadow_query_tests {
+    use tracer::Camera;
+    use tracer::bsdf::*;
+    use tracer::core::*;
+    use tracer::geometry::*;
+    use tracer::light::*;
+    use tracer::scene::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Matrix4x4,
+        Vector3,
+    };
+    use rand::prelude::*;
+
+
+    fn sampler() -> SphereSampler {
+        SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0))
+    }
+
+    fn sphere(center: Vector3<f32>, radius: f32) -> SceneObject {
+        let object = ModelSpaceGeometryObject::new(
+            Sphere::new(Vector3::zero(), radius),
+            Box::new(SimpleLambertianBsdf::new(Vector3::from_fill(0.5))),
+            Box::new(NoLight::new()),
+        );
+
+        SceneObject::new(Box::new(object), Matrix4x4::from_affine_translation(&center))
+    }
+
+    fn scene(objects: Vec<SceneObject>) -> Scene {
+        let camera = Camera::new(Vector3::new(0_f32, 0_f32, 10_f32), Vector3::zero(), Vector3::unit_y(), 90_f32, 1_f32, 0_f32, 10_f32);
+        let mut scene = Scene::new(1, 1, camera);
+        for object in objects {
+            scene.push_object(object);
+        }
+
+        scene
+    }
+
+    #[test]
+    fn test_shadow_query_between_two_points_stops_short_of_the_far_end() {
+        let query = ShadowQuery::between(&Vector3::zero(), &Vector3::new(0_f32, 4_f32, 0_f32), 0.5);
+
+        assert_relative_eq!(query.distance, 4_f32 * (1_f32 - SHADOW_EPSILON), epsilon = 1e-6);
+        assert_relative_eq!(query.t_max(), 1_f32 - SHADOW_EPSILON, epsilon = 1e-6);
+        assert_eq!(query.ray.time, 0.5);
+    }
+
+    #[test]
+    fn test_shadow_query_distance_does_not_depend_on_the_ray_speed() {
+        let scene = scene(vec![sphere(Vector3::new(5_f32, 0_f32, 0_f32), 1_f32)]);
+        for speed in [0.25, 1_f32, 4_f32] {
+            let ray = Ray::new(Vector3::zero(), Vector3::unit_x() * speed);
+
+            assert!(!scene.occluded(&ShadowQuery::new(ray, 3.9)));
+            assert!(scene.occluded(&ShadowQuery::new(ray, 4.1)));
+        }
+    }
+
+    #[test]
+    fn test_objects_behind_the_light_do_not_block_it() {
+        let scene = scene(vec![sphere(Vector3::new(5_f32, 0_f32, 0_f32), 1_f32)]);
+        let light_position = Vector3::new(2_f32, 0_f32, 0_f32);
+
+        assert!(scene.line_of_sight(&Vector3::zero(), &light_position, 0_f32));
+        assert!(!scene.line_of_sight(&Vector3::zero(), &Vector3::new(8_f32, 0_f32, 0_f32), 0_f32));
+    }
+
+    #[test]
+    fn test_occlusion_with_many_blockers() {
+        let objects = (1..10)
+            .map(|i| sphere(Vector3::new(2_f32 * i as f32, 0_f32, 0_f32), 0.5))
+            .collect::<Vec<_>>();
+        let scene = scene(objects);
+        let ray = Ray::new(Vector3::zero(), Vector3::unit_x());
+
+        assert!(scene.occluded(&ShadowQuery::new(ray, 100_f32)));
+        assert!(!scene.occluded(&ShadowQuery::new(ray, 1_f32)));
+        assert!(!scene.occluded(&ShadowQuery::new(Ray::new(Vector3::zero(), Vector3::unit_y()), 100_f32)));
+    }
+
+    #[test]
+    fn test_empty_shadow_query_is_not_occluded() {
+        let scene = scene(vec![sphere(Vector3::zero(), 1_f32)]);
+        let ray = Ray::new(Vector3::new(0_f32, 0_f32, 5_f32), Vector3::zero());
+
+        assert!(!scene.occluded(&ShadowQuery::new(ray, 10_f32)));
+        assert!(scene.line_of_sight(&Vector3::zero(), &Vector3::zero(), 0_f32));
+    }
+
+    #[test]
+    fn test_transparent_object_casts_no_shadows() {
+        let blocker = sphere(Vector3::new(0_f32, 1_f32, 0_f32), 0.25).with_transparent(true);
+        let scene = scene(vec![blocker]);
+        let from = Vector3::zero();
+        let to = Vector3::new(0_f32, 2_f32, 0_f32);
+        let mut sampler = sampler();
+        let ray = Ray::new(from, Vector3::unit_y());
+
+        assert!(scene.objects()[0].is_transparent());
+        assert!(!scene.objects()[0].casts_shadows());
+        assert!(scene.line_of_sight(&from, &to, 0_f32));
+        assert_eq!(scene.transmittance(&from, &to, 0_f32, None, &mut sampler), Vector3::from_fill(1_f32));
+        assert!(scene.intersect(&IntersectionQuery::new(ray, 0_f32, f32::MAX)).is_some());
+    }
+
+    #[test]
+    fn test_opaque_object_casts_shadows() {
+        let scene = scene(vec![sphere(Vector3::new(0_f32, 1_f32, 0_f32), 0.25)]);
+        let from = Vector3::zero();
+        let to = Vector3::new(0_f32, 2_f32, 0_f32);
+        let mut sampler = sampler();
+
+        assert!(scene.objects()[0].casts_shadows());
+        assert!(!scene.line_of_sight(&from, &to, 0_f32));
+        assert_eq!(scene.transmittance(&from, &to, 0_f32, None, &mut sampler), Vector3::zero());
+    }
+
+    #[test]
+    fn test_shadow_catcher_casts_no_shadows() {
+        let catcher = sphere(Vector3::new(0_f32, 1_f32, 0_f32), 0.25).with_shadow_catcher(true);
+        let scene = scene(vec![catcher]);
+
+        assert!(scene.objects()[0].is_shadow_catcher());
+        assert!(!scene.objects()[0].casts_shadows());
+        assert!(scene.line_of_sight(&Vector3::zero(), &Vector3::new(0_f32, 2_f32, 0_f32), 0_f32));
+    }
+
+    #[test]
+    fn test_scene_queries_see_objects_changed_after_a_query() {
+        let mut scene = scene(vec![sphere(Vector3::new(5_f32, 0_f32, 0_f32), 1_f32)]);
+        let x_query = IntersectionQuery::new(Ray::new(Vector3::zero(), Vector3::unit_x()), 0_f32, f32::MAX);
+        let y_query = IntersectionQuery::new(Ray::new(Vector3::zero(), Vector3::unit_y()), 0_f32, f32::MAX);
+
+        assert!(scene.intersect(&x_query).is_some());
+
+        scene.objects_mut()[0] = sphere(Vector3::new(0_f32, 5_f32, 0_f32), 1_f32);
+
+        assert!(scene.intersect(&x_query).is_none());
+        assert!(scene.intersect(&y_query).is_some());
+
+        scene.push_object(sphere(Vector3::new(5_f32, 0_f32, 0_f32), 1_f32));
+        scene.remove_object(0);
+
+        assert_eq!(scene.len_objects(), 1);
+        assert!(scene.intersect(&y_query).is_none());
+        assert_eq!(scene.intersect(&x_query).unwrap().index, 0);
+    }
+
+    #[test]
+    fn test_bvh_traversal_ends_when_the_visitor_asks() {
+        let bounds = (0..64)
+            .map(|i| {
+                let center = Vector3::new(i as f32, 0_f32, 0_f32);
+                BoundingBox::new(center - Vector3::from_fill(0.25), center + Vector3::from_fill(0.25))
+            })
+            .collect::<Vec<_>>();
+        let bvh = Bvh::new(&bounds);
+        let ray = Ray::new(Vector3::new(-1_f32, 0_f32, 0_f32), Vector3::unit_x());
+        let mut visited = 0;
+        bvh.traverse(&ray, 0_f32, f32::MAX, |_index, _t_closest| {
+            visited += 1;
+
+            f32::NEG_INFINITY
+        });
+
+        assert_eq!(visited, 1);
+    }
+}
+
+#[cfg(test)]
+mod shadow_catcher_tests {
+    use tracer::Camera;
+    use tracer::bsdf::*;
+    use tracer::core::*;
+    use tracer::geometry::*;
+    use tracer::light::*;
+    use tracer::renderer::*;
+    use tracer::scene::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Matrix4x4,
+        Vector3,
+    };
+    use rand::prelude::*;
+
+
+    fn sampler() -> SphereSampler {
+        SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0))
+    }
+
+    fn empty_scene() -> Scene {
+        let camera = Camera::new(Vector3::new(0_f32, 3_f32, 3_f32), Vector3::zero(), Vector3::unit_y(), 90_f32, 1_f32, 0_f32, 3_f32);
+        let mut scene = Scene::new(1, 1, camera);
+        scene.push_light(ScenePointLightObject::new(
+            PointLight::new(Vector3::from_fill(4_f32 * std::f32::consts::PI * 16_f32)),
+            Matrix4x4::from_affine_translation(&Vector3::new(0_f32, 2_f32, 0_f32)),
+        ));
+
+        scene
+    }
+
+    /// A shadow catching floor under a small sphere lit from above, so that the
+    /// sphere casts its shadow onto the floor around the origin.
+    fn scene() -> Scene {
+        let floor = ModelSpaceGeometryObject::new(
+            Plane::new(Vector3::zero(), Vector3::unit_y()),
+            Box::new(SimpleLambertianBsdf::new(Vector3::from_fill(0.5))),
+            Box::new(NoLight::new()),
+        );
+        let blocker = ModelSpaceGeometryObject::new(
+            Sphere::new(Vector3::zero(), 0.25),
+            Box::new(SimpleLambertianBsdf::new(Vector3::from_fill(0.5))),
+            Box::new(NoLight::new()),
+        );
+        let mut scene = empty_scene();
+        scene.push_object(SceneObject::new(Box::new(floor), Matrix4x4::identity()).with_shadow_catcher(true));
+        scene.push_object(SceneObject::new(Box::new(blocker), Matrix4x4::from_affine_translation(&Vector3::new(0_f32, 1_f32, 0_f32))));
+
+        scene
+    }
+
+    fn integrators() -> Vec<Box<dyn Integrator>> {
+        vec![
+            Box::new(DirectLightingIntegrator::new(4, f32::MAX)),
+            Box::new(PathTracer::new(4, 16, f32::MAX)),
+        ]
+    }
+
+    #[test]
+    fn test_lit_shadow_catcher_shows_what_lies_behind_it() {
+        let scene = scene();
+        let empty_scene = empty_scene();
+        let ray = Ray::new(Vector3::new(5_f32, 3_f32, 0_f32), Vector3::new(-1_f32, -3_f32, 0_f32));
+        for integrator in integrators() {
+            let mut sampler = sampler();
+            let expected = integrator.radiance(&empty_scene, &ray, &mut sampler);
+            let result = integrator.radiance(&scene, &ray, &mut sampler);
+
+            assert_relative_eq!(result, expected, epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_shadowed_shadow_catcher_is_dark() {
+        let scene = scene();
+        let ray = Ray::new(Vector3::new(2_f32, 3_f32, 0_f32), Vector3::new(-2_f32, -3_f32, 0_f32));
+        for integrator in integrators() {
+            let mut sampler = sampler();
+            let result = integrator.radiance(&scene, &ray, &mut sampler);
+
+            assert_relative_eq!(result, Vector3::zero(), epsilon = 1e-6);
+        }
+    }
+}
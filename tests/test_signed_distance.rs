@@ -0,0 +1,247 @@
+extern crate tracer;
+extern crate approx;
+extern crate rand;
+extern crate rand_isaac;
+
+
+#[cfg(test)]
+mod distance_function_tests {
+    use tracer::geometry::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector3,
+    };
+
+
+    #[test]
+    fn test_box_distance() {
+        let function = BoxDistance::new(Vector3::zero(), Vector3::new(1_f32, 2_f32, 3_f32));
+
+        assert_relative_eq!(function.distance(&Vector3::new(2_f32, 0_f32, 0_f32)), 1_f32, epsilon = 1e-6);
+        assert_relative_eq!(function.distance(&Vector3::new(0.5_f32, 0_f32, 0_f32)), -0.5_f32, epsilon = 1e-6);
+        assert_relative_eq!(function.distance(&Vector3::new(4_f32, 6_f32, 0_f32)), 5_f32, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_torus_distance() {
+        let function = TorusDistance::new(Vector3::zero(), 2_f32, 0.5_f32);
+
+        assert_relative_eq!(function.distance(&Vector3::zero()), 1.5_f32, epsilon = 1e-6);
+        assert_relative_eq!(function.distance(&Vector3::new(0_f32, 0_f32, 2_f32)), -0.5_f32, epsilon = 1e-6);
+        assert_relative_eq!(function.distance(&Vector3::new(2_f32, 1_f32, 0_f32)), 0.5_f32, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_smooth_union_without_smoothness_is_the_union() {
+        let left = Box::new(SphereDistance::new(Vector3::new(-1_f32, 0_f32, 0_f32), 0.5));
+        let right = Box::new(SphereDistance::new(Vector3::new(1_f32, 0_f32, 0_f32), 0.5));
+        let function = SmoothUnion::new(left, right, 0_f32);
+
+        assert_relative_eq!(function.distance(&Vector3::zero()), 0.5_f32, epsilon = 1e-6);
+        assert_relative_eq!(function.distance(&Vector3::new(-1_f32, 0_f32, 0_f32)), -0.5_f32, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_smooth_union_blends_nearby_surfaces() {
+        let left = Box::new(SphereDistance::new(Vector3::new(-1_f32, 0_f32, 0_f32), 0.5));
+        let right = Box::new(SphereDistance::new(Vector3::new(1_f32, 0_f32, 0_f32), 0.5));
+        let function = SmoothUnion::new(left, right, 1_f32);
+
+        // Halfway between the spheres, the blend pulls the surface in by a 
+        // quarter of the smoothness.
+        assert_relative_eq!(function.distance(&Vector3::zero()), 0.25_f32, epsilon = 1e-6);
+        // Far from the gap the blend has no effect.
+        assert_relative_eq!(function.distance(&Vector3::new(-3_f32, 0_f32, 0_f32)), 1.5_f32, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_twist_rotates_cross_sections() {
+        let inner = Box::new(BoxDistance::new(Vector3::zero(), Vector3::new(2_f32, 10_f32, 0.5_f32)));
+        let function = Twist::new(inner, std::f32::consts::FRAC_PI_2);
+
+        // At zero height the box is untwisted, and a unit of height up it has 
+        // turned a quarter turn about the y-axis.
+        assert!(function.distance(&Vector3::new(1.5_f32, 0_f32, 0_f32)) < 0_f32);
+        assert!(function.distance(&Vector3::new(0_f32, 0_f32, 1.5_f32)) > 0_f32);
+        assert!(function.distance(&Vector3::new(1.5_f32, 1_f32, 0_f32)) > 0_f32);
+        assert!(function.distance(&Vector3::new(0_f32, 1_f32, 1.5_f32)) < 0_f32);
+        assert!(function.lipschitz_constant() > 1_f32);
+    }
+
+    #[test]
+    fn test_repetition_repeats_on_grid() {
+        let inner = Box::new(SphereDistance::new(Vector3::zero(), 0.5));
+        let function = Repetition::new(inner, Vector3::new(2_f32, 0_f32, 2_f32));
+
+        assert_relative_eq!(function.distance(&Vector3::new(4_f32, 0_f32, -6_f32)), -0.5_f32, epsilon = 1e-6);
+        assert_relative_eq!(function.distance(&Vector3::new(4_f32, 1_f32, -6_f32)), 0.5_f32, epsilon = 1e-6);
+        assert!(function.bounds().is_infinite());
+        assert_relative_eq!(function.bounds().min.y, -0.5_f32, epsilon = 1e-6);
+    }
+}
+
+#[cfg(test)]
+mod signed_distance_geometry_tests {
+    use tracer::core::*;
+    use tracer::geometry::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector3,
+        Magnitude,
+    };
+    use rand::prelude::*;
+
+
+    fn query(origin: Vector3<f32>, direction: Vector3<f32>) -> IntersectionQuery {
+        IntersectionQuery::new(Ray::new(origin, direction), 0.0001_f32, f32::MAX)
+    }
+
+    fn sphere() -> SignedDistanceGeometry {
+        SignedDistanceGeometry::new(Box::new(SphereDistance::new(Vector3::zero(), 1_f32)))
+    }
+
+    #[test]
+    fn test_sphere_tracing_matches_analytic_sphere() {
+        let geometry = sphere();
+        let analytic = Sphere::new(Vector3::zero(), 1_f32);
+        let mut sampler = SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0));
+        for _ in 0..100 {
+            let origin = sampler.sample_uniform_direction() * 3_f32;
+            let target = sampler.sample_uniform_direction() * 0.5;
+            let query = query(origin, target - origin);
+            let expected = analytic.intersect(&query).unwrap_hit();
+            let result = geometry.intersect(&query).unwrap_hit();
+
+            assert_relative_eq!(result.point, expected.point, epsilon = 1e-3);
+            assert_relative_eq!(result.normal, expected.normal, epsilon = 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_sphere_tracing_hits_surface_on_bounds() {
+        let geometry = SignedDistanceGeometry::new(Box::new(BoxDistance::new(Vector3::zero(), Vector3::from_fill(1_f32))));
+        let data = geometry.intersect(&query(Vector3::new(0.2_f32, 0.3_f32, 5_f32), -Vector3::unit_z())).unwrap_hit();
+
+        assert_relative_eq!(data.t, 4_f32, epsilon = 1e-3);
+        assert_relative_eq!(data.normal, Vector3::unit_z(), epsilon = 1e-3);
+    }
+
+    #[test]
+    fn test_ray_leaving_surface_does_not_hit_it_again() {
+        let geometry = sphere();
+        let point = Vector3::new(0_f32, 0_f32, 1_f32);
+
+        assert!(geometry.intersect(&query(point, Vector3::new(0_f32, 1_f32, 1_f32))).is_miss());
+
+        let data = geometry.intersect(&query(point, -Vector3::unit_z())).unwrap_hit();
+
+        assert_relative_eq!(data.t, 2_f32, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn test_sphere_tracing_twisted_box() {
+        let inner = Box::new(BoxDistance::new(Vector3::zero(), Vector3::new(1_f32, 2_f32, 0.25_f32)));
+        let geometry = SignedDistanceGeometry::new(Box::new(Twist::new(inner, 0.5)));
+        let mut hit_count = 0;
+        for i in 0..100 {
+            let y = -1.9 + 3.8 * (i as f32) / 100_f32;
+            if let IntersectionResult::Hit(data) = geometry.intersect(&query(Vector3::new(0_f32, y, 5_f32), -Vector3::unit_z())) {
+                assert!(f32::abs(geometry.function().distance(&data.point)) < 1e-3);
+                assert_relative_eq!(data.normal.magnitude(), 1_f32, epsilon = 1e-4);
+                hit_count += 1;
+            }
+        }
+
+        assert_eq!(hit_count, 100);
+    }
+
+    #[test]
+    fn test_sphere_tracing_repeated_spheres() {
+        let inner = Box::new(SphereDistance::new(Vector3::zero(), 0.5));
+        let geometry = SignedDistanceGeometry::new(Box::new(Repetition::new(inner, Vector3::new(2_f32, 0_f32, 0_f32))))
+            .with_max_steps(1024);
+        let data = geometry.intersect(&query(Vector3::new(100_f32, 0_f32, 0_f32), -Vector3::unit_x())).unwrap_hit();
+
+        assert_relative_eq!(data.t, 0.5_f32, epsilon = 1e-3);
+        assert!(geometry.intersect(&query(Vector3::new(100_f32, 1_f32, 0_f32), -Vector3::unit_x())).is_miss());
+        assert!(geometry.area().is_infinite());
+    }
+
+    #[test]
+    fn test_signed_distance_area_estimate() {
+        let geometry = sphere();
+
+        assert_relative_eq!(geometry.area(), 4_f32 * std::f32::consts::PI, max_relative = 0.05);
+    }
+
+    #[test]
+    fn test_signed_distance_surface_samples_lie_on_surface() {
+        let geometry = sphere();
+        let mut sampler = SphereSampler::new(rand_isaac::Isaac64Rng::seed_from_u64(0));
+        for _ in 0..100 {
+            let sample = geometry.sample_surface(&mut sampler).unwrap();
+
+            assert_relative_eq!(sample.point.magnitude(), 1_f32, epsilon = 1e-3);
+            assert_relative_eq!(sample.normal, sample.point, epsilon = 1e-3);
+            assert_relative_eq!(sample.pdf, 1_f32 / geometry.area(), epsilon = 1e-6);
+        }
+    }
+}
+
+#[cfg(test)]
+mod signed_distance_scene_tests {
+    use tracer::Camera;
+    use tracer::bsdf::*;
+    use tracer::core::*;
+    use tracer::geometry::*;
+    use tracer::light::*;
+    use tracer::scene::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector3,
+        Matrix4x4,
+    };
+
+
+    #[test]
+    fn test_signed_distance_geometry_shares_scene_with_spheres() {
+        let camera = Camera::new(Vector3::zero(), -Vector3::unit_z(), Vector3::unit_y(), 90_f32, 1_f32, 0_f32, 1_f32);
+        let mut scene = Scene::new(4, 4, camera);
+        let torus = SignedDistanceGeometry::new(Box::new(TorusDistance::new(Vector3::zero(), 1_f32, 0.25)));
+        scene.push_object(SceneObject::new(
+            Box::new(ModelSpaceGeometryObject::new(
+                torus,
+                Box::new(SimpleLambertianBsdf::new(Vector3::from_fill(0.5))),
+                Box::new(NoLight::new())
+            )),
+            Matrix4x4::from_affine_translation(&Vector3::new(0_f32, 0_f32, -5_f32))
+        ));
+        scene.push_object(SceneObject::new(
+            Box::new(ModelSpaceGeometryObject::new(
+                Sphere::new(Vector3::zero(), 0.5),
+                Box::new(SimpleLambertianBsdf::new(Vector3::from_fill(0.5))),
+                Box::new(NoLight::new())
+            )),
+            Matrix4x4::from_affine_translation(&Vector3::new(0_f32, 0_f32, -5_f32))
+        ));
+
+        // Looking down the axis of the torus hits the sphere in its hole, and 
+        // looking down at the ring hits the torus.
+        let query = IntersectionQuery::new(Ray::new(Vector3::new(0_f32, 5_f32, -5_f32), -Vector3::unit_y()), 0.0001, f32::MAX);
+        let result = scene.intersect(&query).unwrap();
+
+        assert_relative_eq!(result.intersection_result.unwrap_hit().t, 4.5_f32, epsilon = 1e-3);
+
+        let query = IntersectionQuery::new(Ray::new(Vector3::new(1_f32, 5_f32, -5_f32), -Vector3::unit_y()), 0.0001, f32::MAX);
+        let result = scene.intersect(&query).unwrap();
+
+        assert_relative_eq!(result.intersection_result.unwrap_hit().t, 4.75_f32, epsilon = 1e-3);
+    }
+}
+
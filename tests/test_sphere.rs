@@ -1,4 +1,5 @@
 extern crate tracer;
+extern crate approx;
 
 
 #[cfg(test)]
@@ -1441,3 +1442,100 @@ mod sphere_tests {
     }
 }
 
+
+
+#[cfg(test)]
+mod sphere_surface_tests {
+    use tracer::core::*;
+    use tracer::geometry::{
+        Sphere,
+    };
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector3,
+        Magnitude,
+    };
+    use std::f32;
+
+
+    fn sphere() -> Sphere {
+        Sphere::new(
+            Vector3::new(1_f32, 2_f32, 3_f32),
+            2_f32
+        )
+    }
+
+    fn cast_towards_center(sphere: &Sphere, i: usize, j: usize) -> IntersectionData {
+        let theta = (i as f32 + 0.5) * f32::consts::PI / 16_f32;
+        let phi = (j as f32) * 2_f32 * f32::consts::PI / 16_f32;
+        let direction = Vector3::new(
+            f32::sin(theta) * f32::cos(phi),
+            f32::cos(theta),
+            f32::sin(theta) * f32::sin(phi),
+        );
+        let ray_origin = sphere.center() + direction * 3_f32 * sphere.radius();
+        let ray = Ray::new(ray_origin, -direction);
+        let query = IntersectionQuery::new(ray, 0.01_f32, f32::MAX);
+
+        sphere.intersect(&query).unwrap_hit()
+    }
+
+    #[test]
+    fn test_surface_parameters_lie_in_unit_square() {
+        let sphere = sphere();
+        for i in 0..16 {
+            for j in 0..16 {
+                let hit = cast_towards_center(&sphere, i, j);
+
+                assert!(hit.uv.x >= 0_f32 && hit.uv.x <= 1_f32);
+                assert!(hit.uv.y >= 0_f32 && hit.uv.y <= 1_f32);
+            }
+        }
+    }
+
+    #[test]
+    fn test_surface_tangents_are_perpendicular_to_normal() {
+        let sphere = sphere();
+        for i in 0..16 {
+            for j in 0..16 {
+                let hit = cast_towards_center(&sphere, i, j);
+
+                assert_relative_eq!(hit.dpdu.dot(&hit.normal), 0_f32, epsilon = 1e-4);
+                assert_relative_eq!(hit.dpdv.dot(&hit.normal), 0_f32, epsilon = 1e-4);
+                assert_relative_eq!(hit.shading_normal, hit.normal);
+            }
+        }
+    }
+
+    #[test]
+    fn test_surface_tangents_are_consistent_with_normal_orientation() {
+        let sphere = sphere();
+        for i in 0..16 {
+            for j in 0..16 {
+                let hit = cast_towards_center(&sphere, i, j);
+                let normal = hit.dpdu.cross(&hit.dpdv).normalize();
+
+                assert_relative_eq!(normal, hit.normal, epsilon = 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn test_shading_frame_is_orthonormal() {
+        let sphere = sphere();
+        for i in 0..16 {
+            for j in 0..16 {
+                let hit = cast_towards_center(&sphere, i, j);
+                let frame = hit.shading_frame();
+
+                assert_relative_eq!(frame.tangent.magnitude(), 1_f32, epsilon = 1e-5);
+                assert_relative_eq!(frame.bitangent.magnitude(), 1_f32, epsilon = 1e-5);
+                assert_relative_eq!(frame.tangent.dot(&frame.bitangent), 0_f32, epsilon = 1e-5);
+                assert_relative_eq!(frame.tangent.dot(&frame.normal), 0_f32, epsilon = 1e-5);
+                assert_relative_eq!(frame.normal, hit.shading_normal);
+            }
+        }
+    }
+}
@@ -8,7 +8,6 @@ mod sphere_lambertian_model_tests {
     use tracer::core::*;
     use tracer::bsdf::{
         SimpleLambertianBsdf,
-        SimpleLambertianBsdfQuerySampler,
     };
     use tracer::core::{
         Ray,
@@ -29,15 +28,14 @@ mod sphere_lambertian_model_tests {
     use rand::prelude::*;
 
 
-    fn sphere() -> ModelSpaceGeometryObject<Sphere, SimpleLambertianBsdf, NoLight> {
+    fn sphere() -> ModelSpaceGeometryObject<Sphere> {
         let sphere = Sphere::new(Vector3::zero(), 1_f32);
         let bsdf = Box::new(
             SimpleLambertianBsdf::new(Vector3::new(0.5, 0.5, 0.5))
         );
-        let bsdf_sampler = Box::new(SimpleLambertianBsdfQuerySampler::new());
         let emitter = Box::new(NoLight::new());
 
-        ModelSpaceGeometryObject::new(sphere, bsdf, bsdf_sampler, emitter)
+        ModelSpaceGeometryObject::new(sphere, bsdf, emitter)
     }
 
     #[test]
@@ -55,7 +53,10 @@ mod sphere_lambertian_model_tests {
         );
         let result = sphere.intersect(&intersection_query);
 
-        assert_eq!(result, expected);
+        assert!(result.is_hit());
+        assert_eq!(result.unwrap_hit().t, expected.unwrap_hit().t);
+        assert_eq!(result.unwrap_hit().point, expected.unwrap_hit().point);
+        assert_eq!(result.unwrap_hit().normal, expected.unwrap_hit().normal);
     }
 
     #[test]
@@ -73,6 +74,8 @@ mod sphere_lambertian_model_tests {
         let scattering_query = ScatteringQuery::new(
             ray_incoming.direction,
             intersection_result.point,
+            intersection_result.uv,
+            intersection_result.shading_frame(),
         );
         let expected = ScatteringResult::new(
             ray_incoming.direction,
@@ -97,7 +100,6 @@ mod sphere_metal_model_tests {
     use tracer::core::*;
     use tracer::bsdf::{
         SimpleMetalBsdf,
-        SimpleMetalBsdfQuerySampler,
     };
     use tracer::core::{
         Ray,
@@ -117,15 +119,14 @@ mod sphere_metal_model_tests {
     };
     use rand::prelude::*;
 
-    fn sphere() -> ModelSpaceGeometryObject<Sphere, SimpleMetalBsdf, NoLight> {
+    fn sphere() -> ModelSpaceGeometryObject<Sphere> {
         let sphere = Sphere::new(Vector3::zero(), 1_f32);
         let bsdf = Box::new(
             SimpleMetalBsdf::new(Vector3::new(0.5, 0.5, 0.5), 0.2)
         );
-        let bsdf_sampler = Box::new(SimpleMetalBsdfQuerySampler::new());
         let emitter = Box::new(NoLight::new());
 
-        ModelSpaceGeometryObject::new(sphere, bsdf, bsdf_sampler, emitter)
+        ModelSpaceGeometryObject::new(sphere, bsdf, emitter)
     }
 
     #[test]
@@ -143,7 +144,10 @@ mod sphere_metal_model_tests {
         );
         let result = sphere.intersect(&intersection_query);
 
-        assert_eq!(result, expected);
+        assert!(result.is_hit());
+        assert_eq!(result.unwrap_hit().t, expected.unwrap_hit().t);
+        assert_eq!(result.unwrap_hit().point, expected.unwrap_hit().point);
+        assert_eq!(result.unwrap_hit().normal, expected.unwrap_hit().normal);
     }
 
     #[test]
@@ -161,6 +165,8 @@ mod sphere_metal_model_tests {
         let scattering_query = ScatteringQuery::new(
             ray_incoming.direction,
             intersection_result.point,
+            intersection_result.uv,
+            intersection_result.shading_frame(),
         );
         let expected = ScatteringResult::new(
             ray_incoming.direction,
@@ -0,0 +1,298 @@
+extern crate tracer;
+extern crate approx;
+extern crate png;
+
+
+#[cfg(test)]
+mod texture_tests {
+    use tracer::core::*;
+    use tracer::texture::*;
+    use approx::{
+        assert_relative_eq,
+    };
+    use cglinalg::{
+        Vector2,
+        Vector3,
+    };
+    use std::sync::Arc;
+
+
+    fn query(u: f32, v: f32) -> TextureQuery {
+        TextureQuery::new(Vector3::zero(), Vector2::new(u, v))
+    }
+
+    /// A 2x2 image with a distinct value in each pixel.
+    fn image() -> Image {
+        Image::new(2, 2, vec![
+            Vector3::from_fill(0_f32), Vector3::from_fill(1_f32),
+            Vector3::from_fill(2_f32), Vector3::from_fill(3_f32),
+        ])
+    }
+
+    #[test]
+    fn test_constant_texture() {
+        let texture = ConstantTexture::new(Vector3::new(1_f32, 2_f32, 3_f32));
+
+        assert_eq!(texture.evaluate(&query(0.3, 0.7)), Vector3::new(1_f32, 2_f32, 3_f32));
+    }
+
+    #[test]
+    fn test_checkerboard_texture_alternates() {
+        let texture = CheckerboardTexture::new(
+            Arc::new(ConstantTexture::new(0_f32)),
+            Arc::new(ConstantTexture::new(1_f32)),
+            Vector2::new(4_f32, 4_f32),
+        );
+
+        assert_eq!(texture.evaluate(&query(0.1, 0.1)), 0_f32);
+        assert_eq!(texture.evaluate(&query(0.3, 0.1)), 1_f32);
+        assert_eq!(texture.evaluate(&query(0.3, 0.3)), 0_f32);
+        assert_eq!(texture.evaluate(&query(-0.1, 0.1)), 1_f32);
+    }
+
+    #[test]
+    fn test_image_texture_texel_centers() {
+        let texture = ImageTexture::new(Arc::new(image()), WrapMode::Clamp, FilterMode::Bilinear);
+
+        // The top row of the image lies at the top of the unit square.
+        assert_relative_eq!(texture.lookup(&Vector2::new(0.25, 0.75)), Vector3::from_fill(0_f32));
+        assert_relative_eq!(texture.lookup(&Vector2::new(0.75, 0.75)), Vector3::from_fill(1_f32));
+        assert_relative_eq!(texture.lookup(&Vector2::new(0.25, 0.25)), Vector3::from_fill(2_f32));
+        assert_relative_eq!(texture.lookup(&Vector2::new(0.75, 0.25)), Vector3::from_fill(3_f32));
+    }
+
+    #[test]
+    fn test_image_texture_bilinear_interpolation() {
+        let texture = ImageTexture::new(Arc::new(image()), WrapMode::Clamp, FilterMode::Bilinear);
+
+        assert_relative_eq!(texture.lookup(&Vector2::new(0.5, 0.5)), Vector3::from_fill(1.5));
+        assert_relative_eq!(texture.lookup(&Vector2::new(0.5, 0.75)), Vector3::from_fill(0.5));
+    }
+
+    #[test]
+    fn test_image_texture_wrap_modes() {
+        let repeat = ImageTexture::new(Arc::new(image()), WrapMode::Repeat, FilterMode::Nearest);
+        let mirror = ImageTexture::new(Arc::new(image()), WrapMode::Mirror, FilterMode::Nearest);
+        let clamp = ImageTexture::new(Arc::new(image()), WrapMode::Clamp, FilterMode::Nearest);
+        let uv = Vector2::new(1.25, 0.75);
+
+        assert_eq!(repeat.lookup(&uv), Vector3::from_fill(0_f32));
+        assert_eq!(mirror.lookup(&uv), Vector3::from_fill(1_f32));
+        assert_eq!(clamp.lookup(&uv), Vector3::from_fill(1_f32));
+    }
+
+    #[test]
+    fn test_image_texture_scalar_lookup_of_grayscale_image() {
+        let texture = ImageTexture::new(Arc::new(image()), WrapMode::Clamp, FilterMode::Nearest);
+        let result: f32 = texture.evaluate(&query(0.75, 0.25));
+
+        assert_relative_eq!(result, 3_f32, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_pfm_decoding() {
+        let mut bytes = b"PF\n2 1\n-1.0\n".to_vec();
+        for value in [1_f32, 2_f32, 3_f32, 4_f32, 5_f32, 6_f32] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        let image = Image::from_pfm(bytes.as_slice()).unwrap();
+
+        assert_eq!(image.width(), 2);
+        assert_eq!(image.height(), 1);
+        assert_eq!(image.pixel(0, 0), Vector3::new(1_f32, 2_f32, 3_f32));
+        assert_eq!(image.pixel(1, 0), Vector3::new(4_f32, 5_f32, 6_f32));
+    }
+
+    #[test]
+    fn test_pfm_decoding_starts_from_bottom_row() {
+        let mut bytes = b"Pf\n1 2\n1.0\n".to_vec();
+        for value in [1_f32, 2_f32] {
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+        let image = Image::from_pfm(bytes.as_slice()).unwrap();
+
+        assert_eq!(image.pixel(0, 0), Vector3::from_fill(2_f32));
+        assert_eq!(image.pixel(0, 1), Vector3::from_fill(1_f32));
+    }
+
+    #[test]
+    fn test_pfm_decoding_truncated_raster() {
+        let bytes = b"PF\n2 2\n-1.0\n\x00\x00".to_vec();
+
+        assert!(Image::from_pfm(bytes.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_pfm_decoding_huge_dimensions() {
+        let header = format!("PF\n{} {}\n-1.0\n", usize::MAX / 2, 3);
+        let mut bytes = header.into_bytes();
+        bytes.extend_from_slice(&[0_u8; 12]);
+        let error = Image::from_pfm(bytes.as_slice()).unwrap_err();
+
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_pfm_decoding_zero_dimension() {
+        let bytes = b"PF\n0 2\n-1.0\n".to_vec();
+        let error = Image::from_pfm(bytes.as_slice()).unwrap_err();
+
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_png_decoding() {
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut bytes, 2, 1);
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header().unwrap();
+            writer.write_image_data(&[255, 0, 0, 0, 255, 128]).unwrap();
+        }
+        let linear = Image::from_png(bytes.as_slice(), ColorEncoding::Linear).unwrap();
+        let srgb = Image::from_png(bytes.as_slice(), ColorEncoding::Srgb).unwrap();
+
+        assert_eq!(linear.width(), 2);
+        assert_eq!(linear.height(), 1);
+        assert_relative_eq!(linear.pixel(0, 0), Vector3::new(1_f32, 0_f32, 0_f32));
+        assert_relative_eq!(linear.pixel(1, 0), Vector3::new(0_f32, 1_f32, 128_f32 / 255_f32));
+        assert_relative_eq!(srgb.pixel(1, 0).z, 0.2158605, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_noise_texture_is_deterministic_and_bounded() {
+        let texture1 = NoiseTexture::new(7, 4_f32, 4);
+        let texture2 = NoiseTexture::new(7, 4_f32, 4);
+        for i in 0..64 {
+            let point = Vector3::new(i as f32 * 0.37, i as f32 * -0.11, i as f32 * 0.05);
+            let query = TextureQuery::new(point, Vector2::zero());
+            let value1: f32 = texture1.evaluate(&query);
+            let value2: f32 = texture2.evaluate(&query);
+
+            assert_eq!(value1, value2);
+            assert!((0_f32..=1_f32).contains(&value1));
+        }
+    }
+
+    #[test]
+    fn test_perlin_noise_vanishes_on_lattice() {
+        let noise = PerlinNoise::new(0);
+        let point = Vector3::new(3_f32, -2_f32, 5_f32);
+
+        assert_eq!(noise.noise(&point), 0_f32);
+    }
+
+    fn sample_points() -> Vec<Vector3<f32>> {
+        (0..64)
+            .map(|i| Vector3::new(i as f32 * 0.37, i as f32 * -0.11 + 0.3, i as f32 * 0.05 - 1.7))
+            .collect()
+    }
+
+    #[test]
+    fn test_simplex_noise_is_deterministic_and_bounded() {
+        let noise1 = SimplexNoise::new(3);
+        let noise2 = SimplexNoise::new(3);
+        let mut varies = false;
+        for point in sample_points() {
+            let value = noise1.noise(&point);
+
+            assert_eq!(value, noise2.noise(&point));
+            assert!((-1_f32..=1_f32).contains(&value));
+            varies |= value != 0_f32;
+        }
+
+        assert!(varies);
+    }
+
+    #[test]
+    fn test_noise_depends_on_seed() {
+        let noise1 = PerlinNoise::new(1);
+        let noise2 = PerlinNoise::new(2);
+        let point = Vector3::new(0.3, 0.6, 0.9);
+
+        assert_ne!(noise1.noise(&point), noise2.noise(&point));
+    }
+
+    #[test]
+    fn test_turbulence_is_bounded() {
+        let noise = PerlinNoise::new(5);
+        for point in sample_points() {
+            let value = turbulence(&noise, &point, 5, 2_f32, 0.5);
+
+            assert!((0_f32..=1_f32).contains(&value));
+        }
+    }
+
+    #[test]
+    fn test_worley_distances_are_ordered() {
+        let noise = WorleyNoise::new(11);
+        for point in sample_points() {
+            let distances = noise.distances(&point);
+
+            assert!(distances.f1 >= 0_f32);
+            assert!(distances.f1 <= distances.f2);
+            // The feature point of the cell containing the point is within the
+            // diagonal of a unit cube.
+            assert!(distances.f1 <= f32::sqrt(3_f32));
+        }
+    }
+
+    #[test]
+    fn test_worley_texture_is_bounded() {
+        let texture = WorleyTexture::new(11, 2_f32, WorleyFeature::F2MinusF1, TextureSpace::Object);
+        for point in sample_points() {
+            let value: f32 = texture.evaluate(&TextureQuery::new(point, Vector2::zero()));
+
+            assert!((0_f32..=1_f32).contains(&value));
+        }
+    }
+
+    #[test]
+    fn test_marble_and_wood_interpolate_colors() {
+        let light = Vector3::new(0.9, 0.9, 0.85);
+        let dark = Vector3::new(0.2, 0.1, 0.05);
+        let marble = MarbleTexture::new(0, 3_f32, light, dark);
+        let wood = WoodTexture::new(0, 8_f32, light, dark);
+        for point in sample_points() {
+            let query = TextureQuery::new(point, Vector2::zero());
+            for (t, color) in [
+                (Texture::<f32>::evaluate(&marble, &query), Texture::<Vector3<f32>>::evaluate(&marble, &query)),
+                (Texture::<f32>::evaluate(&wood, &query), Texture::<Vector3<f32>>::evaluate(&wood, &query)),
+            ] {
+                assert!((0_f32..=1_f32).contains(&t));
+                assert_relative_eq!(color, light * (1_f32 - t) + dark * t, epsilon = 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_solid_checkerboard_alternates_along_each_axis() {
+        let even = Arc::new(ConstantTexture::new(0_f32));
+        let odd = Arc::new(ConstantTexture::new(1_f32));
+        let texture = SolidCheckerboardTexture::new(even, odd, 1_f32, TextureSpace::Object);
+        let value = |x: f32, y: f32, z: f32| texture.evaluate(&TextureQuery::new(Vector3::new(x, y, z), Vector2::zero()));
+
+        assert_eq!(value(0.5, 0.5, 0.5), 0_f32);
+        assert_eq!(value(1.5, 0.5, 0.5), 1_f32);
+        assert_eq!(value(0.5, 1.5, 0.5), 1_f32);
+        assert_eq!(value(0.5, 0.5, -0.5), 1_f32);
+        assert_eq!(value(1.5, 1.5, 0.5), 0_f32);
+    }
+
+    #[test]
+    fn test_texture_space_selects_point() {
+        let even = Arc::new(ConstantTexture::new(0_f32));
+        let odd = Arc::new(ConstantTexture::new(1_f32));
+        let object_space = SolidCheckerboardTexture::new(even.clone(), odd.clone(), 1_f32, TextureSpace::Object);
+        let world_space = SolidCheckerboardTexture::new(even, odd, 1_f32, TextureSpace::World);
+        let query = TextureQuery::from_points(
+            Vector3::new(0.5, 0.5, 0.5),
+            Vector3::new(1.5, 0.5, 0.5),
+            Vector2::zero(),
+        );
+
+        assert_eq!(object_space.evaluate(&query), 0_f32);
+        assert_eq!(world_space.evaluate(&query), 1_f32);
+    }
+}